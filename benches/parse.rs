@@ -0,0 +1,179 @@
+use std::fmt::Write;
+
+use criterion::{black_box, criterion_group, criterion_main, Criterion, Throughput};
+
+use comtrade::ComtradeParserBuilder;
+
+const NUM_ANALOG_CHANNELS: usize = 12;
+const NUM_STATUS_CHANNELS: usize = 16;
+const NUM_SAMPLES: usize = 20_000;
+
+// Synthetic 1999 record laid out like a typical relay recording: a dozen analog
+// channels and a block of status channels, sampled at 4 kHz.
+fn cfg(data_format: &str) -> String {
+    let mut cfg = String::new();
+    let _ = writeln!(cfg, "BENCH STATION,BENCH DEVICE,1999");
+    let _ = writeln!(
+        cfg,
+        "{},{}A,{}D",
+        NUM_ANALOG_CHANNELS + NUM_STATUS_CHANNELS,
+        NUM_ANALOG_CHANNELS,
+        NUM_STATUS_CHANNELS
+    );
+    for i in 1..=NUM_ANALOG_CHANNELS {
+        let _ = writeln!(cfg, "{},CH{},A,Line1,A,0.01,0.0,0,-32768,32767,1,1,S", i, i);
+    }
+    for i in 1..=NUM_STATUS_CHANNELS {
+        let _ = writeln!(cfg, "{},ST{},,Line1,0", i, i);
+    }
+    let _ = writeln!(cfg, "50");
+    let _ = writeln!(cfg, "1");
+    let _ = writeln!(cfg, "4000,{}", NUM_SAMPLES);
+    let _ = writeln!(cfg, "01/02/2020,00:00:00.000000");
+    let _ = writeln!(cfg, "01/02/2020,00:00:00.100000");
+    let _ = writeln!(cfg, "{}", data_format);
+    let _ = writeln!(cfg, "1");
+    cfg
+}
+
+fn analog_value(sample: usize, channel: usize) -> i16 {
+    ((sample * 37 + channel * 1013) % 20000) as i16 - 10000
+}
+
+fn status_value(sample: usize, channel: usize) -> u16 {
+    ((sample / 1000 + channel) % 2) as u16
+}
+
+fn ascii_record() -> (String, String) {
+    let mut dat = String::new();
+    for sample in 1..=NUM_SAMPLES {
+        let _ = write!(dat, "{},{}", sample, (sample - 1) * 250);
+        for channel in 0..NUM_ANALOG_CHANNELS {
+            let _ = write!(
+                dat,
+                ",{}",
+                ((sample * 37 + channel * 1013) % 20000) as i32 - 10000
+            );
+        }
+        for channel in 0..NUM_STATUS_CHANNELS {
+            let _ = write!(dat, ",{}", status_value(sample, channel));
+        }
+        dat.push('\n');
+    }
+
+    (cfg("ASCII"), dat)
+}
+
+fn binary_record() -> (String, Vec<u8>) {
+    let mut dat = vec![];
+    for sample in 1..=NUM_SAMPLES {
+        dat.extend_from_slice(&(sample as u32).to_le_bytes());
+        dat.extend_from_slice(&(((sample - 1) * 250) as u32).to_le_bytes());
+        for channel in 0..NUM_ANALOG_CHANNELS {
+            dat.extend_from_slice(&analog_value(sample, channel).to_le_bytes());
+        }
+        let status_group = (0..NUM_STATUS_CHANNELS).fold(0u16, |group, channel| {
+            group | (status_value(sample, channel) << channel)
+        });
+        dat.extend_from_slice(&status_group.to_le_bytes());
+    }
+
+    (cfg("BINARY"), dat)
+}
+
+fn bench_ascii(c: &mut Criterion) {
+    let (cfg, dat) = ascii_record();
+
+    let mut group = c.benchmark_group("ascii");
+    group.throughput(Throughput::Bytes(dat.len() as u64));
+    group.bench_function("parse", |b| {
+        b.iter(|| {
+            ComtradeParserBuilder::new()
+                .cfg_file(black_box(cfg.as_bytes()))
+                .dat_file(black_box(dat.as_bytes()))
+                .build()
+                .parse()
+                .expect("unable to parse COMTRADE files")
+        })
+    });
+    group.finish();
+}
+
+fn bench_binary(c: &mut Criterion) {
+    let (cfg, dat) = binary_record();
+
+    let mut group = c.benchmark_group("binary16");
+    group.throughput(Throughput::Bytes(dat.len() as u64));
+    group.bench_function("parse", |b| {
+        b.iter(|| {
+            ComtradeParserBuilder::new()
+                .cfg_file(black_box(cfg.as_bytes()))
+                .dat_file(black_box(dat.as_slice()))
+                .build()
+                .parse()
+                .expect("unable to parse COMTRADE files")
+        })
+    });
+    group.finish();
+}
+
+// Configuration of a merging unit style record with thousands of channels, which is
+// where parsing the `.cfg` file itself starts to show up.
+fn large_cfg() -> String {
+    const NUM_ANALOG: usize = 4000;
+    const NUM_STATUS: usize = 4000;
+
+    let mut cfg = String::new();
+    let _ = writeln!(cfg, "BENCH STATION,BENCH DEVICE,2013");
+    let _ = writeln!(
+        cfg,
+        "{},{}A,{}D",
+        NUM_ANALOG + NUM_STATUS,
+        NUM_ANALOG,
+        NUM_STATUS
+    );
+    for i in 1..=NUM_ANALOG {
+        let _ = writeln!(
+            cfg,
+            "{},MU{}/I{},{},Bay{},A,0.000152587890625,0.0,0,-32768,32767,1200,1,S",
+            i,
+            i / 8,
+            i % 8,
+            ["A", "B", "C", "N"][i % 4],
+            i / 8
+        );
+    }
+    for i in 1..=NUM_STATUS {
+        let _ = writeln!(cfg, "{},MU{}/ST{},,Bay{},0", i, i / 8, i % 8, i / 8);
+    }
+    let _ = writeln!(cfg, "50");
+    let _ = writeln!(cfg, "1");
+    let _ = writeln!(cfg, "4000,{}", NUM_SAMPLES);
+    let _ = writeln!(cfg, "01/02/2020,00:00:00.000000");
+    let _ = writeln!(cfg, "01/02/2020,00:00:00.100000");
+    let _ = writeln!(cfg, "BINARY");
+    let _ = writeln!(cfg, "1");
+    let _ = writeln!(cfg, "0,0");
+    let _ = writeln!(cfg, "B,3");
+    cfg
+}
+
+fn bench_cfg(c: &mut Criterion) {
+    let cfg = large_cfg();
+
+    let mut group = c.benchmark_group("cfg");
+    group.throughput(Throughput::Bytes(cfg.len() as u64));
+    group.bench_function("parse_config", |b| {
+        b.iter(|| {
+            ComtradeParserBuilder::new()
+                .cfg_file(black_box(cfg.as_bytes()))
+                .build()
+                .parse_config()
+                .expect("unable to parse COMTRADE config")
+        })
+    });
+    group.finish();
+}
+
+criterion_group!(benches, bench_ascii, bench_binary, bench_cfg);
+criterion_main!(benches);
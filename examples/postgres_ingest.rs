@@ -0,0 +1,34 @@
+//! Load a COMTRADE record into Postgres / TimescaleDB.
+//!
+//! Usage: `DATABASE_URL=postgres://... cargo run --example postgres_ingest --features postgres -- <record.cfg> <record.dat>`
+
+use std::env;
+use std::error::Error;
+use std::fs::File;
+use std::io::BufReader;
+
+use comtrade::postgres::PostgresSink;
+use comtrade::ComtradeParserBuilder;
+
+fn main() -> Result<(), Box<dyn Error>> {
+    let usage = "usage: postgres_ingest <record.cfg> <record.dat>";
+    let cfg_path = env::args().nth(1).ok_or(usage)?;
+    let dat_path = env::args().nth(2).ok_or(usage)?;
+    let database_url = env::var("DATABASE_URL").map_err(|_| "DATABASE_URL must be set")?;
+
+    let mut client = postgres::Client::connect(&database_url, postgres::NoTls)?;
+    let mut sink = PostgresSink::new(&mut client).batch_size(5000);
+
+    ComtradeParserBuilder::new()
+        .cfg_file(BufReader::new(File::open(&cfg_path)?))
+        .dat_file(BufReader::new(File::open(&dat_path)?))
+        .build()
+        .parse_into(&mut sink)?;
+
+    match sink.finish()? {
+        Some(record_id) => println!("loaded {} as record {}", cfg_path, record_id),
+        None => println!("no record found in {}", cfg_path),
+    }
+
+    Ok(())
+}
@@ -0,0 +1,194 @@
+use std::f64::consts::PI;
+
+use chrono::{Duration, NaiveDateTime};
+
+use crate::{AnalogChannel, Comtrade};
+
+/// How to work out the value of a channel between two of its samples.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Interpolation {
+    /// Straight line between the samples either side.
+    Linear,
+
+    /// Windowed sinc (Lanczos) interpolation over the `half_width` samples either side,
+    /// which keeps the shape of the waveform much better than `Linear` so long as it's
+    /// sampled well above the frequencies of interest and evenly. A `half_width` of 3
+    /// to 5 is usual.
+    Sinc { half_width: usize },
+}
+
+/// Analog channels of two records resampled to the same instants, see
+/// `align_records()`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct AlignedRecords {
+    /// Time of the first sample, in the local time of the left record.
+    pub start_time: NaiveDateTime,
+    pub sample_rate_hz: f64,
+
+    /// Time of each sample in seconds relative to the trigger time of the left record,
+    /// so negative before the trigger.
+    pub times: Vec<f64>,
+    pub left: Vec<AnalogChannel>,
+    pub right: Vec<AnalogChannel>,
+
+    /// Values of each of the channels at `times`.
+    pub left_data: Vec<Vec<f64>>,
+    pub right_data: Vec<Vec<f64>>,
+}
+
+/// Resample the analog channels of two records, e.g. from either end of a line, at the
+/// same instants so that they can be compared sample for sample.
+///
+/// The records are lined up using the absolute times of their samples, from their
+/// start times and timestamps, so their clocks need to be synchronised or they won't
+/// line up. If both records give their offsets from UTC (2013 onwards) these are taken
+/// into account; otherwise they're assumed to be in the same time zone.
+///
+/// The resampled channels run at `sample_rate_hz` over the time covered by both
+/// records, starting with the first instant that's a whole number of samples from the
+/// left record's trigger time. Their values are worked out from the samples either side
+/// using `interpolation`, so the two records can be lined up more precisely than one
+/// sample. Values near missing ones are missing too.
+///
+/// Returns `None` if the records don't overlap in time or the sample rate isn't
+/// positive.
+pub fn align_records(
+    left: &Comtrade,
+    right: &Comtrade,
+    sample_rate_hz: f64,
+    interpolation: Interpolation,
+) -> Option<AlignedRecords> {
+    if sample_rate_hz.is_nan() || sample_rate_hz <= 0.0 {
+        return None;
+    }
+
+    // Times are worked out in nanoseconds relative to the left record's trigger time, so
+    // they don't lose precision.
+    let utc_difference = match (left.config.time_offset, right.config.time_offset) {
+        (Some(l), Some(r)) => (l.local_minus_utc() - r.local_minus_utc()) as i64 * 1_000_000_000,
+        _ => 0,
+    };
+    let left_times = sample_times(left, left.config.start_time - left.config.trigger_time, 0)?;
+    let right_times = sample_times(
+        right,
+        right.config.start_time - left.config.trigger_time,
+        utc_difference,
+    )?;
+
+    let start = left_times[0].max(right_times[0]);
+    let end = left_times[left_times.len() - 1].min(right_times[right_times.len() - 1]);
+    if start > end {
+        return None;
+    }
+
+    let period = 1e9 / sample_rate_hz;
+    let first = (start / period).ceil() as i64;
+    let last = (end / period).floor() as i64;
+    let instants: Vec<f64> = (first..=last).map(|i| i as f64 * period).collect();
+    if instants.is_empty() {
+        return None;
+    }
+
+    let channels = |record: &Comtrade| -> Vec<AnalogChannel> {
+        record
+            .config
+            .analog_channels
+            .iter()
+            .map(|channel| AnalogChannel {
+                multiplier: 1.0,
+                offset_adder: 0.0,
+                ..channel.clone()
+            })
+            .collect()
+    };
+    let resample = |record: &Comtrade, times: &[f64]| -> Vec<Vec<f64>> {
+        record
+            .data
+            .analog_data
+            .iter()
+            .map(|data| {
+                instants
+                    .iter()
+                    .map(|t| interpolate(data, times, *t, interpolation))
+                    .collect()
+            })
+            .collect()
+    };
+
+    Some(AlignedRecords {
+        start_time: left.config.trigger_time + Duration::nanoseconds(instants[0].round() as i64),
+        sample_rate_hz,
+        times: instants.iter().map(|t| t / 1e9).collect(),
+        left: channels(left),
+        right: channels(right),
+        left_data: resample(left, &left_times),
+        right_data: resample(right, &right_times),
+    })
+}
+
+/// Times of the record's samples in nanoseconds relative to the reference time, given
+/// how long after the reference the record starts and the difference to add to get from
+/// the record's local time to the reference's.
+fn sample_times(record: &Comtrade, start: Duration, utc_difference: i64) -> Option<Vec<f64>> {
+    if record.data.timestamps.is_empty() {
+        return None;
+    }
+    let start = start.num_nanoseconds()? + utc_difference;
+    Some(
+        record
+            .data
+            .timestamps
+            .iter()
+            .map(|t| (start + t) as f64)
+            .collect(),
+    )
+}
+
+/// Value of the channel at time `t`, which is within the range of `times`.
+fn interpolate(data: &[f64], times: &[f64], t: f64, interpolation: Interpolation) -> f64 {
+    let n = data.len().min(times.len());
+    if n == 0 {
+        return f64::NAN;
+    }
+
+    // Index of the last sample at or before `t`, and how far `t` is towards the next.
+    let i = times[..n]
+        .partition_point(|time| *time <= t)
+        .saturating_sub(1);
+    let fraction = match times.get(i + 1) {
+        Some(next) if i + 1 < n && *next > times[i] => (t - times[i]) / (next - times[i]),
+        _ => 0.0,
+    };
+    if fraction == 0.0 {
+        return data[i];
+    }
+
+    match interpolation {
+        Interpolation::Linear => data[i] + (data[i + 1] - data[i]) * fraction,
+        Interpolation::Sinc { half_width } => {
+            let half_width = half_width.max(1);
+            let x = i as f64 + fraction;
+            let first = (i + 1).saturating_sub(half_width);
+            let last = (i + half_width).min(n - 1);
+
+            // Normalising by the sum of the weights stops the values drooping near the
+            // ends of the channel, where part of the window is missing.
+            let (sum, weights) = (first..=last).fold((0.0, 0.0), |(sum, weights), k| {
+                let weight = lanczos(x - k as f64, half_width as f64);
+                (sum + data[k] * weight, weights + weight)
+            });
+            sum / weights
+        }
+    }
+}
+
+fn lanczos(x: f64, a: f64) -> f64 {
+    if x == 0.0 {
+        1.0
+    } else if x.abs() >= a {
+        0.0
+    } else {
+        let px = PI * x;
+        a * px.sin() * (px / a).sin() / (px * px)
+    }
+}
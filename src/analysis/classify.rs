@@ -0,0 +1,123 @@
+use crate::analysis::fault::FaultType;
+use crate::analysis::phasor::{phasors, Phasor};
+use crate::analysis::sequence::sequence_components;
+use crate::Phase;
+
+/// Phases whose fault current is at least this fraction of the largest phase's are
+/// involved in the fault.
+const PHASE_CURRENT_RATIO: f64 = 0.2;
+
+/// Faults are to ground if the residual current, three times the zero sequence, is at
+/// least this fraction of the largest phase current.
+const RESIDUAL_CURRENT_RATIO: f64 = 0.1;
+
+/// Phase currents around a fault, for `classify_fault()`.
+#[derive(Debug, Clone)]
+pub struct FaultClassifierConfig<'a> {
+    /// Values of the phase A, B and C current channels.
+    pub currents: [&'a [f64]; 3],
+
+    pub samples_per_cycle: f64,
+
+    /// Index of the first sample after fault inception.
+    pub fault_sample: usize,
+}
+
+/// Type of a fault and how sure the classification is, see `classify_fault()`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct FaultClassification {
+    pub fault_type: FaultType,
+
+    /// Confidence between 0 and 1 in whether each of phases A, B and C is involved or
+    /// not, which is lower the closer its current is to the threshold.
+    pub phase_confidence: [f64; 3],
+
+    /// Confidence between 0 and 1 in whether the fault is to ground or not.
+    pub ground_confidence: f64,
+
+    /// Overall confidence in the fault type, the lowest of the others.
+    pub confidence: f64,
+}
+
+impl FaultClassification {
+    /// Phases involved in the fault, e.g. `[Phase::B, Phase::C]` for a `BC` fault.
+    pub fn involved_phases(&self) -> Vec<Phase> {
+        [Phase::A, Phase::B, Phase::C]
+            .into_iter()
+            .zip(self.fault_type.phases())
+            .filter(|(_, involved)| *involved)
+            .map(|(phase, _)| phase)
+            .collect()
+    }
+}
+
+/// Classify a fault by which phases are involved and whether it's to ground.
+///
+/// The phase currents are measured over the first full cycle after fault inception. If
+/// there's a full cycle before the fault, the currents flowing then are taken away,
+/// leaving just the change due to the fault so that load current doesn't make healthy
+/// phases look faulted. Phases whose RMS fault current is at least a fifth of the
+/// largest are involved, and the fault is to ground if its zero sequence current is
+/// significant, at least a tenth of the largest phase current once tripled.
+///
+/// Returns `None` if there isn't a full cycle of data after the fault, or the currents
+/// don't change.
+pub fn classify_fault(config: &FaultClassifierConfig) -> Option<FaultClassification> {
+    let n = config.samples_per_cycle.round() as usize;
+    if n < 2 {
+        return None;
+    }
+
+    let mut currents = [Phasor::default(); 3];
+    for (current, channel) in currents.iter_mut().zip(config.currents.iter()) {
+        let estimates = phasors(channel, config.samples_per_cycle);
+        let fault = (*estimates.get(config.fault_sample + n - 1)?)?;
+        let pre_fault = config
+            .fault_sample
+            .checked_sub(1)
+            .and_then(|idx| estimates[idx])
+            .unwrap_or_default();
+        *current = fault - pre_fault;
+    }
+
+    classify_currents(currents)
+}
+
+/// Classify a fault from the phasors of the currents flowing into it.
+pub(crate) fn classify_currents(currents: [Phasor; 3]) -> Option<FaultClassification> {
+    let magnitudes = currents.map(|i| i.magnitude());
+    let largest = magnitudes.iter().cloned().fold(0.0, f64::max);
+    if largest == 0.0 || !largest.is_finite() {
+        return None;
+    }
+
+    let ratios = magnitudes.map(|i| i / largest);
+    let residual = sequence_components(currents[0], currents[1], currents[2])
+        .zero
+        .magnitude()
+        * 3.0
+        / largest;
+
+    let involved = ratios.map(|r| r >= PHASE_CURRENT_RATIO);
+    let ground = residual >= RESIDUAL_CURRENT_RATIO;
+    let fault_type = FaultType::from_phases(involved[0], involved[1], involved[2], ground)?;
+
+    let phase_confidence = ratios.map(|r| margin(r, PHASE_CURRENT_RATIO));
+    let ground_confidence = margin(residual, RESIDUAL_CURRENT_RATIO);
+    let confidence = phase_confidence
+        .iter()
+        .cloned()
+        .fold(ground_confidence, f64::min);
+
+    Some(FaultClassification {
+        fault_type,
+        phase_confidence,
+        ground_confidence,
+        confidence,
+    })
+}
+
+/// How far the ratio is from the threshold relative to the threshold, up to 1.
+fn margin(ratio: f64, threshold: f64) -> f64 {
+    ((ratio - threshold).abs() / threshold).min(1.0)
+}
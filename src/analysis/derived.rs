@@ -0,0 +1,157 @@
+use crate::{AnalogChannel, MissingDataError, MissingDataPolicy};
+
+/// Types of synthetic channel which can be calculated from an existing analog channel.
+#[derive(Debug, Clone, PartialEq)]
+pub enum DerivedKind {
+    /// Root-mean-square over a trailing window of `window` samples. The first few
+    /// values, before there are enough samples to fill the window, are calculated over
+    /// the samples available so far.
+    Rms { window: usize },
+
+    /// Rate of change per second, using the central difference where possible and a
+    /// one-sided difference at the ends of the channel.
+    Derivative { sample_rate_hz: f64 },
+
+    /// Absolute value of each sample.
+    Absolute,
+
+    /// Peak absolute value over a trailing window of `window` samples.
+    Envelope { window: usize },
+}
+
+/// Calculate a new synthetic channel and its values from an existing analog channel
+/// and its values, e.g. for `Comtrade::append_analog_channel()`.
+///
+/// The new channel holds real values directly, so has a multiplier of 1 and offset
+/// of 0, with the minimum and maximum values set from the calculated data. The
+/// index is left as 0 - it is up to the caller to number the channel when adding it
+/// to a record.
+///
+/// Missing values are skipped, see `MissingDataPolicy::Skip`. The RMS and envelope are
+/// worked out from the values in the window which aren't missing, and the absolute
+/// value and derivative are missing wherever the values they need are.
+pub fn derive_channel(
+    kind: DerivedKind,
+    channel: &AnalogChannel,
+    data: &[f64],
+) -> (AnalogChannel, Vec<f64>) {
+    derive(kind, channel, data)
+}
+
+/// As `derive_channel()`, dealing with missing values according to `policy`.
+pub fn derive_channel_with_policy(
+    kind: DerivedKind,
+    channel: &AnalogChannel,
+    data: &[f64],
+    policy: MissingDataPolicy,
+) -> Result<(AnalogChannel, Vec<f64>), MissingDataError> {
+    Ok(derive(kind, channel, &policy.apply(data)?))
+}
+
+fn derive(kind: DerivedKind, channel: &AnalogChannel, data: &[f64]) -> (AnalogChannel, Vec<f64>) {
+    let (name, units, data) = match kind {
+        DerivedKind::Rms { window } => (
+            format!("RMS({})", channel.name),
+            channel.units.clone(),
+            rms(data, window),
+        ),
+        DerivedKind::Derivative { sample_rate_hz } => (
+            format!("d/dt({})", channel.name),
+            format!("{}/s", channel.units.trim()),
+            derivative(data, sample_rate_hz),
+        ),
+        DerivedKind::Absolute => (
+            format!("ABS({})", channel.name),
+            channel.units.clone(),
+            data.iter().map(|v| v.abs()).collect(),
+        ),
+        DerivedKind::Envelope { window } => (
+            format!("ENV({})", channel.name),
+            channel.units.clone(),
+            envelope(data, window),
+        ),
+    };
+
+    let min_value = data.iter().cloned().fold(f64::INFINITY, f64::min);
+    let max_value = data.iter().cloned().fold(f64::NEG_INFINITY, f64::max);
+
+    let derived = AnalogChannel {
+        index: 0,
+        name_raw: name.clone(),
+        name,
+        phase: channel.phase.clone(),
+        phase_raw: channel.phase_raw.clone(),
+        circuit_component_being_monitored: channel.circuit_component_being_monitored.clone(),
+        units,
+        min_value: if data.is_empty() { 0.0 } else { min_value },
+        max_value: if data.is_empty() { 0.0 } else { max_value },
+        multiplier: 1.0,
+        offset_adder: 0.0,
+        skew: channel.skew,
+        primary_factor: channel.primary_factor,
+        secondary_factor: channel.secondary_factor,
+        scaling_mode: channel.scaling_mode.clone(),
+        extensions: Default::default(),
+    };
+    (derived, data)
+}
+
+fn rms(data: &[f64], window: usize) -> Vec<f64> {
+    let window = window.max(1);
+    let mut sum_squares = 0.0;
+    let mut n = 0;
+
+    (0..data.len())
+        .map(|i| {
+            if !data[i].is_nan() {
+                sum_squares += data[i] * data[i];
+                n += 1;
+            }
+            if i >= window && !data[i - window].is_nan() {
+                sum_squares -= data[i - window] * data[i - window];
+                n -= 1;
+            }
+            if n == 0 {
+                return f64::NAN;
+            }
+            // Running sum can drift very slightly negative due to rounding.
+            (sum_squares.max(0.0) / n as f64).sqrt()
+        })
+        .collect()
+}
+
+fn derivative(data: &[f64], sample_rate_hz: f64) -> Vec<f64> {
+    let n = data.len();
+    if n < 2 {
+        return vec![0.0; n];
+    }
+
+    (0..n)
+        .map(|i| {
+            if i == 0 {
+                (data[1] - data[0]) * sample_rate_hz
+            } else if i == n - 1 {
+                (data[n - 1] - data[n - 2]) * sample_rate_hz
+            } else {
+                (data[i + 1] - data[i - 1]) * sample_rate_hz / 2.0
+            }
+        })
+        .collect()
+}
+
+fn envelope(data: &[f64], window: usize) -> Vec<f64> {
+    let window = window.max(1);
+
+    (0..data.len())
+        .map(|i| {
+            let start = (i + 1).saturating_sub(window);
+            data[start..=i]
+                .iter()
+                .filter(|v| !v.is_nan())
+                .fold(None, |peak: Option<f64>, v| {
+                    Some(peak.map_or(v.abs(), |p| p.max(v.abs())))
+                })
+                .unwrap_or(f64::NAN)
+        })
+        .collect()
+}
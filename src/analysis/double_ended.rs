@@ -0,0 +1,169 @@
+use crate::analysis::classify::classify_currents;
+use crate::analysis::fault::{FaultType, LineImpedance};
+use crate::analysis::phasor::{phasors, Phasor};
+use crate::analysis::sequence::sequence_components;
+
+/// Voltages and currents measured at one end of a line, for
+/// `double_ended_fault_location()`. Currents are positive flowing into the line.
+#[derive(Debug, Clone)]
+pub struct LineTerminal<'a> {
+    /// Values of the phase A, B and C voltage channels.
+    pub voltages: [&'a [f64]; 3],
+
+    /// Values of the phase A, B and C current channels.
+    pub currents: [&'a [f64]; 3],
+
+    pub samples_per_cycle: f64,
+
+    /// Index of the first sample after fault inception. As with `FaultLocatorConfig`,
+    /// phasors are taken from the cycle starting one full cycle after this.
+    pub fault_sample: usize,
+}
+
+/// Estimated location and type of a fault from measurements at both ends of the line.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct DoubleEndedLocation {
+    /// Distance from the local end of the line, in the same unit of length as the line
+    /// impedance.
+    pub distance: f64,
+    pub fault_type: FaultType,
+
+    /// Rough confidence in the estimate between 0 and 1, based on how stable the
+    /// estimate is over the measurement cycle and whether it falls on the line.
+    pub confidence: f64,
+}
+
+/// Estimate the distance to a fault using synchronised measurements from both ends of
+/// the line. Unlike the single-ended `fault_locator()`, this isn't affected by fault
+/// resistance or the load flowing before the fault, and doesn't need to be told the
+/// fault loop.
+///
+/// The voltage at the fault is the same whichever end it's worked out from:
+///
+/// ```text
+/// V_local - m Z I_local = V_remote - (1 - m) Z I_remote
+/// ```
+///
+/// where `Z` is the impedance of the whole line, which is solved for the fraction `m`
+/// of the line between the local end and the fault. The negative sequence components
+/// are used, or the positive sequence components for three-phase faults which have no
+/// negative sequence. The fault type comes from the fault current, i.e. the sum of the
+/// currents flowing into the line from both ends, classified as by `classify_fault()`.
+///
+/// Both terminals must be sampled at the same instants, e.g. having been aligned with
+/// `align_records()`, and have the same `samples_per_cycle` and `fault_sample`.
+/// Returns `None` if they don't, or if there isn't a full cycle of faulted data.
+pub fn double_ended_fault_location(
+    local: &LineTerminal,
+    remote: &LineTerminal,
+    line: &LineImpedance,
+) -> Option<DoubleEndedLocation> {
+    if local.samples_per_cycle != remote.samples_per_cycle
+        || local.fault_sample != remote.fault_sample
+    {
+        return None;
+    }
+
+    let n = local.samples_per_cycle.round() as usize;
+    let start = local.fault_sample + n.saturating_sub(1);
+    let local_phasors = TerminalPhasors::new(local);
+    let remote_phasors = TerminalPhasors::new(remote);
+
+    let measurements: Vec<[[Phasor; 3]; 4]> = (start..start + n)
+        .filter_map(|idx| {
+            let (v_local, i_local) = local_phasors.at(idx)?;
+            let (v_remote, i_remote) = remote_phasors.at(idx)?;
+            Some([v_local, i_local, v_remote, i_remote])
+        })
+        .collect();
+    if n < 2 || measurements.len() < n {
+        return None;
+    }
+
+    let fault_type = fault_type(&measurements[measurements.len() / 2])?;
+    let z = line.z1.scale(line.line_length);
+
+    let fractions: Vec<Phasor> = measurements
+        .iter()
+        .filter_map(|[v_local, i_local, v_remote, i_remote]| {
+            let sequence = |[a, b, c]: &[Phasor; 3]| {
+                let components = sequence_components(*a, *b, *c);
+                if fault_type.is_three_phase() {
+                    components.positive
+                } else {
+                    components.negative
+                }
+            };
+            let denominator = z * (sequence(i_local) + sequence(i_remote));
+            if denominator.magnitude() == 0.0 {
+                return None;
+            }
+            Some((sequence(v_local) - sequence(v_remote) + z * sequence(i_remote)) / denominator)
+        })
+        .collect();
+    if fractions.len() < n {
+        return None;
+    }
+
+    let distances: Vec<f64> = fractions.iter().map(|m| m.re * line.line_length).collect();
+    let mean = distances.iter().sum::<f64>() / distances.len() as f64;
+    let variance =
+        distances.iter().map(|d| (d - mean).powi(2)).sum::<f64>() / distances.len() as f64;
+    let spread = variance.sqrt() / line.line_length.abs().max(f64::EPSILON);
+
+    // The fraction should be real, so any imaginary part means the measurements don't
+    // quite agree, e.g. because the ends aren't perfectly synchronised.
+    let mean_imaginary = fractions.iter().map(|m| m.im.abs()).sum::<f64>() / fractions.len() as f64;
+
+    let mut confidence = 1.0 / (1.0 + 10.0 * spread + 10.0 * mean_imaginary);
+    if mean < 0.0 || mean > line.line_length {
+        confidence *= 0.5;
+    }
+
+    Some(DoubleEndedLocation {
+        distance: mean,
+        fault_type,
+        confidence,
+    })
+}
+
+/// Phasors of the voltage and current channels of a terminal.
+struct TerminalPhasors {
+    voltages: Vec<Vec<Option<Phasor>>>,
+    currents: Vec<Vec<Option<Phasor>>>,
+}
+
+impl TerminalPhasors {
+    fn new(terminal: &LineTerminal) -> Self {
+        let estimate = |channels: &[&[f64]; 3]| {
+            channels
+                .iter()
+                .map(|c| phasors(c, terminal.samples_per_cycle))
+                .collect()
+        };
+        TerminalPhasors {
+            voltages: estimate(&terminal.voltages),
+            currents: estimate(&terminal.currents),
+        }
+    }
+
+    /// Phase voltages and currents at the sample.
+    fn at(&self, idx: usize) -> Option<([Phasor; 3], [Phasor; 3])> {
+        let phases = |phasors: &[Vec<Option<Phasor>>]| {
+            Some([
+                (*phasors[0].get(idx)?)?,
+                (*phasors[1].get(idx)?)?,
+                (*phasors[2].get(idx)?)?,
+            ])
+        };
+        Some((phases(&self.voltages)?, phases(&self.currents)?))
+    }
+}
+
+/// Type of fault from the fault current, which is the sum of the currents flowing into
+/// the line from both ends.
+fn fault_type(measurement: &[[Phasor; 3]; 4]) -> Option<FaultType> {
+    let [_, i_local, _, i_remote] = measurement;
+    let fault = [0, 1, 2].map(|k| i_local[k] + i_remote[k]);
+    Some(classify_currents(fault)?.fault_type)
+}
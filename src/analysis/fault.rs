@@ -0,0 +1,211 @@
+use crate::analysis::phasor::{phasors, Phasor};
+use crate::analysis::sequence::sequence_components;
+
+/// The loop of the power system network involved in a fault, which determines how the
+/// apparent impedance is calculated from the voltages and currents.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum FaultLoop {
+    Ag,
+    Bg,
+    Cg,
+    Ab,
+    Bc,
+    Ca,
+}
+
+/// Which phases are involved in a fault, and whether it's to ground.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum FaultType {
+    Ag,
+    Bg,
+    Cg,
+    Ab,
+    Bc,
+    Ca,
+    Abg,
+    Bcg,
+    Cag,
+    Abc,
+    Abcg,
+}
+
+impl FaultType {
+    /// Fault type involving the given phases, or `None` if there aren't enough phases
+    /// for it to be a fault, i.e. none at all or a single phase not to ground.
+    pub fn from_phases(a: bool, b: bool, c: bool, ground: bool) -> Option<Self> {
+        match (a, b, c, ground) {
+            (true, false, false, true) => Some(FaultType::Ag),
+            (false, true, false, true) => Some(FaultType::Bg),
+            (false, false, true, true) => Some(FaultType::Cg),
+            (true, true, false, false) => Some(FaultType::Ab),
+            (false, true, true, false) => Some(FaultType::Bc),
+            (true, false, true, false) => Some(FaultType::Ca),
+            (true, true, false, true) => Some(FaultType::Abg),
+            (false, true, true, true) => Some(FaultType::Bcg),
+            (true, false, true, true) => Some(FaultType::Cag),
+            (true, true, true, false) => Some(FaultType::Abc),
+            (true, true, true, true) => Some(FaultType::Abcg),
+            _ => None,
+        }
+    }
+
+    /// Label as used in fault reports, e.g. `AG` or `BC`.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            FaultType::Ag => "AG",
+            FaultType::Bg => "BG",
+            FaultType::Cg => "CG",
+            FaultType::Ab => "AB",
+            FaultType::Bc => "BC",
+            FaultType::Ca => "CA",
+            FaultType::Abg => "ABG",
+            FaultType::Bcg => "BCG",
+            FaultType::Cag => "CAG",
+            FaultType::Abc => "ABC",
+            FaultType::Abcg => "ABCG",
+        }
+    }
+
+    /// Phases A, B and C, for whether each is involved.
+    pub fn phases(&self) -> [bool; 3] {
+        let label = self.as_str();
+        [
+            label.contains('A'),
+            label.contains('B'),
+            label.contains('C'),
+        ]
+    }
+
+    pub fn involves_ground(&self) -> bool {
+        self.as_str().ends_with('G')
+    }
+
+    /// Balanced faults involving all three phases, which have no negative sequence
+    /// component.
+    pub fn is_three_phase(&self) -> bool {
+        matches!(self, FaultType::Abc | FaultType::Abcg)
+    }
+}
+
+/// Impedance of a transmission line per unit length. The unit of length can be
+/// anything (km, miles) so long as it's also used for `line_length`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct LineImpedance {
+    /// Positive sequence impedance in ohms per unit length.
+    pub z1: Phasor,
+
+    /// Zero sequence impedance in ohms per unit length.
+    pub z0: Phasor,
+
+    pub line_length: f64,
+}
+
+impl LineImpedance {
+    /// Residual compensation factor k0 = (Z0 - Z1) / 3Z1.
+    pub fn residual_compensation(&self) -> Phasor {
+        (self.z0 - self.z1) / self.z1.scale(3.0)
+    }
+}
+
+/// Inputs to the single-ended fault locator, using the voltages and currents measured
+/// at one end of the line.
+#[derive(Debug, Clone)]
+pub struct FaultLocatorConfig<'a> {
+    /// Values of the phase A, B and C voltage channels.
+    pub voltages: [&'a [f64]; 3],
+
+    /// Values of the phase A, B and C current channels.
+    pub currents: [&'a [f64]; 3],
+
+    pub samples_per_cycle: f64,
+
+    /// Index of the first sample after fault inception. Phasors are taken from the
+    /// cycle starting one full cycle after this, by which time the DFT window covers
+    /// only faulted data.
+    pub fault_sample: usize,
+
+    pub fault_loop: FaultLoop,
+    pub line: LineImpedance,
+}
+
+/// Estimated location of a fault.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct FaultLocation {
+    /// Distance from the measuring end of the line, in the same unit of length as the
+    /// line impedance.
+    pub distance: f64,
+
+    /// Rough confidence in the estimate between 0 and 1, based on how stable the
+    /// estimate is over the measurement cycle and whether it falls on the line.
+    pub confidence: f64,
+}
+
+/// Estimate the distance to a fault using the single-ended reactance method: the
+/// reactance of the apparent impedance seen from the measuring end is proportional to
+/// the distance to the fault, and unaffected by (resistive) fault resistance.
+///
+/// Returns `None` if there isn't a full cycle of faulted data available.
+pub fn fault_locator(config: &FaultLocatorConfig) -> Option<FaultLocation> {
+    let v: Vec<Vec<Option<Phasor>>> = config
+        .voltages
+        .iter()
+        .map(|c| phasors(c, config.samples_per_cycle))
+        .collect();
+    let i: Vec<Vec<Option<Phasor>>> = config
+        .currents
+        .iter()
+        .map(|c| phasors(c, config.samples_per_cycle))
+        .collect();
+
+    let n = config.samples_per_cycle.round() as usize;
+    let start = config.fault_sample + n.saturating_sub(1);
+    let end = start + n;
+    let k0 = config.line.residual_compensation();
+
+    let distances: Vec<f64> = (start..end)
+        .filter_map(|idx| {
+            let va = (*v[0].get(idx)?)?;
+            let vb = (*v[1].get(idx)?)?;
+            let vc = (*v[2].get(idx)?)?;
+            let ia = (*i[0].get(idx)?)?;
+            let ib = (*i[1].get(idx)?)?;
+            let ic = (*i[2].get(idx)?)?;
+
+            let residual = sequence_components(ia, ib, ic).zero.scale(3.0);
+            let (loop_v, loop_i) = match config.fault_loop {
+                FaultLoop::Ag => (va, ia + k0 * residual),
+                FaultLoop::Bg => (vb, ib + k0 * residual),
+                FaultLoop::Cg => (vc, ic + k0 * residual),
+                FaultLoop::Ab => (va - vb, ia - ib),
+                FaultLoop::Bc => (vb - vc, ib - ic),
+                FaultLoop::Ca => (vc - va, ic - ia),
+            };
+
+            if loop_i.magnitude() == 0.0 || config.line.z1.im == 0.0 {
+                return None;
+            }
+            let z = loop_v / loop_i;
+
+            Some(z.im / config.line.z1.im)
+        })
+        .collect();
+
+    if distances.len() < n {
+        return None;
+    }
+
+    let mean = distances.iter().sum::<f64>() / distances.len() as f64;
+    let variance =
+        distances.iter().map(|d| (d - mean).powi(2)).sum::<f64>() / distances.len() as f64;
+    let spread = variance.sqrt() / config.line.line_length.abs().max(f64::EPSILON);
+
+    let mut confidence = 1.0 / (1.0 + 10.0 * spread);
+    if mean < 0.0 || mean > config.line.line_length {
+        confidence *= 0.5;
+    }
+
+    Some(FaultLocation {
+        distance: mean,
+        confidence,
+    })
+}
@@ -0,0 +1,30 @@
+//! Analysis of the waveforms in parsed COMTRADE records.
+
+mod align;
+mod classify;
+mod derived;
+mod double_ended;
+mod fault;
+mod phasor;
+mod power;
+mod power_quality;
+mod sequence;
+mod wavefront;
+mod windows;
+
+pub use align::{align_records, AlignedRecords, Interpolation};
+pub use classify::{classify_fault, FaultClassification, FaultClassifierConfig};
+pub use derived::{derive_channel, derive_channel_with_policy, DerivedKind};
+pub use double_ended::{double_ended_fault_location, DoubleEndedLocation, LineTerminal};
+pub use fault::{
+    fault_locator, FaultLocation, FaultLocatorConfig, FaultLoop, FaultType, LineImpedance,
+};
+pub use phasor::{phasors, phasors_with_policy, Phasor};
+pub use power::{phasor_power, phasor_power_with_policy, power, PowerQuantities};
+pub use power_quality::{
+    flicker_severity, rms_half_cycle, voltage_events, voltage_unbalance, Unbalance, VoltageEvent,
+    VoltageEventConfig, VoltageEventKind,
+};
+pub use sequence::{sequence_components, SequenceComponents};
+pub use wavefront::{wavefront_arrival, WavefrontArrival, WavefrontConfig, WavefrontDetector};
+pub use windows::{select_windows, FaultWindows, WindowSelectionConfig};
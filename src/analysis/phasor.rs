@@ -0,0 +1,138 @@
+use std::f64::consts::{PI, SQRT_2};
+use std::ops::{Add, Div, Mul, Sub};
+
+use crate::{MissingDataError, MissingDataPolicy};
+
+/// Complex value representing the fundamental frequency component of a waveform, with
+/// magnitude as an RMS value.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct Phasor {
+    pub re: f64,
+    pub im: f64,
+}
+
+impl Phasor {
+    pub fn new(re: f64, im: f64) -> Self {
+        Phasor { re, im }
+    }
+
+    /// Create phasor from magnitude and angle in radians.
+    pub fn from_polar(magnitude: f64, angle: f64) -> Self {
+        Phasor {
+            re: magnitude * angle.cos(),
+            im: magnitude * angle.sin(),
+        }
+    }
+
+    pub fn magnitude(&self) -> f64 {
+        self.re.hypot(self.im)
+    }
+
+    /// Angle in radians, between -π and π.
+    pub fn angle(&self) -> f64 {
+        self.im.atan2(self.re)
+    }
+
+    pub fn conj(&self) -> Self {
+        Phasor {
+            re: self.re,
+            im: -self.im,
+        }
+    }
+
+    pub fn scale(&self, factor: f64) -> Self {
+        Phasor {
+            re: self.re * factor,
+            im: self.im * factor,
+        }
+    }
+}
+
+impl Add for Phasor {
+    type Output = Phasor;
+
+    fn add(self, other: Phasor) -> Phasor {
+        Phasor::new(self.re + other.re, self.im + other.im)
+    }
+}
+
+impl Sub for Phasor {
+    type Output = Phasor;
+
+    fn sub(self, other: Phasor) -> Phasor {
+        Phasor::new(self.re - other.re, self.im - other.im)
+    }
+}
+
+impl Mul for Phasor {
+    type Output = Phasor;
+
+    fn mul(self, other: Phasor) -> Phasor {
+        Phasor::new(
+            self.re * other.re - self.im * other.im,
+            self.re * other.im + self.im * other.re,
+        )
+    }
+}
+
+impl Div for Phasor {
+    type Output = Phasor;
+
+    fn div(self, other: Phasor) -> Phasor {
+        let denominator = other.re * other.re + other.im * other.im;
+        (self * other.conj()).scale(1.0 / denominator)
+    }
+}
+
+/// Estimate the fundamental frequency phasor of a channel's values at each sample using a
+/// full-cycle DFT over the trailing `samples_per_cycle` samples (rounded to the
+/// nearest whole number of samples). See `Comtrade::samples_per_cycle()`.
+///
+/// The phasors are referenced to the start of the record, so a steady sinusoid gives a
+/// constant phasor. There isn't a full cycle of data for the first few samples, so
+/// these are `None`. Missing values are skipped, so phasors whose cycle includes a
+/// missing value are also `None`.
+pub fn phasors(data: &[f64], samples_per_cycle: f64) -> Vec<Option<Phasor>> {
+    dft_phasors(data, samples_per_cycle)
+}
+
+/// As `phasors()`, dealing with missing values according to `policy`.
+pub fn phasors_with_policy(
+    data: &[f64],
+    samples_per_cycle: f64,
+    policy: MissingDataPolicy,
+) -> Result<Vec<Option<Phasor>>, MissingDataError> {
+    Ok(dft_phasors(&policy.apply(data)?, samples_per_cycle))
+}
+
+fn dft_phasors(data: &[f64], samples_per_cycle: f64) -> Vec<Option<Phasor>> {
+    let n = samples_per_cycle.round() as usize;
+    if n < 2 {
+        return vec![None; data.len()];
+    }
+
+    let (cos, sin): (Vec<f64>, Vec<f64>) = (0..n)
+        .map(|k| {
+            let theta = 2.0 * PI * k as f64 / n as f64;
+            (theta.cos(), theta.sin())
+        })
+        .unzip();
+    let scale = SQRT_2 / n as f64;
+
+    (0..data.len())
+        .map(|i| {
+            if i + 1 < n {
+                return None;
+            }
+            let start = i + 1 - n;
+            if data[start..=i].iter().any(|v| v.is_nan()) {
+                return None;
+            }
+            let (re, im) = (start..=i).fold((0.0, 0.0), |(re, im), j| {
+                let k = j % n;
+                (re + data[j] * cos[k], im - data[j] * sin[k])
+            });
+            Some(Phasor::new(re * scale, im * scale))
+        })
+        .collect()
+}
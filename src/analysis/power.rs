@@ -0,0 +1,78 @@
+use crate::analysis::phasor::{phasors, phasors_with_policy, Phasor};
+use crate::{MissingDataError, MissingDataPolicy};
+
+/// Power flow at a single point in time, calculated from the fundamental frequency
+/// voltage and current phasors.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PowerQuantities {
+    /// Active power P.
+    pub active: f64,
+
+    /// Reactive power Q, positive for lagging current.
+    pub reactive: f64,
+
+    /// Apparent power S.
+    pub apparent: f64,
+
+    /// Ratio of active to apparent power, or 0 if there is no apparent power.
+    pub power_factor: f64,
+}
+
+/// Instantaneous power at each sample, i.e. the product of the voltage and current
+/// samples. If the channels have different lengths, the result is as long as the
+/// shortest one.
+pub fn power(v_data: &[f64], i_data: &[f64]) -> Vec<f64> {
+    v_data
+        .iter()
+        .zip(i_data.iter())
+        .map(|(v, i)| v * i)
+        .collect()
+}
+
+/// Active, reactive and apparent power and power factor at each sample, calculated
+/// from the phasors of the voltage and current channels. As with `phasors()`, values
+/// before the first full cycle, or where a phasor's cycle includes a missing value, are
+/// `None`.
+pub fn phasor_power(
+    v_data: &[f64],
+    i_data: &[f64],
+    samples_per_cycle: f64,
+) -> Vec<Option<PowerQuantities>> {
+    power_from_phasors(
+        phasors(v_data, samples_per_cycle),
+        phasors(i_data, samples_per_cycle),
+    )
+}
+
+/// As `phasor_power()`, dealing with missing values according to `policy`.
+pub fn phasor_power_with_policy(
+    v_data: &[f64],
+    i_data: &[f64],
+    samples_per_cycle: f64,
+    policy: MissingDataPolicy,
+) -> Result<Vec<Option<PowerQuantities>>, MissingDataError> {
+    Ok(power_from_phasors(
+        phasors_with_policy(v_data, samples_per_cycle, policy)?,
+        phasors_with_policy(i_data, samples_per_cycle, policy)?,
+    ))
+}
+
+fn power_from_phasors(
+    v_phasors: Vec<Option<Phasor>>,
+    i_phasors: Vec<Option<Phasor>>,
+) -> Vec<Option<PowerQuantities>> {
+    v_phasors
+        .into_iter()
+        .zip(i_phasors)
+        .map(|(v, i)| {
+            let s = v? * i?.conj();
+            let apparent = s.magnitude();
+            Some(PowerQuantities {
+                active: s.re,
+                reactive: s.im,
+                apparent,
+                power_factor: if apparent > 0.0 { s.re / apparent } else { 0.0 },
+            })
+        })
+        .collect()
+}
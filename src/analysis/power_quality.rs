@@ -0,0 +1,325 @@
+use crate::analysis::phasor::phasors;
+use crate::analysis::sequence::sequence_components;
+
+/// Smallest voltage change counted by `flicker_severity()`, in percent of the nominal
+/// voltage.
+const FLICKER_DEAD_BAND: f64 = 0.1;
+
+/// Observation period of the short-term flicker severity, in seconds.
+const PST_PERIOD: f64 = 600.0;
+
+/// Thresholds for `voltage_events()`. The thresholds and hysteresis are fractions of
+/// the nominal voltage.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct VoltageEventConfig {
+    /// Declared RMS voltage of the channels, in their units, e.g. 63.5 for phase
+    /// voltages on a 110 kV system recorded in kV.
+    pub nominal_voltage: f64,
+    pub samples_per_cycle: f64,
+    pub line_frequency: f64,
+
+    /// Dips start when the voltage falls below this, usually 0.9.
+    pub dip_threshold: f64,
+
+    /// Swells start when the voltage rises above this, usually 1.1.
+    pub swell_threshold: f64,
+
+    /// Interruptions start when the voltage falls below this, usually 0.05.
+    pub interruption_threshold: f64,
+
+    /// How far the voltage has to come back past the threshold for an event to end,
+    /// usually 0.02.
+    pub hysteresis: f64,
+}
+
+impl VoltageEventConfig {
+    /// Config with the usual thresholds from IEC 61000-4-30.
+    pub fn new(nominal_voltage: f64, samples_per_cycle: f64, line_frequency: f64) -> Self {
+        VoltageEventConfig {
+            nominal_voltage,
+            samples_per_cycle,
+            line_frequency,
+            dip_threshold: 0.9,
+            swell_threshold: 1.1,
+            interruption_threshold: 0.05,
+            hysteresis: 0.02,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum VoltageEventKind {
+    Dip,
+    Swell,
+    Interruption,
+}
+
+/// Voltage dip, swell or interruption found by `voltage_events()`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct VoltageEvent {
+    pub kind: VoltageEventKind,
+
+    /// Index of the first RMS value of the event, as returned by `rms_half_cycle()`.
+    pub start_index: usize,
+
+    /// Time in seconds from the start of the channels to the end of the cycle of the
+    /// first RMS value of the event.
+    pub start_time: f64,
+
+    /// Duration in seconds, to the nearest half cycle.
+    pub duration: f64,
+
+    /// Lowest RMS voltage during a dip or interruption (the residual voltage), or the
+    /// highest during a swell, in the channels' units.
+    pub magnitude: f64,
+
+    /// `magnitude` as a fraction of the nominal voltage.
+    pub magnitude_pu: f64,
+
+    /// Index into the channels of the one which reached `magnitude`.
+    pub worst_channel: usize,
+
+    /// Whether the event both started and ended within the record. If it was already
+    /// going at the start or still going at the end, the duration only covers the
+    /// part that was recorded.
+    pub complete: bool,
+}
+
+/// RMS voltage over one cycle, refreshed every half cycle - the `Urms(1/2)` of IEC
+/// 61000-4-30. Value `k` is worked out over the cycle starting at sample `k` half
+/// cycles into the channel, with `samples_per_cycle` rounded to a whole number of
+/// samples. There are no values if there isn't a full cycle of data.
+///
+/// Missing values are skipped, so the RMS is over the values in the cycle which are
+/// there, and NaN if they're all missing.
+pub fn rms_half_cycle(data: &[f64], samples_per_cycle: f64) -> Vec<f64> {
+    let n = samples_per_cycle.round() as usize;
+    if n < 2 || data.len() < n {
+        return vec![];
+    }
+    let step = n / 2;
+
+    (0..=(data.len() - n) / step)
+        .map(|k| {
+            let (sum, count) = data[k * step..k * step + n]
+                .iter()
+                .filter(|v| !v.is_nan())
+                .fold((0.0, 0), |(sum, count), v| (sum + v * v, count + 1));
+            if count == 0 {
+                f64::NAN
+            } else {
+                (sum / count as f64).sqrt()
+            }
+        })
+        .collect()
+}
+
+/// Find voltage dips, swells and interruptions in the voltage channels, as in IEC
+/// 61000-4-30, from their `rms_half_cycle()` values.
+///
+/// For several channels, e.g. the three phase voltages, a dip starts when any channel
+/// falls below the dip threshold and ends once they've all risen above it plus the
+/// hysteresis, and likewise for swells. An interruption starts only once every channel
+/// has fallen below the interruption threshold, and ends as soon as any rises above it
+/// plus the hysteresis. Each kind of event is looked for on its own, so an interruption
+/// is also part of a dip.
+///
+/// The events are in the order they start, and missing RMS values don't count towards
+/// starting or ending them.
+pub fn voltage_events(voltages: &[&[f64]], config: &VoltageEventConfig) -> Vec<VoltageEvent> {
+    let rms: Vec<Vec<f64>> = voltages
+        .iter()
+        .map(|c| rms_half_cycle(c, config.samples_per_cycle))
+        .collect();
+    let len = rms.iter().map(|r| r.len()).min().unwrap_or(0);
+    let values: Vec<Vec<f64>> = (0..len)
+        .map(|k| rms.iter().map(|r| r[k] / config.nominal_voltage).collect())
+        .collect();
+
+    let dip = config.dip_threshold;
+    let swell = config.swell_threshold;
+    let interruption = config.interruption_threshold;
+    let hysteresis = config.hysteresis;
+    let detectors = [
+        Detector {
+            kind: VoltageEventKind::Dip,
+            starts: &|v| v.iter().any(|v| *v < dip),
+            ends: &|v| v.iter().any(|v| !v.is_nan()) && !v.iter().any(|v| *v < dip + hysteresis),
+        },
+        Detector {
+            kind: VoltageEventKind::Swell,
+            starts: &|v| v.iter().any(|v| *v > swell),
+            ends: &|v| v.iter().any(|v| !v.is_nan()) && !v.iter().any(|v| *v > swell - hysteresis),
+        },
+        Detector {
+            kind: VoltageEventKind::Interruption,
+            starts: &|v| !v.is_empty() && v.iter().all(|v| *v < interruption),
+            ends: &|v| v.iter().any(|v| *v >= interruption + hysteresis),
+        },
+    ];
+
+    let mut events: Vec<VoltageEvent> = detectors
+        .iter()
+        .flat_map(|d| d.detect(&values, config))
+        .collect();
+    events.sort_by_key(|e| e.start_index);
+    events
+}
+
+/// Looks for one kind of event in the per unit RMS values of all the channels at each
+/// half cycle.
+struct Detector<'a> {
+    kind: VoltageEventKind,
+    starts: &'a dyn Fn(&[f64]) -> bool,
+    ends: &'a dyn Fn(&[f64]) -> bool,
+}
+
+impl Detector<'_> {
+    fn detect(&self, values: &[Vec<f64>], config: &VoltageEventConfig) -> Vec<VoltageEvent> {
+        let mut events = vec![];
+        let mut start: Option<usize> = None;
+        for (k, v) in values.iter().enumerate() {
+            match start {
+                None if (self.starts)(v) => start = Some(k),
+                Some(s) if (self.ends)(v) => {
+                    events.push(self.event(&values[s..k], s, s > 0, config));
+                    start = None;
+                }
+                _ => {}
+            }
+        }
+        if let Some(s) = start {
+            events.push(self.event(&values[s..], s, false, config));
+        }
+        events
+    }
+
+    /// Event made up of the RMS values, the first of which is at `start`.
+    fn event(
+        &self,
+        values: &[Vec<f64>],
+        start: usize,
+        complete: bool,
+        config: &VoltageEventConfig,
+    ) -> VoltageEvent {
+        let sample_rate = config.samples_per_cycle * config.line_frequency;
+        let n = config.samples_per_cycle.round() as usize;
+        let half_cycle = (n / 2) as f64 / sample_rate;
+
+        let highest = self.kind == VoltageEventKind::Swell;
+        let (worst_channel, magnitude_pu) = values
+            .iter()
+            .flat_map(|v| v.iter().cloned().enumerate())
+            .filter(|(_, v)| !v.is_nan())
+            .fold(None, |worst: Option<(usize, f64)>, (i, v)| match worst {
+                Some((_, w)) if (highest && w >= v) || (!highest && w <= v) => worst,
+                _ => Some((i, v)),
+            })
+            .unwrap_or((0, f64::NAN));
+
+        VoltageEvent {
+            kind: self.kind,
+            start_index: start,
+            start_time: start as f64 * half_cycle + n as f64 / sample_rate,
+            duration: values.len() as f64 * half_cycle,
+            magnitude: magnitude_pu * config.nominal_voltage,
+            magnitude_pu,
+            worst_channel,
+            complete,
+        }
+    }
+}
+
+/// Voltage unbalance of a three-phase set of voltages, see `voltage_unbalance()`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Unbalance {
+    /// Negative sequence unbalance factor `u2`, the ratio of the negative to the positive
+    /// sequence voltage. This is what's usually meant by "voltage unbalance".
+    pub negative: f64,
+
+    /// Zero sequence unbalance factor `u0`, the ratio of the zero to the positive
+    /// sequence voltage.
+    pub zero: f64,
+}
+
+/// Voltage unbalance factors over the whole of the channels, as in IEC 61000-4-30. The
+/// sequence components are worked out from the phasors of each whole cycle, and
+/// aggregated by taking the RMS over the cycles. Cycles with missing values are left
+/// out.
+///
+/// Returns `None` if there isn't a full cycle of data or there's no positive sequence
+/// voltage.
+pub fn voltage_unbalance(voltages: [&[f64]; 3], samples_per_cycle: f64) -> Option<Unbalance> {
+    let n = samples_per_cycle.round() as usize;
+    if n < 2 {
+        return None;
+    }
+    let [a, b, c] = voltages.map(|channel| phasors(channel, samples_per_cycle));
+
+    let (mut zero, mut positive, mut negative, mut count) = (0.0, 0.0, 0.0, 0);
+    for idx in (n - 1..a.len().min(b.len()).min(c.len())).step_by(n) {
+        if let (Some(a), Some(b), Some(c)) = (a[idx], b[idx], c[idx]) {
+            let components = sequence_components(a, b, c);
+            zero += components.zero.magnitude().powi(2);
+            positive += components.positive.magnitude().powi(2);
+            negative += components.negative.magnitude().powi(2);
+            count += 1;
+        }
+    }
+    if count == 0 || positive == 0.0 {
+        return None;
+    }
+
+    Some(Unbalance {
+        negative: (negative / positive).sqrt(),
+        zero: (zero / positive).sqrt(),
+    })
+}
+
+/// Rough estimate of the short-term flicker severity `Pst` due to the voltage changes in
+/// the channel, using the analytical method of IEC 61000-3-3 rather than a full IEC
+/// 61000-4-15 flickermeter.
+///
+/// Voltage changes are found from the `rms_half_cycle()` values as the swings between
+/// successive highs and lows, ignoring any smaller than 0.1% of the nominal voltage.
+/// Each change of `d` percent adds a flicker impression time of `2.3 d^3.2` seconds,
+/// treating it as a step, and `Pst` is the 3.2th root of the total as a fraction of the
+/// 10 minute `Pst` observation period. The rest of the period is assumed to have no
+/// flicker, as records are much shorter than this. Dips and swells count as changes
+/// too, so records with them will give a high `Pst`.
+///
+/// Returns `None` if there isn't a full cycle of data.
+pub fn flicker_severity(data: &[f64], nominal_voltage: f64, samples_per_cycle: f64) -> Option<f64> {
+    let rms: Vec<f64> = rms_half_cycle(data, samples_per_cycle)
+        .into_iter()
+        .filter(|v| !v.is_nan())
+        .map(|v| v / nominal_voltage * 100.0)
+        .collect();
+    let first = *rms.first()?;
+
+    // Successive highs and lows, with the current extreme and whether it's a high.
+    let mut turning_points = vec![first];
+    let mut extreme: Option<(f64, bool)> = None;
+    for v in rms.iter().cloned() {
+        let last = turning_points[turning_points.len() - 1];
+        extreme = match extreme {
+            None if (v - last).abs() >= FLICKER_DEAD_BAND => Some((v, v > last)),
+            None => None,
+            Some((e, rising)) if (rising && v > e) || (!rising && v < e) => Some((v, rising)),
+            Some((e, rising)) if (e - v).abs() >= FLICKER_DEAD_BAND => {
+                turning_points.push(e);
+                Some((v, !rising))
+            }
+            keep => keep,
+        };
+    }
+    if let Some((e, _)) = extreme {
+        turning_points.push(e);
+    }
+
+    let impression_time: f64 = turning_points
+        .windows(2)
+        .map(|w| 2.3 * (w[1] - w[0]).abs().powf(3.2))
+        .sum();
+    Some((impression_time / PST_PERIOD).powf(1.0 / 3.2))
+}
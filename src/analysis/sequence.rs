@@ -0,0 +1,25 @@
+use std::f64::consts::PI;
+
+use crate::analysis::phasor::Phasor;
+
+/// Symmetrical components of a set of three-phase phasors.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct SequenceComponents {
+    pub zero: Phasor,
+    pub positive: Phasor,
+    pub negative: Phasor,
+}
+
+/// Calculate the zero, positive and negative sequence components of phasors for
+/// phases A, B and C (in ABC rotation).
+pub fn sequence_components(a: Phasor, b: Phasor, c: Phasor) -> SequenceComponents {
+    // The "a" operator - unit phasor at 120 degrees.
+    let op = Phasor::from_polar(1.0, 2.0 * PI / 3.0);
+    let op2 = op * op;
+
+    SequenceComponents {
+        zero: (a + b + c).scale(1.0 / 3.0),
+        positive: (a + op * b + op2 * c).scale(1.0 / 3.0),
+        negative: (a + op2 * b + op * c).scale(1.0 / 3.0),
+    }
+}
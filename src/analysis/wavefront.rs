@@ -0,0 +1,154 @@
+/// How `wavefront_arrival()` picks out the sharp change at the front of a travelling
+/// wave from the rest of the waveform.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum WavefrontDetector {
+    /// Difference between successive samples. Reacts to the sharpest fronts but is the
+    /// most sensitive to noise.
+    Derivative,
+
+    /// Haar wavelet, i.e. the difference between the means of the `scale` samples
+    /// either side. Larger scales smooth out more noise at the cost of blurring fronts
+    /// that rise over fewer samples than the scale. A `scale` of 1 is the same as
+    /// `Derivative`.
+    Wavelet { scale: usize },
+}
+
+/// Settings for `wavefront_arrival()`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct WavefrontConfig {
+    pub sample_rate_hz: f64,
+    pub detector: WavefrontDetector,
+
+    /// How many times the noise level the detector output has to reach for a wavefront
+    /// to have arrived. The noise level is estimated from the median of the absolute
+    /// detector output over the whole channel, so it isn't thrown off by the wavefront.
+    pub threshold: f64,
+}
+
+impl WavefrontConfig {
+    /// Config using the `Derivative` detector with a threshold of 10 times the noise.
+    pub fn new(sample_rate_hz: f64) -> Self {
+        WavefrontConfig {
+            sample_rate_hz,
+            detector: WavefrontDetector::Derivative,
+            threshold: 10.0,
+        }
+    }
+}
+
+/// First travelling wave to reach the measuring point, see `wavefront_arrival()`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct WavefrontArrival {
+    /// Index of the first sample at which the detector output crossed the threshold.
+    pub sample_index: usize,
+
+    /// Time of arrival in seconds since the first sample of the channel, to a fraction
+    /// of a sample. This is the steepest point of the front.
+    pub time: f64,
+
+    /// Detector output at the steepest point of the front, positive for a rising front
+    /// and negative for a falling one.
+    pub peak: f64,
+
+    /// Size of `peak` relative to the noise level.
+    pub signal_to_noise: f64,
+}
+
+/// Find when the first travelling wave reached the measuring point, from a channel
+/// sampled fast enough to see it (usually at 1 MHz or more).
+///
+/// The front is where the detector output first rises above the threshold. Its arrival
+/// time is taken as the steepest point of the front, with a parabola fitted through the
+/// largest detector output and the outputs either side placing it between samples. The
+/// channel must be sampled evenly at `config.sample_rate_hz`.
+///
+/// Returns `None` if no front crosses the threshold. Missing values are taken to be the
+/// same as the value before.
+pub fn wavefront_arrival(data: &[f64], config: &WavefrontConfig) -> Option<WavefrontArrival> {
+    if config.sample_rate_hz.is_nan() || config.sample_rate_hz <= 0.0 {
+        return None;
+    }
+
+    let output = detect(data, config.detector);
+    let magnitudes: Vec<f64> = output.iter().map(|v| v.abs()).collect();
+    let largest = magnitudes.iter().cloned().fold(0.0, f64::max);
+    if largest == 0.0 {
+        return None;
+    }
+
+    // Median absolute deviation as an estimate of the noise's standard deviation. This
+    // is floored relative to the largest output so that noiseless waveforms don't
+    // trigger on rounding errors.
+    let mut sorted = magnitudes.clone();
+    sorted.sort_by(|a, b| a.total_cmp(b));
+    let noise = (sorted[sorted.len() / 2] / 0.6745).max(largest * 1e-6);
+    let threshold = config.threshold * noise;
+
+    let start = magnitudes.iter().position(|m| *m > threshold)?;
+    let end = magnitudes[start..]
+        .iter()
+        .position(|m| *m <= threshold)
+        .map_or(magnitudes.len(), |len| start + len);
+    let peak = (start..end)
+        .max_by(|a, b| magnitudes[*a].total_cmp(&magnitudes[*b]))
+        .unwrap_or(start);
+
+    // Vertex of the parabola through the peak and the samples either side.
+    let offset = match (peak.checked_sub(1), magnitudes.get(peak + 1)) {
+        (Some(before), Some(after)) => {
+            let (y0, y1, y2) = (magnitudes[before], magnitudes[peak], *after);
+            let curvature = y0 - 2.0 * y1 + y2;
+            if curvature < 0.0 {
+                (0.5 * (y0 - y2) / curvature).clamp(-0.5, 0.5)
+            } else {
+                0.0
+            }
+        }
+        _ => 0.0,
+    };
+
+    // The detector output at sample k compares the samples before k with those from k
+    // on, so it's centred half a sample before k.
+    Some(WavefrontArrival {
+        sample_index: start,
+        time: (peak as f64 + offset - 0.5) / config.sample_rate_hz,
+        peak: output[peak],
+        signal_to_noise: magnitudes[peak] / noise,
+    })
+}
+
+/// Output of the detector at each sample, 0 where there aren't enough samples either
+/// side.
+fn detect(data: &[f64], detector: WavefrontDetector) -> Vec<f64> {
+    let scale = match detector {
+        WavefrontDetector::Derivative => 1,
+        WavefrontDetector::Wavelet { scale } => scale.max(1),
+    };
+
+    // Missing values take the value before, or the first value there is at the start.
+    let mut last = data.iter().cloned().find(|v| !v.is_nan()).unwrap_or(0.0);
+    let data: Vec<f64> = data
+        .iter()
+        .map(|v| {
+            if !v.is_nan() {
+                last = *v;
+            }
+            last
+        })
+        .collect();
+
+    let mut output = vec![0.0; data.len()];
+    if data.len() < 2 * scale {
+        return output;
+    }
+    let mut before: f64 = data[..scale].iter().sum();
+    let mut after: f64 = data[scale..2 * scale].iter().sum();
+    for k in scale..=data.len() - scale {
+        output[k] = (after - before) / scale as f64;
+        if k + scale < data.len() {
+            before += data[k] - data[k - scale];
+            after += data[k + scale] - data[k];
+        }
+    }
+    output
+}
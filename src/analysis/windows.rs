@@ -0,0 +1,146 @@
+use std::ops::Range;
+
+/// Channels and thresholds for `select_windows()`. The thresholds are fractions of each
+/// channel's largest absolute value.
+#[derive(Debug, Clone)]
+pub struct WindowSelectionConfig<'a> {
+    /// Values of the channels to look at, e.g. the phase currents and voltages.
+    pub channels: Vec<&'a [f64]>,
+    pub samples_per_cycle: f64,
+
+    /// How far a sample has to be from the one a cycle before for the fault to have
+    /// started, usually 0.1.
+    pub change_threshold: f64,
+
+    /// How far the RMS over a cycle can be from the RMS over the cycle before for the
+    /// waveform to be steady, usually 0.02.
+    pub steady_tolerance: f64,
+}
+
+impl<'a> WindowSelectionConfig<'a> {
+    /// Config with the usual thresholds.
+    pub fn new(channels: Vec<&'a [f64]>, samples_per_cycle: f64) -> Self {
+        WindowSelectionConfig {
+            channels,
+            samples_per_cycle,
+            change_threshold: 0.1,
+            steady_tolerance: 0.02,
+        }
+    }
+}
+
+/// Cycles of samples either side of a fault, from `select_windows()`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FaultWindows {
+    /// Index of the first sample found to differ from the cycle before.
+    pub change_sample: usize,
+
+    /// Last full cycle before the fault, or `None` if the record doesn't go back far
+    /// enough.
+    pub pre_fault: Option<Range<usize>>,
+
+    /// First full cycle after the fault once the RMS has settled, or `None` if it
+    /// doesn't settle for a cycle before the record ends, e.g. because the fault is
+    /// cleared too quickly.
+    pub post_fault: Option<Range<usize>>,
+}
+
+/// Pick a cycle of samples before a fault and a cycle after it, for the methods which
+/// need the phasors either side of a fault, e.g. `classify_fault()`.
+///
+/// The fault starts at the first sample, in any of the channels, which differs from the
+/// sample a cycle before by more than the change threshold. As a fault can start some
+/// way below the threshold, the pre-fault cycle ends a quarter of a cycle before that
+/// sample. The post-fault cycle is the first one from the start of the fault whose RMS
+/// is within the steady tolerance of the next cycle's, in every channel, so that it's
+/// clear of the transients at the start of the fault.
+///
+/// Missing values never count as a change or as steady. Returns `None` if there are no
+/// channels, a cycle is shorter than two samples or nothing changes.
+pub fn select_windows(config: &WindowSelectionConfig) -> Option<FaultWindows> {
+    let n = config.samples_per_cycle.round() as usize;
+    if n < 2 || config.channels.is_empty() {
+        return None;
+    }
+    let len = config.channels.iter().map(|c| c.len()).min()?;
+    let scales: Vec<f64> = config
+        .channels
+        .iter()
+        .map(|c| {
+            c.iter()
+                .filter(|v| !v.is_nan())
+                .fold(0.0, |m, v| v.abs().max(m))
+        })
+        .collect();
+
+    let change_sample = (n..len).find(|i| {
+        config
+            .channels
+            .iter()
+            .zip(scales.iter())
+            .any(|(c, scale)| (c[*i] - c[i - n]).abs() > config.change_threshold * scale)
+    })?;
+
+    let guard = n / 4;
+    let pre_fault = change_sample
+        .checked_sub(n + guard)
+        .map(|start| start..start + n);
+
+    let sums: Vec<SquareSums> = config
+        .channels
+        .iter()
+        .map(|c| SquareSums::new(&c[..len]))
+        .collect();
+    let post_fault = (change_sample..len.saturating_sub(2 * n) + 1)
+        .find(|start| {
+            sums.iter().zip(scales.iter()).all(|(sums, scale)| {
+                let this = sums.rms(*start..start + n);
+                let next = sums.rms(start + n..start + 2 * n);
+                (next - this).abs() <= config.steady_tolerance * scale
+            })
+        })
+        .map(|start| start..start + n);
+
+    Some(FaultWindows {
+        change_sample,
+        pre_fault,
+        post_fault,
+    })
+}
+
+/// Running sums of the squares of a channel's values, to get the RMS of any run of
+/// samples quickly.
+struct SquareSums {
+    squares: Vec<f64>,
+    counts: Vec<usize>,
+}
+
+impl SquareSums {
+    fn new(data: &[f64]) -> Self {
+        let mut squares = Vec::with_capacity(data.len() + 1);
+        let mut counts = Vec::with_capacity(data.len() + 1);
+        squares.push(0.0);
+        counts.push(0);
+        for value in data {
+            let (square, count) = if value.is_nan() {
+                (0.0, 0)
+            } else {
+                (value * value, 1)
+            };
+            squares.push(squares[squares.len() - 1] + square);
+            counts.push(counts[counts.len() - 1] + count);
+        }
+        SquareSums { squares, counts }
+    }
+
+    /// RMS of the values in `range` which aren't missing, or NaN if they all are.
+    fn rms(&self, range: Range<usize>) -> f64 {
+        let count = self.counts[range.end] - self.counts[range.start];
+        if count == 0 {
+            return f64::NAN;
+        }
+        ((self.squares[range.end] - self.squares[range.start]) / count as f64)
+            .max(0.0)
+            .sqrt()
+    }
+}
@@ -0,0 +1,281 @@
+//! `approx::AbsDiffEq` and `approx::RelativeEq` for the record and its parts, so that
+//! parsed records can be checked with `assert_abs_diff_eq!` and `assert_relative_eq!`.
+//!
+//! Floating point fields are compared within the given tolerance, and every other field
+//! must be exactly equal, as with `PartialEq`.
+
+use alloc::vec::Vec;
+
+use approx::{AbsDiffEq, RelativeEq};
+
+#[cfg(feature = "std")]
+use crate::analysis::{
+    DoubleEndedLocation, FaultClassification, FaultLocation, LineImpedance, Phasor,
+    PowerQuantities, SequenceComponents, Unbalance, VoltageEvent, WavefrontArrival,
+};
+use crate::{AnalogChannel, Comtrade, SamplingRate, SamplingSegment, StatusChannel};
+
+/// Compare every field, using `float_eq` for the floating point ones.
+trait FieldsEq {
+    fn fields_eq(&self, other: &Self, float_eq: &dyn Fn(f64, f64) -> bool) -> bool;
+}
+
+fn slices_eq<T: FieldsEq>(left: &[T], right: &[T], float_eq: &dyn Fn(f64, f64) -> bool) -> bool {
+    left.len() == right.len()
+        && left
+            .iter()
+            .zip(right.iter())
+            .all(|(l, r)| l.fields_eq(r, float_eq))
+}
+
+impl FieldsEq for f64 {
+    fn fields_eq(&self, other: &Self, float_eq: &dyn Fn(f64, f64) -> bool) -> bool {
+        float_eq(*self, *other)
+    }
+}
+
+impl<T: FieldsEq> FieldsEq for Vec<T> {
+    fn fields_eq(&self, other: &Self, float_eq: &dyn Fn(f64, f64) -> bool) -> bool {
+        slices_eq(self, other, float_eq)
+    }
+}
+
+impl FieldsEq for Comtrade {
+    fn fields_eq(&self, other: &Self, float_eq: &dyn Fn(f64, f64) -> bool) -> bool {
+        self.config.station_name == other.config.station_name
+            && self.config.recording_device_id == other.config.recording_device_id
+            && self.config.revision == other.config.revision
+            && self.config.num_total_channels == other.config.num_total_channels
+            && self.config.num_analog_channels == other.config.num_analog_channels
+            && self.config.num_status_channels == other.config.num_status_channels
+            && self.data.sample_numbers == other.data.sample_numbers
+            && self.data.timestamps == other.data.timestamps
+            && self.data.raw_timestamps == other.data.raw_timestamps
+            && slices_eq(
+                &self.config.analog_channels,
+                &other.config.analog_channels,
+                float_eq,
+            )
+            && self.config.status_channels == other.config.status_channels
+            && slices_eq(&self.data.analog_data, &other.data.analog_data, float_eq)
+            && self.data.analog_quality == other.data.analog_quality
+            && self.data.status_data == other.data.status_data
+            && float_eq(self.config.line_frequency, other.config.line_frequency)
+            && slices_eq(
+                &self.config.sampling_rates,
+                &other.config.sampling_rates,
+                float_eq,
+            )
+            && self.config.total_num_samples == other.config.total_num_samples
+            && self.config.start_time == other.config.start_time
+            && self.config.trigger_time == other.config.trigger_time
+            && self.config.data_format == other.config.data_format
+            && float_eq(
+                self.config.timestamp_multiplication_factor,
+                other.config.timestamp_multiplication_factor,
+            )
+            && self.config.time_offset == other.config.time_offset
+            && self.config.local_offset == other.config.local_offset
+            && self.config.time_quality == other.config.time_quality
+            && self.config.leap_second_status == other.config.leap_second_status
+            && self.config.extra_cfg_lines == other.config.extra_cfg_lines
+            && self.config.header == other.config.header
+            && self.config.extensions == other.config.extensions
+            && self.config.warnings == other.config.warnings
+            && self.config.timestamp_unit == other.config.timestamp_unit
+            && self.data.irregular_samples == other.data.irregular_samples
+    }
+}
+
+impl FieldsEq for AnalogChannel {
+    fn fields_eq(&self, other: &Self, float_eq: &dyn Fn(f64, f64) -> bool) -> bool {
+        self.index == other.index
+            && self.name == other.name
+            && self.name_raw == other.name_raw
+            && self.phase == other.phase
+            && self.phase_raw == other.phase_raw
+            && self.circuit_component_being_monitored == other.circuit_component_being_monitored
+            && self.units == other.units
+            && float_eq(self.min_value, other.min_value)
+            && float_eq(self.max_value, other.max_value)
+            && float_eq(self.multiplier, other.multiplier)
+            && float_eq(self.offset_adder, other.offset_adder)
+            && float_eq(self.skew, other.skew)
+            && float_eq(self.primary_factor, other.primary_factor)
+            && float_eq(self.secondary_factor, other.secondary_factor)
+            && self.scaling_mode == other.scaling_mode
+            && self.extensions == other.extensions
+    }
+}
+
+/// Status channels have no floating point fields, but implementing the traits lets
+/// them be compared in the same way as everything else.
+impl FieldsEq for StatusChannel {
+    fn fields_eq(&self, other: &Self, _: &dyn Fn(f64, f64) -> bool) -> bool {
+        self == other
+    }
+}
+
+impl FieldsEq for SamplingRate {
+    fn fields_eq(&self, other: &Self, float_eq: &dyn Fn(f64, f64) -> bool) -> bool {
+        float_eq(self.rate_hz, other.rate_hz) && self.end_sample_number == other.end_sample_number
+    }
+}
+
+impl FieldsEq for SamplingSegment {
+    fn fields_eq(&self, other: &Self, float_eq: &dyn Fn(f64, f64) -> bool) -> bool {
+        self.rate_index == other.rate_index
+            && float_eq(self.rate_hz, other.rate_hz)
+            && self.samples == other.samples
+    }
+}
+
+#[cfg(feature = "std")]
+impl FieldsEq for Phasor {
+    fn fields_eq(&self, other: &Self, float_eq: &dyn Fn(f64, f64) -> bool) -> bool {
+        float_eq(self.re, other.re) && float_eq(self.im, other.im)
+    }
+}
+
+#[cfg(feature = "std")]
+impl FieldsEq for SequenceComponents {
+    fn fields_eq(&self, other: &Self, float_eq: &dyn Fn(f64, f64) -> bool) -> bool {
+        self.zero.fields_eq(&other.zero, float_eq)
+            && self.positive.fields_eq(&other.positive, float_eq)
+            && self.negative.fields_eq(&other.negative, float_eq)
+    }
+}
+
+#[cfg(feature = "std")]
+impl FieldsEq for PowerQuantities {
+    fn fields_eq(&self, other: &Self, float_eq: &dyn Fn(f64, f64) -> bool) -> bool {
+        float_eq(self.active, other.active)
+            && float_eq(self.reactive, other.reactive)
+            && float_eq(self.apparent, other.apparent)
+            && float_eq(self.power_factor, other.power_factor)
+    }
+}
+
+#[cfg(feature = "std")]
+impl FieldsEq for LineImpedance {
+    fn fields_eq(&self, other: &Self, float_eq: &dyn Fn(f64, f64) -> bool) -> bool {
+        self.z1.fields_eq(&other.z1, float_eq)
+            && self.z0.fields_eq(&other.z0, float_eq)
+            && float_eq(self.line_length, other.line_length)
+    }
+}
+
+#[cfg(feature = "std")]
+impl FieldsEq for FaultLocation {
+    fn fields_eq(&self, other: &Self, float_eq: &dyn Fn(f64, f64) -> bool) -> bool {
+        float_eq(self.distance, other.distance) && float_eq(self.confidence, other.confidence)
+    }
+}
+
+#[cfg(feature = "std")]
+impl FieldsEq for DoubleEndedLocation {
+    fn fields_eq(&self, other: &Self, float_eq: &dyn Fn(f64, f64) -> bool) -> bool {
+        float_eq(self.distance, other.distance)
+            && self.fault_type == other.fault_type
+            && float_eq(self.confidence, other.confidence)
+    }
+}
+
+#[cfg(feature = "std")]
+impl FieldsEq for FaultClassification {
+    fn fields_eq(&self, other: &Self, float_eq: &dyn Fn(f64, f64) -> bool) -> bool {
+        self.fault_type == other.fault_type
+            && slices_eq(&self.phase_confidence, &other.phase_confidence, float_eq)
+            && float_eq(self.ground_confidence, other.ground_confidence)
+            && float_eq(self.confidence, other.confidence)
+    }
+}
+
+#[cfg(feature = "std")]
+impl FieldsEq for VoltageEvent {
+    fn fields_eq(&self, other: &Self, float_eq: &dyn Fn(f64, f64) -> bool) -> bool {
+        self.kind == other.kind
+            && self.start_index == other.start_index
+            && float_eq(self.start_time, other.start_time)
+            && float_eq(self.duration, other.duration)
+            && float_eq(self.magnitude, other.magnitude)
+            && float_eq(self.magnitude_pu, other.magnitude_pu)
+            && self.worst_channel == other.worst_channel
+            && self.complete == other.complete
+    }
+}
+
+#[cfg(feature = "std")]
+impl FieldsEq for Unbalance {
+    fn fields_eq(&self, other: &Self, float_eq: &dyn Fn(f64, f64) -> bool) -> bool {
+        float_eq(self.negative, other.negative) && float_eq(self.zero, other.zero)
+    }
+}
+
+#[cfg(feature = "std")]
+impl FieldsEq for WavefrontArrival {
+    fn fields_eq(&self, other: &Self, float_eq: &dyn Fn(f64, f64) -> bool) -> bool {
+        self.sample_index == other.sample_index
+            && float_eq(self.time, other.time)
+            && float_eq(self.peak, other.peak)
+            && float_eq(self.signal_to_noise, other.signal_to_noise)
+    }
+}
+
+macro_rules! impl_approx {
+    ($($(#[$attr:meta])* $type:ty),* $(,)?) => {
+        $(
+            $(#[$attr])*
+            impl AbsDiffEq for $type {
+                type Epsilon = f64;
+
+                fn default_epsilon() -> f64 {
+                    f64::default_epsilon()
+                }
+
+                fn abs_diff_eq(&self, other: &Self, epsilon: f64) -> bool {
+                    self.fields_eq(other, &|l, r| l.abs_diff_eq(&r, epsilon))
+                }
+            }
+
+            $(#[$attr])*
+            impl RelativeEq for $type {
+                fn default_max_relative() -> f64 {
+                    f64::default_max_relative()
+                }
+
+                fn relative_eq(&self, other: &Self, epsilon: f64, max_relative: f64) -> bool {
+                    self.fields_eq(other, &|l, r| l.relative_eq(&r, epsilon, max_relative))
+                }
+            }
+        )*
+    };
+}
+
+impl_approx!(
+    Comtrade,
+    AnalogChannel,
+    StatusChannel,
+    SamplingRate,
+    SamplingSegment,
+    #[cfg(feature = "std")]
+    Phasor,
+    #[cfg(feature = "std")]
+    SequenceComponents,
+    #[cfg(feature = "std")]
+    PowerQuantities,
+    #[cfg(feature = "std")]
+    LineImpedance,
+    #[cfg(feature = "std")]
+    FaultLocation,
+    #[cfg(feature = "std")]
+    DoubleEndedLocation,
+    #[cfg(feature = "std")]
+    FaultClassification,
+    #[cfg(feature = "std")]
+    VoltageEvent,
+    #[cfg(feature = "std")]
+    Unbalance,
+    #[cfg(feature = "std")]
+    WavefrontArrival,
+);
@@ -0,0 +1,223 @@
+//! Caching parsed records, so that analysing the same archive again doesn't mean parsing
+//! every record in it again.
+//!
+//! Records are cached as blobs, each one a record encoded as a protobuf `Record`
+//! message (see the `protobuf` module) and compressed with deflate, under a key which is
+//! a hash of the contents of the files it was parsed from. Anywhere that can store blobs
+//! by key, e.g. Redis or an object store, can hold the cache by implementing
+//! `RecordCache`. `DirectoryCache` keeps them as files in a directory.
+//!
+//! A cached record has everything the protobuf message does, which is all that analysis
+//! needs, but not e.g. the parse warnings or the time quality of 2013 records.
+
+use std::fmt;
+use std::fs;
+use std::io::{self, Read, Write};
+use std::path::PathBuf;
+
+use flate2::read::DeflateDecoder;
+use flate2::write::DeflateEncoder;
+use flate2::Compression;
+use prost::Message;
+
+use crate::fingerprint::Fnv1a;
+use crate::protobuf::ProtoRecord;
+use crate::source::RecordFiles;
+use crate::{Comtrade, ParseError};
+
+/// Marks the start of a cached record, followed by the version of the format.
+const MAGIC: &[u8; 4] = b"CTRC";
+const VERSION: u8 = 1;
+
+/// Why a record couldn't be fetched from or stored in a cache.
+#[derive(Debug)]
+pub enum CacheError<E> {
+    /// The cache itself failed, e.g. the connection to it was lost.
+    Storage(E),
+
+    /// The blob in the cache isn't a cached record, or is from a different version of
+    /// the format.
+    Corrupt(String),
+
+    /// The record wasn't in the cache and its files couldn't be parsed.
+    Parse(ParseError),
+}
+
+impl<E: fmt::Display> fmt::Display for CacheError<E> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CacheError::Storage(err) => write!(f, "record cache failed: {}", err),
+            CacheError::Corrupt(message) => write!(f, "invalid cached record: {}", message),
+            CacheError::Parse(err) => write!(f, "unable to parse record: {}", err),
+        }
+    }
+}
+
+impl<E: fmt::Debug + fmt::Display> std::error::Error for CacheError<E> {}
+
+/// Somewhere parsed records can be cached, e.g. Redis or an object store. Implementations
+/// only have to store and fetch blobs by key, and get the methods for caching records
+/// and parsing through the cache for free.
+///
+/// ```
+/// use std::collections::HashMap;
+/// use std::convert::Infallible;
+///
+/// use comtrade::cache::RecordCache;
+///
+/// /// Records cached in memory for as long as the process runs.
+/// #[derive(Default)]
+/// struct InMemory(HashMap<u64, Vec<u8>>);
+///
+/// impl RecordCache for InMemory {
+///     type Error = Infallible;
+///
+///     fn get(&mut self, key: u64) -> Result<Option<Vec<u8>>, Infallible> {
+///         Ok(self.0.get(&key).cloned())
+///     }
+///
+///     fn put(&mut self, key: u64, blob: &[u8]) -> Result<(), Infallible> {
+///         self.0.insert(key, blob.to_vec());
+///         Ok(())
+///     }
+/// }
+/// ```
+pub trait RecordCache {
+    type Error;
+
+    /// Blob stored under `key`, or `None` if there isn't one.
+    fn get(&mut self, key: u64) -> Result<Option<Vec<u8>>, Self::Error>;
+
+    /// Store `blob` under `key`, replacing anything already stored under it.
+    fn put(&mut self, key: u64, blob: &[u8]) -> Result<(), Self::Error>;
+
+    /// Record cached under `key`, or `None` if there isn't one.
+    fn get_record(&mut self, key: u64) -> Result<Option<Comtrade>, CacheError<Self::Error>> {
+        match self.get(key).map_err(CacheError::Storage)? {
+            Some(blob) => decode_record(&blob).map(Some),
+            None => Ok(None),
+        }
+    }
+
+    fn put_record(&mut self, key: u64, record: &Comtrade) -> Result<(), CacheError<Self::Error>> {
+        self.put(key, &encode_record(record))
+            .map_err(CacheError::Storage)
+    }
+
+    /// Record from the cache if its files have been parsed before, otherwise parse them
+    /// and cache the record for next time. A blob in the cache which can't be read,
+    /// e.g. because it was cached by a different version of this crate, is replaced.
+    fn parse(&mut self, files: &RecordFiles) -> Result<Comtrade, CacheError<Self::Error>> {
+        let key = files.fingerprint();
+        match self.get_record(key) {
+            Ok(Some(record)) => return Ok(record),
+            Ok(None) | Err(CacheError::Corrupt(_)) => {}
+            Err(err) => return Err(err),
+        }
+
+        let record = files.parse().map_err(CacheError::Parse)?;
+        self.put_record(key, &record)?;
+        Ok(record)
+    }
+}
+
+impl RecordFiles {
+    /// Hash of the contents of the files, which is the same on every platform and every
+    /// run. Unlike `Comtrade::fingerprint()` the files don't have to be parsed first,
+    /// so this is what `RecordCache` keys records by.
+    pub fn fingerprint(&self) -> u64 {
+        let mut hasher = Fnv1a::new();
+        let mut write_file = |file: Option<&[u8]>| match file {
+            Some(file) => {
+                hasher.write_u64(file.len() as u64 + 1);
+                hasher.write(file);
+            }
+            None => hasher.write_u64(0),
+        };
+        match self {
+            RecordFiles::Separate { cfg, dat, hdr, inf } => {
+                write_file(Some(cfg));
+                write_file(Some(dat));
+                write_file(hdr.as_deref());
+                write_file(inf.as_deref());
+            }
+            RecordFiles::Combined(cff) => write_file(Some(cff)),
+        }
+        hasher.finish()
+    }
+}
+
+/// Record as it's stored in a cache: a protobuf `Record` message compressed with
+/// deflate, after a short header.
+pub fn encode_record(record: &Comtrade) -> Vec<u8> {
+    let mut blob = MAGIC.to_vec();
+    blob.push(VERSION);
+    let mut encoder = DeflateEncoder::new(blob, Compression::fast());
+    encoder
+        .write_all(&ProtoRecord::from(record).encode_to_vec())
+        .and_then(|_| encoder.finish())
+        .expect("writing to a Vec can't fail")
+}
+
+/// Record from a blob written by `encode_record()`.
+pub fn decode_record<E>(blob: &[u8]) -> Result<Comtrade, CacheError<E>> {
+    let corrupt = |message: String| CacheError::Corrupt(message);
+    if blob.len() <= MAGIC.len() || &blob[..MAGIC.len()] != MAGIC {
+        return Err(corrupt("missing cached record header".to_string()));
+    }
+    if blob[MAGIC.len()] != VERSION {
+        return Err(corrupt(format!(
+            "unsupported version {}",
+            blob[MAGIC.len()]
+        )));
+    }
+
+    let mut encoded = Vec::new();
+    DeflateDecoder::new(&blob[MAGIC.len() + 1..])
+        .read_to_end(&mut encoded)
+        .map_err(|err| corrupt(err.to_string()))?;
+    let message =
+        ProtoRecord::decode(encoded.as_slice()).map_err(|err| corrupt(err.to_string()))?;
+    Comtrade::try_from(message).map_err(|err| corrupt(err.to_string()))
+}
+
+/// Cache keeping each record as a file in a directory, named after its key, e.g. on a
+/// local disk or a mounted object store.
+#[derive(Debug, Clone)]
+pub struct DirectoryCache {
+    dir: PathBuf,
+}
+
+impl DirectoryCache {
+    /// Cache in `dir`, which is created if it doesn't exist yet.
+    pub fn new<P: Into<PathBuf>>(dir: P) -> io::Result<Self> {
+        let dir = dir.into();
+        fs::create_dir_all(&dir)?;
+        Ok(DirectoryCache { dir })
+    }
+
+    fn path(&self, key: u64) -> PathBuf {
+        self.dir.join(format!("{:016x}.ctrc", key))
+    }
+}
+
+impl RecordCache for DirectoryCache {
+    type Error = io::Error;
+
+    fn get(&mut self, key: u64) -> io::Result<Option<Vec<u8>>> {
+        match fs::read(self.path(key)) {
+            Ok(blob) => Ok(Some(blob)),
+            Err(err) if err.kind() == io::ErrorKind::NotFound => Ok(None),
+            Err(err) => Err(err),
+        }
+    }
+
+    /// Writes the blob to a temporary file first and then renames it, so that a
+    /// process reading the cache at the same time never sees half a blob.
+    fn put(&mut self, key: u64, blob: &[u8]) -> io::Result<()> {
+        let path = self.path(key);
+        let partial = path.with_extension("ctrc.partial");
+        fs::write(&partial, blob)?;
+        fs::rename(&partial, &path)
+    }
+}
@@ -0,0 +1,237 @@
+//! Index of the records in an archive, for finding records by station or time without
+//! parsing every one of them again. Only the configuration of each record is read when
+//! it's indexed.
+//!
+//! With the `serde` feature the catalog can be serialized, e.g. saved to disk as JSON
+//! and loaded back in the next time rather than rescanning the whole archive.
+
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use chrono::NaiveDateTime;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use crate::{Comtrade, ComtradeParserBuilder, ParseError, ParseResult};
+
+/// Metadata about a single record in a `ComtradeCatalog`.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct CatalogEntry {
+    /// Path to the `.cfg` or `.cff` file of the record.
+    pub path: PathBuf,
+    pub station_name: String,
+    pub recording_device_id: String,
+    pub start_time: NaiveDateTime,
+    pub trigger_time: NaiveDateTime,
+
+    /// Duration of the record in seconds, see `Comtrade::duration()`.
+    pub duration: f64,
+    pub analog_channel_names: Vec<String>,
+    pub status_channel_names: Vec<String>,
+}
+
+impl CatalogEntry {
+    /// Entry for a record which was parsed from `path`. The record only needs the
+    /// configuration, but the duration can only be worked out from the samples if the
+    /// record has no sampling rates.
+    pub fn new<P: Into<PathBuf>>(path: P, record: &Comtrade) -> Self {
+        CatalogEntry {
+            path: path.into(),
+            station_name: record.config.station_name.trim().to_string(),
+            recording_device_id: record.config.recording_device_id.trim().to_string(),
+            start_time: record.config.start_time,
+            trigger_time: record.config.trigger_time,
+            duration: config_duration(record).unwrap_or_else(|| record.duration()),
+            analog_channel_names: record
+                .config
+                .analog_channels
+                .iter()
+                .map(|c| c.name.clone())
+                .collect(),
+            status_channel_names: record
+                .config
+                .status_channels
+                .iter()
+                .map(|c| c.name.clone())
+                .collect(),
+        }
+    }
+}
+
+/// Index of COMTRADE records, normally built by scanning archive directories with
+/// `scan_dir()`.
+///
+/// ```no_run
+/// use chrono::NaiveDate;
+/// use comtrade::ComtradeCatalog;
+///
+/// let mut catalog = ComtradeCatalog::new();
+/// let failures = catalog.scan_dir("/data/fault-records")?;
+/// for (path, error) in failures {
+///     eprintln!("skipped {}: {}", path.display(), error);
+/// }
+///
+/// let day = NaiveDate::from_ymd(2020, 2, 1).and_hms(0, 0, 0);
+/// for entry in catalog
+///     .by_station("SMARTSTATION")
+///     .filter(|e| e.trigger_time >= day)
+/// {
+///     println!("{} triggered at {}", entry.path.display(), entry.trigger_time);
+/// }
+/// # Ok::<(), std::io::Error>(())
+/// ```
+#[derive(Debug, Clone, PartialEq, Default)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct ComtradeCatalog {
+    entries: Vec<CatalogEntry>,
+}
+
+impl ComtradeCatalog {
+    pub fn new() -> Self {
+        ComtradeCatalog::default()
+    }
+
+    /// Index every `.cfg` and `.cff` file in `dir` and its subdirectories. Records
+    /// already in the catalog are re-indexed, so this can be run again to pick up new
+    /// or changed records.
+    ///
+    /// Files which can't be parsed are left out of the catalog and returned along with
+    /// the reason, so that one bad record doesn't stop the rest of the archive being
+    /// indexed. An error is only returned if a directory can't be read.
+    pub fn scan_dir<P: AsRef<Path>>(&mut self, dir: P) -> io::Result<Vec<(PathBuf, ParseError)>> {
+        let mut failures = vec![];
+
+        let mut dirs = vec![dir.as_ref().to_path_buf()];
+        while let Some(dir) = dirs.pop() {
+            let mut paths = fs::read_dir(&dir)?
+                .map(|entry| entry.map(|e| e.path()))
+                .collect::<io::Result<Vec<PathBuf>>>()?;
+            paths.sort();
+
+            for path in paths {
+                if path.is_dir() {
+                    dirs.push(path);
+                    continue;
+                }
+                if !is_record_path(&path) {
+                    continue;
+                }
+
+                match read_entry(&path) {
+                    Ok(entry) => self.insert(entry),
+                    Err(error) => {
+                        self.remove(&path);
+                        failures.push((path, error));
+                    }
+                }
+            }
+        }
+
+        Ok(failures)
+    }
+
+    /// Add an entry, replacing any existing entry for the same path.
+    pub fn insert(&mut self, entry: CatalogEntry) {
+        match self.entries.iter_mut().find(|e| e.path == entry.path) {
+            Some(existing) => *existing = entry,
+            None => self.entries.push(entry),
+        }
+    }
+
+    /// Remove the entry for the record at `path`, returning it if there was one.
+    pub fn remove<P: AsRef<Path>>(&mut self, path: P) -> Option<CatalogEntry> {
+        let index = self.entries.iter().position(|e| e.path == path.as_ref())?;
+        Some(self.entries.remove(index))
+    }
+
+    /// Remove the entries for records whose files no longer exist.
+    pub fn remove_missing(&mut self) {
+        self.entries.retain(|e| e.path.exists());
+    }
+
+    pub fn entries(&self) -> &[CatalogEntry] {
+        &self.entries
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Records from the station with this name, ignoring case and any padding.
+    pub fn by_station<'a>(
+        &'a self,
+        station_name: &'a str,
+    ) -> impl Iterator<Item = &'a CatalogEntry> {
+        self.entries
+            .iter()
+            .filter(move |e| e.station_name.eq_ignore_ascii_case(station_name.trim()))
+    }
+
+    /// Records triggered from `start` up to but not including `end`.
+    pub fn by_time_range(
+        &self,
+        start: NaiveDateTime,
+        end: NaiveDateTime,
+    ) -> impl Iterator<Item = &CatalogEntry> {
+        self.entries
+            .iter()
+            .filter(move |e| e.trigger_time >= start && e.trigger_time < end)
+    }
+}
+
+fn is_record_path(path: &Path) -> bool {
+    let extension = path
+        .extension()
+        .and_then(|e| e.to_str())
+        .map(|e| e.to_lowercase());
+    matches!(extension.as_deref(), Some("cfg") | Some("cff"))
+}
+
+fn read_entry(path: &Path) -> ParseResult<CatalogEntry> {
+    let file = fs::File::open(path)
+        .map_err(|e| ParseError::new(format!("unable to open {}: {}", path.display(), e)))?;
+    let builder = ComtradeParserBuilder::new();
+    let builder = if path
+        .extension()
+        .is_some_and(|e| e.eq_ignore_ascii_case("cff"))
+    {
+        builder.cff_file(io::BufReader::new(file))
+    } else {
+        builder.cfg_file(io::BufReader::new(file))
+    };
+    let config = builder.build().parse_config()?;
+
+    if config_duration(&config).is_some() {
+        Ok(CatalogEntry::new(path, &config))
+    } else {
+        // The timestamps in the data file are needed to know how long the record is.
+        Ok(CatalogEntry::new(path, &Comtrade::try_from(path)?))
+    }
+}
+
+/// Duration of the record worked out from the sampling rates in its configuration, in
+/// the same way as the timestamps are when the record is parsed. `None` if there are
+/// no sampling rates, so the timestamps in the data file are needed.
+fn config_duration(record: &Comtrade) -> Option<f64> {
+    let last_sample = record
+        .config
+        .sampling_rates
+        .last()
+        .map(|r| r.end_sample_number)
+        .filter(|n| *n > 0)?;
+    let rate = record
+        .config
+        .sampling_rates
+        .iter()
+        .find(|r| last_sample <= r.end_sample_number)
+        .map(|r| r.rate_hz)
+        .filter(|rate| *rate > 0.0)?;
+
+    Some((last_sample - 1) as f64 / rate)
+}
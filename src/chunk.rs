@@ -0,0 +1,304 @@
+//! Splitting records into messages of bounded size and putting them back together, for
+//! streaming fault records through message buses like Kafka which limit the size of
+//! each message.
+//!
+//! A record is sent as a config message, its `.cfg` file, followed by data messages
+//! holding whole scans of its `.dat` file, in the record's data format. Each message
+//! starts with a header giving the ID of the record it's part of, its sequence number,
+//! with the config message as 0, and the number of messages the record was split into,
+//! so that the messages can arrive in any order, interleaved with those of other
+//! records, and more than once.
+
+use alloc::collections::{BTreeMap, BTreeSet, VecDeque};
+use alloc::format;
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::fmt;
+
+use byteorder::{ByteOrder, LittleEndian};
+
+use crate::{Comtrade, ComtradeParser, ComtradeWriter, DataFormat, ParseError};
+
+/// Number of the most recently completed records `RecordReassembler` remembers, to drop
+/// messages for them which are delivered again afterwards.
+const MAX_COMPLETED_RECORDS: usize = 1024;
+
+/// Marks the start of an encoded `RecordMessage`.
+const MAGIC: &[u8; 4] = b"CTRM";
+
+/// Size in bytes of the header at the start of each encoded message, counted towards
+/// the maximum message size.
+pub const HEADER_SIZE: usize = 20;
+
+/// Why a record couldn't be split into messages or put back together.
+#[derive(Debug)]
+pub enum ChunkError {
+    /// The maximum message size is too small to hold the `.cfg` file or a single scan,
+    /// which needs messages of at least `needed` bytes.
+    MessageTooSmall { needed: usize },
+
+    /// The bytes aren't a record message, or the message doesn't agree with those
+    /// already received for the same record.
+    InvalidMessage(String),
+
+    /// The record's messages were all received but its files couldn't be parsed.
+    Parse(ParseError),
+}
+
+impl fmt::Display for ChunkError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ChunkError::MessageTooSmall { needed } => write!(
+                f,
+                "messages must be at least {} bytes to split the record",
+                needed
+            ),
+            ChunkError::InvalidMessage(message) => write!(f, "invalid record message: {}", message),
+            ChunkError::Parse(err) => write!(f, "unable to parse reassembled record: {}", err),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for ChunkError {}
+
+/// Part of a record, from `split_record()`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RecordMessage {
+    /// Identifies the record across its messages, `Comtrade::fingerprint()` by default.
+    pub record_id: u64,
+
+    /// Position of the message in the record, with the config message as 0.
+    pub sequence: u32,
+
+    /// Number of messages the record was split into, including the config message.
+    pub num_messages: u32,
+
+    /// The `.cfg` file for the config message, otherwise part of the `.dat` file.
+    pub payload: Vec<u8>,
+}
+
+impl RecordMessage {
+    pub fn is_config(&self) -> bool {
+        self.sequence == 0
+    }
+
+    /// The message with its header, as it's sent.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(HEADER_SIZE + self.payload.len());
+        bytes.extend_from_slice(MAGIC);
+        let mut header = [0; HEADER_SIZE - 4];
+        LittleEndian::write_u64(&mut header[0..8], self.record_id);
+        LittleEndian::write_u32(&mut header[8..12], self.sequence);
+        LittleEndian::write_u32(&mut header[12..16], self.num_messages);
+        bytes.extend_from_slice(&header);
+        bytes.extend_from_slice(&self.payload);
+        bytes
+    }
+
+    /// Message from the bytes written by `to_bytes()`.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, ChunkError> {
+        if bytes.len() < HEADER_SIZE || &bytes[0..4] != MAGIC {
+            return Err(ChunkError::InvalidMessage(
+                "missing record message header".into(),
+            ));
+        }
+        let message = RecordMessage {
+            record_id: LittleEndian::read_u64(&bytes[4..12]),
+            sequence: LittleEndian::read_u32(&bytes[12..16]),
+            num_messages: LittleEndian::read_u32(&bytes[16..20]),
+            payload: bytes[HEADER_SIZE..].to_vec(),
+        };
+        if message.sequence >= message.num_messages {
+            return Err(ChunkError::InvalidMessage(format!(
+                "message {} of a record split into {}",
+                message.sequence, message.num_messages
+            )));
+        }
+        Ok(message)
+    }
+}
+
+/// Split a record into messages of at most `max_message_size` bytes each once encoded
+/// with `RecordMessage::to_bytes()`, a config message followed by as few data messages
+/// as will hold its samples, with the record's fingerprint as their ID.
+///
+/// Only the `.cfg` and `.dat` files are sent, so the header and anything which is only
+/// in the `.inf` file are lost.
+pub fn split_record(
+    record: &Comtrade,
+    max_message_size: usize,
+) -> Result<Vec<RecordMessage>, ChunkError> {
+    let writer = ComtradeWriter::new(record);
+    let cfg = writer.cfg().into_bytes();
+    let dat = writer.dat();
+    let capacity = max_message_size.saturating_sub(HEADER_SIZE);
+    if cfg.len() > capacity {
+        return Err(ChunkError::MessageTooSmall {
+            needed: HEADER_SIZE + cfg.len(),
+        });
+    }
+
+    // Data messages are split between scans, so each one can be decoded by itself.
+    let scan_ends: Vec<usize> = match record.config.data_format {
+        DataFormat::Ascii => dat
+            .iter()
+            .enumerate()
+            .filter(|(_, b)| **b == b'\n')
+            .map(|(i, _)| i + 1)
+            .collect(),
+        _ => {
+            let scan_size = record.bytes_per_scan().unwrap_or(dat.len()).max(1);
+            (1..=dat.len() / scan_size).map(|i| i * scan_size).collect()
+        }
+    };
+    let mut chunks: Vec<&[u8]> = Vec::new();
+    let (mut chunk_start, mut scan_start) = (0, 0);
+    for scan_end in scan_ends {
+        if scan_end - scan_start > capacity {
+            return Err(ChunkError::MessageTooSmall {
+                needed: HEADER_SIZE + scan_end - scan_start,
+            });
+        }
+        if scan_end - chunk_start > capacity {
+            chunks.push(&dat[chunk_start..scan_start]);
+            chunk_start = scan_start;
+        }
+        scan_start = scan_end;
+    }
+    if scan_start > chunk_start {
+        chunks.push(&dat[chunk_start..scan_start]);
+    }
+
+    let record_id = record.fingerprint();
+    let num_messages = chunks.len() as u32 + 1;
+    let mut messages = Vec::with_capacity(chunks.len() + 1);
+    messages.push(RecordMessage {
+        record_id,
+        sequence: 0,
+        num_messages,
+        payload: cfg,
+    });
+    for (i, chunk) in chunks.into_iter().enumerate() {
+        messages.push(RecordMessage {
+            record_id,
+            sequence: i as u32 + 1,
+            num_messages,
+            payload: chunk.to_vec(),
+        });
+    }
+    Ok(messages)
+}
+
+/// Puts records back together from the messages `split_record()` splits them into,
+/// received in any order and interleaved with those of other records.
+///
+/// ```rust
+/// use comtrade::chunk::{split_record, RecordReassembler};
+/// use comtrade::ComtradeParser;
+/// # let (cfg, dat) = (
+/// #     "STATION,DEVICE,1999\n1,1A,0D\n1,IA,A,,A,1,0,0,-99,99,1,1,P\n50\n1\n1000,2\n\
+/// #      01/01/2020,00:00:00.000000\n01/01/2020,00:00:00.000000\nASCII\n1\n",
+/// #     "1,0,5\n2,1000,6\n",
+/// # );
+///
+/// let record = ComtradeParser::from_bytes(cfg.as_bytes(), dat.as_bytes()).parse()?;
+/// let mut reassembler = RecordReassembler::new();
+/// let mut reassembled = None;
+/// for message in split_record(&record, 256)?.into_iter().rev() {
+///     reassembled = reassembler.push(message)?;
+/// }
+/// let reassembled = reassembled.unwrap();
+/// assert_eq!(reassembled.config.analog_channels, record.config.analog_channels);
+/// assert_eq!(reassembled.data.analog_data, record.data.analog_data);
+/// # Ok::<(), Box<dyn std::error::Error>>(())
+/// ```
+#[derive(Debug, Default)]
+pub struct RecordReassembler {
+    pending: BTreeMap<u64, PendingRecord>,
+    completed: BTreeSet<u64>,
+    completed_order: VecDeque<u64>,
+}
+
+#[derive(Debug)]
+struct PendingRecord {
+    num_messages: u32,
+    payloads: BTreeMap<u32, Vec<u8>>,
+}
+
+impl RecordReassembler {
+    pub fn new() -> Self {
+        RecordReassembler::default()
+    }
+
+    /// Add a message, returning the record it's part of if that was the last message
+    /// needed. Messages already received are ignored, so they can be delivered more
+    /// than once, as are messages for any of the last 1024 records completed.
+    pub fn push(&mut self, message: RecordMessage) -> Result<Option<Comtrade>, ChunkError> {
+        if message.sequence >= message.num_messages {
+            return Err(ChunkError::InvalidMessage(format!(
+                "message {} of a record split into {}",
+                message.sequence, message.num_messages
+            )));
+        }
+        if self.completed.contains(&message.record_id) {
+            return Ok(None);
+        }
+
+        let pending = self
+            .pending
+            .entry(message.record_id)
+            .or_insert_with(|| PendingRecord {
+                num_messages: message.num_messages,
+                payloads: BTreeMap::new(),
+            });
+        if pending.num_messages != message.num_messages {
+            return Err(ChunkError::InvalidMessage(format!(
+                "record {:016x} split into both {} and {} messages",
+                message.record_id, pending.num_messages, message.num_messages
+            )));
+        }
+        pending
+            .payloads
+            .entry(message.sequence)
+            .or_insert(message.payload);
+        if pending.payloads.len() < pending.num_messages as usize {
+            return Ok(None);
+        }
+
+        self.mark_completed(message.record_id);
+        let mut payloads = self
+            .pending
+            .remove(&message.record_id)
+            .map(|pending| pending.payloads.into_values())
+            .into_iter()
+            .flatten();
+        let cfg = payloads.next().unwrap_or_default();
+        let dat: Vec<u8> = payloads.flatten().collect();
+        ComtradeParser::from_bytes(&cfg, &dat)
+            .parse()
+            .map(Some)
+            .map_err(ChunkError::Parse)
+    }
+
+    fn mark_completed(&mut self, record_id: u64) {
+        if self.completed_order.len() == MAX_COMPLETED_RECORDS {
+            if let Some(oldest) = self.completed_order.pop_front() {
+                self.completed.remove(&oldest);
+            }
+        }
+        self.completed.insert(record_id);
+        self.completed_order.push_back(record_id);
+    }
+
+    /// Number of records with some but not all of their messages received.
+    pub fn num_pending(&self) -> usize {
+        self.pending.len()
+    }
+
+    /// Forget the messages received for a record, e.g. once it's clear the rest
+    /// aren't coming. Returns whether there were any.
+    pub fn discard(&mut self, record_id: u64) -> bool {
+        self.pending.remove(&record_id).is_some()
+    }
+}
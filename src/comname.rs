@@ -0,0 +1,261 @@
+//! File names following the IEEE C37.232 naming convention (COMNAME), which puts when
+//! and where a record was made into the name of its files, e.g.
+//! `200201,000000000,+1h,SMARTSTATION,IED123,ACME,0.0325s.cfg`.
+//!
+//! The fields are separated by commas:
+//!
+//! 1. start date, `YYMMDD` (or `YYYYMMDD`)
+//! 2. start time, `HHMMSS` followed by the fraction of the second, e.g. `124316394`
+//! 3. time code, which is the offset from UTC, e.g. `+5h30`, `-8` or `0`
+//! 4. station name
+//! 5. device ID
+//! 6. company name
+//!
+//! Any fields after these are user-defined. If the first of them is a number of seconds
+//! with an `s` suffix, e.g. `1.5s`, it's taken as the duration of the record.
+
+use alloc::format;
+use alloc::string::String;
+use alloc::vec;
+use alloc::vec::Vec;
+
+use chrono::{FixedOffset, NaiveDate, NaiveDateTime, Timelike};
+
+use crate::{Comtrade, ParseError, ParseResult};
+
+/// Characters which can't appear in a field, because they separate the fields or aren't
+/// allowed in file names on some systems.
+const RESERVED_CHARS: &[char] = &[',', '/', '\\', ':', '*', '?', '"', '<', '>', '|'];
+
+/// Metadata from a COMNAME file name.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ComName {
+    pub start_time: NaiveDateTime,
+
+    /// Time code as written in the file name, see `offset()`.
+    pub time_code: String,
+    pub station_name: String,
+    pub device_id: String,
+    pub company_name: String,
+
+    /// Duration of the record in seconds, if the file name includes it.
+    pub duration: Option<f64>,
+
+    /// User-defined fields after the company name (and duration), kept verbatim.
+    pub user_fields: Vec<String>,
+}
+
+impl ComName {
+    /// Parse a file name, with or without its extension or directory.
+    pub fn parse(file_name: &str) -> ParseResult<Self> {
+        let file_name = file_name.rsplit(['/', '\\']).next().unwrap_or(file_name);
+        let stem = match file_name.rsplit_once('.') {
+            Some((stem, extension)) if extension.bytes().all(|b| b.is_ascii_alphabetic()) => stem,
+            _ => file_name,
+        };
+
+        let fields: Vec<&str> = stem.split(',').collect();
+        if fields.len() < 6 {
+            return Err(ParseError::new(format!(
+                "COMNAME file name {} has {} fields; expected at least 6",
+                file_name,
+                fields.len()
+            )));
+        }
+
+        let start_time = parse_start_time(fields[0], fields[1]).ok_or_else(|| {
+            ParseError::new(format!(
+                "invalid start date and time in COMNAME file name: {},{}",
+                fields[0], fields[1]
+            ))
+        })?;
+        if parse_time_code(fields[2]).is_none() {
+            return Err(ParseError::new(format!(
+                "invalid time code in COMNAME file name: {}",
+                fields[2]
+            )));
+        }
+
+        let mut user_fields = &fields[6..];
+        let duration = user_fields.first().and_then(|f| parse_duration(f));
+        if duration.is_some() {
+            user_fields = &user_fields[1..];
+        }
+
+        Ok(ComName {
+            start_time,
+            time_code: String::from(fields[2]),
+            station_name: String::from(fields[3]),
+            device_id: String::from(fields[4]),
+            company_name: String::from(fields[5]),
+            duration,
+            user_fields: user_fields.iter().map(|f| String::from(*f)).collect(),
+        })
+    }
+
+    /// Name for a record's files, taking the start time, offset, station name and
+    /// device ID from the record. The company name is left empty.
+    pub fn from_record(record: &Comtrade) -> Self {
+        ComName {
+            start_time: record.config.start_time,
+            time_code: record
+                .config
+                .time_offset
+                .map(format_time_code)
+                .unwrap_or_else(|| String::from("0")),
+            station_name: String::from(record.config.station_name.trim()),
+            device_id: String::from(record.config.recording_device_id.trim()),
+            company_name: String::new(),
+            duration: Some(record.duration()),
+            user_fields: vec![],
+        }
+    }
+
+    /// Offset from UTC given by the time code, or `None` if it isn't valid.
+    pub fn offset(&self) -> Option<FixedOffset> {
+        parse_time_code(&self.time_code)
+    }
+
+    /// File name with the given extension, e.g. `cfg`. Any reserved characters in the
+    /// fields, like commas, are replaced with underscores.
+    pub fn file_name(&self, extension: &str) -> String {
+        let micros = self.start_time.nanosecond() / 1000 % 1_000_000;
+        let fraction = if micros.is_multiple_of(1000) {
+            format!("{:03}", micros / 1000)
+        } else {
+            format!("{:06}", micros)
+        };
+
+        let mut fields = vec![
+            format!("{}", self.start_time.format("%y%m%d")),
+            format!("{}{}", self.start_time.format("%H%M%S"), fraction),
+            sanitize_field(&self.time_code),
+            sanitize_field(&self.station_name),
+            sanitize_field(&self.device_id),
+            sanitize_field(&self.company_name),
+        ];
+        if let Some(duration) = self.duration {
+            fields.push(format!("{}s", duration));
+        }
+        fields.extend(self.user_fields.iter().map(|f| sanitize_field(f)));
+
+        format!("{}.{}", fields.join(","), extension)
+    }
+
+    /// Record the fields in the record's `extensions`, keyed `COMNAME/<field>`, e.g.
+    /// `COMNAME/Station_Name`.
+    pub fn attach_to(&self, record: &mut Comtrade) {
+        let mut insert = |key: &str, value: String| {
+            record
+                .config
+                .extensions
+                .insert(format!("COMNAME/{}", key), value);
+        };
+
+        insert(
+            "Start_Time",
+            format!("{}", self.start_time.format("%Y-%m-%dT%H:%M:%S%.f")),
+        );
+        insert("Time_Code", self.time_code.clone());
+        insert("Station_Name", self.station_name.clone());
+        insert("Device_ID", self.device_id.clone());
+        insert("Company_Name", self.company_name.clone());
+        if let Some(duration) = self.duration {
+            insert("Duration", format!("{}", duration));
+        }
+        for (i, field) in self.user_fields.iter().enumerate() {
+            insert(&format!("User_Field_{}", i + 1), field.clone());
+        }
+    }
+}
+
+fn parse_start_time(date: &str, time: &str) -> Option<NaiveDateTime> {
+    if !date.bytes().all(|b| b.is_ascii_digit()) || !time.bytes().all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+
+    let (year, rest) = match date.len() {
+        // Same pivot as chrono's `%y`: 70-99 are 19xx, 00-69 are 20xx.
+        6 => {
+            let year: i32 = date[..2].parse().ok()?;
+            (
+                if year < 70 { 2000 + year } else { 1900 + year },
+                &date[2..],
+            )
+        }
+        8 => (date[..4].parse().ok()?, &date[4..]),
+        _ => return None,
+    };
+    let month = rest[..2].parse().ok()?;
+    let day = rest[2..].parse().ok()?;
+
+    if time.len() < 6 || time.len() > 15 {
+        return None;
+    }
+    let hour = time[..2].parse().ok()?;
+    let minute = time[2..4].parse().ok()?;
+    let second = time[4..6].parse().ok()?;
+    let fraction = &time[6..];
+    let nanoseconds = if fraction.is_empty() {
+        0
+    } else {
+        let digits: u32 = fraction.parse().ok()?;
+        digits * 10u32.pow(9 - fraction.len() as u32)
+    };
+
+    NaiveDate::from_ymd_opt(year, month, day)?.and_hms_nano_opt(hour, minute, second, nanoseconds)
+}
+
+/// Time code like `+5h30`, `-8`, `5h` or `0`, with hours and optionally minutes.
+fn parse_time_code(time_code: &str) -> Option<FixedOffset> {
+    let (sign, rest) = match time_code.strip_prefix('-') {
+        Some(rest) => (-1, rest),
+        None => (1, time_code.strip_prefix('+').unwrap_or(time_code)),
+    };
+    let (hours, minutes) = match rest.split_once('h') {
+        Some((hours, "")) => (hours, "0"),
+        Some((hours, minutes)) => (hours, minutes),
+        None => (rest, "0"),
+    };
+    if hours.is_empty() || hours.len() > 2 || minutes.len() > 2 {
+        return None;
+    }
+
+    let hours: i32 = hours.parse().ok()?;
+    let minutes: i32 = minutes.parse().ok()?;
+    if minutes >= 60 || hours < 0 || minutes < 0 {
+        return None;
+    }
+
+    FixedOffset::east_opt(sign * (hours * 3600 + minutes * 60))
+}
+
+fn format_time_code(offset: FixedOffset) -> String {
+    let seconds = offset.local_minus_utc();
+    let sign = if seconds < 0 { "-" } else { "+" };
+    let hours = seconds.abs() / 3600;
+    let minutes = seconds.abs() % 3600 / 60;
+
+    match (seconds, minutes) {
+        (0, _) => String::from("0"),
+        (_, 0) => format!("{}{}h", sign, hours),
+        _ => format!("{}{}h{:02}", sign, hours, minutes),
+    }
+}
+
+fn parse_duration(field: &str) -> Option<f64> {
+    let seconds: f64 = field.strip_suffix('s')?.parse().ok()?;
+    if seconds.is_finite() && seconds >= 0.0 {
+        Some(seconds)
+    } else {
+        None
+    }
+}
+
+fn sanitize_field(field: &str) -> String {
+    field
+        .trim()
+        .chars()
+        .map(|c| if RESERVED_CHARS.contains(&c) { '_' } else { c })
+        .collect()
+}
@@ -0,0 +1,257 @@
+//! Checking a configuration against a template of what a device is expected to record,
+//! to catch devices across a fleet whose configuration has been changed without anyone
+//! noticing, e.g. channels swapped round or a CT ratio changed.
+
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::fmt;
+
+use crate::{AnalogChannel, AnalogScalingMode, ComtradeConfig, DataFormat};
+
+/// How an analog channel's values are scaled, for `ConfigMismatch::AnalogChannelScaling`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ChannelScaling {
+    pub multiplier: f64,
+    pub offset_adder: f64,
+    pub primary_factor: f64,
+    pub secondary_factor: f64,
+    pub scaling_mode: AnalogScalingMode,
+}
+
+impl ChannelScaling {
+    fn of(channel: &AnalogChannel) -> Self {
+        ChannelScaling {
+            multiplier: channel.multiplier,
+            offset_adder: channel.offset_adder,
+            primary_factor: channel.primary_factor,
+            secondary_factor: channel.secondary_factor,
+            scaling_mode: channel.scaling_mode.clone(),
+        }
+    }
+
+    fn matches(&self, other: &ChannelScaling) -> bool {
+        same(self.multiplier, other.multiplier)
+            && same(self.offset_adder, other.offset_adder)
+            && same(self.primary_factor, other.primary_factor)
+            && same(self.secondary_factor, other.secondary_factor)
+            && self.scaling_mode == other.scaling_mode
+    }
+}
+
+/// A way in which a configuration differs from the template it's checked against with
+/// `ComtradeConfig::compatible_with()`. Channels are given by their 1-based index in the
+/// template.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ConfigMismatch {
+    AnalogChannelCount {
+        expected: usize,
+        found: usize,
+    },
+    StatusChannelCount {
+        expected: usize,
+        found: usize,
+    },
+
+    /// Channels are matched up by position, so a channel which has moved shows up as a
+    /// different name in each of the positions it has moved between.
+    AnalogChannelName {
+        channel_index: u32,
+        expected: String,
+        found: String,
+    },
+    AnalogChannelUnits {
+        channel_index: u32,
+        expected: String,
+        found: String,
+    },
+    AnalogChannelScaling {
+        channel_index: u32,
+        expected: ChannelScaling,
+        found: ChannelScaling,
+    },
+    StatusChannelName {
+        channel_index: u32,
+        expected: String,
+        found: String,
+    },
+    LineFrequency {
+        expected: f64,
+        found: f64,
+    },
+
+    /// Sampling rates in Hz. The end sample numbers aren't compared, as they depend on
+    /// how long each record is.
+    SamplingRates {
+        expected: Vec<f64>,
+        found: Vec<f64>,
+    },
+    DataFormat {
+        expected: DataFormat,
+        found: DataFormat,
+    },
+}
+
+impl fmt::Display for ConfigMismatch {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ConfigMismatch::AnalogChannelCount { expected, found } => {
+                write!(f, "expected {} analog channels, found {}", expected, found)
+            }
+            ConfigMismatch::StatusChannelCount { expected, found } => {
+                write!(f, "expected {} status channels, found {}", expected, found)
+            }
+            ConfigMismatch::AnalogChannelName {
+                channel_index,
+                expected,
+                found,
+            } => write!(
+                f,
+                "expected analog channel {} to be '{}', found '{}'",
+                channel_index, expected, found
+            ),
+            ConfigMismatch::AnalogChannelUnits {
+                channel_index,
+                expected,
+                found,
+            } => write!(
+                f,
+                "expected analog channel {} in '{}', found '{}'",
+                channel_index, expected, found
+            ),
+            ConfigMismatch::AnalogChannelScaling {
+                channel_index,
+                expected,
+                found,
+            } => write!(
+                f,
+                "expected analog channel {} to be scaled by {:?}, found {:?}",
+                channel_index, expected, found
+            ),
+            ConfigMismatch::StatusChannelName {
+                channel_index,
+                expected,
+                found,
+            } => write!(
+                f,
+                "expected status channel {} to be '{}', found '{}'",
+                channel_index, expected, found
+            ),
+            ConfigMismatch::LineFrequency { expected, found } => write!(
+                f,
+                "expected line frequency of {} Hz, found {} Hz",
+                expected, found
+            ),
+            ConfigMismatch::SamplingRates { expected, found } => write!(
+                f,
+                "expected sampling rates of {:?} Hz, found {:?} Hz",
+                expected, found
+            ),
+            ConfigMismatch::DataFormat { expected, found } => {
+                write!(f, "expected data format {:?}, found {:?}", expected, found)
+            }
+        }
+    }
+}
+
+impl ComtradeConfig {
+    /// Check that the configuration records what `template` does, e.g. a configuration
+    /// kept from when the device was commissioned, in the same way: the same channels in
+    /// the same order with the same names (trimmed), units and scaling, and the same
+    /// line frequency, sampling rates and data format. Everything else, e.g. the times,
+    /// the number of samples and the station name, is free to differ.
+    ///
+    /// Returns every difference found, not just the first.
+    pub fn compatible_with(&self, template: &ComtradeConfig) -> Result<(), Vec<ConfigMismatch>> {
+        let mut mismatches = Vec::new();
+
+        if self.analog_channels.len() != template.analog_channels.len() {
+            mismatches.push(ConfigMismatch::AnalogChannelCount {
+                expected: template.analog_channels.len(),
+                found: self.analog_channels.len(),
+            });
+        }
+        for (expected, found) in template.analog_channels.iter().zip(&self.analog_channels) {
+            if expected.name.trim() != found.name.trim() {
+                mismatches.push(ConfigMismatch::AnalogChannelName {
+                    channel_index: expected.index,
+                    expected: expected.name.trim().into(),
+                    found: found.name.trim().into(),
+                });
+            }
+            if expected.units.trim() != found.units.trim() {
+                mismatches.push(ConfigMismatch::AnalogChannelUnits {
+                    channel_index: expected.index,
+                    expected: expected.units.trim().into(),
+                    found: found.units.trim().into(),
+                });
+            }
+            let (expected_scaling, found_scaling) =
+                (ChannelScaling::of(expected), ChannelScaling::of(found));
+            if !expected_scaling.matches(&found_scaling) {
+                mismatches.push(ConfigMismatch::AnalogChannelScaling {
+                    channel_index: expected.index,
+                    expected: expected_scaling,
+                    found: found_scaling,
+                });
+            }
+        }
+
+        if self.status_channels.len() != template.status_channels.len() {
+            mismatches.push(ConfigMismatch::StatusChannelCount {
+                expected: template.status_channels.len(),
+                found: self.status_channels.len(),
+            });
+        }
+        for (expected, found) in template.status_channels.iter().zip(&self.status_channels) {
+            if expected.name.trim() != found.name.trim() {
+                mismatches.push(ConfigMismatch::StatusChannelName {
+                    channel_index: expected.index,
+                    expected: expected.name.trim().into(),
+                    found: found.name.trim().into(),
+                });
+            }
+        }
+
+        if !same(self.line_frequency, template.line_frequency) {
+            mismatches.push(ConfigMismatch::LineFrequency {
+                expected: template.line_frequency,
+                found: self.line_frequency,
+            });
+        }
+
+        let rates = |config: &ComtradeConfig| -> Vec<f64> {
+            config.sampling_rates.iter().map(|r| r.rate_hz).collect()
+        };
+        let (expected_rates, found_rates) = (rates(template), rates(self));
+        let same_rates = expected_rates.len() == found_rates.len()
+            && expected_rates
+                .iter()
+                .zip(found_rates.iter())
+                .all(|(a, b)| same(*a, *b));
+        if !same_rates {
+            mismatches.push(ConfigMismatch::SamplingRates {
+                expected: expected_rates,
+                found: found_rates,
+            });
+        }
+
+        if self.data_format != template.data_format {
+            mismatches.push(ConfigMismatch::DataFormat {
+                expected: template.data_format.clone(),
+                found: self.data_format.clone(),
+            });
+        }
+
+        if mismatches.is_empty() {
+            Ok(())
+        } else {
+            Err(mismatches)
+        }
+    }
+}
+
+/// Whether two values from `.cfg` files are the same, allowing for them having been
+/// written with different numbers of digits.
+fn same(a: f64, b: f64) -> bool {
+    a == b || (a - b).abs() <= 1e-9 * a.abs().max(b.abs())
+}
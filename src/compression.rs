@@ -0,0 +1,86 @@
+//! Transparent decompression of files compressed one by one, as archives often keep them,
+//! e.g. `.dat.gz` or `.cff.zst`. With the `gzip` or `zstd` feature, every file the
+//! parser reads is checked for the magic bytes at the start of a gzip or zstd stream and
+//! decompressed if they're there, so compressed and uncompressed files can be mixed
+//! freely. Paths ending in `.gz` or `.zst` are recognised by their extension underneath,
+//! and the `.dat`, `.hdr` and `.inf` files of a record are found whether or not they're
+//! compressed.
+
+use alloc::vec::Vec;
+use std::io::Read;
+
+use crate::parser::{ComtradeRead, FileReader, ReadError};
+
+/// How a file is compressed, from `Compression::detect()`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Compression {
+    Gzip,
+    Zstd,
+}
+
+impl Compression {
+    /// Compression of a file from the magic bytes at its start, or `None` if it isn't
+    /// compressed in a way that's recognised.
+    pub fn detect(bytes: &[u8]) -> Option<Compression> {
+        if bytes.starts_with(&[0x1f, 0x8b]) {
+            Some(Compression::Gzip)
+        } else if bytes.starts_with(&[0x28, 0xb5, 0x2f, 0xfd]) {
+            Some(Compression::Zstd)
+        } else {
+            None
+        }
+    }
+}
+
+/// Contents of a file which may be compressed, decompressed if it is and the feature
+/// for the compression is enabled, and otherwise as they are. At most `limit` bytes are
+/// decompressed, so that a small compressed file can't make the parser allocate huge
+/// amounts of memory.
+pub fn decompress(bytes: &[u8], limit: usize) -> Result<Vec<u8>, ReadError> {
+    let mut out = Vec::new();
+    decompress_into(bytes, &mut out, limit)?;
+    Ok(out)
+}
+
+fn decompress_into(bytes: &[u8], out: &mut Vec<u8>, limit: usize) -> Result<(), ReadError> {
+    let limit = limit as u64;
+    match Compression::detect(bytes) {
+        #[cfg(feature = "gzip")]
+        Some(Compression::Gzip) => flate2::read::MultiGzDecoder::new(bytes)
+            .take(limit)
+            .read_to_end(out)
+            .map(|_| ())
+            .map_err(|_| ReadError),
+        #[cfg(feature = "zstd")]
+        Some(Compression::Zstd) => ruzstd::decoding::StreamingDecoder::new(bytes)
+            .map_err(|_| ReadError)?
+            .take(limit)
+            .read_to_end(out)
+            .map(|_| ())
+            .map_err(|_| ReadError),
+        _ => {
+            out.extend_from_slice(&bytes[..bytes.len().min(limit as usize)]);
+            Ok(())
+        }
+    }
+}
+
+/// File the parser reads, decompressing it as it's read if it's compressed.
+pub(crate) struct Decompress<'a>(pub FileReader<'a>);
+
+impl ComtradeRead for Decompress<'_> {
+    fn read_all(&mut self, buf: &mut Vec<u8>) -> Result<(), ReadError> {
+        self.read_at_most(buf, usize::MAX)
+    }
+
+    fn read_at_most(&mut self, buf: &mut Vec<u8>, limit: usize) -> Result<(), ReadError> {
+        let mut bytes = Vec::new();
+        self.0.read_at_most(&mut bytes, limit)?;
+        if Compression::detect(&bytes).is_none() && buf.is_empty() {
+            // Avoid copying the whole file again in the usual case.
+            *buf = bytes;
+            return Ok(());
+        }
+        decompress_into(&bytes, buf, limit)
+    }
+}
@@ -0,0 +1,235 @@
+//! The two parts of a record: its configuration, i.e. everything in the `.cfg` file, and
+//! its samples. Data files can be parsed against a configuration which doesn't change,
+//! e.g. from a device which streams fresh `.dat` files without sending the `.cfg` file
+//! each time, and the configuration of a record can be kept or cloned without its
+//! samples, e.g. for a catalog of an archive.
+
+use alloc::collections::BTreeMap;
+use alloc::string::String;
+use alloc::vec::Vec;
+
+use chrono::{FixedOffset, NaiveDateTime};
+use derive_builder::Builder;
+
+use crate::{
+    AnalogChannel, Comtrade, DataFormat, FormatRevision, IrregularSample, LeapSecondStatus,
+    ParseWarning, SamplingRate, SourceFiles, StatusChannel, TimeQuality,
+};
+
+/// Everything about a record but its samples, i.e. everything in the `.cfg` file along
+/// with the header and other metadata. This is all that's needed to parse a `.dat` file,
+/// with `ComtradeParser::from_config()`. Get one from
+/// `ComtradeParser::parse_comtrade_config()`, or from a record as `Comtrade::config`.
+#[derive(Debug, Clone, Builder, PartialEq)]
+#[cfg_attr(not(feature = "std"), builder(no_std))]
+pub struct ComtradeConfig {
+    pub station_name: String,
+    pub recording_device_id: String,
+    pub revision: FormatRevision,
+
+    // Don't think it's really necessary for have these fields.
+    pub num_total_channels: u32,
+    pub num_analog_channels: u32,
+    pub num_status_channels: u32,
+
+    pub analog_channels: Vec<AnalogChannel>,
+    pub status_channels: Vec<StatusChannel>,
+
+    pub line_frequency: f64,
+
+    pub sampling_rates: Vec<SamplingRate>,
+
+    /// Number of samples the `.cfg` file says are in the record. This is the final
+    /// end sample number, or the total given explicitly if there are no sampling rates.
+    #[builder(default)]
+    pub total_num_samples: u64,
+
+    pub start_time: NaiveDateTime,
+    pub trigger_time: NaiveDateTime,
+
+    // Don't think these is necessary either, it's just used to parse / process the data file.
+    pub data_format: DataFormat,
+
+    // Below data are 1999 format onwards only.
+
+    // Don't use option for this - just default to 1 if it's not present.
+    pub timestamp_multiplication_factor: f64,
+
+    // Below data are 2013 format onwards only.
+    pub time_offset: Option<FixedOffset>,
+    pub local_offset: Option<FixedOffset>,
+
+    pub time_quality: Option<TimeQuality>,
+    pub leap_second_status: Option<LeapSecondStatus>,
+
+    /// Any vendor-specific lines found after the standard fields in the `.cfg` file,
+    /// kept verbatim.
+    #[builder(default)]
+    pub extra_cfg_lines: Vec<String>,
+
+    /// Contents of the `.hdr` file, if there was one.
+    #[builder(default)]
+    pub header: String,
+
+    /// Extra metadata about the record, e.g. from the `.inf` file, so that vendor
+    /// information isn't lost when the record is processed.
+    ///
+    /// Entries from the `.inf` file are keyed by `section/key`, e.g.
+    /// `Public Record_Information/Source`. Entries in sections for a particular channel,
+    /// e.g. `[Public Analog_Channel_#1]`, go in that channel's `extensions` keyed by the
+    /// owner of the section followed by the key, e.g. `Public/Channel_ID`. Extra columns
+    /// on a channel's line in the `.cfg` file go in its `extensions` too, as
+    /// `CFG/Extra_Column_1` onwards, and unknown sections of a `.cff` file as
+    /// `CFF/<name>`. `VendorProfile`s only change how the standard fields are read, so
+    /// don't add anything here.
+    #[builder(default)]
+    pub extensions: BTreeMap<String, String>,
+
+    /// Non-fatal problems found while parsing the record.
+    #[builder(default)]
+    pub warnings: Vec<ParseWarning>,
+
+    /// Nanoseconds per unit of the timestamps in the data file: 1000 if the start time
+    /// in the `.cfg` file is given to the microsecond, or 1 if to the nanosecond.
+    #[builder(default = "1000")]
+    pub timestamp_unit: i64,
+
+    /// Whether the `.cfg` file was semicolon-separated with commas for decimal points,
+    /// either detected or forced with `Quirks::decimal_comma`. An ASCII `.dat` file
+    /// parsed with `ComtradeParser::from_config()` is read the same way.
+    #[builder(default)]
+    pub decimal_comma: bool,
+}
+
+impl ComtradeConfig {
+    /// Number of bytes taken up by each scan in the `.dat` file, or `None` for ASCII
+    /// data. See `DataFormat::bytes_per_scan()`.
+    pub fn bytes_per_scan(&self) -> Option<usize> {
+        self.data_format
+            .bytes_per_scan(self.num_analog_channels, self.num_status_channels)
+    }
+
+    /// Size in bytes the `.dat` file should be for the total number of samples, e.g. to
+    /// check an upload before parsing it or to allocate a buffer for streaming one in.
+    /// `None` for ASCII data, whose size depends on the values, or if it's too large
+    /// to count.
+    pub fn expected_dat_size(&self) -> Option<u64> {
+        (self.bytes_per_scan()? as u64).checked_mul(self.total_num_samples)
+    }
+
+    /// Nanoseconds per unit of the timestamps for the precision of the start and trigger
+    /// times: 1 if either has a fraction of a microsecond, and otherwise 1000. This is
+    /// what the parser sets `timestamp_unit` to, for configurations put together from
+    /// elsewhere.
+    #[cfg(any(feature = "json", feature = "protobuf"))]
+    pub(crate) fn implied_timestamp_unit(&self) -> i64 {
+        use chrono::Timelike;
+
+        let has_nanoseconds = |time: &NaiveDateTime| !time.nanosecond().is_multiple_of(1000);
+        if has_nanoseconds(&self.start_time) || has_nanoseconds(&self.trigger_time) {
+            1
+        } else {
+            1000
+        }
+    }
+}
+
+impl Default for ComtradeConfig {
+    fn default() -> Self {
+        ComtradeConfig {
+            station_name: Default::default(),
+            recording_device_id: Default::default(),
+            revision: Default::default(),
+            num_total_channels: Default::default(),
+            num_analog_channels: Default::default(),
+            num_status_channels: Default::default(),
+            analog_channels: Default::default(),
+            status_channels: Default::default(),
+            line_frequency: Default::default(),
+            sampling_rates: Default::default(),
+            total_num_samples: Default::default(),
+            start_time: NaiveDateTime::from_timestamp(0, 0),
+            trigger_time: NaiveDateTime::from_timestamp(0, 0),
+            data_format: Default::default(),
+            timestamp_multiplication_factor: 1.0,
+            time_offset: Default::default(),
+            local_offset: Default::default(),
+            time_quality: Default::default(),
+            leap_second_status: Default::default(),
+            extra_cfg_lines: Default::default(),
+            header: Default::default(),
+            extensions: Default::default(),
+            warnings: Default::default(),
+            timestamp_unit: 1000,
+            decimal_comma: false,
+        }
+    }
+}
+
+/// Samples of a record, i.e. everything in the `.dat` file.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ComtradeData {
+    pub sample_numbers: Vec<u64>,
+
+    /// Time of each sample relative to the start time, in nanoseconds. These are kept as
+    /// integers so that long records with nanosecond timestamps don't lose precision -
+    /// use `Comtrade::timestamp_seconds()` or `Comtrade::timestamps_seconds()` for
+    /// seconds.
+    pub timestamps: Vec<i64>,
+
+    /// Timestamp of each sample as it was in the `.dat` file, or `None` where it was
+    /// missing. This lines up with `timestamps`.
+    ///
+    /// These are only 32 bits, so they can roll over in long records. Where timestamps
+    /// are critical, the parser counts on past the end of the range in `timestamps`; see
+    /// `Comtrade::timestamp_overflow_detected()`.
+    pub raw_timestamps: Vec<Option<u32>>,
+
+    /// Values of each analog channel, in the order of `ComtradeConfig::analog_channels`,
+    /// with any that were missing from the `.dat` file (blank in ASCII files, or the
+    /// most negative integer in binary files) as NaN. See `MissingDataPolicy` for
+    /// dealing with these.
+    pub analog_data: Vec<Vec<f64>>,
+
+    /// Quality flags of each value of each analog channel, in the order of
+    /// `ComtradeConfig::analog_channels`, decoded from the status channels given by a
+    /// `QualityMapping`. Channels without quality flags have none, and this is empty if
+    /// none of them do. See `Comtrade::quality()`.
+    pub analog_quality: Vec<Vec<u32>>,
+
+    /// Values of each status channel, 0 or 1, in the order of
+    /// `ComtradeConfig::status_channels`.
+    pub status_data: Vec<Vec<u8>>,
+
+    /// Samples whose sample number or timestamp doesn't follow on from the sample
+    /// before it in the `.dat` file.
+    pub irregular_samples: Vec<IrregularSample>,
+
+    /// Files the record was parsed from, if the parser was asked to keep them, for
+    /// writing the record back out byte for byte. See `SourceFiles`.
+    pub source_files: Option<SourceFiles>,
+}
+
+impl Comtrade {
+    /// Put a record together from its configuration and its samples.
+    pub fn from_parts(config: ComtradeConfig, data: ComtradeData) -> Self {
+        Comtrade { config, data }
+    }
+
+    /// Split the record into its configuration and its samples.
+    pub fn into_parts(self) -> (ComtradeConfig, ComtradeData) {
+        (self.config, self.data)
+    }
+
+    /// Number of bytes taken up by each scan in binary data, as
+    /// `ComtradeConfig::bytes_per_scan()` gives.
+    pub fn bytes_per_scan(&self) -> Option<usize> {
+        self.config.bytes_per_scan()
+    }
+
+    /// Size in bytes of the `.dat` file for the record in binary, as
+    /// `ComtradeConfig::expected_dat_size()` gives.
+    pub fn expected_dat_size(&self) -> Option<u64> {
+        self.config.expected_dat_size()
+    }
+}
@@ -0,0 +1,132 @@
+//! Conversion of records between revisions of the COMTRADE standard, e.g. for exporting
+//! to tools that only understand 1991 files.
+
+use alloc::vec;
+use alloc::vec::Vec;
+
+use chrono::FixedOffset;
+
+use crate::{Comtrade, DataFormat, FormatRevision, LeapSecondStatus, TimeQuality};
+
+/// A piece of information that can't be represented in the revision a record was
+/// converted to, so was dropped. Each holds the value that was lost.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Loss {
+    /// 1999 onwards only. The timestamps in the record are already scaled by this, so
+    /// it only matters if the record is written back out with the original timestamps.
+    TimestampMultiplicationFactor(f64),
+
+    /// 2013 only.
+    TimeOffset(FixedOffset),
+
+    /// 2013 only.
+    LocalOffset(FixedOffset),
+
+    /// 2013 only.
+    TimeQuality(TimeQuality),
+
+    /// 2013 only.
+    LeapSecondStatus(LeapSecondStatus),
+
+    /// The 32-bit binary and floating point formats are 2013 only, so the record is
+    /// changed to use 16-bit binary, which may lose resolution or range.
+    DataFormat(DataFormat),
+
+    /// 1999 onwards only. Primary and secondary factors of the analog channel with this
+    /// index, which are reset to 1.
+    ScalingFactors {
+        channel_index: u32,
+        primary_factor: f64,
+        secondary_factor: f64,
+    },
+}
+
+/// Everything that was dropped when converting a record to a different revision.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct LossReport {
+    pub losses: Vec<Loss>,
+}
+
+impl LossReport {
+    /// Whether the conversion kept everything in the original record.
+    pub fn is_lossless(&self) -> bool {
+        self.losses.is_empty()
+    }
+}
+
+impl Comtrade {
+    /// Convert the record to the 1991 revision, which lacks the time multiplication
+    /// factor, channel scaling factors and everything added in 2013.
+    pub fn to_1991(&self) -> (Comtrade, LossReport) {
+        let mut record = self.clone();
+        let mut report = LossReport::default();
+
+        drop_2013_fields(&mut record, &mut report);
+
+        if record.config.timestamp_multiplication_factor != 1.0 {
+            report.losses.push(Loss::TimestampMultiplicationFactor(
+                record.config.timestamp_multiplication_factor,
+            ));
+            record.config.timestamp_multiplication_factor = 1.0;
+        }
+
+        for channel in record.config.analog_channels.iter_mut() {
+            if channel.primary_factor != 1.0 || channel.secondary_factor != 1.0 {
+                report.losses.push(Loss::ScalingFactors {
+                    channel_index: channel.index,
+                    primary_factor: channel.primary_factor,
+                    secondary_factor: channel.secondary_factor,
+                });
+                channel.primary_factor = 1.0;
+                channel.secondary_factor = 1.0;
+            }
+        }
+
+        record.config.revision = FormatRevision::Revision1991;
+        (record, report)
+    }
+
+    /// Convert the record to the 1999 revision, which lacks everything added in 2013.
+    pub fn to_1999(&self) -> (Comtrade, LossReport) {
+        let mut record = self.clone();
+        let mut report = LossReport::default();
+
+        drop_2013_fields(&mut record, &mut report);
+
+        record.config.revision = FormatRevision::Revision1999;
+        (record, report)
+    }
+
+    /// Convert the record to the 2013 revision. As this is the latest revision, nothing
+    /// is lost.
+    pub fn to_2013(&self) -> (Comtrade, LossReport) {
+        let mut record = self.clone();
+        record.config.revision = FormatRevision::Revision2013;
+        (record, LossReport { losses: vec![] })
+    }
+}
+
+fn drop_2013_fields(record: &mut Comtrade, report: &mut LossReport) {
+    if let Some(offset) = record.config.time_offset.take() {
+        report.losses.push(Loss::TimeOffset(offset));
+    }
+    if let Some(offset) = record.config.local_offset.take() {
+        report.losses.push(Loss::LocalOffset(offset));
+    }
+    if let Some(quality) = record.config.time_quality.take() {
+        report.losses.push(Loss::TimeQuality(quality));
+    }
+    if let Some(status) = record.config.leap_second_status.take() {
+        report.losses.push(Loss::LeapSecondStatus(status));
+    }
+
+    if matches!(
+        record.config.data_format,
+        DataFormat::Binary32 | DataFormat::Float32
+    ) {
+        report
+            .losses
+            .push(Loss::DataFormat(record.config.data_format.clone()));
+        record.config.data_format = DataFormat::Binary16;
+    }
+}
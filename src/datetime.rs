@@ -0,0 +1,43 @@
+//! Record times as `time::OffsetDateTime`, for projects which use the `time` crate rather
+//! than `chrono`. The parser still works with `chrono` types internally; these convert
+//! from them.
+
+use chrono::{FixedOffset, NaiveDateTime};
+use time::{Duration, OffsetDateTime, UtcOffset};
+
+use crate::Comtrade;
+
+impl Comtrade {
+    /// Start time of the record, in the record's time code, i.e. `time_offset`, or UTC if
+    /// it doesn't have one.
+    pub fn start_offset_date_time(&self) -> Option<OffsetDateTime> {
+        to_offset_date_time(self.config.start_time, self.config.time_offset)
+    }
+
+    /// Trigger time of the record, in the record's time code or UTC if it doesn't have
+    /// one.
+    pub fn trigger_offset_date_time(&self) -> Option<OffsetDateTime> {
+        to_offset_date_time(self.config.trigger_time, self.config.time_offset)
+    }
+
+    /// Absolute time of the sample at `index`, as `absolute_time()` gives it, in the
+    /// record's time code or UTC if it doesn't have one.
+    ///
+    /// `time` can't represent leap seconds, so samples during an added leap second come
+    /// out in the first second of the next day.
+    pub fn absolute_offset_date_time(&self, index: usize) -> Option<OffsetDateTime> {
+        to_offset_date_time(self.absolute_time(index)?, self.config.time_offset)
+    }
+}
+
+/// Wall-clock time `time` in the offset `offset`, or UTC if there isn't one. `None` if the
+/// time or offset is out of the range `time` supports.
+pub(crate) fn to_offset_date_time(
+    time: NaiveDateTime,
+    offset: Option<FixedOffset>,
+) -> Option<OffsetDateTime> {
+    let offset = UtcOffset::from_whole_seconds(offset.map_or(0, |o| o.local_minus_utc())).ok()?;
+    let wall_clock = OffsetDateTime::from_unix_timestamp(time.timestamp()).ok()?
+        + Duration::nanoseconds(time.timestamp_subsec_nanos() as i64);
+    Some(wall_clock.replace_offset(offset))
+}
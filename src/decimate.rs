@@ -0,0 +1,171 @@
+//! Downsampling records into light overview records, e.g. for browsing a long archive,
+//! without losing any of the changes in the status channels.
+
+use alloc::vec;
+use alloc::vec::Vec;
+use core::ops::Range;
+
+use crate::{Comtrade, ComtradeConfig, ComtradeData, SamplingRate};
+
+/// Change in a status channel, at the time it happened in the original record.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct StatusEvent {
+    /// 0-based index into `ComtradeConfig::status_channels`.
+    pub channel: usize,
+
+    /// 0-based index of the first sample with the new value in the original record.
+    pub index: usize,
+
+    /// Time of that sample relative to the start time, in nanoseconds.
+    pub timestamp: i64,
+    pub value: u8,
+}
+
+/// Result of `Comtrade::decimate()`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DecimatedRecord {
+    pub record: Comtrade,
+
+    /// Every change in every status channel of the original record, in order of time,
+    /// as the decimated record only shows roughly when they happened.
+    pub events: Vec<StatusEvent>,
+}
+
+impl Comtrade {
+    /// Downsample the record by `factor`, so that every window of `factor` samples
+    /// becomes one sample at the time of the first sample in the window.
+    ///
+    /// Analog values are the mean over the window, ignoring missing values. Status
+    /// values are chosen so that every change still shows: a channel which changes
+    /// within a window has the new value for that window, even if it changes back
+    /// before the end of it, in which case it changes back in the next window. The
+    /// exact time of each change is kept in `DecimatedRecord::events`.
+    ///
+    /// Windows don't cross changes in sampling rate, and each sampling rate is divided
+    /// by `factor`. A window left part full at the end of a sampling rate still makes a
+    /// sample. The samples are renumbered from 1, and raw timestamps aren't kept as
+    /// they'd no longer make sense. A `factor` of 0 is treated as 1.
+    pub fn decimate(&self, factor: usize) -> DecimatedRecord {
+        let factor = factor.max(1);
+        let mut record = Comtrade::from_parts(
+            ComtradeConfig {
+                sampling_rates: vec![],
+                ..self.config.clone()
+            },
+            ComtradeData {
+                analog_data: vec![vec![]; self.data.analog_data.len()],
+                status_data: vec![vec![]; self.data.status_data.len()],
+                ..ComtradeData::default()
+            },
+        );
+
+        let segments: Vec<(Range<usize>, f64)> = if self.config.sampling_rates.is_empty() {
+            vec![(0..self.data.timestamps.len(), 0.0)]
+        } else {
+            self.sampling_segments()
+                .into_iter()
+                .map(|segment| (segment.samples, segment.rate_hz))
+                .collect()
+        };
+
+        for (samples, rate_hz) in segments {
+            if samples.is_empty() {
+                continue;
+            }
+            let windows: Vec<Range<usize>> = samples
+                .clone()
+                .step_by(factor)
+                .map(|start| start..(start + factor).min(samples.end))
+                .collect();
+
+            for window in windows.iter() {
+                record
+                    .data
+                    .timestamps
+                    .push(self.data.timestamps[window.start]);
+            }
+            for (data, original) in record
+                .data
+                .analog_data
+                .iter_mut()
+                .zip(self.data.analog_data.iter())
+            {
+                data.extend(windows.iter().map(|w| mean(&original[w.clone()])));
+            }
+            for (data, original) in record
+                .data
+                .status_data
+                .iter_mut()
+                .zip(self.data.status_data.iter())
+            {
+                let mut previous = original[samples.start];
+                for window in windows.iter() {
+                    let values = &original[window.clone()];
+                    let last = values[values.len() - 1];
+                    previous = if last != previous {
+                        last
+                    } else if values.iter().any(|v| *v != previous) {
+                        1 - previous.min(1)
+                    } else {
+                        previous
+                    };
+                    data.push(previous);
+                }
+            }
+
+            if rate_hz > 0.0 {
+                record.config.sampling_rates.push(SamplingRate {
+                    rate_hz: rate_hz / factor as f64,
+                    end_sample_number: record.data.timestamps.len() as u64,
+                });
+            }
+        }
+
+        record.data.sample_numbers = (1..=record.data.timestamps.len() as u64).collect();
+        record.config.total_num_samples = record.data.timestamps.len() as u64;
+
+        DecimatedRecord {
+            record,
+            events: self.status_events(),
+        }
+    }
+
+    /// Every change in the status channels, in order of time.
+    fn status_events(&self) -> Vec<StatusEvent> {
+        let mut events: Vec<StatusEvent> = self
+            .data
+            .status_data
+            .iter()
+            .enumerate()
+            .flat_map(|(channel, data)| {
+                data.windows(2)
+                    .enumerate()
+                    .filter(|(_, pair)| pair[0] != pair[1])
+                    .map(move |(i, pair)| (channel, i + 1, pair[1]))
+            })
+            .filter_map(|(channel, index, value)| {
+                Some(StatusEvent {
+                    channel,
+                    index,
+                    timestamp: *self.data.timestamps.get(index)?,
+                    value,
+                })
+            })
+            .collect();
+        events.sort_by_key(|event| (event.index, event.channel));
+        events
+    }
+}
+
+/// Mean of the values which aren't missing, or NaN if they all are.
+fn mean(values: &[f64]) -> f64 {
+    let (sum, count) = values
+        .iter()
+        .filter(|v| !v.is_nan())
+        .fold((0.0, 0), |(sum, count), v| (sum + v, count + 1));
+    if count == 0 {
+        f64::NAN
+    } else {
+        sum / count as f64
+    }
+}
@@ -0,0 +1,128 @@
+use std::io::{self, BufRead};
+
+use crate::parser::parse_cff_header;
+
+// Proportion of non-whitespace control characters above which we assume the data is
+// binary rather than text.
+const BINARY_CONTROL_CHAR_RATIO: f64 = 0.1;
+
+/// Kind of COMTRADE file, as guessed from the contents by `detect_file_type()`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum DetectedFileType {
+    Cfg,
+    Cff,
+    AsciiDat,
+    BinaryDat,
+
+    /// Text which doesn't look like any of the machine-readable COMTRADE files, e.g.
+    /// a `.hdr` or `.inf` file.
+    Unknown,
+}
+
+/// Guess what kind of COMTRADE file the reader contains based on its structure,
+/// which is useful when files have been received with wrong or missing extensions.
+///
+/// Only the data already available in the reader's buffer is looked at, and nothing
+/// is consumed, so the same reader can then be passed on to the parser.
+pub fn detect_file_type<R: BufRead>(reader: &mut R) -> io::Result<DetectedFileType> {
+    let buffer = reader.fill_buf()?;
+    Ok(detect_file_type_from_bytes(buffer))
+}
+
+fn detect_file_type_from_bytes(bytes: &[u8]) -> DetectedFileType {
+    // Some Windows tools write `.cff` files as UTF-16 with a byte order mark, which
+    // would otherwise look like binary data.
+    let little_endian = match bytes {
+        [0xFF, 0xFE, ..] => Some(true),
+        [0xFE, 0xFF, ..] => Some(false),
+        _ => None,
+    };
+    if let Some(little_endian) = little_endian {
+        let units = bytes[2..].chunks_exact(2).map(|pair| {
+            if little_endian {
+                u16::from_le_bytes([pair[0], pair[1]])
+            } else {
+                u16::from_be_bytes([pair[0], pair[1]])
+            }
+        });
+        let text: String = char::decode_utf16(units)
+            .map(|c| c.unwrap_or(char::REPLACEMENT_CHARACTER))
+            .collect();
+        return detect_file_type_from_bytes(text.as_bytes());
+    }
+    let bytes = bytes.strip_prefix(b"\xEF\xBB\xBF").unwrap_or(bytes);
+
+    if bytes.is_empty() {
+        return DetectedFileType::Unknown;
+    }
+
+    let num_control_chars = bytes
+        .iter()
+        .filter(|b| b.is_ascii_control() && !b"\r\n\t".contains(b))
+        .count();
+    if bytes.contains(&0)
+        || num_control_chars as f64 / bytes.len() as f64 > BINARY_CONTROL_CHAR_RATIO
+    {
+        return DetectedFileType::BinaryDat;
+    }
+
+    // Text files may not be UTF-8 (e.g. latin1 `.cfg` files), but all the structure
+    // we're interested in is ASCII.
+    let text = String::from_utf8_lossy(bytes);
+    let mut lines = text.lines().map(|l| l.trim()).filter(|l| !l.is_empty());
+
+    let first_line = match lines.next() {
+        Some(line) => line,
+        None => return DetectedFileType::Unknown,
+    };
+
+    if parse_cff_header(first_line).is_some() {
+        return DetectedFileType::Cff;
+    }
+
+    if let Some(second_line) = lines.clone().next() {
+        if is_channel_count_line(second_line) {
+            return DetectedFileType::Cfg;
+        }
+    }
+
+    // Don't look at the final line as it's probably been cut off part way through.
+    let mut data_lines = std::iter::once(first_line)
+        .chain(lines)
+        .collect::<Vec<&str>>();
+    if data_lines.len() > 1 {
+        data_lines.pop();
+    }
+    if data_lines.iter().all(|l| is_ascii_data_line(l)) {
+        return DetectedFileType::AsciiDat;
+    }
+
+    DetectedFileType::Unknown
+}
+
+/// Second line of a `.cfg` file looks like `TT,##A,##D`.
+fn is_channel_count_line(line: &str) -> bool {
+    let values: Vec<&str> = line.split(',').map(|v| v.trim()).collect();
+    if values.len() != 3 {
+        return false;
+    }
+
+    let is_count = |value: &str, suffix: char| {
+        let mut chars = value.chars();
+        chars.next_back().map(|c| c.to_ascii_uppercase()) == Some(suffix)
+            && chars.as_str().trim().parse::<u32>().is_ok()
+    };
+
+    values[0].parse::<u32>().is_ok() && is_count(values[1], 'A') && is_count(values[2], 'D')
+}
+
+/// Lines in ASCII data files are all numbers: sample number, timestamp (possibly
+/// missing) and then the channel values.
+fn is_ascii_data_line(line: &str) -> bool {
+    let values: Vec<&str> = line.split(',').map(|v| v.trim()).collect();
+    values.len() >= 2
+        && values[0].parse::<u32>().is_ok()
+        && values[1..]
+            .iter()
+            .all(|v| v.is_empty() || v.parse::<f64>().is_ok())
+}
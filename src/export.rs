@@ -0,0 +1,204 @@
+//! Exporting records in formats for other tools to consume.
+
+use alloc::string::String;
+use core::fmt::Write;
+use core::ops::Range;
+
+use crate::{envelope, Comtrade, FormatRevision, MissingDataError, MissingDataPolicy};
+
+/// Compact JSON preview of a record, for web viewers showing many records at once.
+///
+/// This contains the record metadata and, for each channel, the minimum and maximum
+/// values over at most `max_points` buckets of samples (see `envelope()`),
+/// along with the time in seconds of the start of each bucket:
+///
+/// ```json
+/// {
+///   "station_name": "...", "recording_device_id": "...", "revision": "1999",
+///   "start_time": "2020-02-01T00:00:00", "trigger_time": "2020-02-01T00:00:00.001",
+///   "line_frequency": 50, "num_samples": 4, "duration": 0.003,
+///   "times": [0, 0.002],
+///   "analog_channels": [
+///     {"index": 1, "name": "IA", "phase": "A", "units": "A", "min": [5, 15], "max": [10, 20]}
+///   ],
+///   "status_channels": [
+///     {"index": 1, "name": "TRIP", "phase": "", "min": [0, 1], "max": [0, 1]}
+///   ]
+/// }
+/// ```
+///
+/// Values which aren't finite are given as `null`. Missing values are left out of the
+/// buckets, so a bucket where every value is missing has a `null` minimum and maximum.
+pub fn preview_json(record: &Comtrade, max_points: usize) -> String {
+    preview_json_with_policy(record, max_points, MissingDataPolicy::Skip)
+        .expect("skipping missing values never fails")
+}
+
+/// As `preview_json()`, dealing with missing values according to `policy`.
+pub fn preview_json_with_policy(
+    record: &Comtrade,
+    max_points: usize,
+    policy: MissingDataPolicy,
+) -> Result<String, MissingDataError> {
+    let num_samples = record.data.sample_numbers.len();
+    let num_buckets = max_points.min(num_samples);
+    let buckets = || {
+        (0..num_buckets).map(|i| i * num_samples / num_buckets..(i + 1) * num_samples / num_buckets)
+    };
+
+    let mut json = String::new();
+    json.push('{');
+
+    write_key(&mut json, "station_name");
+    write_string(&mut json, record.config.station_name.trim());
+    json.push(',');
+    write_key(&mut json, "recording_device_id");
+    write_string(&mut json, record.config.recording_device_id.trim());
+    json.push(',');
+    write_key(&mut json, "revision");
+    write_string(
+        &mut json,
+        match record.config.revision {
+            FormatRevision::Revision1991 => "1991",
+            FormatRevision::Revision1999 => "1999",
+            FormatRevision::Revision2013 => "2013",
+        },
+    );
+    json.push(',');
+    write_key(&mut json, "start_time");
+    write_string(&mut json, &format_time(record.config.start_time));
+    json.push(',');
+    write_key(&mut json, "trigger_time");
+    write_string(&mut json, &format_time(record.config.trigger_time));
+    json.push(',');
+    write_key(&mut json, "line_frequency");
+    write_number(&mut json, record.config.line_frequency);
+    json.push(',');
+    write_key(&mut json, "num_samples");
+    let _ = write!(json, "{}", num_samples);
+    json.push(',');
+    write_key(&mut json, "duration");
+    write_number(&mut json, record.duration());
+    json.push(',');
+
+    write_key(&mut json, "times");
+    write_array(
+        &mut json,
+        buckets().map(|bucket| record.timestamp_seconds(bucket.start).unwrap_or(f64::NAN)),
+    );
+    json.push(',');
+
+    write_key(&mut json, "analog_channels");
+    json.push('[');
+    for (i, (channel, data)) in record.analog_channels_with_data().enumerate() {
+        if i > 0 {
+            json.push(',');
+        }
+        let envelope = envelope(&policy.apply(data)?, num_buckets);
+
+        json.push('{');
+        write_key(&mut json, "index");
+        let _ = write!(json, "{}", channel.index);
+        json.push(',');
+        write_key(&mut json, "name");
+        write_string(&mut json, &channel.name);
+        json.push(',');
+        write_key(&mut json, "phase");
+        write_string(&mut json, channel.phase.as_str().trim());
+        json.push(',');
+        write_key(&mut json, "units");
+        write_string(&mut json, channel.units.trim());
+        json.push(',');
+        write_key(&mut json, "min");
+        write_array(&mut json, envelope.iter().map(|(min, _)| *min));
+        json.push(',');
+        write_key(&mut json, "max");
+        write_array(&mut json, envelope.iter().map(|(_, max)| *max));
+        json.push('}');
+    }
+    json.push_str("],");
+
+    write_key(&mut json, "status_channels");
+    json.push('[');
+    for (i, (channel, data)) in record.status_channels_with_data().enumerate() {
+        if i > 0 {
+            json.push(',');
+        }
+        let values = |bucket: Range<usize>| data.get(bucket).unwrap_or(&[]).iter().copied();
+
+        json.push('{');
+        write_key(&mut json, "index");
+        let _ = write!(json, "{}", channel.index);
+        json.push(',');
+        write_key(&mut json, "name");
+        write_string(&mut json, &channel.name);
+        json.push(',');
+        write_key(&mut json, "phase");
+        write_string(&mut json, channel.phase.as_str().trim());
+        json.push(',');
+        write_key(&mut json, "min");
+        write_array(
+            &mut json,
+            buckets().map(|bucket| values(bucket).min().unwrap_or(0) as f64),
+        );
+        json.push(',');
+        write_key(&mut json, "max");
+        write_array(
+            &mut json,
+            buckets().map(|bucket| values(bucket).max().unwrap_or(0) as f64),
+        );
+        json.push('}');
+    }
+    json.push(']');
+
+    json.push('}');
+    Ok(json)
+}
+
+fn format_time(time: chrono::NaiveDateTime) -> String {
+    let mut formatted = String::new();
+    let _ = write!(formatted, "{}", time.format("%Y-%m-%dT%H:%M:%S%.f"));
+    formatted
+}
+
+fn write_key(json: &mut String, key: &str) {
+    write_string(json, key);
+    json.push(':');
+}
+
+fn write_string(json: &mut String, value: &str) {
+    json.push('"');
+    for c in value.chars() {
+        match c {
+            '"' => json.push_str("\\\""),
+            '\\' => json.push_str("\\\\"),
+            '\n' => json.push_str("\\n"),
+            '\r' => json.push_str("\\r"),
+            '\t' => json.push_str("\\t"),
+            c if (c as u32) < 0x20 => {
+                let _ = write!(json, "\\u{:04x}", c as u32);
+            }
+            c => json.push(c),
+        }
+    }
+    json.push('"');
+}
+
+fn write_number(json: &mut String, value: f64) {
+    if value.is_finite() {
+        let _ = write!(json, "{}", value);
+    } else {
+        json.push_str("null");
+    }
+}
+
+fn write_array<I: Iterator<Item = f64>>(json: &mut String, values: I) {
+    json.push('[');
+    for (i, value) in values.enumerate() {
+        if i > 0 {
+            json.push(',');
+        }
+        write_number(json, value);
+    }
+    json.push(']');
+}
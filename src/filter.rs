@@ -0,0 +1,99 @@
+//! Anti-aliasing filters of analog channels, which the standard leaves out of the `.cfg`
+//! file but some recorders describe in their channel sections of the `.inf` file, and
+//! compensating for the delay they add to the signal.
+//!
+//! The filter is described by these keys in any section for the channel, e.g.
+//! `[Public Analog_Channel_#1]` or a vendor's own, ignoring case:
+//!
+//! - `Filter_Type`, e.g. `Butterworth`.
+//! - `Filter_Cutoff_Frequency`, in Hz.
+//! - `Filter_Order`.
+//! - `Filter_Group_Delay`, in microseconds like the channel's skew.
+
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+
+use crate::parser::round_nanoseconds;
+use crate::{AnalogChannel, Comtrade};
+
+/// Anti-aliasing filter of an analog channel, from `AnalogChannel::filter()`. Anything
+/// the channel's extensions don't give, or give a value for that can't be parsed, is
+/// `None`.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct AnalogFilter {
+    pub filter_type: Option<String>,
+    pub cutoff_hz: Option<f64>,
+    pub order: Option<u32>,
+
+    /// Delay the filter adds to the signal, in microseconds.
+    pub group_delay: Option<f64>,
+}
+
+impl AnalogChannel {
+    /// Anti-aliasing filter of the channel, or `None` if its extensions don't describe
+    /// one. See the `filter` module for the keys used.
+    pub fn filter(&self) -> Option<AnalogFilter> {
+        let filter = AnalogFilter {
+            filter_type: self.filter_value("Filter_Type").map(|v| v.to_string()),
+            cutoff_hz: self.filter_value("Filter_Cutoff_Frequency").and_then(parse),
+            order: self.filter_value("Filter_Order").and_then(parse),
+            group_delay: self.filter_value("Filter_Group_Delay").and_then(parse),
+        };
+        if filter == AnalogFilter::default() {
+            return None;
+        }
+        Some(filter)
+    }
+
+    /// Delay in microseconds the channel's anti-aliasing filter adds to the signal, or
+    /// `None` if it isn't given.
+    pub fn group_delay(&self) -> Option<f64> {
+        self.filter_value("Filter_Group_Delay").and_then(parse)
+    }
+
+    /// Value of the extension with the given key, whichever section it's from.
+    fn filter_value(&self, key: &str) -> Option<&str> {
+        self.extensions
+            .iter()
+            .find(|(name, _)| {
+                let name_key = name.rsplit_once('/').map_or(name.as_str(), |(_, k)| k);
+                name_key.eq_ignore_ascii_case(key)
+            })
+            .map(|(_, value)| value.trim())
+            .filter(|value| !value.is_empty())
+    }
+}
+
+fn parse<T: core::str::FromStr>(value: &str) -> Option<T> {
+    value.parse().ok()
+}
+
+impl Comtrade {
+    /// Timestamps in nanoseconds of the instants the values of an analog channel were
+    /// actually measured at, i.e. `ComtradeData::timestamps` corrected for the channel's
+    /// skew and, with `compensate_group_delay`, the delay of its anti-aliasing filter.
+    /// This lines channels with different filters up precisely, e.g. before comparing
+    /// their phases.
+    ///
+    /// Returns `None` if there's no analog channel with the index `channel_idx`.
+    pub fn channel_timestamps(
+        &self,
+        channel_idx: usize,
+        compensate_group_delay: bool,
+    ) -> Option<Vec<i64>> {
+        let channel = self.config.analog_channels.get(channel_idx)?;
+        let mut correction = channel.skew;
+        if compensate_group_delay {
+            correction -= channel.group_delay().unwrap_or(0.0);
+        }
+        let correction = round_nanoseconds(correction * 1000.0);
+
+        Some(
+            self.data
+                .timestamps
+                .iter()
+                .map(|t| t + correction)
+                .collect(),
+        )
+    }
+}
@@ -0,0 +1,109 @@
+//! Fingerprinting of records, for spotting the same fault record when it's been
+//! retrieved more than once, e.g. from both the relay and the substation's data
+//! concentrator.
+
+use chrono::NaiveDateTime;
+
+use crate::Comtrade;
+
+impl Comtrade {
+    /// Hash of the record's normalized metadata and data, which is the same on every
+    /// platform and every run so it can be stored and compared against later.
+    ///
+    /// Only what describes the recording itself goes into the fingerprint: the station
+    /// name, device ID and channel names and units (all trimmed), the channel phases,
+    /// the start and trigger times, the line frequency, the timestamps and the values.
+    /// How the record was stored doesn't, so the fingerprint is the same whatever
+    /// revision, data format or file name it's saved with, and regardless of the
+    /// header, `.inf` file and anything else in `extensions`. Analog values are compared
+    /// at single precision, so that a record converted to the `FLOAT32` format still
+    /// matches the original.
+    ///
+    /// The hash isn't cryptographic, so while different records are very unlikely to
+    /// have the same fingerprint by chance, it's easy to make them do so deliberately.
+    pub fn fingerprint(&self) -> u64 {
+        let mut hasher = Fnv1a::new();
+
+        hasher.write_str(self.config.station_name.trim());
+        hasher.write_str(self.config.recording_device_id.trim());
+        hasher.write_time(self.config.start_time);
+        hasher.write_time(self.config.trigger_time);
+        hasher.write_u64(self.config.line_frequency.to_bits());
+
+        hasher.write_u64(self.data.timestamps.len() as u64);
+        for timestamp in self.data.timestamps.iter() {
+            hasher.write_u64(*timestamp as u64);
+        }
+
+        hasher.write_u64(self.config.analog_channels.len() as u64);
+        for (channel, data) in self.analog_channels_with_data() {
+            hasher.write_str(&channel.name);
+            hasher.write_str(channel.units.trim());
+            hasher.write_str(channel.phase.as_str().trim());
+            hasher.write_u64(data.len() as u64);
+            for value in data.iter() {
+                hasher.write_u64(normalize_value(*value) as u64);
+            }
+        }
+
+        hasher.write_u64(self.config.status_channels.len() as u64);
+        for (channel, data) in self.status_channels_with_data() {
+            hasher.write_str(&channel.name);
+            hasher.write_str(channel.phase.as_str().trim());
+            hasher.write_u64(data.len() as u64);
+            hasher.write(data);
+        }
+
+        hasher.finish()
+    }
+}
+
+/// Bits of the value at single precision, with zero and NaN made consistent so that
+/// equal values always hash the same.
+fn normalize_value(value: f64) -> u32 {
+    let value = value as f32;
+    if value == 0.0 {
+        0
+    } else if value.is_nan() {
+        f32::NAN.to_bits()
+    } else {
+        value.to_bits()
+    }
+}
+
+/// 64-bit FNV-1a hash, which unlike the hashers in `std` is fixed and so gives the same
+/// result everywhere.
+pub(crate) struct Fnv1a(u64);
+
+impl Fnv1a {
+    pub fn new() -> Self {
+        Fnv1a(0xcbf29ce484222325)
+    }
+
+    pub fn write(&mut self, bytes: &[u8]) {
+        for byte in bytes {
+            self.0 ^= *byte as u64;
+            self.0 = self.0.wrapping_mul(0x100000001b3);
+        }
+    }
+
+    pub fn write_u64(&mut self, value: u64) {
+        self.write(&value.to_le_bytes());
+    }
+
+    /// Write the string along with its length, so that e.g. `"ab", "c"` and `"a", "bc"`
+    /// hash differently.
+    pub fn write_str(&mut self, value: &str) {
+        self.write_u64(value.len() as u64);
+        self.write(value.as_bytes());
+    }
+
+    fn write_time(&mut self, time: NaiveDateTime) {
+        self.write_u64(time.timestamp() as u64);
+        self.write_u64(time.timestamp_subsec_nanos() as u64);
+    }
+
+    pub fn finish(&self) -> u64 {
+        self.0
+    }
+}
@@ -0,0 +1,523 @@
+//! Full records as JSON, metadata and samples, so that e.g. web applications can create
+//! records which are then written out as standard COMTRADE files with `ComtradeWriter`.
+//! Unlike `export::preview_json()`, nothing is left out, so a record read back with
+//! `from_json()` has the same channels and values as the one written with `to_json()`.
+//!
+//! The format is described by the JSON Schema in `JSON_SCHEMA`:
+//!
+//! ```json
+//! {
+//!   "station_name": "...", "recording_device_id": "...", "revision": "1999",
+//!   "line_frequency": 50,
+//!   "start_time": "2020-02-01T00:00:00", "trigger_time": "2020-02-01T00:00:00.001",
+//!   "time_offset_minutes": 60, "local_offset_minutes": 60,
+//!   "data_format": "ASCII", "timestamp_multiplication_factor": 1,
+//!   "sampling_rates": [{"rate_hz": 1000, "end_sample_number": 4}],
+//!   "sample_numbers": [1, 2, 3, 4],
+//!   "timestamps": [0, 1000000, 2000000, 3000000],
+//!   "analog_channels": [
+//!     {"name": "IA", "phase": "A", "units": "A", "multiplier": 0.5, "values": [5, 10, null, 20]}
+//!   ],
+//!   "status_channels": [
+//!     {"name": "TRIP", "normal_status_value": 0, "values": [0, 0, 1, 1]}
+//!   ]
+//! }
+//! ```
+//!
+//! Timestamps are in nanoseconds from the start time, and missing analog values are
+//! `null`. Everything after `trigger_time` is optional apart from `timestamps` and the
+//! channels' `name` and `values`, with the defaults given in the schema.
+
+use alloc::collections::BTreeMap;
+use alloc::format;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use core::fmt;
+use core::str::FromStr;
+
+use chrono::{FixedOffset, NaiveDateTime};
+use serde::{Deserialize, Serialize};
+
+use crate::writer::channel_phase;
+use crate::{
+    AnalogChannel, AnalogScalingMode, Comtrade, ComtradeConfig, ComtradeData, DataFormat,
+    FormatRevision, Phase, SamplingRate, StatusChannel,
+};
+
+/// JSON Schema (draft 2020-12) of the format read by `from_json()` and written by
+/// `to_json()`, for validating records before they're sent.
+pub const JSON_SCHEMA: &str = r##"{
+  "$schema": "https://json-schema.org/draft/2020-12/schema",
+  "title": "COMTRADE record",
+  "type": "object",
+  "required": ["station_name", "recording_device_id", "revision", "line_frequency",
+               "start_time", "trigger_time", "timestamps"],
+  "additionalProperties": false,
+  "properties": {
+    "station_name": {"type": "string"},
+    "recording_device_id": {"type": "string"},
+    "revision": {"enum": ["1991", "1999", "2013"]},
+    "line_frequency": {"type": "number", "minimum": 0},
+    "start_time": {"$ref": "#/$defs/time"},
+    "trigger_time": {"$ref": "#/$defs/time"},
+    "time_offset_minutes": {"type": ["integer", "null"], "minimum": -1440, "maximum": 1440},
+    "local_offset_minutes": {"type": ["integer", "null"], "minimum": -1440, "maximum": 1440},
+    "data_format": {"enum": ["ASCII", "BINARY", "BINARY32", "FLOAT32"], "default": "ASCII"},
+    "timestamp_multiplication_factor": {"type": "number", "exclusiveMinimum": 0, "default": 1},
+    "sampling_rates": {
+      "type": "array",
+      "default": [],
+      "items": {
+        "type": "object",
+        "required": ["rate_hz", "end_sample_number"],
+        "additionalProperties": false,
+        "properties": {
+          "rate_hz": {"type": "number", "minimum": 0},
+          "end_sample_number": {"type": "integer", "minimum": 0}
+        }
+      }
+    },
+    "sample_numbers": {
+      "description": "Defaults to 1, 2, 3 and so on.",
+      "type": "array",
+      "items": {"type": "integer", "minimum": 0}
+    },
+    "timestamps": {"type": "array", "items": {"type": "integer"}},
+    "analog_channels": {"type": "array", "default": [], "items": {"$ref": "#/$defs/analog"}},
+    "status_channels": {"type": "array", "default": [], "items": {"$ref": "#/$defs/status"}}
+  },
+  "$defs": {
+    "time": {
+      "type": "string",
+      "pattern": "^\\d{4}-\\d{2}-\\d{2}T\\d{2}:\\d{2}:\\d{2}(\\.\\d{1,9})?$"
+    },
+    "analog": {
+      "type": "object",
+      "required": ["name", "values"],
+      "additionalProperties": false,
+      "properties": {
+        "name": {"type": "string"},
+        "phase": {"type": "string", "default": ""},
+        "circuit_component_being_monitored": {"type": "string", "default": ""},
+        "units": {"type": "string", "default": ""},
+        "multiplier": {"type": "number", "default": 1},
+        "offset_adder": {"type": "number", "default": 0},
+        "skew": {"type": "number", "default": 0},
+        "min_value": {
+          "description": "Defaults to the smallest value in the .dat file.",
+          "type": "number"
+        },
+        "max_value": {
+          "description": "Defaults to the largest value in the .dat file.",
+          "type": "number"
+        },
+        "primary_factor": {"type": "number", "default": 1},
+        "secondary_factor": {"type": "number", "default": 1},
+        "scaling_mode": {"enum": ["P", "S"], "default": "P"},
+        "values": {"type": "array", "items": {"type": ["number", "null"]}}
+      }
+    },
+    "status": {
+      "type": "object",
+      "required": ["name", "values"],
+      "additionalProperties": false,
+      "properties": {
+        "name": {"type": "string"},
+        "phase": {"type": "string", "default": ""},
+        "circuit_component_being_monitored": {"type": "string", "default": ""},
+        "normal_status_value": {"enum": [0, 1], "default": 0},
+        "values": {"type": "array", "items": {"enum": [0, 1]}}
+      }
+    }
+  }
+}"##;
+
+/// Why a JSON record couldn't be read.
+#[derive(Debug)]
+pub enum JsonRecordError {
+    /// The JSON is malformed, or doesn't have the fields and types in the schema.
+    Json(serde_json::Error),
+
+    /// The JSON matches the schema but doesn't make a valid record, e.g. a channel
+    /// doesn't have a value for every timestamp.
+    Invalid(String),
+}
+
+impl fmt::Display for JsonRecordError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            JsonRecordError::Json(err) => write!(f, "invalid JSON record: {}", err),
+            JsonRecordError::Invalid(message) => write!(f, "invalid JSON record: {}", message),
+        }
+    }
+}
+
+impl std::error::Error for JsonRecordError {}
+
+/// Record in the JSON format, see the module documentation.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct JsonRecord {
+    pub station_name: String,
+    pub recording_device_id: String,
+    pub revision: String,
+    pub line_frequency: f64,
+    pub start_time: String,
+    pub trigger_time: String,
+    #[serde(default)]
+    pub time_offset_minutes: Option<i32>,
+    #[serde(default)]
+    pub local_offset_minutes: Option<i32>,
+    #[serde(default = "default_data_format")]
+    pub data_format: String,
+    #[serde(default = "one")]
+    pub timestamp_multiplication_factor: f64,
+    #[serde(default)]
+    pub sampling_rates: Vec<JsonSamplingRate>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sample_numbers: Option<Vec<u64>>,
+    pub timestamps: Vec<i64>,
+    #[serde(default)]
+    pub analog_channels: Vec<JsonAnalogChannel>,
+    #[serde(default)]
+    pub status_channels: Vec<JsonStatusChannel>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct JsonSamplingRate {
+    pub rate_hz: f64,
+    pub end_sample_number: u64,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct JsonAnalogChannel {
+    pub name: String,
+    #[serde(default)]
+    pub phase: String,
+    #[serde(default)]
+    pub circuit_component_being_monitored: String,
+    #[serde(default)]
+    pub units: String,
+    #[serde(default = "one")]
+    pub multiplier: f64,
+    #[serde(default)]
+    pub offset_adder: f64,
+    #[serde(default)]
+    pub skew: f64,
+    #[serde(default)]
+    pub min_value: Option<f64>,
+    #[serde(default)]
+    pub max_value: Option<f64>,
+    #[serde(default = "one")]
+    pub primary_factor: f64,
+    #[serde(default = "one")]
+    pub secondary_factor: f64,
+    #[serde(default = "default_scaling_mode")]
+    pub scaling_mode: String,
+    pub values: Vec<Option<f64>>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct JsonStatusChannel {
+    pub name: String,
+    #[serde(default)]
+    pub phase: String,
+    #[serde(default)]
+    pub circuit_component_being_monitored: String,
+    #[serde(default)]
+    pub normal_status_value: u8,
+    pub values: Vec<u8>,
+}
+
+fn one() -> f64 {
+    1.0
+}
+
+fn default_data_format() -> String {
+    "ASCII".to_string()
+}
+
+fn default_scaling_mode() -> String {
+    "P".to_string()
+}
+
+/// The record as JSON, in the format described by `JSON_SCHEMA`.
+pub fn to_json(record: &Comtrade) -> String {
+    serde_json::to_string(&JsonRecord::from(record)).expect("JSON records always serialize")
+}
+
+/// Record from JSON in the format described by `JSON_SCHEMA`, checking that it makes a
+/// valid record.
+pub fn from_json(json: &str) -> Result<Comtrade, JsonRecordError> {
+    let record: JsonRecord = serde_json::from_str(json).map_err(JsonRecordError::Json)?;
+    Comtrade::try_from(record)
+}
+
+impl From<&Comtrade> for JsonRecord {
+    fn from(record: &Comtrade) -> Self {
+        JsonRecord {
+            station_name: record.config.station_name.clone(),
+            recording_device_id: record.config.recording_device_id.clone(),
+            revision: match record.config.revision {
+                FormatRevision::Revision1991 => "1991",
+                FormatRevision::Revision1999 => "1999",
+                FormatRevision::Revision2013 => "2013",
+            }
+            .to_string(),
+            line_frequency: record.config.line_frequency,
+            start_time: format_time(record.config.start_time),
+            trigger_time: format_time(record.config.trigger_time),
+            time_offset_minutes: record.config.time_offset.map(offset_minutes),
+            local_offset_minutes: record.config.local_offset.map(offset_minutes),
+            data_format: match record.config.data_format {
+                DataFormat::Ascii => "ASCII",
+                DataFormat::Binary16 => "BINARY",
+                DataFormat::Binary32 => "BINARY32",
+                DataFormat::Float32 => "FLOAT32",
+            }
+            .to_string(),
+            timestamp_multiplication_factor: record.config.timestamp_multiplication_factor,
+            sampling_rates: record
+                .config
+                .sampling_rates
+                .iter()
+                .map(|rate| JsonSamplingRate {
+                    rate_hz: rate.rate_hz,
+                    end_sample_number: rate.end_sample_number,
+                })
+                .collect(),
+            sample_numbers: Some(record.data.sample_numbers.clone()),
+            timestamps: record.data.timestamps.clone(),
+            analog_channels: record
+                .analog_channels_with_data()
+                .map(|(channel, data)| JsonAnalogChannel {
+                    name: channel.name.clone(),
+                    phase: channel_phase(&channel.phase, &channel.phase_raw)
+                        .trim()
+                        .to_string(),
+                    circuit_component_being_monitored: channel
+                        .circuit_component_being_monitored
+                        .clone(),
+                    units: channel.units.clone(),
+                    multiplier: channel.multiplier,
+                    offset_adder: channel.offset_adder,
+                    skew: channel.skew,
+                    min_value: Some(channel.min_value),
+                    max_value: Some(channel.max_value),
+                    primary_factor: channel.primary_factor,
+                    secondary_factor: channel.secondary_factor,
+                    scaling_mode: match channel.scaling_mode {
+                        AnalogScalingMode::Primary => "P",
+                        AnalogScalingMode::Secondary => "S",
+                    }
+                    .to_string(),
+                    values: data
+                        .iter()
+                        .map(|value| Some(*value).filter(|v| v.is_finite()))
+                        .collect(),
+                })
+                .collect(),
+            status_channels: record
+                .status_channels_with_data()
+                .map(|(channel, data)| JsonStatusChannel {
+                    name: channel.name.clone(),
+                    phase: channel_phase(&channel.phase, &channel.phase_raw)
+                        .trim()
+                        .to_string(),
+                    circuit_component_being_monitored: channel
+                        .circuit_component_being_monitored
+                        .clone(),
+                    normal_status_value: channel.normal_status_value,
+                    values: data.to_vec(),
+                })
+                .collect(),
+        }
+    }
+}
+
+impl TryFrom<JsonRecord> for Comtrade {
+    type Error = JsonRecordError;
+
+    fn try_from(json: JsonRecord) -> Result<Self, Self::Error> {
+        let invalid = |message: String| JsonRecordError::Invalid(message);
+        let num_samples = json.timestamps.len();
+
+        let revision =
+            FormatRevision::from_str(&json.revision).map_err(|err| invalid(err.to_string()))?;
+        let data_format =
+            DataFormat::from_str(&json.data_format).map_err(|err| invalid(err.to_string()))?;
+        let start_time = parse_time(&json.start_time)
+            .ok_or_else(|| invalid(format!("invalid start time: '{}'", json.start_time)))?;
+        let trigger_time = parse_time(&json.trigger_time)
+            .ok_or_else(|| invalid(format!("invalid trigger time: '{}'", json.trigger_time)))?;
+        let time_offset = parse_offset(json.time_offset_minutes)
+            .map_err(|minutes| invalid(format!("invalid time offset: {} minutes", minutes)))?;
+        let local_offset = parse_offset(json.local_offset_minutes)
+            .map_err(|minutes| invalid(format!("invalid local offset: {} minutes", minutes)))?;
+        if json.timestamp_multiplication_factor <= 0.0 {
+            return Err(invalid(
+                "timestamp multiplication factor must be positive".to_string(),
+            ));
+        }
+
+        let sample_numbers = match json.sample_numbers {
+            Some(sample_numbers) if sample_numbers.len() != num_samples => {
+                return Err(invalid(format!(
+                    "{} sample numbers given for {} timestamps",
+                    sample_numbers.len(),
+                    num_samples
+                )))
+            }
+            Some(sample_numbers) => sample_numbers,
+            None => (1..=num_samples as u64).collect(),
+        };
+
+        let mut analog_channels = Vec::with_capacity(json.analog_channels.len());
+        let mut analog_data = Vec::with_capacity(json.analog_channels.len());
+        for (i, channel) in json.analog_channels.into_iter().enumerate() {
+            if channel.values.len() != num_samples {
+                return Err(invalid(format!(
+                    "analog channel '{}' has {} values but there are {} timestamps",
+                    channel.name,
+                    channel.values.len(),
+                    num_samples
+                )));
+            }
+            let scaling_mode = AnalogScalingMode::from_str(&channel.scaling_mode)
+                .map_err(|err| invalid(err.to_string()))?;
+            let data: Vec<f64> = channel
+                .values
+                .iter()
+                .map(|value| value.unwrap_or(f64::NAN))
+                .collect();
+
+            // Range of the values as they'd be in the `.dat` file.
+            let raw = |value: f64| (value - channel.offset_adder) / channel.multiplier;
+            let (min, max) = data
+                .iter()
+                .filter(|v| !v.is_nan())
+                .fold((f64::INFINITY, f64::NEG_INFINITY), |(min, max), &v| {
+                    (min.min(raw(v)), max.max(raw(v)))
+                });
+            let (min, max) = if min > max { (0.0, 0.0) } else { (min, max) };
+
+            analog_channels.push(AnalogChannel {
+                index: i as u32 + 1,
+                name_raw: channel.name.clone(),
+                name: channel.name.trim().to_string(),
+                phase: Phase::from(channel.phase.as_str()),
+                phase_raw: channel.phase.clone(),
+                circuit_component_being_monitored: channel.circuit_component_being_monitored,
+                units: channel.units,
+                min_value: channel.min_value.unwrap_or(min),
+                max_value: channel.max_value.unwrap_or(max),
+                multiplier: channel.multiplier,
+                offset_adder: channel.offset_adder,
+                skew: channel.skew,
+                primary_factor: channel.primary_factor,
+                secondary_factor: channel.secondary_factor,
+                scaling_mode,
+                extensions: BTreeMap::new(),
+            });
+            analog_data.push(data);
+        }
+
+        let mut status_channels = Vec::with_capacity(json.status_channels.len());
+        let mut status_data = Vec::with_capacity(json.status_channels.len());
+        for (i, channel) in json.status_channels.into_iter().enumerate() {
+            if channel.values.len() != num_samples {
+                return Err(invalid(format!(
+                    "status channel '{}' has {} values but there are {} timestamps",
+                    channel.name,
+                    channel.values.len(),
+                    num_samples
+                )));
+            }
+            if channel.normal_status_value > 1 || channel.values.iter().any(|v| *v > 1) {
+                return Err(invalid(format!(
+                    "status channel '{}' has values other than 0 and 1",
+                    channel.name
+                )));
+            }
+
+            status_channels.push(StatusChannel {
+                index: i as u32 + 1,
+                name_raw: channel.name.clone(),
+                name: channel.name.trim().to_string(),
+                phase: Phase::from(channel.phase.as_str()),
+                phase_raw: channel.phase.clone(),
+                circuit_component_being_monitored: channel.circuit_component_being_monitored,
+                normal_status_value: channel.normal_status_value,
+                extensions: BTreeMap::new(),
+            });
+            status_data.push(channel.values);
+        }
+
+        let sampling_rates: Vec<SamplingRate> = json
+            .sampling_rates
+            .iter()
+            .map(|rate| SamplingRate {
+                rate_hz: rate.rate_hz,
+                end_sample_number: rate.end_sample_number,
+            })
+            .collect();
+        let total_num_samples = sampling_rates
+            .last()
+            .map_or(num_samples as u64, |rate| rate.end_sample_number);
+
+        let mut config = ComtradeConfig {
+            station_name: json.station_name,
+            recording_device_id: json.recording_device_id,
+            revision,
+            num_total_channels: (analog_channels.len() + status_channels.len()) as u32,
+            num_analog_channels: analog_channels.len() as u32,
+            num_status_channels: status_channels.len() as u32,
+            analog_channels,
+            status_channels,
+            line_frequency: json.line_frequency,
+            sampling_rates,
+            total_num_samples,
+            start_time,
+            trigger_time,
+            data_format,
+            timestamp_multiplication_factor: json.timestamp_multiplication_factor,
+            time_offset,
+            local_offset,
+            ..Default::default()
+        };
+        config.timestamp_unit = config.implied_timestamp_unit();
+        let data = ComtradeData {
+            sample_numbers,
+            timestamps: json.timestamps,
+            analog_data,
+            status_data,
+            ..Default::default()
+        };
+        Ok(Comtrade::from_parts(config, data))
+    }
+}
+
+fn format_time(time: NaiveDateTime) -> String {
+    time.format("%Y-%m-%dT%H:%M:%S%.f").to_string()
+}
+
+fn parse_time(value: &str) -> Option<NaiveDateTime> {
+    NaiveDateTime::parse_from_str(value, "%Y-%m-%dT%H:%M:%S%.f").ok()
+}
+
+fn offset_minutes(offset: FixedOffset) -> i32 {
+    offset.local_minus_utc() / 60
+}
+
+/// Offset of `minutes` from UTC, or the minutes back if they're out of range.
+fn parse_offset(minutes: Option<i32>) -> Result<Option<FixedOffset>, i32> {
+    match minutes {
+        Some(minutes) => FixedOffset::east_opt(minutes.saturating_mul(60))
+            .map(Some)
+            .ok_or(minutes),
+        None => Ok(None),
+    }
+}
@@ -1,12 +1,108 @@
+#![cfg_attr(not(feature = "std"), no_std)]
+
+extern crate alloc;
+
+#[cfg(feature = "std")]
+pub mod analysis;
+#[cfg(feature = "approx")]
+mod approx_eq;
+#[cfg(feature = "cache")]
+pub mod cache;
+#[cfg(feature = "std")]
+pub mod catalog;
+pub mod chunk;
+pub mod comname;
+pub mod compat;
+#[cfg(any(feature = "gzip", feature = "zstd"))]
+pub mod compression;
+pub mod config;
+pub mod convert;
+#[cfg(feature = "time")]
+pub mod datetime;
+pub mod decimate;
+#[cfg(feature = "std")]
+pub mod detect;
+pub mod export;
+pub mod filter;
+pub mod fingerprint;
+#[cfg(feature = "json")]
+pub mod json;
+pub mod missing;
 pub mod parser;
-
-use chrono::{FixedOffset, NaiveDateTime};
-use derive_builder::Builder;
-
-pub use parser::{ComtradeParser, ComtradeParserBuilder, ParseError, ParseResult};
-
+pub mod passthrough;
+#[cfg(feature = "postgres")]
+pub mod postgres;
+#[cfg(feature = "protobuf")]
+pub mod protobuf;
+pub mod push;
+pub mod quality;
+pub mod rates;
+#[cfg(feature = "std")]
+pub mod report;
+pub mod rows;
+pub mod sanitize;
+#[cfg(feature = "sftp")]
+pub mod sftp;
+#[cfg(feature = "sidecar")]
+pub mod sidecar;
+pub mod sink;
+pub mod slice;
+pub mod source;
+pub mod status;
+pub mod stitch;
+#[cfg(feature = "testkit")]
+pub mod testkit;
+#[cfg(feature = "tz")]
+pub mod tz;
+pub mod units;
+pub mod vendor;
+pub mod writer;
+#[cfg(feature = "xml")]
+pub mod xml;
+
+use alloc::collections::BTreeMap;
+use alloc::string::String;
+use alloc::vec;
+use alloc::vec::Vec;
+use core::fmt;
+use core::ops::Range;
+
+use chrono::{Duration, NaiveDateTime, Timelike};
+
+#[cfg(feature = "std")]
+pub use catalog::{CatalogEntry, ComtradeCatalog};
+pub use comname::ComName;
+pub use compat::{ChannelScaling, ConfigMismatch};
+pub use config::{ComtradeConfig, ComtradeConfigBuilder, ComtradeData};
+pub use convert::{Loss, LossReport};
+pub use decimate::{DecimatedRecord, StatusEvent};
+#[cfg(feature = "std")]
+pub use detect::{detect_file_type, DetectedFileType};
+pub use filter::AnalogFilter;
+pub use missing::{MissingDataError, MissingDataPolicy};
+pub use parser::{
+    ComtradeParser, ComtradeParserBuilder, ComtradeRead, FileReader, ParseError, ParseLimits,
+    ParseResult, ParseStats, ParseWarning, ParserScratch, ReadError, SampleCountPolicy,
+    SampleOrderPolicy, Scan,
+};
+pub use passthrough::SourceFiles;
+pub use push::{Event, PushParser};
+pub use quality::QualityMapping;
+pub use rates::InferredSamplingRate;
+pub use rows::AnalogRows;
+pub use sanitize::{SanitizeAction, SanitizeOptions};
+pub use sink::DataSink;
+pub use slice::{AnalogChannelSlice, ComtradeSlice, StatusChannelSlice};
+pub use status::{StatusBits, StatusGroup, StatusTransition};
+pub use stitch::{stitch, StitchError};
+pub use units::{UnitFix, UnitFixReport};
+pub use vendor::{Quirks, VendorProfile};
+pub use writer::{AppendChannelError, ComtradeWriter};
+
+/// Files making up a COMTRADE record, which are also the sections of a combined `.cff`
+/// file.
 #[derive(Debug, Clone, PartialEq)]
-enum FileType {
+pub enum FileType {
     Cfg,
     Dat,
     Hdr,
@@ -28,18 +124,132 @@ pub enum DataFormat {
     Float32,
 }
 
+#[allow(clippy::derivable_impls)]
 impl Default for DataFormat {
     fn default() -> Self {
         DataFormat::Ascii
     }
 }
 
+impl DataFormat {
+    /// Number of bytes taken up by each analog value in binary data, or `None` for ASCII
+    /// data.
+    pub fn analog_value_size(&self) -> Option<usize> {
+        match self {
+            DataFormat::Ascii => None,
+            DataFormat::Binary16 => Some(2),
+            DataFormat::Binary32 | DataFormat::Float32 => Some(4),
+        }
+    }
+
+    /// Number of bytes taken up by each scan in binary data with the given numbers of
+    /// channels, or `None` for ASCII data. Each scan is a 4-byte sample number and
+    /// timestamp, followed by the analog values and then the status channels packed 16
+    /// to a 2-byte word.
+    ///
+    /// ```rust
+    /// use comtrade::DataFormat;
+    ///
+    /// assert_eq!(DataFormat::Binary16.bytes_per_scan(4, 16), Some(8 + 4 * 2 + 2));
+    /// assert_eq!(DataFormat::Float32.bytes_per_scan(2, 17), Some(8 + 2 * 4 + 2 * 2));
+    /// assert_eq!(DataFormat::Ascii.bytes_per_scan(4, 16), None);
+    /// ```
+    pub fn bytes_per_scan(
+        &self,
+        num_analog_channels: u32,
+        num_status_channels: u32,
+    ) -> Option<usize> {
+        let num_status_words = (num_status_channels as usize).div_ceil(16);
+        Some(8 + num_analog_channels as usize * self.analog_value_size()? + num_status_words * 2)
+    }
+}
+
 #[derive(Debug, Clone, PartialEq)]
 pub enum AnalogScalingMode {
     Primary,
     Secondary,
 }
 
+/// Phase identification of a channel, from the `ph` field of the `.cfg` file.
+///
+/// The standard doesn't fix the values used, so the common conventions are recognised
+/// regardless of case: `A`/`B`/`C`, `R`/`S`/`T` and `L1`/`L2`/`L3` for the three phases,
+/// `N` for neutral and `Pos`/`Neg`/`Zero` (or `+`/`-`/`0`) for sequence quantities. The
+/// text they were recognised from is kept in the channel's `phase_raw`.
+///
+/// ```rust
+/// use comtrade::Phase;
+///
+/// assert_eq!(Phase::from("L2"), Phase::B);
+/// assert_eq!(Phase::from(" t "), Phase::C);
+/// assert_eq!(Phase::from("AB"), Phase::Other("AB".to_string()));
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub enum Phase {
+    A,
+    B,
+    C,
+    N,
+    Positive,
+    Negative,
+    Zero,
+
+    /// Anything else, including a blank field, as it was in the file.
+    Other(String),
+}
+
+impl Phase {
+    /// Standard label for the phase, or the original text for `Phase::Other`.
+    pub fn as_str(&self) -> &str {
+        match self {
+            Phase::A => "A",
+            Phase::B => "B",
+            Phase::C => "C",
+            Phase::N => "N",
+            Phase::Positive => "Pos",
+            Phase::Negative => "Neg",
+            Phase::Zero => "Zero",
+            Phase::Other(value) => value,
+        }
+    }
+
+    /// Whether this is one of the three phases, i.e. `A`, `B` or `C`.
+    pub fn is_line_phase(&self) -> bool {
+        matches!(self, Phase::A | Phase::B | Phase::C)
+    }
+}
+
+impl From<&str> for Phase {
+    fn from(value: &str) -> Self {
+        let is_any =
+            |options: &[&str]| options.iter().any(|o| value.trim().eq_ignore_ascii_case(o));
+
+        if is_any(&["A", "R", "L1"]) {
+            Phase::A
+        } else if is_any(&["B", "S", "L2"]) {
+            Phase::B
+        } else if is_any(&["C", "T", "L3"]) {
+            Phase::C
+        } else if is_any(&["N"]) {
+            Phase::N
+        } else if is_any(&["Pos", "Positive", "+"]) {
+            Phase::Positive
+        } else if is_any(&["Neg", "Negative", "-"]) {
+            Phase::Negative
+        } else if is_any(&["Zero", "Zer", "0"]) {
+            Phase::Zero
+        } else {
+            Phase::Other(String::from(value))
+        }
+    }
+}
+
+impl Default for Phase {
+    fn default() -> Self {
+        Phase::Other(String::new())
+    }
+}
+
 // TODO: Most of these members can be private and just used for calculations, some of
 //       them don't even need to be in the actual struct at all but can just be used
 //       at parse-time (e.g. multiplying/additive factors).
@@ -48,8 +258,16 @@ pub enum AnalogScalingMode {
 pub struct AnalogChannel {
     /// 1-indexed counter used to determine which channel this is in a COMTRADE record.
     pub index: u32,
+
+    /// Channel name with any padding trimmed off.
     pub name: String,
-    pub phase: String,
+
+    /// Channel name exactly as it was in the `.cfg` file, padding and all.
+    pub name_raw: String,
+    pub phase: Phase,
+
+    /// Phase exactly as it was in the `.cfg` file, e.g. `L1` or `r` for `Phase::A`.
+    pub phase_raw: String,
     pub circuit_component_being_monitored: String,
     pub units: String,
     pub min_value: f64,
@@ -70,40 +288,119 @@ pub struct AnalogChannel {
 
     pub scaling_mode: AnalogScalingMode,
 
-    pub data: Vec<f64>,
+    /// Extra metadata about the channel, e.g. from its sections of the `.inf` file.
+    /// See `ComtradeConfig::extensions` for how the keys are named.
+    pub extensions: BTreeMap<String, String>,
 }
 
-impl AnalogChannel {
-    fn push_datum(&mut self, value: f64) {
-        self.data.push(value);
+/// Channel in primary values with a multiplier and primary and secondary factors of 1,
+/// so that values are as they are in the `.dat` file, and everything else empty or 0.
+impl Default for AnalogChannel {
+    fn default() -> Self {
+        AnalogChannel {
+            index: Default::default(),
+            name: Default::default(),
+            name_raw: Default::default(),
+            phase: Default::default(),
+            phase_raw: Default::default(),
+            circuit_component_being_monitored: Default::default(),
+            units: Default::default(),
+            min_value: Default::default(),
+            max_value: Default::default(),
+            multiplier: 1.0,
+            offset_adder: Default::default(),
+            skew: Default::default(),
+            primary_factor: 1.0,
+            secondary_factor: 1.0,
+            scaling_mode: AnalogScalingMode::Primary,
+            extensions: Default::default(),
+        }
     }
+}
 
-    // TODO: Method for retrieving datum at index / sample number including value and time calculations.
+/// Downsample channel data for display by splitting it into `n_buckets` contiguous
+/// buckets of (as near as possible) equal size and taking the minimum and maximum value
+/// of each. Unlike simply picking every nth sample, this preserves the peaks of the
+/// waveform.
+///
+/// If there are fewer samples than buckets, each sample gets a bucket to itself.
+/// Missing values are left out, so a bucket of only missing values has a minimum of
+/// infinity and a maximum of negative infinity.
+pub fn envelope(data: &[f64], n_buckets: usize) -> Vec<(f64, f64)> {
+    let n_samples = data.len();
+    if n_buckets == 0 || n_samples == 0 {
+        return vec![];
+    }
+    let n_buckets = n_buckets.min(n_samples);
+
+    (0..n_buckets)
+        .map(|bucket_idx| {
+            let start = bucket_idx * n_samples / n_buckets;
+            let end = (bucket_idx + 1) * n_samples / n_buckets;
+            // `min()` and `max()` ignore NaN, so missing values are skipped.
+            data[start..end]
+                .iter()
+                .fold((f64::INFINITY, f64::NEG_INFINITY), |(min, max), &value| {
+                    (min.min(value), max.max(value))
+                })
+        })
+        .collect()
 }
 
-#[derive(Debug, Clone, PartialEq)]
+/// One-line summary, e.g. `IA (A, phase A)`.
+impl fmt::Display for AnalogChannel {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} ({}", self.name, self.units.trim())?;
+        if !self.phase.as_str().trim().is_empty() {
+            write!(f, ", phase {}", self.phase.as_str().trim())?;
+        }
+        write!(f, ")")
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
 pub struct StatusChannel {
     pub index: u32,
+
+    /// Channel name with any padding trimmed off.
     pub name: String,
-    pub phase: String,
+
+    /// Channel name exactly as it was in the `.cfg` file, padding and all.
+    pub name_raw: String,
+    pub phase: Phase,
+
+    /// Phase exactly as it was in the `.cfg` file, e.g. `L1` or `r` for `Phase::A`.
+    pub phase_raw: String,
     pub circuit_component_being_monitored: String,
     pub normal_status_value: u8,
 
-    pub data: Vec<u8>, // Values are 0 or 1.
+    /// Extra metadata about the channel, e.g. from its sections of the `.inf` file.
+    /// See `ComtradeConfig::extensions` for how the keys are named.
+    pub extensions: BTreeMap<String, String>,
 }
 
-impl StatusChannel {
-    fn push_datum(&mut self, value: u8) {
-        self.data.push(value);
+/// One-line summary, e.g. `TRIP (normally 0)`.
+impl fmt::Display for StatusChannel {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} (normally {})", self.name, self.normal_status_value)
     }
-
-    // TODO: Method for retrieving datum at index / sample number including time calculations.
 }
 
 #[derive(Debug, Clone, PartialEq)]
 pub struct SamplingRate {
     pub rate_hz: f64,
-    pub end_sample_number: u32,
+    pub end_sample_number: u64,
+}
+
+/// Run of consecutive samples in a record that use the same sampling rate.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SamplingSegment {
+    /// Index into `ComtradeConfig::sampling_rates` of the rate used.
+    pub rate_index: usize,
+    pub rate_hz: f64,
+
+    /// 0-based indices of the samples in the segment.
+    pub samples: Range<usize>,
 }
 
 #[derive(Debug, Clone, PartialEq)]
@@ -149,67 +446,410 @@ pub enum LeapSecondStatus {
     NotPresent,
 }
 
-#[derive(Debug, Clone, Builder, PartialEq)]
+/// Way in which a sample doesn't follow on from the one before it.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum IrregularSampleKind {
+    DuplicateSampleNumber,
+    SampleNumberOutOfOrder,
+    DuplicateTimestamp,
+    TimestampBackwards,
+}
+
+/// Sample found to be out of sequence in the `.dat` file, which some recorders produce
+/// when they glitch, e.g. around a GPS resync. See `SampleOrderPolicy`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct IrregularSample {
+    /// 0-based position of the sample in the `.dat` file.
+    pub index: usize,
+    pub sample_number: u64,
+    pub kind: IrregularSampleKind,
+}
+
+/// Timestamp of a single sample, both as it was in the `.dat` file and the time it
+/// works out to. See `Comtrade::timestamp()`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Timestamp {
+    /// Value in the `.dat` file, in the base unit implied by the start time and before
+    /// the time multiplication factor is applied. `None` if it was missing, i.e. blank
+    /// in ASCII data or `0xFFFFFFFF` in binary data.
+    pub raw: Option<u32>,
+
+    /// Time of the sample relative to the start time, in nanoseconds. This comes from
+    /// the raw value if the timestamps are critical, otherwise from the sampling rate.
+    pub nanoseconds: i64,
+}
+
+impl Timestamp {
+    pub fn is_missing(&self) -> bool {
+        self.raw.is_none()
+    }
+
+    /// Time of the sample relative to the start time, in seconds.
+    pub fn seconds(&self) -> f64 {
+        self.nanoseconds as f64 / 1e9
+    }
+}
+
+/// A COMTRADE record: its configuration, i.e. everything in the `.cfg` file along with
+/// the header and other metadata, and its samples. The two can be taken apart and put
+/// back together without copying anything, e.g. to keep the metadata of many records
+/// around without their samples, or parse several `.dat` files against one
+/// configuration.
+#[derive(Debug, Clone, Default, PartialEq)]
 pub struct Comtrade {
-    pub station_name: String,
-    pub recording_device_id: String,
-    pub revision: FormatRevision,
+    pub config: ComtradeConfig,
+    pub data: ComtradeData,
+}
+
+impl Comtrade {
+    /// Total duration of the record in seconds, from the first sample to the last.
+    pub fn duration(&self) -> f64 {
+        match (self.data.timestamps.first(), self.data.timestamps.last()) {
+            (Some(first), Some(last)) => (last - first) as f64 / 1e9,
+            _ => 0.0,
+        }
+    }
+
+    /// Timestamp of the sample at `index` in seconds.
+    pub fn timestamp_seconds(&self, index: usize) -> Option<f64> {
+        self.data.timestamps.get(index).map(|t| *t as f64 / 1e9)
+    }
+
+    /// Timestamps of all the samples in seconds.
+    pub fn timestamps_seconds(&self) -> Vec<f64> {
+        self.data
+            .timestamps
+            .iter()
+            .map(|t| *t as f64 / 1e9)
+            .collect()
+    }
+
+    /// Raw and computed timestamp of the sample at `index`.
+    pub fn timestamp(&self, index: usize) -> Option<Timestamp> {
+        Some(Timestamp {
+            raw: self.data.raw_timestamps.get(index).copied().flatten(),
+            nanoseconds: *self.data.timestamps.get(index)?,
+        })
+    }
+
+    /// Raw and computed timestamps of all the samples, in order.
+    pub fn iter_timestamps(&self) -> impl Iterator<Item = Timestamp> + '_ {
+        (0..self.data.timestamps.len()).filter_map(move |i| self.timestamp(i))
+    }
 
-    // Don't think it's really necessary for have these fields.
-    pub num_total_channels: u32,
-    pub num_analog_channels: u32,
-    pub num_status_channels: u32,
+    /// Whether the raw timestamps in the `.dat` file roll over at some point, i.e. one
+    /// is less than the one before it by more than half the 32-bit range. This happens in
+    /// long power quality records with microsecond timestamps, which roll over after
+    /// about 71 minutes.
+    pub fn timestamp_overflow_detected(&self) -> bool {
+        let mut previous = None;
+        for raw in self.data.raw_timestamps.iter().flatten() {
+            if previous.is_some_and(|previous| is_timestamp_rollover(previous, *raw)) {
+                return true;
+            }
+            previous = Some(*raw);
+        }
+        false
+    }
 
-    pub sample_numbers: Vec<u32>,
-    pub timestamps: Vec<f64>,
-    pub analog_channels: Vec<AnalogChannel>,
-    pub status_channels: Vec<StatusChannel>,
+    /// Time in seconds between the start of the record and the trigger point.
+    pub fn pre_fault_duration(&self) -> f64 {
+        let pre_fault = self.config.trigger_time - self.config.start_time;
+        match pre_fault.num_nanoseconds() {
+            Some(ns) => ns as f64 * 1e-9,
+            None => pre_fault.num_milliseconds() as f64 * 1e-3,
+        }
+    }
 
-    pub line_frequency: f64,
+    /// Time in seconds between the trigger point and the end of the record.
+    pub fn post_fault_duration(&self) -> f64 {
+        (self.duration() - self.pre_fault_duration()).max(0.0)
+    }
 
-    pub sampling_rates: Vec<SamplingRate>,
-    pub start_time: NaiveDateTime,
-    pub trigger_time: NaiveDateTime,
+    /// Number of samples taken per power system cycle, based on the line frequency
+    /// and the first sampling rate. Returns `None` if the record doesn't have a fixed
+    /// sampling rate or the line frequency isn't known.
+    pub fn samples_per_cycle(&self) -> Option<f64> {
+        let rate = self.config.sampling_rates.first()?.rate_hz;
+        if rate <= 0.0 || self.config.line_frequency <= 0.0 {
+            return None;
+        }
+        Some(rate / self.config.line_frequency)
+    }
 
-    // Don't think these is necessary either, it's just used to parse / process the data file.
-    pub data_format: DataFormat,
+    /// Number of power system cycles covered by the record.
+    pub fn num_cycles(&self) -> f64 {
+        self.duration() * self.config.line_frequency
+    }
 
-    // Below data are 1999 format onwards only.
+    /// Split the samples into runs which share a sampling rate, so that changes in rate
+    /// can be shown on plots or each run resampled on its own. Samples not covered by
+    /// any of the sampling rates, e.g. in records without a fixed rate, are left out.
+    pub fn sampling_segments(&self) -> Vec<SamplingSegment> {
+        let mut segments: Vec<SamplingSegment> = vec![];
+
+        for (i, sample_number) in self.data.sample_numbers.iter().enumerate() {
+            let rate_index = match self
+                .config
+                .sampling_rates
+                .iter()
+                .position(|r| *sample_number <= r.end_sample_number)
+            {
+                Some(rate_index) => rate_index,
+                None => continue,
+            };
+
+            match segments.last_mut() {
+                Some(segment) if segment.rate_index == rate_index && segment.samples.end == i => {
+                    segment.samples.end = i + 1;
+                }
+                _ => segments.push(SamplingSegment {
+                    rate_index,
+                    rate_hz: self.config.sampling_rates[rate_index].rate_hz,
+                    samples: i..i + 1,
+                }),
+            }
+        }
 
-    // Don't use option for this - just default to 1 if it's not present.
-    pub timestamp_multiplication_factor: f64,
+        segments
+    }
 
-    // Below data are 2013 format onwards only.
-    pub time_offset: Option<FixedOffset>,
-    pub local_offset: Option<FixedOffset>,
+    /// Indices of the samples at which the sampling rate changes, i.e. the first
+    /// sample of each sampling segment after the first.
+    pub fn segment_boundaries(&self) -> Vec<usize> {
+        self.sampling_segments()
+            .iter()
+            .skip(1)
+            .map(|segment| segment.samples.start)
+            .collect()
+    }
 
-    pub time_quality: Option<TimeQuality>,
-    pub leap_second_status: Option<LeapSecondStatus>,
+    /// Status channels packed back into 16-bit words for each sample, in the same
+    /// layout as binary `.dat` files: bit `n` of word `w` is status channel `16 * w + n`,
+    /// with any bits after the final channel left 0. Useful for vendor-defined fields
+    /// that are spread over adjacent status channels.
+    pub fn status_words(&self) -> Vec<Vec<u16>> {
+        let num_words = self.config.status_channels.len().div_ceil(16);
+        let mut words = vec![vec![0u16; num_words]; self.data.sample_numbers.len()];
+
+        for (c, data) in self.data.status_data.iter().enumerate() {
+            for (sample_words, value) in words.iter_mut().zip(data.iter()) {
+                sample_words[c / 16] |= ((*value & 1) as u16) << (c % 16);
+            }
+        }
+
+        words
+    }
+
+    /// First analog channel with this name, ignoring any padding around either name.
+    pub fn analog_channel(&self, name: &str) -> Option<&AnalogChannel> {
+        self.config
+            .analog_channels
+            .iter()
+            .find(|c| c.name == name.trim())
+    }
+
+    /// First status channel with this name, ignoring any padding around either name.
+    pub fn status_channel(&self, name: &str) -> Option<&StatusChannel> {
+        self.config
+            .status_channels
+            .iter()
+            .find(|c| c.name == name.trim())
+    }
+
+    /// Values of the first analog channel with this name, see `analog_channel()`.
+    pub fn analog_data(&self, name: &str) -> Option<&[f64]> {
+        let name = name.trim();
+        self.analog_channels_with_data()
+            .find(|(c, _)| c.name == name)
+            .map(|(_, data)| data)
+    }
+
+    /// Values of the first status channel with this name, see `status_channel()`.
+    pub fn status_data(&self, name: &str) -> Option<&[u8]> {
+        let name = name.trim();
+        self.status_channels_with_data()
+            .find(|(c, _)| c.name == name)
+            .map(|(_, data)| data)
+    }
+
+    /// Each analog channel along with its values. A channel without any values in
+    /// `data` gets an empty slice.
+    pub fn analog_channels_with_data(&self) -> impl Iterator<Item = (&AnalogChannel, &[f64])> {
+        let mut data = self.data.analog_data.iter();
+        self.config
+            .analog_channels
+            .iter()
+            .map(move |c| (c, data.next().map_or(&[][..], Vec::as_slice)))
+    }
+
+    /// Each status channel along with its values. A channel without any values in
+    /// `data` gets an empty slice.
+    pub fn status_channels_with_data(&self) -> impl Iterator<Item = (&StatusChannel, &[u8])> {
+        let mut data = self.data.status_data.iter();
+        self.config
+            .status_channels
+            .iter()
+            .map(move |c| (c, data.next().map_or(&[][..], Vec::as_slice)))
+    }
+
+    /// Absolute time of the sample at `index`, i.e. the start time of the record plus
+    /// the sample's timestamp, in the same time zone as the start time.
+    ///
+    /// If a leap second was added or subtracted during the record, samples from the leap
+    /// second onwards are shifted by a second so that they line up with UTC. Samples
+    /// during an added leap second are given as 23:59:60 (in UTC terms) using chrono's
+    /// leap second representation.
+    pub fn absolute_time(&self, index: usize) -> Option<NaiveDateTime> {
+        let time =
+            self.config.start_time + Duration::nanoseconds(*self.data.timestamps.get(index)?);
+        let leap_second_time = match self.leap_second_time() {
+            Some(leap_second_time) => leap_second_time,
+            None => return Some(time),
+        };
+        let one_second = Duration::seconds(1);
+
+        match self.config.leap_second_status {
+            Some(LeapSecondStatus::Added) if time >= leap_second_time + one_second => {
+                Some(time - one_second)
+            }
+            Some(LeapSecondStatus::Added) if time >= leap_second_time => {
+                let into_leap_second = (time - leap_second_time).num_nanoseconds()? as u32;
+                (leap_second_time - one_second).with_nanosecond(1_000_000_000 + into_leap_second)
+            }
+            Some(LeapSecondStatus::Subtracted) if time >= leap_second_time - one_second => {
+                Some(time + one_second)
+            }
+            _ => Some(time),
+        }
+    }
+
+    /// Local time of the UTC midnight at which the leap second indicated by
+    /// `leap_second_status` took place, assuming the timestamps count elapsed time
+    /// straight through it. `None` if there was no leap second during the record.
+    ///
+    /// The record's time code is used to find UTC midnight; if there isn't one, the start
+    /// time is assumed to be in UTC.
+    pub fn leap_second_time(&self) -> Option<NaiveDateTime> {
+        if !matches!(
+            self.config.leap_second_status,
+            Some(LeapSecondStatus::Added) | Some(LeapSecondStatus::Subtracted)
+        ) {
+            return None;
+        }
+
+        let utc_offset = Duration::seconds(
+            self.config
+                .time_offset
+                .map_or(0, |offset| offset.local_minus_utc() as i64),
+        );
+        let start_utc = self.config.start_time - utc_offset;
+        let midnight_utc = start_utc.date().succ().and_hms(0, 0, 0);
+        let leap_second_time = midnight_utc + utc_offset;
+
+        let end_time = self.config.start_time
+            + Duration::nanoseconds(self.data.timestamps.last().copied().unwrap_or_default());
+        if leap_second_time > self.config.start_time
+            && leap_second_time <= end_time + Duration::seconds(1)
+        {
+            Some(leap_second_time)
+        } else {
+            None
+        }
+    }
+
+    /// Indices of the samples whose absolute time is adjusted to account for a leap
+    /// second, if there was one during the record.
+    pub fn leap_second_span(&self) -> Option<Range<usize>> {
+        let leap_second_time = self.leap_second_time()?;
+        let first_affected = match self.config.leap_second_status {
+            Some(LeapSecondStatus::Subtracted) => leap_second_time - Duration::seconds(1),
+            _ => leap_second_time,
+        };
+
+        let start =
+            self.data.timestamps.iter().position(|t| {
+                self.config.start_time + Duration::nanoseconds(*t) >= first_affected
+            })?;
+        Some(start..self.data.timestamps.len())
+    }
 }
 
-impl Default for Comtrade {
-    fn default() -> Self {
-        Comtrade {
-            station_name: Default::default(),
-            recording_device_id: Default::default(),
-            revision: Default::default(),
-            num_total_channels: Default::default(),
-            num_analog_channels: Default::default(),
-            num_status_channels: Default::default(),
-            sample_numbers: Default::default(),
-            timestamps: Default::default(),
-            analog_channels: Default::default(),
-            status_channels: Default::default(),
-            line_frequency: Default::default(),
-            sampling_rates: Default::default(),
-            start_time: NaiveDateTime::from_timestamp(0, 0),
-            trigger_time: NaiveDateTime::from_timestamp(0, 0),
-            data_format: Default::default(),
-            timestamp_multiplication_factor: 1.0,
-            time_offset: Default::default(),
-            local_offset: Default::default(),
-            time_quality: Default::default(),
-            leap_second_status: Default::default(),
+/// Whether a raw timestamp following `previous` has wrapped around past `u32::MAX`
+/// rather than just going backwards.
+pub(crate) fn is_timestamp_rollover(previous: u32, current: u32) -> bool {
+    current < previous && previous - current > u32::MAX / 2
+}
+
+/// Summary of the record over a few lines: where and when it was recorded, how it was
+/// sampled and a line for each channel.
+impl fmt::Display for Comtrade {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let revision = match self.config.revision {
+            FormatRevision::Revision1991 => "1991",
+            FormatRevision::Revision1999 => "1999",
+            FormatRevision::Revision2013 => "2013",
+        };
+        writeln!(
+            f,
+            "{} / {} ({} revision)",
+            self.config.station_name.trim(),
+            self.config.recording_device_id.trim(),
+            revision
+        )?;
+        writeln!(
+            f,
+            "Started {}, triggered {}",
+            self.config.start_time, self.config.trigger_time
+        )?;
+
+        write!(f, "{} samples", self.data.sample_numbers.len())?;
+        for (i, rate) in self
+            .config
+            .sampling_rates
+            .iter()
+            .filter(|r| r.rate_hz > 0.0)
+            .enumerate()
+        {
+            write!(
+                f,
+                "{} {} Hz",
+                if i == 0 { " at" } else { "," },
+                rate.rate_hz
+            )?;
+        }
+        writeln!(
+            f,
+            " over {} s, {} Hz line frequency",
+            self.duration(),
+            self.config.line_frequency
+        )?;
+
+        writeln!(f, "{} analog channels", self.config.analog_channels.len())?;
+        for (channel, data) in self.analog_channels_with_data() {
+            write!(f, "  {}: {} samples", channel, data.len())?;
+            let (min, max) = data
+                .iter()
+                .fold((f64::INFINITY, f64::NEG_INFINITY), |(min, max), &value| {
+                    (min.min(value), max.max(value))
+                });
+            if min <= max {
+                write!(f, " from {:.3} to {:.3}", min, max)?;
+            }
+            writeln!(f)?;
+        }
+        write!(f, "{} status channels", self.config.status_channels.len())?;
+        for (channel, data) in self.status_channels_with_data() {
+            let n_changes = data.windows(2).filter(|w| w[0] != w[1]).count();
+            write!(
+                f,
+                "\n  {}: {} samples, {} {}",
+                channel,
+                data.len(),
+                n_changes,
+                if n_changes == 1 { "change" } else { "changes" }
+            )?;
         }
+        Ok(())
     }
 }
@@ -0,0 +1,99 @@
+//! Dealing with analog values that were missing from the `.dat` file, which are NaN in
+//! `ComtradeData::analog_data`.
+
+use alloc::borrow::Cow;
+use alloc::vec::Vec;
+use core::fmt;
+
+/// What analysis and export functions do with missing values.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum MissingDataPolicy {
+    /// Leave out the missing values. Results worked out over a window of samples use the
+    /// values that are there, results which need a particular missing value are missing
+    /// themselves, and exports leave out the missing points.
+    #[default]
+    Skip,
+
+    /// Fill in missing values by linear interpolation between the values either side.
+    /// Missing values at the start or end of the channel take the nearest value.
+    Interpolate,
+
+    /// Treat missing values as 0.
+    Zero,
+
+    /// Give an error if there are any missing values.
+    Error,
+}
+
+/// Missing value found while using `MissingDataPolicy::Error`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct MissingDataError {
+    /// 0-based index of the first missing sample.
+    pub sample_index: usize,
+}
+
+impl fmt::Display for MissingDataError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "missing analog value at sample {}", self.sample_index)
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for MissingDataError {}
+
+impl MissingDataPolicy {
+    /// Values with the missing ones dealt with according to the policy. With `Skip` the
+    /// values are returned as they are, missing values and all, for the caller to leave
+    /// out. With any other policy, there's nothing missing in what's returned.
+    ///
+    /// If every value is missing, there's nothing to interpolate from, so `Interpolate`
+    /// gives zeros.
+    pub fn apply<'a>(&self, data: &'a [f64]) -> Result<Cow<'a, [f64]>, MissingDataError> {
+        let first_missing = match data.iter().position(|v| v.is_nan()) {
+            Some(index) => index,
+            None => return Ok(Cow::Borrowed(data)),
+        };
+
+        match self {
+            MissingDataPolicy::Skip => Ok(Cow::Borrowed(data)),
+            MissingDataPolicy::Error => Err(MissingDataError {
+                sample_index: first_missing,
+            }),
+            MissingDataPolicy::Zero => Ok(Cow::Owned(
+                data.iter()
+                    .map(|v| if v.is_nan() { 0.0 } else { *v })
+                    .collect(),
+            )),
+            MissingDataPolicy::Interpolate => Ok(Cow::Owned(interpolate(data))),
+        }
+    }
+}
+
+fn interpolate(data: &[f64]) -> Vec<f64> {
+    let mut filled = data.to_vec();
+    let mut previous: Option<usize> = None;
+
+    for i in 0..data.len() {
+        if data[i].is_nan() {
+            continue;
+        }
+
+        let start = previous.map_or(0, |p| p + 1);
+        for (j, value) in filled.iter_mut().enumerate().take(i).skip(start) {
+            *value = match previous {
+                Some(p) => {
+                    let fraction = (j - p) as f64 / (i - p) as f64;
+                    data[p] + (data[i] - data[p]) * fraction
+                }
+                None => data[i],
+            };
+        }
+        previous = Some(i);
+    }
+
+    let trailing = previous.map_or(0, |p| p + 1);
+    let last_value = previous.map_or(0.0, |p| data[p]);
+    filled[trailing..].fill(last_value);
+
+    filled
+}
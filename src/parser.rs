@@ -1,17 +1,54 @@
-use std::io::{BufRead, Cursor};
-use std::str::FromStr;
-
-use byteorder::{LittleEndian, ReadBytesExt};
+use alloc::borrow::ToOwned;
+use alloc::boxed::Box;
+use alloc::collections::BTreeMap;
+use alloc::format;
+use alloc::string::{String, ToString};
+use alloc::vec;
+use alloc::vec::Vec;
+use core::fmt;
+use core::ops::Range;
+use core::str::FromStr;
+use core::time::Duration;
+#[cfg(feature = "std")]
+use std::fs::File;
+#[cfg(feature = "std")]
+use std::io::BufReader;
+#[cfg(feature = "std")]
+use std::path::{Path, PathBuf};
+
+use byteorder::{ByteOrder, LittleEndian};
 use chrono::{FixedOffset, NaiveDateTime};
-use lazy_static::lazy_static;
-use regex::Regex;
-
+#[cfg(feature = "decimal")]
+use rust_decimal::prelude::{Decimal, FromPrimitive, ToPrimitive};
+
+#[cfg(feature = "std")]
+use crate::comname::ComName;
+use crate::passthrough::SourceFiles;
+use crate::quality::QualityMapping;
+use crate::rates::infer_sampling_rates;
+use crate::sink::DataSink;
 use crate::{
-    AnalogChannel, AnalogScalingMode, Comtrade, ComtradeBuilder, DataFormat, FileType,
-    FormatRevision, LeapSecondStatus, SamplingRate, StatusChannel, TimeQuality,
+    is_timestamp_rollover, AnalogChannel, AnalogScalingMode, Comtrade, ComtradeConfig,
+    ComtradeConfigBuilder, ComtradeData, DataFormat, FileType, FormatRevision, IrregularSample,
+    IrregularSampleKind, LeapSecondStatus, Phase, Quirks, SamplingRate, StatusChannel, TimeQuality,
+    VendorProfile,
 };
 
-const CFG_SEPARATOR: &str = ",";
+/// Most samples to allocate space for before reading the data file.
+const MAX_PREALLOCATED_SAMPLES: usize = 1 << 24;
+
+/// Most channels or sampling rates to allocate space for before reading them from the
+/// `.cfg` file.
+const MAX_PREALLOCATED_ENTRIES: usize = 1 << 12;
+
+const CFG_SEPARATOR: char = ',';
+
+// Used instead of a comma by recorders in locales where the comma is the decimal separator.
+const CFG_SEPARATOR_DECIMAL_COMMA: char = ';';
+
+// Fraction by which the sampling rate inferred from the timestamps can differ from the
+// declared rate before it's warned about, allowing for jitter in the timestamps.
+const SAMPLING_RATE_TOLERANCE: f64 = 0.01;
 
 // 1991 revision uses mm/dd/yyyy format for date whereas 1999 and 2013 use dd/mm/yyyy.
 // 1991 revision uses mm/dd/yyyy format for date whereas 1999 and 2013 use dd/mm/yyyy
@@ -22,7 +59,7 @@ const CFG_DATETIME_FORMAT: &str = "%d/%m/%Y,%H:%M:%S%.f";
 // and float32 data formats when a timestamp is missing.
 const TIMESTAMP_MISSING: u32 = 0xffffffff;
 
-pub type ParseResult<T> = std::result::Result<T, ParseError>;
+pub type ParseResult<T> = core::result::Result<T, ParseError>;
 
 #[derive(Debug, Clone)]
 pub struct ParseError {
@@ -30,11 +67,60 @@ pub struct ParseError {
 }
 
 impl ParseError {
-    fn new(message: String) -> Self {
+    pub(crate) fn new(message: String) -> Self {
         ParseError { message }
     }
 }
 
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.message)
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for ParseError {}
+
+/// A problem found in the COMTRADE files that isn't serious enough to stop parsing,
+/// e.g. a non-critical value that was missing or invalid and has been replaced with
+/// a sensible default. These are collected in `ComtradeConfig::warnings`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ParseWarning {
+    message: String,
+}
+
+impl ParseWarning {
+    fn new(message: String) -> Self {
+        ParseWarning { message }
+    }
+
+    pub fn message(&self) -> &str {
+        &self.message
+    }
+}
+
+impl fmt::Display for ParseWarning {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.message)
+    }
+}
+
+/// A single decoded sample from the `.dat` file, with the analog values already
+/// scaled using the channel multipliers and offsets. Values are in the same order as
+/// the channels in the `.cfg` file.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Scan {
+    pub sample_number: u64,
+
+    /// Time of the sample in nanoseconds relative to the start of the record.
+    pub timestamp: i64,
+
+    /// Timestamp as it was in the `.dat` file, or `None` if it was missing.
+    pub raw_timestamp: Option<u32>,
+    pub analog_values: Vec<f64>,
+    pub status_values: Vec<u8>,
+}
+
 impl FromStr for FileType {
     type Err = ParseError;
 
@@ -49,6 +135,7 @@ impl FromStr for FileType {
     }
 }
 
+#[allow(clippy::derivable_impls)]
 impl Default for FormatRevision {
     fn default() -> Self {
         FormatRevision::Revision1991
@@ -91,9 +178,9 @@ impl FromStr for DataFormat {
 impl FromStr for AnalogScalingMode {
     type Err = ParseError;
     fn from_str(value: &str) -> ParseResult<Self> {
-        match value.to_lowercase().as_str() {
-            "p" => Ok(AnalogScalingMode::Primary),
-            "s" => Ok(AnalogScalingMode::Secondary),
+        match value {
+            "p" | "P" => Ok(AnalogScalingMode::Primary),
+            "s" | "S" => Ok(AnalogScalingMode::Secondary),
             _ => Err(ParseError::new(format!(
                 "invalid analog scaling mode: '{}'; must be one of: 's', 'S', 'p', 'P'",
                 value,
@@ -145,24 +232,550 @@ impl FromStr for LeapSecondStatus {
     }
 }
 
-lazy_static! {
-    static ref CFF_HEADER_REGEXP: Regex = Regex::new(r#"(?i)---\s*file type:\s*(?P<file_type>[a-z]+)(\s+(?P<data_format>[a-z]+))?\s*(:\s*(?P<data_size>\d+))?\s*---$"#).unwrap();
-    static ref DATE_REGEXP: Regex = Regex::new("([0-9]{1,2})/([0-9]{1,2})/([0-9]{2,4})").unwrap();
-    static ref TIME_REGEXP: Regex = Regex::new("([0-9]{2}):([0-9]{2}):([0-9]{2})(\\.([0-9]{1,12}))?").unwrap();
+#[cfg(feature = "std")]
+impl Comtrade {
+    /// Parse a combined `.cff` file from disk.
+    pub fn from_cff_path<P: AsRef<Path>>(cff_path: P) -> ParseResult<Self> {
+        ComtradeParserBuilder::new()
+            .cff_file(open_file(cff_path.as_ref())?)
+            .build()
+            .parse()
+    }
+
+    /// Parse separate `.cfg` and `.dat` files from disk.
+    pub fn from_cfg_dat_paths<P: AsRef<Path>, Q: AsRef<Path>>(
+        cfg_path: P,
+        dat_path: Q,
+    ) -> ParseResult<Self> {
+        ComtradeParserBuilder::new()
+            .cfg_file(open_file(cfg_path.as_ref())?)
+            .dat_file(open_file(dat_path.as_ref())?)
+            .build()
+            .parse()
+    }
+}
+
+/// Parse a record from disk given the path to either a combined `.cff` file or a
+/// `.cfg` file. In the latter case, the `.dat` file is expected to be alongside it
+/// with the same name, and any `.hdr` and `.inf` files there are also picked up.
+///
+/// If the file is named following the COMNAME convention, the fields of the name are
+/// recorded in the record's `extensions` - see `ComName::attach_to()`.
+#[cfg(feature = "std")]
+impl TryFrom<&Path> for Comtrade {
+    type Error = ParseError;
+
+    fn try_from(path: &Path) -> ParseResult<Self> {
+        let mut record = parse_path(path)?;
+        if let Some(comname) = path
+            .file_name()
+            .and_then(|f| f.to_str())
+            .and_then(|f| ComName::parse(f).ok())
+        {
+            comname.attach_to(&mut record);
+        }
+        Ok(record)
+    }
+}
+
+#[cfg(feature = "std")]
+fn parse_path(path: &Path) -> ParseResult<Comtrade> {
+    let base = strip_compressed_extension(path);
+    let extension = base
+        .extension()
+        .and_then(|e| e.to_str())
+        .map(|e| e.to_lowercase());
+
+    match extension.as_deref() {
+        Some("cff") => Comtrade::from_cff_path(path),
+        Some("cfg") => {
+            let dat_path = sibling_path(&base, "dat").ok_or_else(|| {
+                ParseError::new(format!(
+                    "unable to find .dat file to go with {}",
+                    path.display()
+                ))
+            })?;
+
+            let mut builder = ComtradeParserBuilder::new()
+                .cfg_file(open_file(path)?)
+                .dat_file(open_file(&dat_path)?);
+            if let Some(hdr_path) = sibling_path(&base, "hdr") {
+                builder = builder.hdr_file(open_file(&hdr_path)?);
+            }
+            if let Some(inf_path) = sibling_path(&base, "inf") {
+                builder = builder.inf_file(open_file(&inf_path)?);
+            }
+
+            builder.build().parse()
+        }
+        _ => Err(ParseError::new(format!(
+            "unable to determine COMTRADE file type from extension of {}; expected .cff or .cfg",
+            path.display()
+        ))),
+    }
+}
+
+/// Parse a record from the contents of a combined `.cff` file.
+impl FromStr for Comtrade {
+    type Err = ParseError;
+
+    fn from_str(value: &str) -> ParseResult<Self> {
+        ComtradeParserBuilder::new()
+            .cff_file(value.as_bytes())
+            .build()
+            .parse()
+    }
+}
+
+#[cfg(feature = "std")]
+fn open_file(path: &Path) -> ParseResult<BufReader<File>> {
+    let file = File::open(path)
+        .map_err(|e| ParseError::new(format!("unable to open {}: {}", path.display(), e)))?;
+    Ok(BufReader::new(file))
+}
+
+/// Extensions of files which are decompressed as they're read, e.g. `.dat.gz`.
+#[cfg(feature = "std")]
+const COMPRESSED_EXTENSIONS: &[&str] = &[
+    #[cfg(feature = "gzip")]
+    "gz",
+    #[cfg(feature = "zstd")]
+    "zst",
+];
+
+/// Path without the extension of a compressed file, e.g. `record.cfg` for
+/// `record.cfg.gz`, so that the extension underneath can be found.
+#[cfg(feature = "std")]
+fn strip_compressed_extension(path: &Path) -> PathBuf {
+    match path.extension().and_then(|e| e.to_str()) {
+        Some(e) if COMPRESSED_EXTENSIONS.contains(&e.to_lowercase().as_str()) => {
+            path.with_extension("")
+        }
+        _ => path.to_path_buf(),
+    }
+}
+
+/// Find file with the same name but different extension, which might be in lower or
+/// upper case, and might be compressed.
+#[cfg(feature = "std")]
+fn sibling_path(path: &Path, extension: &str) -> Option<PathBuf> {
+    [extension.to_lowercase(), extension.to_uppercase()]
+        .iter()
+        .flat_map(|e| {
+            let upper = e.chars().any(|c| c.is_ascii_uppercase());
+            core::iter::once(e.clone()).chain(COMPRESSED_EXTENSIONS.iter().map(move |c| {
+                let c = if upper {
+                    c.to_uppercase()
+                } else {
+                    c.to_string()
+                };
+                format!("{}.{}", e, c)
+            }))
+        })
+        .map(|e| path.with_extension(e))
+        .find(|p| p.is_file())
+}
+
+/// Lines of a combined `.cff` file, each with the offset it starts at and including its
+/// line ending, so that sections can be sliced out of the file exactly as they were
+/// written.
+pub(crate) struct CffLines<'a> {
+    contents: &'a str,
+    position: usize,
+}
+
+impl<'a> CffLines<'a> {
+    pub fn new(contents: &'a str) -> Self {
+        CffLines {
+            contents,
+            position: 0,
+        }
+    }
+
+    /// Length of the line at the start of `contents`, including its line ending.
+    fn line_length(contents: &str) -> usize {
+        contents.find('\n').map_or(contents.len(), |i| i + 1)
+    }
+}
+
+impl<'a> Iterator for CffLines<'a> {
+    type Item = (usize, &'a str);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let rest = &self.contents[self.position..];
+        if rest.is_empty() {
+            return None;
+        }
+        let start = self.position;
+        self.position += CffLines::line_length(rest);
+        Some((start, &self.contents[start..self.position]))
+    }
+}
+
+/// Section header in a combined `.cff` file, e.g. `--- file type: DAT BINARY: 1234 ---`.
+pub(crate) struct CffHeader<'a> {
+    pub file_type: &'a str,
+    pub data_format: Option<&'a str>,
+    pub data_size: Option<&'a str>,
+}
+
+/// Match a `.cff` section header line. Real-world files aren't always consistent about
+/// spacing, case or the number of dashes around the header, and sometimes miss off the
+/// trailing dashes altogether, so we're pretty lenient here.
+pub(crate) fn parse_cff_header(line: &str) -> Option<CffHeader<'_>> {
+    let line = line.trim();
+    if !line.starts_with("--") {
+        return None;
+    }
+
+    let rest = line.trim_start_matches('-').trim_start();
+    let rest = strip_prefix_ignore_case(rest, "file")?.trim_start();
+    let rest = strip_prefix_ignore_case(rest, "type")?.trim_start();
+    let rest = rest.strip_prefix(':')?.trim_start();
+
+    // Trailing dashes are optional, but there must be at least two of them if present.
+    let without_dashes = rest.trim_end_matches('-');
+    if rest.len() - without_dashes.len() == 1 {
+        return None;
+    }
+    let rest = without_dashes.trim_end();
+
+    let (rest, data_size) = match rest.split_once(':') {
+        Some((rest, size)) => {
+            let size = size.trim();
+            if size.is_empty() || !size.bytes().all(|b| b.is_ascii_digit()) {
+                return None;
+            }
+            (rest, Some(size))
+        }
+        None => (rest, None),
+    };
+
+    let mut tokens = rest.split_whitespace();
+    let file_type = tokens.next()?;
+    let data_format = tokens.next();
+    if tokens.next().is_some() || !file_type.bytes().all(|b| b.is_ascii_alphabetic()) {
+        return None;
+    }
+    if let Some(data_format) = data_format {
+        if !data_format.bytes().all(|b| b.is_ascii_alphanumeric()) {
+            return None;
+        }
+    }
+
+    Some(CffHeader {
+        file_type,
+        data_format,
+        data_size,
+    })
+}
+
+/// Whether a line could be a scan in an ASCII data section, i.e. it's made up of
+/// numbers and separators. Anything else after the data section of a `.cff` file is
+/// trailing content, e.g. a vendor blob appended by the exporter.
+pub(crate) fn is_ascii_scan_line(line: &str) -> bool {
+    line.bytes().all(|b| {
+        b.is_ascii_digit()
+            || matches!(
+                b,
+                b',' | b';' | b'.' | b'-' | b'+' | b'e' | b'E' | b' ' | b'\t'
+            )
+    })
+}
+
+fn strip_prefix_ignore_case<'a>(value: &'a str, prefix: &str) -> Option<&'a str> {
+    let head = value.get(..prefix.len())?;
+    if head.eq_ignore_ascii_case(prefix) {
+        Some(&value[prefix.len()..])
+    } else {
+        None
+    }
+}
+
+/// Error returned by a `ComtradeRead` source when its contents can't be read.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ReadError;
+
+impl fmt::Display for ReadError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "unable to read from source")
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for ReadError {}
+
+/// Source of the contents of one of the files making up a record.
+///
+/// With the `std` feature enabled (the default) this is implemented for everything
+/// implementing `std::io::Read`. Without it, it's implemented for byte slices, and can
+/// be implemented for whatever buffer or peripheral the records arrive through.
+pub trait ComtradeRead {
+    /// Read all remaining bytes from the source, appending them to `buf`.
+    fn read_all(&mut self, buf: &mut Vec<u8>) -> Result<(), ReadError>;
+
+    /// Read at most `limit` bytes from the source, appending them to `buf`. This lets the
+    /// parser give up on a file which is bigger than it should be without reading all
+    /// of it, although the default implementation does read it all and then drops
+    /// whatever is past the limit.
+    fn read_at_most(&mut self, buf: &mut Vec<u8>, limit: usize) -> Result<(), ReadError> {
+        let start = buf.len();
+        self.read_all(buf)?;
+        buf.truncate(start.saturating_add(limit));
+        Ok(())
+    }
+
+    /// Read all remaining bytes from the source as UTF-8 text, appending it to `buf`.
+    fn read_all_to_string(&mut self, buf: &mut String) -> Result<(), ReadError> {
+        let mut bytes = vec![];
+        self.read_all(&mut bytes)?;
+        if buf.is_empty() {
+            // Avoid copying the whole file again in the usual case.
+            *buf = String::from_utf8(bytes).map_err(|_| ReadError)?;
+        } else {
+            buf.push_str(core::str::from_utf8(&bytes).map_err(|_| ReadError)?);
+        }
+        Ok(())
+    }
+}
+
+#[cfg(feature = "std")]
+impl<R: std::io::Read> ComtradeRead for R {
+    fn read_all(&mut self, buf: &mut Vec<u8>) -> Result<(), ReadError> {
+        self.read_to_end(buf).map(|_| ()).map_err(|_| ReadError)
+    }
+
+    fn read_at_most(&mut self, buf: &mut Vec<u8>, limit: usize) -> Result<(), ReadError> {
+        use std::io::Read;
+
+        self.by_ref()
+            .take(limit as u64)
+            .read_to_end(buf)
+            .map(|_| ())
+            .map_err(|_| ReadError)
+    }
+}
+
+#[cfg(not(feature = "std"))]
+impl ComtradeRead for &[u8] {
+    fn read_all(&mut self, buf: &mut Vec<u8>) -> Result<(), ReadError> {
+        buf.extend_from_slice(self);
+        *self = &[];
+        Ok(())
+    }
+}
+
+/// Upper limits on the size of a record, so that files from an untrusted source can't
+/// make the parser allocate huge amounts of memory, e.g. with a `.cfg` file claiming to
+/// have billions of channels. Records over any of the limits fail to parse.
+///
+/// The defaults are the largest the standard allows, which is plenty for real records
+/// but still a lot of memory - set lower limits with `ComtradeParserBuilder::limits()`
+/// when parsing files from elsewhere.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ParseLimits {
+    /// Size of the `.cfg` file, or the configuration section of a `.cff` file.
+    pub max_cfg_bytes: usize,
+
+    /// Total number of analog and status channels.
+    pub max_channels: u32,
+    pub max_sampling_rates: u32,
+    pub max_samples: u64,
+}
+
+impl Default for ParseLimits {
+    fn default() -> Self {
+        ParseLimits {
+            max_cfg_bytes: 256 * 1024 * 1024,
+            max_channels: 999_999,
+            max_sampling_rates: 999,
+            max_samples: 9_999_999_999,
+        }
+    }
+}
+
+/// Statistics about parsing a record, from `ComtradeParser::parse_with_stats()`, e.g.
+/// for ingestion telemetry.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ParseStats {
+    /// Size of the `.cfg` file, or the configuration section of a `.cff` file. The other
+    /// sizes are the same for the other files.
+    pub cfg_bytes: usize,
+    pub dat_bytes: usize,
+    pub hdr_bytes: usize,
+    pub inf_bytes: usize,
+
+    pub num_samples: usize,
+
+    /// Number of samples found in the `.dat` file, which can differ from `num_samples`
+    /// when it doesn't match the total in the `.cfg` file, see `SampleCountPolicy`.
+    pub num_samples_found: usize,
+
+    /// Size of each sample in binary data, or `None` for ASCII data.
+    pub scan_size: Option<usize>,
+    pub num_warnings: usize,
+
+    /// Time spent reading and parsing everything but the data. `None` without the `std`
+    /// feature, as there's no clock to time it with.
+    pub cfg_time: Option<Duration>,
+
+    /// Time spent reading the data and decoding the samples.
+    pub dat_time: Option<Duration>,
+}
+
+/// Times the stages of parsing, if there's a clock to time them with.
+struct Stopwatch {
+    #[cfg(feature = "std")]
+    start: std::time::Instant,
+}
+
+impl Stopwatch {
+    fn start() -> Self {
+        Stopwatch {
+            #[cfg(feature = "std")]
+            start: std::time::Instant::now(),
+        }
+    }
+
+    fn elapsed(&self) -> Option<Duration> {
+        #[cfg(feature = "std")]
+        return Some(self.start.elapsed());
+        #[cfg(not(feature = "std"))]
+        None
+    }
+}
+
+/// Buffers kept from one parse to the next by `ComtradeParser::parse_with_scratch()`,
+/// so that ingesting thousands of small records doesn't allocate the file contents and
+/// sample buffers afresh for each one.
+///
+/// The buffers for the file contents are kept automatically. The sample buffers belong
+/// to the record once it's parsed, so hand the record back with `recycle()` when you're
+/// done with it to have them reused.
+#[derive(Debug, Clone, Default)]
+pub struct ParserScratch {
+    cfg: Vec<u8>,
+    dat: Vec<u8>,
+    analog_data: Vec<Vec<f64>>,
+    status_data: Vec<Vec<u8>>,
+    sample_numbers: Vec<u64>,
+    timestamps: Vec<i64>,
+    raw_timestamps: Vec<Option<u32>>,
+}
+
+impl ParserScratch {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Take back the sample buffers of a record which is no longer needed, for the next
+    /// parse to reuse.
+    pub fn recycle(&mut self, record: Comtrade) {
+        self.analog_data.extend(record.data.analog_data);
+        self.status_data.extend(record.data.status_data);
+        keep_larger(&mut self.sample_numbers, record.data.sample_numbers);
+        keep_larger(&mut self.timestamps, record.data.timestamps);
+        keep_larger(&mut self.raw_timestamps, record.data.raw_timestamps);
+    }
+
+    fn analog_buffer(&mut self, capacity: usize) -> Vec<f64> {
+        reuse(self.analog_data.pop().unwrap_or_default(), capacity)
+    }
+
+    fn status_buffer(&mut self, capacity: usize) -> Vec<u8> {
+        reuse(self.status_data.pop().unwrap_or_default(), capacity)
+    }
+}
+
+fn non_binary_format_err() -> ParseError {
+    ParseError::new("tried to parse binary data for non-binary or invalid data format".to_string())
+}
+
+/// Empty buffer with at least the given capacity, reusing `buffer`'s allocation.
+fn reuse<T>(mut buffer: Vec<T>, capacity: usize) -> Vec<T> {
+    buffer.clear();
+    buffer.reserve(capacity);
+    buffer
+}
+
+fn keep_larger<T>(kept: &mut Vec<T>, buffer: Vec<T>) {
+    if buffer.capacity() > kept.capacity() {
+        *kept = buffer;
+    }
+}
+
+/// What to do when the sample numbers in the `.dat` file aren't increasing or the
+/// timestamps go backwards. Whichever is chosen, the affected samples are listed in
+/// `ComtradeData::irregular_samples`.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum SampleOrderPolicy {
+    /// Keep the samples in the order they appear in the file, with a warning.
+    #[default]
+    Keep,
+
+    /// Fail to parse the record.
+    Error,
+
+    /// Sort the samples by sample number, with a warning. Samples with the same sample
+    /// number are kept in file order.
+    Reorder,
 }
 
-// Cannot derive builder for this because of complexity of wrapping `T: BufRead` in
+/// What to do when the number of samples in binary data differs from the total declared
+/// in the `.cfg` file. Whichever is chosen, the number found is given in
+/// `ParseStats::num_samples_found`.
+///
+/// ASCII data is always read in full, as some recorders declare the total from sample
+/// numbers which don't start at 1.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum SampleCountPolicy {
+    /// Keep the declared number of samples, ignoring any extra ones with a warning.
+    /// Fewer samples than declared is an error.
+    #[default]
+    Truncate,
+
+    /// Fail to parse the record.
+    Error,
+
+    /// Keep every sample in the `.dat` file, with a warning, and set the record's total
+    /// to the number found.
+    Extend,
+}
+
+/// Boxed reader for one of the files making up a record, so that each file can come
+/// from a different source, e.g. a `BufReader<File>` for the `.cfg` and an in-memory
+/// `Cursor` for the `.dat`.
+pub type FileReader<'a> = Box<dyn ComtradeRead + 'a>;
+
+// Cannot derive builder for this because of complexity of wrapping the readers in
 // `Option` - I can't figure out how to stop the default implementation from complaining
 // that `BufReader<File>` doesn't implement `Copy`.
-pub struct ComtradeParserBuilder<T: BufRead> {
-    cff_file: Option<T>,
-    cfg_file: Option<T>,
-    dat_file: Option<T>,
-    hdr_file: Option<T>,
-    inf_file: Option<T>,
+pub struct ComtradeParserBuilder<'a> {
+    cff_file: Option<FileReader<'a>>,
+    cfg_file: Option<FileReader<'a>>,
+    dat_file: Option<FileReader<'a>>,
+    hdr_file: Option<FileReader<'a>>,
+    inf_file: Option<FileReader<'a>>,
+    config: Option<&'a ComtradeConfig>,
+    strict: bool,
+    vendor_profile: VendorProfile,
+    sample_order_policy: SampleOrderPolicy,
+    sample_count_policy: SampleCountPolicy,
+    limits: ParseLimits,
+    keep_source_files: bool,
+    infer_sampling_rates: bool,
+    quality_mappings: Vec<QualityMapping>,
+    #[cfg(feature = "decimal")]
+    exact_scaling: bool,
 }
 
-impl<T: BufRead> ComtradeParserBuilder<T> {
+impl Default for ComtradeParserBuilder<'_> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<'a> ComtradeParserBuilder<'a> {
     pub fn new() -> Self {
         Self {
             cff_file: None,
@@ -170,192 +783,766 @@ impl<T: BufRead> ComtradeParserBuilder<T> {
             dat_file: None,
             hdr_file: None,
             inf_file: None,
+            config: None,
+            strict: false,
+            vendor_profile: VendorProfile::Standard,
+            sample_order_policy: SampleOrderPolicy::Keep,
+            sample_count_policy: SampleCountPolicy::Truncate,
+            limits: ParseLimits::default(),
+            keep_source_files: false,
+            infer_sampling_rates: false,
+            quality_mappings: vec![],
+            #[cfg(feature = "decimal")]
+            exact_scaling: false,
         }
     }
 
-    pub fn cff_file(mut self, file: T) -> Self {
-        self.cff_file = Some(file);
+    pub fn cff_file<R: ComtradeRead + 'a>(mut self, file: R) -> Self {
+        self.cff_file = Some(Box::new(file));
+        self
+    }
+
+    pub fn cfg_file<R: ComtradeRead + 'a>(mut self, file: R) -> Self {
+        self.cfg_file = Some(Box::new(file));
+        self
+    }
+
+    pub fn dat_file<R: ComtradeRead + 'a>(mut self, file: R) -> Self {
+        self.dat_file = Some(Box::new(file));
+        self
+    }
+
+    pub fn hdr_file<R: ComtradeRead + 'a>(mut self, file: R) -> Self {
+        self.hdr_file = Some(Box::new(file));
+        self
+    }
+
+    pub fn inf_file<R: ComtradeRead + 'a>(mut self, file: R) -> Self {
+        self.inf_file = Some(Box::new(file));
+        self
+    }
+
+    /// Parse the `.dat` file against an existing configuration rather than a `.cfg`
+    /// file, see `ComtradeParser::from_config()`.
+    pub fn config(mut self, config: &'a ComtradeConfig) -> Self {
+        self.config = Some(config);
+        self
+    }
+
+    /// In strict mode, quirks in the files which would otherwise be worked around
+    /// with a warning are treated as errors instead. Off by default.
+    pub fn strict(mut self, strict: bool) -> Self {
+        self.strict = strict;
+        self
+    }
+
+    /// Work around the known quirks in files produced by a particular vendor's
+    /// recording devices.
+    pub fn vendor_profile(mut self, profile: VendorProfile) -> Self {
+        self.vendor_profile = profile;
+        self
+    }
+
+    /// How to handle samples which are out of order in the `.dat` file.
+    pub fn sample_order_policy(mut self, policy: SampleOrderPolicy) -> Self {
+        self.sample_order_policy = policy;
+        self
+    }
+
+    /// How to handle a `.dat` file with a different number of samples to the total in
+    /// the `.cfg` file.
+    pub fn sample_count_policy(mut self, policy: SampleCountPolicy) -> Self {
+        self.sample_count_policy = policy;
+        self
+    }
+
+    /// Limits on the size of the record, see `ParseLimits`.
+    pub fn limits(mut self, limits: ParseLimits) -> Self {
+        self.limits = limits;
         self
     }
 
-    pub fn cfg_file(mut self, file: T) -> Self {
-        self.cfg_file = Some(file);
+    /// Keep the files in the record, see `ComtradeParser::keep_source_files()`.
+    pub fn keep_source_files(mut self, keep: bool) -> Self {
+        self.keep_source_files = keep;
         self
     }
 
-    pub fn dat_file(mut self, file: T) -> Self {
-        self.dat_file = Some(file);
+    /// Check the sampling rates against the timestamps, see
+    /// `ComtradeParser::infer_sampling_rates()`.
+    pub fn infer_sampling_rates(mut self, infer: bool) -> Self {
+        self.infer_sampling_rates = infer;
         self
     }
 
-    pub fn hdr_file(mut self, file: T) -> Self {
-        self.hdr_file = Some(file);
+    /// Decode the quality flags of an analog channel, see
+    /// `ComtradeParser::quality_mapping()`.
+    pub fn quality_mapping(mut self, mapping: QualityMapping) -> Self {
+        self.quality_mappings.push(mapping);
         self
     }
 
-    pub fn inf_file(mut self, file: T) -> Self {
-        self.inf_file = Some(file);
+    /// Scale the analog values exactly, see `ComtradeParser::exact_scaling()`.
+    #[cfg(feature = "decimal")]
+    pub fn exact_scaling(mut self, exact: bool) -> Self {
+        self.exact_scaling = exact;
         self
     }
 
-    pub fn build(self) -> ComtradeParser<T> {
-        ComtradeParser::new(
+    pub fn build(self) -> ComtradeParser<'a> {
+        let parser = ComtradeParser::new(
             self.cff_file,
             self.cfg_file,
             self.dat_file,
             self.hdr_file,
             self.inf_file,
         )
+        .with_config(self.config)
+        .strict(self.strict)
+        .vendor_profile(self.vendor_profile)
+        .sample_order_policy(self.sample_order_policy)
+        .sample_count_policy(self.sample_count_policy)
+        .limits(self.limits)
+        .keep_source_files(self.keep_source_files)
+        .infer_sampling_rates(self.infer_sampling_rates);
+        let parser = self
+            .quality_mappings
+            .into_iter()
+            .fold(parser, |parser, mapping| parser.quality_mapping(mapping));
+
+        #[cfg(feature = "decimal")]
+        let parser = parser.exact_scaling(self.exact_scaling);
+
+        parser
     }
 }
 
-pub struct ComtradeParser<T: BufRead> {
-    cff_file: Option<T>,
-    cfg_file: Option<T>,
-    dat_file: Option<T>,
-    hdr_file: Option<T>,
-    inf_file: Option<T>,
+pub struct ComtradeParser<'a> {
+    cff_file: Option<FileReader<'a>>,
+    cfg_file: Option<FileReader<'a>>,
+    dat_file: Option<FileReader<'a>>,
+    hdr_file: Option<FileReader<'a>>,
+    inf_file: Option<FileReader<'a>>,
+    config: Option<&'a ComtradeConfig>,
+    strict: bool,
+    quirks: Quirks,
+    sample_order_policy: SampleOrderPolicy,
+    sample_count_policy: SampleCountPolicy,
+    limits: ParseLimits,
+    keep_source_files: bool,
+    infer_sampling_rates: bool,
+    quality_mappings: Vec<QualityMapping>,
+    separator: char,
+    decimal_comma: bool,
 
     cfg_contents: String,
     ascii_dat_contents: String,
     binary_dat_contents: Vec<u8>,
     hdr_contents: String,
     inf_contents: String,
+    // Unknown sections and trailing content in a `.cff` file, for the record's extensions.
+    cff_extensions: BTreeMap<String, String>,
 
-    builder: ComtradeBuilder,
-    total_num_samples: u32,
+    builder: ComtradeConfigBuilder,
+    // Samples are decoded straight into here, and moved into the record at the end.
+    data: ComtradeData,
+    total_num_samples: u64,
     num_analog_channels: u32,
     num_status_channels: u32,
     analog_channels: Vec<AnalogChannel>,
     status_channels: Vec<StatusChannel>,
+    sampling_rates: Vec<SamplingRate>,
+    timestamp_multiplication_factor: f64,
     is_timestamp_critical: bool,
-    ts_base_unit: f64,
+    // Nanoseconds per unit of the timestamps in the data file.
+    ts_base_unit: i64,
     data_format: Option<DataFormat>,
+    warnings: Vec<ParseWarning>,
+    stats: ParseStats,
+    scratch: ParserScratch,
+
+    #[cfg(feature = "decimal")]
+    exact_scaling: bool,
+    // Multiplier and offset adder of each analog channel as written in the `.cfg` file,
+    // or `None` where they can't be represented as decimals.
+    #[cfg(feature = "decimal")]
+    exact_factors: Vec<Option<(Decimal, Decimal)>>,
 }
 
-impl<T: BufRead> ComtradeParser<T> {
+impl<'a> ComtradeParser<'a> {
     pub fn new(
-        cff_file: Option<T>,
-        cfg_file: Option<T>,
-        dat_file: Option<T>,
-        hdr_file: Option<T>,
-        inf_file: Option<T>,
+        cff_file: Option<FileReader<'a>>,
+        cfg_file: Option<FileReader<'a>>,
+        dat_file: Option<FileReader<'a>>,
+        hdr_file: Option<FileReader<'a>>,
+        inf_file: Option<FileReader<'a>>,
     ) -> Self {
+        // Every file is checked for compression as it's read, so that compressed and
+        // uncompressed files can be mixed.
+        #[cfg(any(feature = "gzip", feature = "zstd"))]
+        let decompress = |file: Option<FileReader<'a>>| {
+            file.map(|f| Box::new(crate::compression::Decompress(f)) as FileReader<'a>)
+        };
+        #[cfg(any(feature = "gzip", feature = "zstd"))]
+        let (cff_file, cfg_file, dat_file, hdr_file, inf_file) = (
+            decompress(cff_file),
+            decompress(cfg_file),
+            decompress(dat_file),
+            decompress(hdr_file),
+            decompress(inf_file),
+        );
+
         Self {
             cff_file,
             cfg_file,
             dat_file,
             hdr_file,
             inf_file,
+            config: None,
+            strict: false,
+            quirks: Quirks::default(),
+            sample_order_policy: SampleOrderPolicy::Keep,
+            sample_count_policy: SampleCountPolicy::Truncate,
+            limits: ParseLimits::default(),
+            keep_source_files: false,
+            infer_sampling_rates: false,
+            quality_mappings: vec![],
+            separator: CFG_SEPARATOR,
+            decimal_comma: false,
 
             cfg_contents: String::new(),
             ascii_dat_contents: String::new(),
             binary_dat_contents: vec![],
             hdr_contents: String::new(),
             inf_contents: String::new(),
+            cff_extensions: BTreeMap::new(),
 
-            builder: ComtradeBuilder::default(),
+            builder: ComtradeConfigBuilder::default(),
+            data: ComtradeData::default(),
             total_num_samples: 0,
             num_analog_channels: 0,
             num_status_channels: 0,
             analog_channels: vec![],
             status_channels: vec![],
+            sampling_rates: vec![],
+            timestamp_multiplication_factor: 1.0,
             is_timestamp_critical: false,
-            ts_base_unit: 0.0,
+            ts_base_unit: 0,
             data_format: None,
+            warnings: vec![],
+            stats: ParseStats::default(),
+            scratch: ParserScratch::default(),
+
+            #[cfg(feature = "decimal")]
+            exact_scaling: false,
+            #[cfg(feature = "decimal")]
+            exact_factors: vec![],
         }
     }
 
-    pub fn dat_file(mut self, file: T) -> Self {
-        self.dat_file = Some(file);
+    /// Parser for the contents of `.cfg` and `.dat` files which are already in memory,
+    /// e.g. from an upload.
+    pub fn from_bytes(cfg: &'a [u8], dat: &'a [u8]) -> Self {
+        Self::new(None, Some(Box::new(cfg)), Some(Box::new(dat)), None, None)
+    }
+
+    /// Parser for just a `.dat` file, against a configuration which has already been
+    /// parsed, e.g. for a device which streams fresh `.dat` files with the same `.cfg`
+    /// file. The `.hdr` and `.inf` files can still be given with `hdr_file()` and
+    /// `inf_file()`.
+    pub fn from_config<R: ComtradeRead + 'a>(config: &'a ComtradeConfig, dat_file: R) -> Self {
+        Self::new(None, None, Some(Box::new(dat_file)), None, None).with_config(Some(config))
+    }
+
+    fn with_config(mut self, config: Option<&'a ComtradeConfig>) -> Self {
+        self.config = config;
         self
     }
 
-    pub fn hdr_file(mut self, file: T) -> Self {
-        self.hdr_file = Some(file);
+    /// Parser for the contents of a combined `.cff` file which is already in memory.
+    pub fn from_cff_bytes(cff: &'a [u8]) -> Self {
+        Self::new(Some(Box::new(cff)), None, None, None, None)
+    }
+
+    pub fn dat_file<R: ComtradeRead + 'a>(mut self, file: R) -> Self {
+        self.dat_file = Some(Box::new(file));
         self
     }
 
-    pub fn inf_file(mut self, file: T) -> Self {
-        self.inf_file = Some(file);
+    pub fn hdr_file<R: ComtradeRead + 'a>(mut self, file: R) -> Self {
+        self.hdr_file = Some(Box::new(file));
         self
     }
 
-    pub fn parse(mut self) -> ParseResult<Comtrade> {
-        if self.cff_file.is_some() {
-            self.load_cff()?;
-            self.parse_cfg()?;
-            self.parse_dat()?;
-        } else {
-            if let Some(ref mut cfg_file) = self.cfg_file {
-                cfg_file
-                    .read_to_string(&mut self.cfg_contents)
-                    .map_err(|_| {
-                        ParseError::new("unable to read specified .cfg file".to_string())
-                    })?;
-            } else {
-                return Err(ParseError::new(
-                    "you must specify either .cff or .cfg file".to_string(),
-                ));
-            }
+    pub fn inf_file<R: ComtradeRead + 'a>(mut self, file: R) -> Self {
+        self.inf_file = Some(Box::new(file));
+        self
+    }
 
-            self.parse_cfg()?;
+    pub fn strict(mut self, strict: bool) -> Self {
+        self.strict = strict;
+        self
+    }
 
-            if let Some(ref mut dat_file) = self.dat_file {
-                match self.data_format {
-                    Some(DataFormat::Ascii) => {
-                        dat_file
-                            .read_to_string(&mut self.ascii_dat_contents)
-                            .map_err(|_| {
-                                ParseError::new("unable to read specified .dat file".into())
-                            })?;
-                    }
-                    None => {
-                        return Err(ParseError::new("unknown data format for data file.".into()));
-                    }
-                    // Other binary format.
-                    _ => {
-                        dat_file
-                            .read_to_end(&mut self.binary_dat_contents)
-                            .map_err(|_| {
-                                ParseError::new("unable to read specified .dat file".into())
-                            })?;
-                    }
-                }
-            } else {
-                return Err(ParseError::new(
-                    "you must specify either .cff or .dat file".to_string(),
-                ));
-            }
+    pub fn vendor_profile(mut self, profile: VendorProfile) -> Self {
+        self.quirks = profile.quirks();
+        self
+    }
 
-            self.parse_dat()?;
+    pub fn sample_order_policy(mut self, policy: SampleOrderPolicy) -> Self {
+        self.sample_order_policy = policy;
+        self
+    }
 
-            if let Some(ref mut hdr_file) = self.hdr_file {
-                hdr_file
-                    .read_to_string(&mut self.hdr_contents)
-                    .map_err(|_| {
-                        ParseError::new("unable to read specified .hdr file".to_string())
-                    })?;
-            }
+    pub fn sample_count_policy(mut self, policy: SampleCountPolicy) -> Self {
+        self.sample_count_policy = policy;
+        self
+    }
 
-            if let Some(ref mut inf_file) = self.inf_file {
-                inf_file
-                    .read_to_string(&mut self.inf_contents)
+    pub fn limits(mut self, limits: ParseLimits) -> Self {
+        self.limits = limits;
+        self
+    }
+
+    /// Keep the contents of the `.cfg` and `.dat` files in the record's `source_files`,
+    /// so that `ComtradeWriter::passthrough()` can write it back out byte for byte if
+    /// it hasn't been changed. This doubles the memory the record takes up, so is off by
+    /// default. For records parsed from a `.cff` file, its `CFG` and `DAT` sections are
+    /// kept as the files, byte for byte apart from being in UTF-8.
+    pub fn keep_source_files(mut self, keep: bool) -> Self {
+        self.keep_source_files = keep;
+        self
+    }
+
+    /// Work out the sampling rates from the timestamps in the `.dat` file, as
+    /// `Comtrade::infer_sampling_rates()` does, and add a warning for each that differs
+    /// from the rate declared in the `.cfg` file by more than 1%. Off by default.
+    pub fn infer_sampling_rates(mut self, infer: bool) -> Self {
+        self.infer_sampling_rates = infer;
+        self
+    }
+
+    /// Decode the quality flags of an analog channel from status channels once the
+    /// record is parsed, as `Comtrade::apply_quality_mapping()` does. Call this once for
+    /// each channel with quality flags. Parsing fails if the mapping doesn't fit the
+    /// record.
+    pub fn quality_mapping(mut self, mapping: QualityMapping) -> Self {
+        self.quality_mappings.push(mapping);
+        self
+    }
+
+    /// Scale the analog values with decimal arithmetic, from the multipliers and offset
+    /// adders exactly as they're written in the `.cfg` file, rather than in floating
+    /// point. The values are still given as `f64`, but are the nearest `f64` to the
+    /// exact result, without the small errors that floating point arithmetic adds when
+    /// the factors have many digits. This is much slower, so is off by default.
+    ///
+    /// This only applies to `parse()` and the methods like it which collect the samples
+    /// into a record. Values which can't be scaled exactly, e.g. because they're too
+    /// large, are scaled in floating point as normal, as are all the values of channels
+    /// whose factors can't be represented as decimals, with a warning.
+    #[cfg(feature = "decimal")]
+    pub fn exact_scaling(mut self, exact: bool) -> Self {
+        self.exact_scaling = exact;
+        self
+    }
+
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(name = "comtrade_parse", skip_all, err)
+    )]
+    pub fn parse(self) -> ParseResult<Comtrade> {
+        self.parse_with_stats().map(|(record, _)| record)
+    }
+
+    /// Parse the files as `parse()` does, along with statistics about the parsing.
+    pub fn parse_with_stats(mut self) -> ParseResult<(Comtrade, ParseStats)> {
+        self.parse_record()
+    }
+
+    /// Parse the files as `parse()` does, reusing the buffers in `scratch` rather than
+    /// allocating new ones, and leaving this parse's buffers there for the next one.
+    pub fn parse_with_scratch(mut self, scratch: &mut ParserScratch) -> ParseResult<Comtrade> {
+        self.scratch = core::mem::take(scratch);
+        let result = self.parse_record();
+
+        // The file contents are kept whether or not parsing worked.
+        self.scratch.cfg = reuse(core::mem::take(&mut self.cfg_contents).into_bytes(), 0);
+        let ascii_dat = core::mem::take(&mut self.ascii_dat_contents).into_bytes();
+        let binary_dat = core::mem::take(&mut self.binary_dat_contents);
+        keep_larger(&mut self.scratch.dat, ascii_dat);
+        keep_larger(&mut self.scratch.dat, binary_dat);
+        *scratch = core::mem::take(&mut self.scratch);
+
+        result.map(|(record, _)| record)
+    }
+
+    fn parse_record(&mut self) -> ParseResult<(Comtrade, ParseStats)> {
+        let stopwatch = Stopwatch::start();
+        self.load()?;
+        let load_time = stopwatch.elapsed();
+
+        // Taken before parsing the data, which uses up the contents of the `.dat` file.
+        let source_files = if self.keep_source_files {
+            let cfg = match self.config {
+                Some(_) => None,
+                None => Some(self.cfg_contents.clone().into_bytes()),
+            };
+            let mut dat = self.ascii_dat_contents.clone().into_bytes();
+            dat.extend_from_slice(&self.binary_dat_contents);
+            Some((cfg, dat))
+        } else {
+            None
+        };
+
+        let stopwatch = Stopwatch::start();
+        self.parse_dat()?;
+        let parse_dat_time = stopwatch.elapsed();
+
+        // Reading the data file is counted as part of the time for the data.
+        let mut stats = core::mem::take(&mut self.stats);
+        let read_dat_time = stats.dat_time.unwrap_or_default();
+        stats.cfg_time = load_time.map(|t| t.saturating_sub(read_dat_time));
+        stats.dat_time = parse_dat_time.map(|t| t + read_dat_time);
+        stats.scan_size = match self.data_format {
+            Some(DataFormat::Ascii) | None => None,
+            Some(_) => self.binary_sample_size().ok(),
+        };
+        stats.num_warnings = self.warnings.len();
+
+        #[cfg(feature = "tracing")]
+        {
+            for warning in self.warnings.iter() {
+                tracing::warn!(%warning, "quirk in COMTRADE files worked around");
+            }
+            tracing::info!(
+                num_samples = self.total_num_samples,
+                num_analog_channels = self.num_analog_channels,
+                num_status_channels = self.num_status_channels,
+                num_warnings = self.warnings.len(),
+                "parsed COMTRADE record"
+            );
+        }
+
+        let config = Self::build_config(
+            core::mem::take(&mut self.builder),
+            core::mem::take(&mut self.analog_channels),
+            core::mem::take(&mut self.status_channels),
+            core::mem::take(&mut self.warnings),
+            self.ts_base_unit,
+        )?;
+        let mut record = Comtrade::from_parts(config, core::mem::take(&mut self.data));
+
+        if self.infer_sampling_rates {
+            self.check_sampling_rates(&mut record);
+            stats.num_warnings = record.config.warnings.len();
+        }
+
+        for mapping in self.quality_mappings.iter() {
+            if !record.apply_quality_mapping(mapping) {
+                return Err(ParseError::new(format!(
+                    "quality mapping for analog channel {} refers to channels not in the record",
+                    mapping.analog_channel + 1
+                )));
+            }
+        }
+
+        if let Some((cfg, dat)) = source_files {
+            record.data.source_files = Some(SourceFiles::new(&record, cfg, dat));
+        }
+
+        stats.num_samples = record.data.timestamps.len();
+        Ok((record, stats))
+    }
+
+    /// Warn about sampling rates in the record which don't match its timestamps.
+    fn check_sampling_rates(&self, record: &mut Comtrade) {
+        for rate in infer_sampling_rates(record, self.ts_base_unit) {
+            if !rate.is_discrepancy(SAMPLING_RATE_TOLERANCE) {
+                continue;
+            }
+            record.config.warnings.push(ParseWarning::new(format!(
+                "[DAT] timestamps of samples {} to {} give a sampling rate of {} Hz, but the \
+                 .cfg file says {} Hz",
+                rate.samples.start + 1,
+                rate.samples.end,
+                rate.inferred_hz.unwrap_or_default(),
+                rate.declared_hz
+            )));
+        }
+    }
+
+    /// Parse the files, handing the record to `sink` as soon as the configuration has
+    /// been parsed and then each sample as it's decoded, rather than collecting the
+    /// samples into the record. The record given to the sink has no samples.
+    ///
+    /// The order of the samples isn't checked, so the sample order policy has no effect.
+    pub fn parse_into<S: DataSink + ?Sized>(mut self, sink: &mut S) -> ParseResult<()> {
+        self.load()?;
+        sink.on_config(&self.config_record()?);
+
+        // Same scan is reused for every sample so there are no allocations per sample.
+        let mut scan = Scan::default();
+
+        match self.data_format {
+            Some(DataFormat::Ascii) => {
+                let contents = core::mem::take(&mut self.ascii_dat_contents);
+                for (i, line) in contents
+                    .split('\n')
+                    .filter(|l| !l.trim().is_empty())
+                    .enumerate()
+                {
+                    self.check_num_samples(i as u64 + 1)?;
+                    self.read_ascii_scan(line, i, &mut scan)?;
+                    self.scale_scan(&mut scan);
+                    sink.on_scan(&scan);
+                }
+            }
+            Some(_) => {
+                let contents = core::mem::take(&mut self.binary_dat_contents);
+                let sample_size = self.binary_sample_size()?;
+                let num_samples = self.num_binary_samples(contents.len(), sample_size)?;
+                for sample in contents.chunks_exact(sample_size).take(num_samples) {
+                    self.read_binary_scan(sample, &mut scan)?;
+                    sink.on_scan(&scan);
+                }
+            }
+            None => return Err(ParseError::new("Data format not specified.".into())),
+        }
+
+        Ok(())
+    }
+
+    /// Parse just the configuration, returning the record as described by the `.cfg`
+    /// file (or the `.cfg` section of the `.cff` file) without any samples. The data,
+    /// header and information files aren't read, so this is much quicker than `parse()`
+    /// for e.g. indexing large archives of records.
+    pub fn parse_config(mut self) -> ParseResult<Comtrade> {
+        if self.cff_file.is_some() {
+            self.load_cff()?;
+        } else {
+            self.read_cfg_file()?;
+        }
+        self.parse_cfg()?;
+        Ok(Comtrade::from_parts(
+            self.into_config()?,
+            ComtradeData::default(),
+        ))
+    }
+
+    /// Parse just the configuration as `parse_config()` does, for parsing data files
+    /// against with `from_config()`.
+    pub fn parse_comtrade_config(mut self) -> ParseResult<ComtradeConfig> {
+        if self.cff_file.is_some() {
+            self.load_cff()?;
+        } else {
+            self.read_cfg_file()?;
+        }
+        self.parse_cfg()?;
+        self.into_config()
+    }
+
+    /// Read in all the files and parse everything apart from the data file.
+    fn load(&mut self) -> ParseResult<()> {
+        if self.cff_file.is_some() {
+            self.load_cff()?;
+            self.parse_cfg()?;
+        } else {
+            match self.config {
+                Some(config) => self.apply_config(config)?,
+                None => {
+                    self.read_cfg_file()?;
+                    self.parse_cfg()?;
+                }
+            }
+
+            if let Some(ref mut dat_file) = self.dat_file {
+                let stopwatch = Stopwatch::start();
+                let read_err = |_| ParseError::new("unable to read specified .dat file".into());
+                let mut bytes = reuse(core::mem::take(&mut self.scratch.dat), 0);
+                match self.data_format {
+                    Some(DataFormat::Ascii) => {
+                        dat_file.read_all(&mut bytes).map_err(read_err)?;
+                        self.ascii_dat_contents = String::from_utf8(bytes).map_err(|_| {
+                            ParseError::new("unable to read specified .dat file".into())
+                        })?;
+                    }
+                    None => {
+                        return Err(ParseError::new("unknown data format for data file.".into()));
+                    }
+                    // Other binary format.
+                    _ => {
+                        dat_file.read_all(&mut bytes).map_err(read_err)?;
+                        self.binary_dat_contents = bytes;
+                    }
+                }
+                self.stats.dat_time = stopwatch.elapsed();
+            } else {
+                return Err(ParseError::new(
+                    "you must specify either .cff or .dat file".to_string(),
+                ));
+            }
+
+            if let Some(ref mut hdr_file) = self.hdr_file {
+                hdr_file
+                    .read_all_to_string(&mut self.hdr_contents)
+                    .map_err(|_| {
+                        ParseError::new("unable to read specified .hdr file".to_string())
+                    })?;
+            }
+
+            if let Some(ref mut inf_file) = self.inf_file {
+                inf_file
+                    .read_all_to_string(&mut self.inf_contents)
                     .map_err(|_| {
                         ParseError::new("unable to read specified .inf file".to_string())
                     })?;
             }
         }
 
-        // `.hdr` and `.inf` files don't need parsing - if present they're
-        // non-machine-readable text files for reference for humans to look at.
+        self.stats.cfg_bytes = self.cfg_contents.len();
+        self.stats.dat_bytes = self.ascii_dat_contents.len() + self.binary_dat_contents.len();
+        self.stats.hdr_bytes = self.hdr_contents.len();
+        self.stats.inf_bytes = self.inf_contents.len();
+
+        // `.hdr` files don't need parsing - if present they're non-machine-readable text
+        // files for reference for humans to look at.
+        self.builder.header(core::mem::take(&mut self.hdr_contents));
+        self.parse_inf()?;
+
+        Ok(())
+    }
+
+    /// Set everything up as parsing the `.cfg` file would, from a configuration which
+    /// has already been parsed.
+    fn apply_config(&mut self, config: &ComtradeConfig) -> ParseResult<()> {
+        self.total_num_samples = config.total_num_samples;
+        self.check_num_samples(self.total_num_samples)?;
+
+        self.builder
+            .station_name(config.station_name.clone())
+            .recording_device_id(config.recording_device_id.clone())
+            .revision(config.revision)
+            .num_total_channels(config.num_total_channels)
+            .num_analog_channels(config.num_analog_channels)
+            .num_status_channels(config.num_status_channels)
+            .line_frequency(config.line_frequency)
+            .sampling_rates(config.sampling_rates.clone())
+            .total_num_samples(config.total_num_samples)
+            .start_time(config.start_time)
+            .trigger_time(config.trigger_time)
+            .data_format(config.data_format.clone())
+            .timestamp_multiplication_factor(config.timestamp_multiplication_factor)
+            .time_offset(config.time_offset)
+            .local_offset(config.local_offset)
+            .time_quality(config.time_quality.clone())
+            .leap_second_status(config.leap_second_status.clone())
+            .extra_cfg_lines(config.extra_cfg_lines.clone());
+
+        self.num_analog_channels = config.analog_channels.len() as u32;
+        self.num_status_channels = config.status_channels.len() as u32;
+        self.analog_channels = config.analog_channels.clone();
+        self.status_channels = config.status_channels.clone();
+        self.sampling_rates = config.sampling_rates.clone();
+        self.timestamp_multiplication_factor = config.timestamp_multiplication_factor;
+
+        self.is_timestamp_critical = config.sampling_rates.is_empty();
+        self.ts_base_unit = config.timestamp_unit;
+        self.data_format = Some(config.data_format.clone());
+        if config.decimal_comma || self.quirks.decimal_comma {
+            self.use_decimal_comma();
+        }
+        Ok(())
+    }
+
+    fn read_cfg_file(&mut self) -> ParseResult<()> {
+        let cfg_file = match self.cfg_file {
+            Some(ref mut cfg_file) => cfg_file,
+            None => {
+                return Err(ParseError::new(
+                    "you must specify either .cff or .cfg file".to_string(),
+                ))
+            }
+        };
+
+        // Reading one byte past the limit shows whether there's more to the file.
+        let max_cfg_bytes = self.limits.max_cfg_bytes;
+        let mut bytes = reuse(core::mem::take(&mut self.scratch.cfg), 0);
+        cfg_file
+            .read_at_most(&mut bytes, max_cfg_bytes.saturating_add(1))
+            .map_err(|_| ParseError::new("unable to read specified .cfg file".to_string()))?;
+        self.check_cfg_size(bytes.len())?;
+        self.cfg_contents = String::from_utf8(bytes)
+            .map_err(|_| ParseError::new("unable to read specified .cfg file".to_string()))?;
+        Ok(())
+    }
+
+    fn check_cfg_size(&self, size: usize) -> ParseResult<()> {
+        if size > self.limits.max_cfg_bytes {
+            return Err(ParseError::new(format!(
+                ".cfg file is larger than the limit of {} bytes",
+                self.limits.max_cfg_bytes
+            )));
+        }
+        Ok(())
+    }
+
+    /// Parse `.cfg` contents that have already been read in, returning the record as
+    /// described by the configuration but without any samples.
+    pub(crate) fn parse_cfg_contents(&mut self, contents: String) -> ParseResult<Comtrade> {
+        self.check_cfg_size(contents.len())?;
+        self.cfg_contents = contents;
+        self.parse_cfg()?;
+        self.config_record()
+    }
+
+    /// Record as described by the configuration, without any samples.
+    fn config_record(&self) -> ParseResult<Comtrade> {
+        let config = Self::build_config(
+            self.builder.clone(),
+            self.analog_channels.clone(),
+            self.status_channels.clone(),
+            self.warnings.clone(),
+            self.ts_base_unit,
+        )?;
+        Ok(Comtrade::from_parts(config, ComtradeData::default()))
+    }
+
+    /// Configuration as parsed, moving the channels into it rather than cloning them,
+    /// which adds up for records with thousands of channels.
+    fn into_config(mut self) -> ParseResult<ComtradeConfig> {
+        Self::build_config(
+            core::mem::take(&mut self.builder),
+            core::mem::take(&mut self.analog_channels),
+            core::mem::take(&mut self.status_channels),
+            core::mem::take(&mut self.warnings),
+            self.ts_base_unit,
+        )
+    }
 
-        self.builder.analog_channels(self.analog_channels);
-        self.builder.status_channels(self.status_channels);
+    fn build_config(
+        mut builder: ComtradeConfigBuilder,
+        analog_channels: Vec<AnalogChannel>,
+        status_channels: Vec<StatusChannel>,
+        warnings: Vec<ParseWarning>,
+        timestamp_unit: i64,
+    ) -> ParseResult<ComtradeConfig> {
+        builder
+            .analog_channels(analog_channels)
+            .status_channels(status_channels)
+            .warnings(warnings)
+            .timestamp_unit(timestamp_unit);
+
+        builder
+            .build()
+            .map_err(|e| ParseError::new(format!("incomplete .cfg file: {}", e)))
+    }
 
-        Ok(self.builder.build().unwrap())
+    pub(crate) fn data_format(&self) -> Option<&DataFormat> {
+        self.data_format.as_ref()
     }
 
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", skip_all))]
     fn load_cff(&mut self) -> ParseResult<()> {
         let file = match &mut self.cff_file {
             Some(reader) => reader,
@@ -366,63 +1553,131 @@ impl<T: BufRead> ComtradeParser<T> {
             }
         };
 
-        let mut cfg_lines: Vec<String> = vec![];
-        let mut dat_lines: Vec<String> = vec![];
-        let mut hdr_lines: Vec<String> = vec![];
-        let mut inf_lines: Vec<String> = vec![];
+        let mut bytes = vec![];
+        file.read_all(&mut bytes)
+            .map_err(|_| ParseError::new("unable to read specified .cff file".to_string()))?;
+        let contents = decode_cff(bytes)?;
+
+        // Sections are sliced out of the file as they were written, line endings and
+        // all, rather than copied line by line.
+        let mut cfg_ranges: Vec<Range<usize>> = vec![];
+        let mut dat_ranges: Vec<Range<usize>> = vec![];
+        let mut hdr_ranges: Vec<Range<usize>> = vec![];
+        let mut inf_ranges: Vec<Range<usize>> = vec![];
 
         let mut current_file: Option<FileType> = None;
+        // Where the contents of the section being read start.
+        let mut section_start = 0;
+        // Name of the unknown section being skipped, if that's what's being read.
+        let mut skipped_section: Option<String> = None;
+        // Where content after the data section which can't be data starts.
+        let mut trailing_start: Option<usize> = None;
         let mut data_format: Option<DataFormat> = None;
-        let mut data_size: Option<usize> = None;
-
-        loop {
-            // TODO: Analyse performance of using single `line` across each iteration
-            //       vs. using shared buffer and cloning at end of each iteration.
-            let mut line = String::new();
-            let bytes_read = file.read_line(&mut line).unwrap();
-            if bytes_read == 0 {
-                break;
+        // Not currently needed - the ASCII data section is read line by line until the next header.
+        let mut _data_size: Option<usize> = None;
+
+        let mut end_section = |parser: &mut Self,
+                               current_file: Option<FileType>,
+                               skipped_section: Option<String>,
+                               trailing_start: Option<usize>,
+                               range: Range<usize>| {
+            let end = trailing_start.unwrap_or(range.end);
+            parser.keep_cff_section("Trailing".to_string(), &contents[end..range.end]);
+            if let Some(name) = skipped_section {
+                parser.keep_cff_section(name, &contents[range.clone()]);
             }
-            line = line.trim().to_string();
-
-            let maybe_file_header_match = CFF_HEADER_REGEXP.captures(line.as_str());
-            if let Some(header_match) = maybe_file_header_match {
-                let file_type_token = header_match.name("file_type").ok_or_else(|| {
-                    ParseError::new("unable to find file type in CFF header Regexp".to_string())
-                })?;
-
-                let maybe_data_format_token = header_match.name("data_format");
-                let maybe_data_size_token = header_match.name("data_size");
+            match current_file {
+                Some(FileType::Cfg) => cfg_ranges.push(range),
+                Some(FileType::Dat) => dat_ranges.push(range.start..end),
+                Some(FileType::Hdr) => hdr_ranges.push(range),
+                Some(FileType::Inf) => inf_ranges.push(range),
+                None => {}
+            }
+        };
 
-                current_file = Some(FileType::from_str(file_type_token.as_str())?);
+        for (offset, line) in CffLines::new(&contents) {
+            let line = line.trim();
+
+            if let Some(header) = parse_cff_header(line) {
+                #[cfg(feature = "tracing")]
+                tracing::debug!(
+                    file_type = header.file_type,
+                    data_format = header.data_format,
+                    data_size = header.data_size,
+                    "found .cff section"
+                );
+
+                end_section(
+                    self,
+                    current_file,
+                    skipped_section.take(),
+                    trailing_start.take(),
+                    section_start..offset,
+                );
+                section_start = offset + CffLines::line_length(&contents[offset..]);
+
+                current_file = match FileType::from_str(header.file_type) {
+                    Ok(file_type) => Some(file_type),
+                    Err(err) if !self.strict => {
+                        self.warnings.push(ParseWarning::new(format!(
+                            "keeping unknown section in .cff file in the record's extensions: {}",
+                            err
+                        )));
+                        skipped_section = Some(header.file_type.to_string());
+                        None
+                    }
+                    Err(err) => return Err(err),
+                };
 
-                if let Some(data_format_token) = maybe_data_format_token {
-                    data_format = Some(DataFormat::from_str(data_format_token.as_str())?);
+                if let Some(data_format_token) = header.data_format {
+                    data_format = Some(parse_data_format(data_format_token, &self.quirks)?);
                 }
 
-                if let Some(data_size_token) = maybe_data_size_token {
-                    data_size = Some(data_size_token.as_str().parse::<usize>().map_err(|_| {
-                        ParseError::new(format!(
-                            "unable to parse .dat size: '{}'",
-                            data_size_token.as_str()
-                        ))
+                if let Some(data_size_token) = header.data_size {
+                    _data_size = Some(data_size_token.parse::<usize>().map_err(|_| {
+                        ParseError::new(format!("unable to parse .dat size: '{}'", data_size_token))
                     })?)
                 }
 
+                // The file is read as text, so only ASCII data can be split out of it.
+                if current_file == Some(FileType::Dat) {
+                    match data_format {
+                        Some(DataFormat::Ascii) => {}
+                        Some(format) => {
+                            return Err(ParseError::new(format!(
+                                "unable to read {:?} data from .cff file; only ASCII is supported",
+                                format
+                            )))
+                        }
+                        None => {
+                            return Err(ParseError::new(
+                                "missing data format for DAT section of .cff file".to_string(),
+                            ))
+                        }
+                    }
+                }
+
                 continue;
             }
 
             match current_file {
-                Some(FileType::Cfg) => cfg_lines.push(line),
-                Some(FileType::Dat) => {
-                    if data_format == Some(DataFormat::Ascii) {
-                        dat_lines.push(line);
-                    } else {
-                        unimplemented!()
+                // Once the data section has been followed by something which can't be
+                // data, everything up to the next section is trailing content.
+                Some(FileType::Dat) if trailing_start.is_some() => {}
+                Some(FileType::Dat) if !is_ascii_scan_line(line) => {
+                    if self.strict {
+                        return Err(ParseError::new(
+                            "unexpected content after data in .cff file".to_string(),
+                        ));
                     }
+                    self.warnings.push(ParseWarning::new(
+                        "keeping content after data in .cff file in the record's extensions"
+                            .to_string(),
+                    ));
+                    trailing_start = Some(offset);
                 }
-                Some(FileType::Hdr) => hdr_lines.push(line),
-                Some(FileType::Inf) => inf_lines.push(line),
+                Some(_) => {}
+                None if skipped_section.is_some() => {}
                 None => {
                     return Err(ParseError::new(
                         "encountered file contents line before header in .cff".to_string(),
@@ -430,19 +1685,64 @@ impl<T: BufRead> ComtradeParser<T> {
                 }
             }
         }
+        end_section(
+            self,
+            current_file,
+            skipped_section,
+            trailing_start,
+            section_start..contents.len(),
+        );
+
+        let join = |ranges: &[Range<usize>]| -> String {
+            ranges.iter().map(|r| &contents[r.clone()]).collect()
+        };
+        self.cfg_contents = join(&cfg_ranges);
+        self.check_cfg_size(self.cfg_contents.len())?;
+        // The header is free text, so the line ending before the next section isn't
+        // kept as part of it.
+        let hdr = join(&hdr_ranges);
+        let hdr_end = hdr.strip_suffix('\n').unwrap_or(&hdr);
+        self.hdr_contents = hdr_end.strip_suffix('\r').unwrap_or(hdr_end).to_string();
+        self.inf_contents = join(&inf_ranges);
+        // The data section is nearly all of the file, so it's cut out of the contents
+        // in place rather than copied when it's all in one piece.
+        self.ascii_dat_contents = match dat_ranges.as_slice() {
+            [range] => {
+                let range = range.clone();
+                let mut dat = contents;
+                dat.truncate(range.end);
+                dat.drain(..range.start);
+                dat
+            }
+            ranges => join(ranges),
+        };
 
-        // TODO: Create `io::Cursor()` here instead of simply whacking all the contents
-        //  into a string. This would allow for buffered reading of separate files, at least.
-
-        self.cfg_contents = cfg_lines.join("\n");
-        self.ascii_dat_contents = dat_lines.join("\n");
-        self.hdr_contents = hdr_lines.join("\n");
-        self.inf_contents = inf_lines.join("\n");
+        #[cfg(feature = "tracing")]
+        tracing::debug!(
+            cfg_bytes = self.cfg_contents.len(),
+            dat_bytes = self.ascii_dat_contents.len(),
+            hdr_bytes = self.hdr_contents.len(),
+            inf_bytes = self.inf_contents.len(),
+            "split .cff file into sections"
+        );
 
         Ok(())
     }
 
+    /// Keep part of a `.cff` file which isn't part of any of the standard files in the
+    /// record's extensions as `CFF/<name>`, unless it's empty.
+    fn keep_cff_section(&mut self, name: String, contents: &str) {
+        let contents = contents.trim_end();
+        if !contents.is_empty() {
+            let key = format!("CFF/{}", name);
+            self.cff_extensions.insert(key, contents.to_string());
+        }
+    }
+
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", skip_all))]
     fn parse_cfg(&mut self) -> ParseResult<()> {
+        self.detect_decimal_comma()?;
+
         // TODO: There must be a more efficient way of doing this using line iterators,
         //  I just need to figure out how to create my own line iterator in the
         //  `load_cff()` function.
@@ -451,22 +1751,28 @@ impl<T: BufRead> ComtradeParser<T> {
         let early_end_err = || ParseError::new("unexpected end of .cfg file".to_string());
 
         let mut line_number = 1;
-        let mut line = "";
-        let mut line_values: Vec<&str> = vec![];
 
-        line = lines.next().ok_or_else(early_end_err)?;
-        line_values = line.split(CFG_SEPARATOR).collect();
+        let mut line = lines.next().ok_or_else(early_end_err)?;
 
         // Station name, identification and optionally revision year:
         // 1991:       station_name,rec_dev_id
         // 1999, 2013: station_name,rec_dev_id,rev_year
+        //
+        // The names may be quoted so that they can contain commas.
+        let header_values = split_quoted(line, self.separator);
+        if header_values.len() < 2 {
+            return Err(ParseError::new(format!(
+                "unexpected number of values on line {}",
+                line_number
+            )));
+        }
 
         // We need this value later to know when to quit.
-        self.builder.station_name(line_values[0].to_string());
-        self.builder.recording_device_id(line_values[1].to_string());
+        self.builder.station_name(header_values[0].clone());
+        self.builder.recording_device_id(header_values[1].clone());
 
-        let format_revision = match line_values.len() {
-            3 => FormatRevision::from_str(line_values[2].trim())?,
+        let format_revision = match header_values.len() {
+            3 => FormatRevision::from_str(header_values[2].trim())?,
             2 => FormatRevision::Revision1991,
             _ => {
                 return Err(ParseError::new(format!(
@@ -480,7 +1786,9 @@ impl<T: BufRead> ComtradeParser<T> {
         line_number += 1;
 
         line = lines.next().ok_or_else(early_end_err)?;
-        line_values = line.split(CFG_SEPARATOR).collect();
+        // One buffer is reused for the values of every line.
+        let mut line_values: Vec<&str> = Vec::with_capacity(16);
+        split_line(&mut line_values, line, self.separator);
 
         // Number and type of channels:
         // TT,##A,##D
@@ -499,152 +1807,144 @@ impl<T: BufRead> ComtradeParser<T> {
         })?;
         self.builder.num_total_channels(num_total_channels);
 
-        let mut num_analog_channels_token = line_values[1].to_string();
-        // Last character contains "A" identifier.
-        num_analog_channels_token.pop();
-        let num_analog_channels = num_analog_channels_token.trim().parse().map_err(|_| {
-            ParseError::new(format!(
-                "invalid integer value for number of analog channels: '{}'",
-                num_analog_channels_token
-            ))
-        })?;
+        let num_analog_channels = Self::parse_channel_count(
+            line_values[1],
+            'A',
+            "analog",
+            line_number,
+            self.strict,
+            &mut self.warnings,
+        )?;
         self.builder.num_analog_channels(num_analog_channels);
         self.num_analog_channels = num_analog_channels;
 
-        let mut num_status_channels_token = line_values[2].to_string();
-        // Last character contains "D" identifier.
-        num_status_channels_token.pop();
-        let num_status_channels = num_status_channels_token.trim().parse().map_err(|_| {
-            ParseError::new(format!(
-                "invalid integer value for number of status channels: '{}'",
-                num_status_channels_token
-            ))
-        })?;
+        let num_status_channels = Self::parse_channel_count(
+            line_values[2],
+            'D',
+            "status",
+            line_number,
+            self.strict,
+            &mut self.warnings,
+        )?;
         self.builder.num_status_channels(num_status_channels);
         self.num_status_channels = num_status_channels;
 
+        let max_channels = self.limits.max_channels as u64;
+        let num_channels = num_analog_channels as u64 + num_status_channels as u64;
+        if num_channels.max(num_total_channels as u64) > max_channels {
+            return Err(ParseError::new(format!(
+                "record has {} channels, more than the limit of {}",
+                num_channels.max(num_total_channels as u64),
+                max_channels
+            )));
+        }
+
         line_number += 1;
 
         let mut analog_channels: Vec<AnalogChannel> =
-            Vec::with_capacity(self.num_analog_channels as usize);
+            Vec::with_capacity((self.num_analog_channels as usize).min(MAX_PREALLOCATED_ENTRIES));
         let mut status_channels: Vec<StatusChannel> =
-            Vec::with_capacity(self.num_status_channels as usize);
+            Vec::with_capacity((self.num_status_channels as usize).min(MAX_PREALLOCATED_ENTRIES));
 
         // Analog channel information:
         // An,ch_id,ph,ccbm,uu,a,b,skew,min,max,primary,secondary,PS
         for i in 0..self.num_analog_channels {
             // todo should early_end_err just be a closure?
             line = lines.next().ok_or_else(early_end_err)?;
-            line_values = line.split(CFG_SEPARATOR).collect();
+            split_line(&mut line_values, line, self.separator);
 
-            if line_values.len() != 13 {
-                return Err(ParseError::new(format!(
-                    "unexpected number of values on line {}",
-                    line_number
-                )));
-            }
+            let extensions = Self::extra_columns(
+                &line_values,
+                13,
+                line_number,
+                self.strict,
+                &mut self.warnings,
+            )?;
 
-            let analog_index = line_values[0]
-                .trim()
-                .to_string()
-                .parse::<u32>()
-                .map_err(|_| {
-                    ParseError::new(format!(
-                        "invalid integer value for analog channel {} index: {}",
-                        i, line_values[0]
-                    ))
-                })?;
+            let analog_index = line_values[0].trim().parse::<u32>().map_err(|_| {
+                ParseError::new(format!(
+                    "invalid integer value for analog channel {} index: {}",
+                    i, line_values[0]
+                ))
+            })?;
 
-            let name = line_values[1].to_string();
-            let phase = line_values[2].to_string(); // Non-critical.
-            let circuit_component_being_monitored = line_values[3].to_string(); // Non-critical.
-            let units = line_values[4].to_string();
+            let name_raw = line_values[1].to_string();
+            let name = name_raw.trim().to_string();
+            let phase_raw = line_values[2].to_string();
+            let phase = Phase::from(channel_field(&phase_raw, &self.quirks)); // Non-critical.
+            let circuit_component_being_monitored =
+                channel_field(line_values[3], &self.quirks).to_string(); // Non-critical.
+            let units = channel_field(line_values[4], &self.quirks).to_string();
 
-            let multiplier = line_values[5]
-                .trim()
-                .to_string()
-                .parse::<f64>()
-                .map_err(|_| {
-                    ParseError::new(format!(
-                        "invalid real numeric value for analog channel {} multiplier: {}",
-                        i, line_values[5]
-                    ))
-                })?;
+            let multiplier = parse_real(line_values[5], self.decimal_comma).map_err(|_| {
+                ParseError::new(format!(
+                    "invalid real numeric value for analog channel {} multiplier: {}",
+                    i, line_values[5]
+                ))
+            })?;
 
-            let offset_adder = line_values[6]
-                .trim()
-                .to_string()
-                .parse::<f64>()
-                .map_err(|_| {
-                    ParseError::new(format!(
-                        "invalid real numeric value for analog channel {} offset adder: {}",
-                        i, line_values[6]
-                    ))
-                })?;
+            let offset_adder = parse_real(line_values[6], self.decimal_comma).map_err(|_| {
+                ParseError::new(format!(
+                    "invalid real numeric value for analog channel {} offset adder: {}",
+                    i, line_values[6]
+                ))
+            })?;
 
-            let skew = line_values[7]
-                .trim()
-                .to_string()
-                .parse::<f64>()
-                .map_err(|_| {
-                    ParseError::new(format!(
-                        "invalid real numeric value for analog channel {} skew: {}",
-                        i, line_values[7]
-                    ))
-                })?;
+            #[cfg(feature = "decimal")]
+            if self.exact_scaling {
+                self.exact_factors.push(
+                    exact_factor(line_values[5], multiplier, self.decimal_comma).zip(exact_factor(
+                        line_values[6],
+                        offset_adder,
+                        self.decimal_comma,
+                    )),
+                );
+            }
 
-            let min_value = line_values[8]
-                .trim()
-                .to_string()
-                .parse::<f64>()
-                .map_err(|_| {
-                    ParseError::new(format!(
-                        "invalid real numeric value for analog channel {} minimum value: {}",
-                        i, line_values[8]
-                    ))
-                })?;
+            let skew = parse_real(line_values[7], self.decimal_comma).map_err(|_| {
+                ParseError::new(format!(
+                    "invalid real numeric value for analog channel {} skew: {}",
+                    i, line_values[7]
+                ))
+            })?;
 
-            let max_value = line_values[9]
-                .trim()
-                .to_string()
-                .parse::<f64>()
-                .map_err(|_| {
-                    ParseError::new(format!(
-                        "invalid real numeric value for analog channel {} maximum value: {}",
-                        i, line_values[9]
-                    ))
-                })?;
+            let min_value = parse_real(line_values[8], self.decimal_comma).map_err(|_| {
+                ParseError::new(format!(
+                    "invalid real numeric value for analog channel {} minimum value: {}",
+                    i, line_values[8]
+                ))
+            })?;
 
-            let primary_factor =
-                line_values[10]
-                    .trim()
-                    .to_string()
-                    .parse::<f64>()
-                    .map_err(|_| {
-                        ParseError::new(format!(
-                            "invalid real numeric value for analog channel {} primary factor: {}",
-                            i, line_values[10]
-                        ))
-                    })?;
+            let max_value = parse_real(line_values[9], self.decimal_comma).map_err(|_| {
+                ParseError::new(format!(
+                    "invalid real numeric value for analog channel {} maximum value: {}",
+                    i, line_values[9]
+                ))
+            })?;
+
+            let primary_factor = parse_real(line_values[10], self.decimal_comma).map_err(|_| {
+                ParseError::new(format!(
+                    "invalid real numeric value for analog channel {} primary factor: {}",
+                    i, line_values[10]
+                ))
+            })?;
 
             let secondary_factor =
-                line_values[11]
-                    .trim()
-                    .to_string()
-                    .parse::<f64>()
-                    .map_err(|_| {
-                        ParseError::new(format!(
-                            "invalid real numeric value for analog channel {} secondary factor: {}",
-                            i, line_values[11]
-                        ))
-                    })?;
+                parse_real(line_values[11], self.decimal_comma).map_err(|_| {
+                    ParseError::new(format!(
+                        "invalid real numeric value for analog channel {} secondary factor: {}",
+                        i, line_values[11]
+                    ))
+                })?;
 
-            let scaling_mode = AnalogScalingMode::from_str(line_values[12].trim())?;
+            let scaling_mode = parse_scaling_mode(line_values[12].trim(), &self.quirks)?;
 
             analog_channels.push(AnalogChannel {
                 index: analog_index,
                 name,
+                name_raw,
                 phase,
+                phase_raw,
                 circuit_component_being_monitored,
                 units,
                 min_value,
@@ -655,7 +1955,7 @@ impl<T: BufRead> ComtradeParser<T> {
                 primary_factor,
                 secondary_factor,
                 scaling_mode,
-                data: vec![],
+                extensions,
             });
 
             line_number += 1;
@@ -666,41 +1966,36 @@ impl<T: BufRead> ComtradeParser<T> {
         // Dn,ch_id,ph,ccbm,y
         for i in 0..self.num_status_channels {
             line = lines.next().ok_or_else(early_end_err)?;
-            line_values = line.split(CFG_SEPARATOR).collect();
+            split_line(&mut line_values, line, self.separator);
 
-            if line_values.len() != 5 {
-                return Err(ParseError::new(format!(
-                    "unexpected number of values on line {}",
-                    line_number
-                )));
-            }
+            let extensions = Self::extra_columns(
+                &line_values,
+                5,
+                line_number,
+                self.strict,
+                &mut self.warnings,
+            )?;
 
-            let status_index = line_values[0]
-                .trim()
-                .to_string()
-                .parse::<u32>()
-                .map_err(|_| {
-                    ParseError::new(format!(
-                        "invalid integer value for status channel {} index: {}",
-                        i, line_values[0]
-                    ))
-                })?;
+            let status_index = line_values[0].trim().parse::<u32>().map_err(|_| {
+                ParseError::new(format!(
+                    "invalid integer value for status channel {} index: {}",
+                    i, line_values[0]
+                ))
+            })?;
 
-            let name = line_values[1].to_string();
-            let phase = line_values[2].to_string(); // Non-critical.
-            let circuit_component_being_monitored = line_values[3].to_string(); // Non-critical.
+            let name_raw = line_values[1].to_string();
+            let name = name_raw.trim().to_string();
+            let phase_raw = line_values[2].to_string();
+            let phase = Phase::from(channel_field(&phase_raw, &self.quirks)); // Non-critical.
+            let circuit_component_being_monitored =
+                channel_field(line_values[3], &self.quirks).to_string(); // Non-critical.
 
-            let normal_status_value =
-                line_values[4]
-                    .trim()
-                    .to_string()
-                    .parse::<u8>()
-                    .map_err(|_| {
-                        ParseError::new(format!(
-                            "invalid integer value for status channel {} normal value: {}",
-                            i, line_values[4]
-                        ))
-                    })?;
+            let normal_status_value = line_values[4].trim().parse::<u8>().map_err(|_| {
+                ParseError::new(format!(
+                    "invalid integer value for status channel {} normal value: {}",
+                    i, line_values[4]
+                ))
+            })?;
             if normal_status_value != 0 && normal_status_value != 1 {
                 return Err(ParseError::new(format!("invalid normal status value for status channel {}: {}; expected one of : '0', '1'", i, line_values[4])));
             }
@@ -708,10 +2003,12 @@ impl<T: BufRead> ComtradeParser<T> {
             status_channels.push(StatusChannel {
                 index: status_index,
                 name,
+                name_raw,
                 phase,
+                phase_raw,
                 circuit_component_being_monitored,
                 normal_status_value,
-                data: vec![],
+                extensions,
             });
 
             line_number += 1;
@@ -722,7 +2019,7 @@ impl<T: BufRead> ComtradeParser<T> {
 
         // Line frequency
         // lf
-        let line_frequency = line.trim().to_string().parse::<f64>().map_err(|_| {
+        let line_frequency = parse_real(line, self.decimal_comma).map_err(|_| {
             ParseError::new(format!(
                 "invalid real numeric value for line frequency: '{}'",
                 line,
@@ -733,7 +2030,7 @@ impl<T: BufRead> ComtradeParser<T> {
         line_number += 1;
 
         line = lines.next().ok_or_else(early_end_err)?;
-        line_values = line.split(CFG_SEPARATOR).collect();
+        split_line(&mut line_values, line, self.separator);
 
         // Sampling rate information
         // nrates (x 1)
@@ -745,23 +2042,27 @@ impl<T: BufRead> ComtradeParser<T> {
             )));
         }
 
-        let num_sampling_rates =
-            line_values[0]
-                .trim()
-                .to_string()
-                .parse::<u32>()
-                .map_err(|_| {
-                    ParseError::new(format!(
-                        "invalid integer value for number of sample rates: {}",
-                        line_values[0]
-                    ))
-                })?;
+        let num_sampling_rates = line_values[0].trim().parse::<u32>().map_err(|_| {
+            ParseError::new(format!(
+                "invalid integer value for number of sample rates: {}",
+                line_values[0]
+            ))
+        })?;
 
-        let mut sampling_rates: Vec<SamplingRate> = Vec::with_capacity(num_sampling_rates as usize);
+        if num_sampling_rates > self.limits.max_sampling_rates {
+            return Err(ParseError::new(format!(
+                "record has {} sampling rates, more than the limit of {}",
+                num_sampling_rates, self.limits.max_sampling_rates
+            )));
+        }
+
+        let mut sampling_rates: Vec<SamplingRate> =
+            Vec::with_capacity((num_sampling_rates as usize).min(MAX_PREALLOCATED_ENTRIES));
 
         for i in 0..num_sampling_rates {
+            line_number += 1;
             line = lines.next().ok_or_else(early_end_err)?;
-            line_values = line.split(CFG_SEPARATOR).collect();
+            split_line(&mut line_values, line, self.separator);
 
             if line_values.len() != 2 {
                 return Err(ParseError::new(format!(
@@ -771,30 +2072,21 @@ impl<T: BufRead> ComtradeParser<T> {
             }
 
             // The sample rate in Hertz of this sample.
-            let rate_hz = line_values[0]
-                .trim()
-                .to_string()
-                .parse::<f64>()
-                .map_err(|_| {
-                    ParseError::new(format!(
+            let rate_hz = parse_real(line_values[0], self.decimal_comma).map_err(|_| {
+                ParseError::new(format!(
                     "invalid float value for sample rate frequency for rate n# {} on line {}: {}",
                     i, line_number, line_values[0]
                 ))
-                })?;
+            })?;
 
             // The sample number of the final sample that uses this sample rate. Note this corresponds
             // to the sample number value in the data itself, not an index.
-            let end_sample_number =
-                line_values[1]
-                    .trim()
-                    .to_string()
-                    .parse::<u32>()
-                    .map_err(|_| {
-                        ParseError::new(format!(
-                        "invalid integer value for end sample number for rate n# {} on line {}: {}",
-                        i, line_number, line_values[1]
-                    ))
-                    })?;
+            let end_sample_number = line_values[1].trim().parse::<u64>().map_err(|_| {
+                ParseError::new(format!(
+                    "invalid integer value for end sample number for rate n# {} on line {}: {}",
+                    i, line_number, line_values[1]
+                ))
+            })?;
 
             sampling_rates.push(SamplingRate {
                 rate_hz,
@@ -802,35 +2094,90 @@ impl<T: BufRead> ComtradeParser<T> {
             });
         }
 
-        self.total_num_samples = sampling_rates
-            .iter()
-            .map(|r| r.end_sample_number)
-            .max()
-            .unwrap();
-
-        // Now that we know how many samples we have in total, we can update the channel buffers
-        // with the correct capacity to make `push()` operations more efficient.
-        for c in self.analog_channels.iter_mut() {
-            c.data = Vec::with_capacity(self.total_num_samples as usize);
-        }
-        for c in self.status_channels.iter_mut() {
-            c.data = Vec::with_capacity(self.total_num_samples as usize);
-        }
-
-        // If file has 0 for number of sample rates, there's an extra line which just contains 0
-        // indicating no fixed sample rate and the total number of samples. We don't need this data
-        // so we just ignore it.
+        // If file has 0 for number of sample rates, there's an extra line which contains 0
+        // indicating no fixed sample rate and the total number of samples. Some recorders
+        // leave this out, in which case the total is counted from the `.dat` file instead.
+        let mut date_line = None;
         if num_sampling_rates == 0 {
+            line = lines.next().ok_or_else(early_end_err)?;
+            split_line(&mut line_values, line, self.separator);
+        }
+        if num_sampling_rates == 0 && line_values[0].trim() != "0" {
+            let message = format!("missing total number of samples after line {}", line_number);
+            if self.strict {
+                return Err(ParseError::new(message));
+            }
+            self.warnings.push(ParseWarning::new(format!(
+                "{}; counting samples in .dat file",
+                message
+            )));
+            date_line = Some(line);
+            self.total_num_samples = 0;
+        } else if num_sampling_rates == 0 {
             line_number += 1;
-            lines.next().ok_or_else(early_end_err)?;
+
+            if line_values.len() != 2 {
+                return Err(ParseError::new(format!(
+                    "unexpected number of values on line {}",
+                    line_number
+                )));
+            }
+
+            // Some recorders write the total as a real number, e.g. `8000.0`.
+            let total = parse_real(line_values[1], self.decimal_comma)
+                .ok()
+                .filter(|total| *total >= 0.0 && *total < u64::MAX as f64)
+                .ok_or_else(|| {
+                    ParseError::new(format!(
+                        "invalid value for total number of samples on line {}: {}",
+                        line_number, line_values[1]
+                    ))
+                })?;
+            // Parsed as an integer where possible, as not all large totals fit exactly
+            // in an `f64`.
+            let total_num_samples = line_values[1].trim().parse::<u64>().unwrap_or(total as u64);
+            if total_num_samples as f64 != total {
+                let message = format!(
+                    "non-integer total number of samples on line {}: {}",
+                    line_number,
+                    line_values[1].trim(),
+                );
+                if self.strict {
+                    return Err(ParseError::new(message));
+                }
+                self.warnings
+                    .push(ParseWarning::new(format!("{}; rounding down", message)));
+            }
+            self.total_num_samples = total_num_samples;
+        } else {
+            self.total_num_samples = sampling_rates
+                .iter()
+                .map(|r| r.end_sample_number)
+                .max()
+                .unwrap_or(0);
         }
+        self.check_num_samples(self.total_num_samples)?;
+        self.builder.total_num_samples(self.total_num_samples);
+
+        #[cfg(feature = "tracing")]
+        tracing::debug!(
+            revision = ?format_revision,
+            num_analog_channels = self.num_analog_channels,
+            num_status_channels = self.num_status_channels,
+            num_sampling_rates = sampling_rates.len(),
+            num_samples = self.total_num_samples,
+            "parsed .cfg channel and sampling information"
+        );
 
         self.is_timestamp_critical = num_sampling_rates == 0;
-        self.builder.sampling_rates(sampling_rates);
+        self.builder.sampling_rates(sampling_rates.clone());
+        self.sampling_rates = sampling_rates;
 
         line_number += 1;
-        line = lines.next().ok_or_else(early_end_err)?;
-        line_values = line.split(CFG_SEPARATOR).collect();
+        line = match date_line {
+            Some(line) => line,
+            None => lines.next().ok_or_else(early_end_err)?,
+        };
 
         // Date/time stamps
         // dd/mm/yyyy,hh:mm:ss.ssssss
@@ -839,14 +2186,16 @@ impl<T: BufRead> ComtradeParser<T> {
         //       real time values from timestamps (I think - not 100% on this).
 
         // Time of the first data sample in data file.
-        let datetime_format = if format_revision == FormatRevision::Revision1991 {
-            CFG_DATETIME_FORMAT_OLD
-        } else {
-            CFG_DATETIME_FORMAT
-        };
+        let datetime_format =
+            if format_revision == FormatRevision::Revision1991 || self.quirks.month_first_dates {
+                CFG_DATETIME_FORMAT_OLD
+            } else {
+                CFG_DATETIME_FORMAT
+            };
 
+        let datetime_stamp = normalise_datetime_stamp(line, self.decimal_comma);
         let start_time =
-            NaiveDateTime::parse_from_str(line.trim(), datetime_format).map_err(|_| {
+            NaiveDateTime::parse_from_str(&datetime_stamp, datetime_format).map_err(|_| {
                 ParseError::new(format!(
                     "invalid datetime value for start time on line {}: {}",
                     line_number, line,
@@ -854,14 +2203,15 @@ impl<T: BufRead> ComtradeParser<T> {
             })?;
         self.builder.start_time(start_time);
 
-        self.ts_base_unit = ts_base_unit(line.trim())?;
+        self.ts_base_unit = ts_base_unit(&datetime_stamp)?;
 
         line_number += 1;
         line = lines.next().ok_or_else(early_end_err)?;
 
         // Time that the COMTRADE record recording was triggered.
-        let trigger_time =
-            NaiveDateTime::parse_from_str(line.trim(), datetime_format).map_err(|_| {
+        let datetime_stamp = normalise_datetime_stamp(line, self.decimal_comma);
+        let trigger_time = NaiveDateTime::parse_from_str(&datetime_stamp, datetime_format)
+            .map_err(|_| {
                 ParseError::new(format!(
                     "invalid datetime value for trigger time on line {}: {}",
                     line_number, line,
@@ -872,19 +2222,28 @@ impl<T: BufRead> ComtradeParser<T> {
         // According to the spec, if the start time is in micro/nanoseconds, the
         // other one should be too. If they are inconsistent, just take the lower one
         // to be safe. In the future this would be a good place to raise a warning.
-        self.ts_base_unit = self.ts_base_unit.min(ts_base_unit(line.trim())?);
+        self.ts_base_unit = self.ts_base_unit.min(ts_base_unit(&datetime_stamp)?);
 
         line_number += 1;
         line = lines.next().ok_or_else(early_end_err)?;
 
         // Data file type
         // ft
-        let data_format = DataFormat::from_str(line)?;
+        let data_format = parse_data_format(line, &self.quirks)?;
         self.data_format = Some(data_format.clone());
         self.builder.data_format(data_format);
 
+        // Default values for optional revision-based fields.
+        self.builder.timestamp_multiplication_factor(1.0);
+        self.timestamp_multiplication_factor = 1.0;
+        self.builder.time_offset(None);
+        self.builder.local_offset(None);
+        self.builder.time_quality(None);
+        self.builder.leap_second_status(None);
+
         // 1991 format ends here - rest of values are 1999 and 2013 only.
         if format_revision == FormatRevision::Revision1991 {
+            self.builder.extra_cfg_lines(extra_cfg_lines(lines));
             return Ok(());
         }
 
@@ -898,225 +2257,873 @@ impl<T: BufRead> ComtradeParser<T> {
         // Regardless, this multiplicative factor allows you to store longer time ranges
         // within a single COMTRADE record.
 
-        let time_mult = line.trim().parse::<f64>().map_err(|_| {
-            ParseError::new(format!(
-                "invalid float value for time multiplication factor on line {}: {}",
-                line_number, line,
-            ))
-        })?;
-        self.builder.timestamp_multiplication_factor(time_mult);
+        let time_mult = match line.trim() {
+            "" => 0.0,
+            v => parse_real(v, self.decimal_comma).map_err(|_| {
+                ParseError::new(format!(
+                    "invalid float value for time multiplication factor on line {}: {}",
+                    line_number, line,
+                ))
+            })?,
+        };
 
-        // Default values for optional revision-based fields.
-        self.builder.time_offset(None);
-        self.builder.local_offset(None);
-        self.builder.time_quality(None);
-        self.builder.leap_second_status(None);
+        // Some files in the wild have a blank or zero multiplication factor, which
+        // would make every timestamp zero so we assume they meant 1.
+        let time_mult = if time_mult == 0.0 {
+            let message = format!(
+                "missing or zero time multiplication factor on line {}: '{}'",
+                line_number,
+                line.trim(),
+            );
+            if self.strict {
+                return Err(ParseError::new(message));
+            }
+            self.warnings
+                .push(ParseWarning::new(format!("{}; assuming 1", message)));
+            1.0
+        } else {
+            time_mult
+        };
+        self.builder.timestamp_multiplication_factor(time_mult);
+        self.timestamp_multiplication_factor = time_mult;
 
         // 1999 format ends here - rest of values are 2013 only.
         if format_revision == FormatRevision::Revision1999 {
+            self.builder.extra_cfg_lines(extra_cfg_lines(lines));
             return Ok(());
         }
 
+        // Some recorders stop after the time multiplication factor even in 2013 files,
+        // so unless parsing strictly the time codes and time quality are optional.
         line_number += 1;
-        line = lines.next().ok_or_else(early_end_err)?;
-        line_values = line.split(CFG_SEPARATOR).collect();
+        line = match lines.next() {
+            Some(line) if self.strict || !line.trim().is_empty() => line,
+            None if self.strict => return Err(early_end_err()),
+            _ => {
+                self.warnings.push(ParseWarning::new(format!(
+                    "no time codes or time quality from line {}; leaving them unknown",
+                    line_number
+                )));
+                self.builder.extra_cfg_lines(extra_cfg_lines(lines));
+                return Ok(());
+            }
+        };
+        split_line(&mut line_values, line, self.separator);
 
         // Time information and relationship between local time and UTC
         // time_code, local_code
+        if line_values.len() != 2 {
+            return Err(ParseError::new(format!(
+                "unexpected number of values on line {}",
+                line_number
+            )));
+        }
+
         self.builder.time_offset(parse_time_offset(line_values[0])?);
         self.builder
             .local_offset(parse_time_offset(line_values[1])?);
 
         line_number += 1;
-        line = lines.next().ok_or_else(early_end_err)?;
-        line_values = line.split(CFG_SEPARATOR).collect();
+        line = match lines.next() {
+            Some(line) => line,
+            None if self.strict => return Err(early_end_err()),
+            None => {
+                self.warnings.push(ParseWarning::new(format!(
+                    "no time quality on line {}; leaving it unknown",
+                    line_number
+                )));
+                self.builder.extra_cfg_lines(vec![]);
+                return Ok(());
+            }
+        };
+        split_line(&mut line_values, line, self.separator);
 
         // Time quality of samples
         // tmq_code,leapsec
-        let tmq_code = TimeQuality::from_str(line_values[0])?;
-        self.builder.time_quality(Some(tmq_code));
+        //
+        // Some files leave these blank, or write `x` as for the time codes, which unless
+        // parsing strictly is read as not known.
+        if line_values.len() > 2 || (line_values.len() != 2 && self.strict) {
+            return Err(ParseError::new(format!(
+                "unexpected number of values on line {}",
+                line_number
+            )));
+        }
+        let tmq_value = line_values[0];
+        let leapsec_value = line_values.get(1).copied().unwrap_or("");
+        let is_unknown = |value: &str| {
+            let value = value.trim();
+            !self.strict && (value.is_empty() || value.eq_ignore_ascii_case("x"))
+        };
+
+        let time_quality = if is_unknown(tmq_value) {
+            self.warnings.push(ParseWarning::new(format!(
+                "no time quality on line {}; leaving it unknown",
+                line_number
+            )));
+            None
+        } else {
+            Some(TimeQuality::from_str(tmq_value)?)
+        };
+        self.builder.time_quality(time_quality);
+
+        let leap_second_status = if is_unknown(leapsec_value) {
+            self.warnings.push(ParseWarning::new(format!(
+                "no leap second indicator on line {}; leaving it unknown",
+                line_number
+            )));
+            None
+        } else {
+            Some(LeapSecondStatus::from_str(leapsec_value)?)
+        };
+        self.builder.leap_second_status(leap_second_status);
+
+        self.builder.extra_cfg_lines(extra_cfg_lines(lines));
+
+        Ok(())
+    }
+
+    /// Read the sections of the `.inf` file into the extensions of the record and its
+    /// channels. Each section starts with a `[owner section name]` line, followed by
+    /// `key=value` lines, with comments starting with `;`.
+    fn parse_inf(&mut self) -> ParseResult<()> {
+        let mut extensions = core::mem::take(&mut self.cff_extensions);
+        let mut section: Option<&str> = None;
+
+        for (i, line) in self.inf_contents.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with(';') {
+                continue;
+            }
+
+            if let Some(name) = line.strip_prefix('[').and_then(|l| l.strip_suffix(']')) {
+                section = Some(name.trim());
+                continue;
+            }
+
+            let entry = section.zip(line.split_once('='));
+            let (section, (key, value)) = match entry {
+                Some(entry) => entry,
+                None => {
+                    let message = format!("[INF] invalid entry on line {}: '{}'", i + 1, line);
+                    if self.strict {
+                        return Err(ParseError::new(message));
+                    }
+                    self.warnings
+                        .push(ParseWarning::new(format!("{}; ignoring", message)));
+                    continue;
+                }
+            };
+            let (key, value) = (key.trim(), value.trim().to_string());
+
+            let channel_extensions = match inf_channel_section(section) {
+                Some((owner, InfChannel::Analog(index))) => self
+                    .analog_channels
+                    .iter_mut()
+                    .find(|c| c.index == index)
+                    .map(|c| (owner, &mut c.extensions)),
+                Some((owner, InfChannel::Status(index))) => self
+                    .status_channels
+                    .iter_mut()
+                    .find(|c| c.index == index)
+                    .map(|c| (owner, &mut c.extensions)),
+                None => None,
+            };
+            match channel_extensions {
+                Some((owner, channel_extensions)) => {
+                    channel_extensions.insert(format!("{}/{}", owner, key), value);
+                }
+                None => {
+                    extensions.insert(format!("{}/{}", section, key), value);
+                }
+            }
+        }
+
+        self.builder.extensions(extensions);
+
+        Ok(())
+    }
 
-        let leap_second_status = LeapSecondStatus::from_str(line_values[1])?;
-        self.builder.leap_second_status(Some(leap_second_status));
+    /// Recorders in locales which use a comma as the decimal separator sometimes write
+    /// them in numeric fields too, using semicolons to separate the fields instead. We
+    /// detect this from the channel count line, which never contains a decimal.
+    fn detect_decimal_comma(&mut self) -> ParseResult<()> {
+        let channels_line = self.cfg_contents.split('\n').nth(1).unwrap_or("");
+        let is_semicolon_separated = channels_line.contains(CFG_SEPARATOR_DECIMAL_COMMA)
+            && !channels_line.contains(CFG_SEPARATOR);
+
+        if !self.quirks.decimal_comma && is_semicolon_separated {
+            let message =
+                "found semicolon-separated values in .cfg file, expected comma-separated values"
+                    .to_string();
+            if self.strict {
+                return Err(ParseError::new(message));
+            }
+            self.warnings.push(ParseWarning::new(format!(
+                "{}; assuming decimal commas",
+                message
+            )));
+        }
+
+        if self.quirks.decimal_comma || is_semicolon_separated {
+            self.use_decimal_comma();
+        }
 
         Ok(())
     }
 
+    fn use_decimal_comma(&mut self) {
+        self.separator = CFG_SEPARATOR_DECIMAL_COMMA;
+        self.decimal_comma = true;
+        self.builder.decimal_comma(true);
+    }
+
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", skip_all, fields(data_format = ?self.data_format)))]
     fn parse_dat(&mut self) -> ParseResult<()> {
+        self.allocate_channel_buffers();
         match self.data_format {
-            Some(DataFormat::Ascii) => self.parse_dat_ascii(),
-            Some(_) => self.parse_dat_binary(),
-            None => Err(ParseError::new("Data format not specified.".into())),
+            Some(DataFormat::Ascii) => self.parse_dat_ascii()?,
+            Some(_) => self.parse_dat_binary()?,
+            None => return Err(ParseError::new("Data format not specified.".into())),
+        }
+
+        // Without a total from the `.cfg` file, the record is however long the data is.
+        if self.total_num_samples == 0 {
+            self.total_num_samples = self.data.sample_numbers.len() as u64;
+            self.builder.total_num_samples(self.total_num_samples);
         }
+
+        // Values are decoded raw and scaled afterwards in one pass per channel, which is
+        // much quicker than doing it value by value in amongst the decoding.
+        self.scale_analog_channels();
+
+        self.unwrap_timestamps();
+        self.check_sample_order()?;
+
+        #[cfg(feature = "tracing")]
+        tracing::debug!(
+            num_samples = self.data.sample_numbers.len(),
+            "decoded .dat file"
+        );
+
+        Ok(())
+    }
+
+    /// Now that we know how many samples we have in total, we can give the channels
+    /// buffers with the correct capacity to make `push()` operations more efficient.
+    /// This waits until the data is parsed, so that parsing just the configuration of a
+    /// record with thousands of channels doesn't allocate room for samples it never reads.
+    fn allocate_channel_buffers(&mut self) {
+        let sample_capacity = self.sample_capacity();
+        self.data.analog_data = (0..self.analog_channels.len())
+            .map(|_| self.scratch.analog_buffer(sample_capacity))
+            .collect();
+        self.data.status_data = (0..self.status_channels.len())
+            .map(|_| self.scratch.status_buffer(sample_capacity))
+            .collect();
     }
 
     fn parse_dat_ascii(&mut self) -> ParseResult<()> {
-        // One column for index, one for timestamp.
-        let expected_num_cols = (self.num_status_channels + self.num_analog_channels + 2) as usize;
+        let capacity = self.sample_capacity();
+        let mut sample_numbers = reuse(core::mem::take(&mut self.scratch.sample_numbers), capacity);
+        let mut timestamps = reuse(core::mem::take(&mut self.scratch.timestamps), capacity);
+        let mut raw_timestamps = reuse(core::mem::take(&mut self.scratch.raw_timestamps), capacity);
 
-        let mut sample_numbers: Vec<u32> = Vec::with_capacity(self.total_num_samples as usize);
-        let mut timestamps: Vec<f64> = Vec::with_capacity(self.total_num_samples as usize);
+        // Same scan is reused for every line so there are no allocations per line.
+        let mut scan = Scan::default();
 
-        for (i, line) in self
-            .ascii_dat_contents
+        let contents = core::mem::take(&mut self.ascii_dat_contents);
+        for (i, line) in contents
             .split('\n')
             .filter(|l| !l.trim().is_empty())
             .enumerate()
         {
-            let data_values: Vec<&str> = line.split(',').collect();
+            self.check_num_samples(i as u64 + 1)?;
+            self.read_ascii_scan(line, i, &mut scan)?;
+            self.push_scan(&scan, &mut sample_numbers, &mut timestamps);
+            raw_timestamps.push(scan.raw_timestamp);
+        }
+        self.ascii_dat_contents = contents;
+        self.stats.num_samples_found = sample_numbers.len();
 
-            if data_values.len() != expected_num_cols {
-                return Err(ParseError::new(format!(
-                    "Row {} has incorrect number of columns; expected {} but got {}.",
-                    i,
-                    expected_num_cols,
-                    data_values.len()
+        self.data.sample_numbers = sample_numbers;
+        self.data.timestamps = timestamps;
+        self.data.raw_timestamps = raw_timestamps;
+
+        Ok(())
+    }
+
+    fn parse_dat_binary(&mut self) -> ParseResult<()> {
+        let sample_size = self.binary_sample_size()?;
+        let analog_value_size = self.analog_value_size()?;
+        let contents = core::mem::take(&mut self.binary_dat_contents);
+
+        let num_samples = self.num_binary_samples(contents.len(), sample_size)?;
+        let samples = || contents.chunks_exact(sample_size).take(num_samples);
+
+        let mut sample_numbers = reuse(
+            core::mem::take(&mut self.scratch.sample_numbers),
+            num_samples,
+        );
+        sample_numbers.extend(samples().map(|sample| LittleEndian::read_u32(&sample[0..4]) as u64));
+        let mut raw_timestamps = reuse(
+            core::mem::take(&mut self.scratch.raw_timestamps),
+            num_samples,
+        );
+        raw_timestamps.extend(samples().map(|sample| {
+            match LittleEndian::read_u32(&sample[4..8]) {
+                TIMESTAMP_MISSING => None,
+                timestamp => Some(timestamp),
+            }
+        }));
+        let mut timestamps = reuse(core::mem::take(&mut self.scratch.timestamps), num_samples);
+        for (timestamp, sample_number) in raw_timestamps.iter().zip(sample_numbers.iter()) {
+            timestamps.push(self.real_time(*sample_number, *timestamp)?);
+        }
+
+        // Values are decoded a channel at a time straight into the channel buffers, which
+        // already have the capacity for all the samples. They're scaled afterwards.
+        for (channel_idx, data) in self.data.analog_data.iter_mut().enumerate() {
+            let start = 8 + channel_idx * analog_value_size;
+            let end = start + analog_value_size;
+
+            match self.data_format {
+                Some(DataFormat::Binary16) => {
+                    data.extend(samples().map(|sample| binary16_value(&sample[start..end])))
+                }
+                Some(DataFormat::Binary32) => {
+                    data.extend(samples().map(|sample| binary32_value(&sample[start..end])))
+                }
+                _ => data.extend(
+                    samples().map(|sample| LittleEndian::read_f32(&sample[start..end]) as f64),
+                ),
+            }
+        }
+
+        // Status channels are packed into 16-bit groups, with the least significant bit
+        // being the first channel in the group.
+        let status_start = 8 + self.analog_channels.len() * analog_value_size;
+        for (channel_idx, data) in self.data.status_data.iter_mut().enumerate() {
+            let start = status_start + (channel_idx / 16) * 2;
+            let bit_idx = channel_idx % 16;
+            data.extend(samples().map(|sample| {
+                ((LittleEndian::read_u16(&sample[start..start + 2]) >> bit_idx) & 0b01) as u8
+            }));
+        }
+
+        self.binary_dat_contents = contents;
+
+        self.data.sample_numbers = sample_numbers;
+        self.data.timestamps = timestamps;
+        self.data.raw_timestamps = raw_timestamps;
+
+        Ok(())
+    }
+
+    /// Number of samples to read from binary data of the given length. Any bytes left
+    /// over after the last whole sample are ignored.
+    fn num_binary_samples(&mut self, data_len: usize, sample_size: usize) -> ParseResult<usize> {
+        let num_samples = self.resolve_num_samples(data_len / sample_size)?;
+        self.check_num_samples(num_samples as u64)?;
+        Ok(num_samples)
+    }
+
+    /// Number of samples to keep out of the `found` whole samples in binary data,
+    /// applying the sample count policy when it isn't the total declared in the `.cfg`
+    /// file. When more are kept, the total and the end of the last sampling rate are
+    /// updated to match.
+    fn resolve_num_samples(&mut self, found: usize) -> ParseResult<usize> {
+        self.stats.num_samples_found = found;
+        let declared = self.total_num_samples;
+        if declared == 0 || found as u64 == declared {
+            return Ok(found);
+        }
+
+        let message = format!(
+            "[DAT] found {} samples, but the .cfg file says there are {}",
+            found, declared
+        );
+        match self.sample_count_policy {
+            SampleCountPolicy::Error => return Err(ParseError::new(message)),
+            SampleCountPolicy::Truncate if (found as u64) < declared => {
+                return Err(ParseError::new(message))
+            }
+            SampleCountPolicy::Truncate => {
+                self.warnings.push(ParseWarning::new(format!(
+                    "{}; ignoring the last {}",
+                    message,
+                    found as u64 - declared
                 )));
+                return Ok(declared as usize);
             }
+            SampleCountPolicy::Extend => {}
+        }
+
+        self.warnings.push(ParseWarning::new(format!(
+            "{}; setting the total to {}",
+            message, found
+        )));
+        self.total_num_samples = found as u64;
+        self.builder.total_num_samples(found as u64);
+        if let Some(rate) = self
+            .sampling_rates
+            .last_mut()
+            .filter(|rate| rate.end_sample_number == declared)
+        {
+            rate.end_sample_number = found as u64;
+            self.builder.sampling_rates(self.sampling_rates.clone());
+        }
+        Ok(found)
+    }
+
+    /// Values past the `expected` number on a channel line, which some tools add, keyed
+    /// for the channel's extensions as `CFG/Extra_Column_1` onwards. Fewer values than
+    /// expected is an error, as is more in strict mode.
+    fn extra_columns(
+        line_values: &[&str],
+        expected: usize,
+        line_number: usize,
+        strict: bool,
+        warnings: &mut Vec<ParseWarning>,
+    ) -> ParseResult<BTreeMap<String, String>> {
+        let mut extensions = BTreeMap::new();
+        if line_values.len() == expected {
+            return Ok(extensions);
+        }
+        if line_values.len() < expected || strict {
+            return Err(ParseError::new(format!(
+                "unexpected number of values on line {}",
+                line_number
+            )));
+        }
+
+        warnings.push(ParseWarning::new(format!(
+            "{} extra values on line {}; keeping them in the channel's extensions",
+            line_values.len() - expected,
+            line_number
+        )));
+        for (i, value) in line_values[expected..].iter().enumerate() {
+            extensions.insert(
+                format!("CFG/Extra_Column_{}", i + 1),
+                value.trim().to_string(),
+            );
+        }
+        Ok(extensions)
+    }
+
+    /// Number of channels of one type from a token on the channel count line, e.g.
+    /// `12A` or `4D`. The letter may be lower case or padded out from the number, as
+    /// some recorders write them; if it's missing altogether the number is read as it
+    /// is, with a warning, unless parsing strictly.
+    fn parse_channel_count(
+        token: &str,
+        suffix: char,
+        kind: &str,
+        line_number: usize,
+        strict: bool,
+        warnings: &mut Vec<ParseWarning>,
+    ) -> ParseResult<u32> {
+        let invalid = || {
+            ParseError::new(format!(
+                "invalid integer value for number of {} channels: '{}'",
+                kind, token
+            ))
+        };
+
+        let trimmed = token.trim();
+        let count = match trimmed.strip_suffix([suffix, suffix.to_ascii_lowercase()]) {
+            Some(count) => count.trim_end(),
+            None if !strict
+                && !trimmed.is_empty()
+                && trimmed.bytes().all(|b| b.is_ascii_digit()) =>
+            {
+                warnings.push(ParseWarning::new(format!(
+                    "no '{}' after number of {} channels on line {}; reading it as {}",
+                    suffix, kind, line_number, trimmed
+                )));
+                trimmed
+            }
+            None => return Err(invalid()),
+        };
+        count.parse().map_err(|_| invalid())
+    }
+
+    fn check_num_samples(&self, num_samples: u64) -> ParseResult<()> {
+        if num_samples > self.limits.max_samples {
+            return Err(ParseError::new(format!(
+                "record has more than the limit of {} samples",
+                self.limits.max_samples
+            )));
+        }
+        Ok(())
+    }
+
+    /// Number of samples to allocate space for up front. This is capped so that a bogus
+    /// total in the `.cfg` file can't make us try to allocate more memory than there is,
+    /// and the buffers just grow as normal past the cap.
+    fn sample_capacity(&self) -> usize {
+        usize::try_from(self.total_num_samples)
+            .unwrap_or(usize::MAX)
+            .min(MAX_PREALLOCATED_SAMPLES)
+    }
+
+    fn push_scan(&mut self, scan: &Scan, sample_numbers: &mut Vec<u64>, timestamps: &mut Vec<i64>) {
+        sample_numbers.push(scan.sample_number);
+        timestamps.push(scan.timestamp);
+
+        for (data, v) in self
+            .data
+            .analog_data
+            .iter_mut()
+            .zip(scan.analog_values.iter())
+        {
+            data.push(*v);
+        }
+        for (data, v) in self
+            .data
+            .status_data
+            .iter_mut()
+            .zip(scan.status_values.iter())
+        {
+            data.push(*v);
+        }
+    }
+
+    /// Decode a single line of ASCII data, where `i` is the 0-based index of the line
+    /// amongst the non-blank lines of the data.
+    pub(crate) fn parse_ascii_scan(&self, line: &str, i: usize) -> ParseResult<Scan> {
+        let mut scan = Scan::default();
+        self.read_ascii_scan(line, i, &mut scan)?;
+        self.scale_scan(&mut scan);
+        Ok(scan)
+    }
+
+    fn scale_scan(&self, scan: &mut Scan) {
+        for (value, channel) in scan
+            .analog_values
+            .iter_mut()
+            .zip(self.analog_channels.iter())
+        {
+            *value = *value * channel.multiplier + channel.offset_adder;
+        }
+    }
 
-            let sample_number = data_values[0].trim().parse::<u32>().map_err(|_| {
+    /// As `parse_ascii_scan()`, but decoding into an existing scan to reuse its buffers
+    /// and leaving the analog values unscaled.
+    fn read_ascii_scan(&self, line: &str, i: usize, scan: &mut Scan) -> ParseResult<()> {
+        // One column for index, one for timestamp.
+        let expected_num_cols = (self.num_status_channels + self.num_analog_channels + 2) as usize;
+
+        // This is the hot loop when parsing large ASCII records, so the line is dealt
+        // with as bytes to avoid the cost of UTF-8 aware splitting and trimming, and
+        // without collecting the fields into a `Vec`.
+        let separator = self.separator as u8;
+        let mut data_values = line
+            .as_bytes()
+            .split(move |b| *b == separator)
+            .map(<[u8]>::trim_ascii);
+        let wrong_num_cols = || {
+            ParseError::new(format!(
+                "Row {} has incorrect number of columns; expected {} but got {}.",
+                i,
+                expected_num_cols,
+                line.split(self.separator).count()
+            ))
+        };
+        let mut next_value = || data_values.next().ok_or_else(wrong_num_cols);
+
+        let sample_number_bytes = next_value()?;
+        let sample_number = parse_integer(sample_number_bytes)
+            .and_then(|v| u64::try_from(v).ok())
+            .ok_or_else(|| {
                 ParseError::new(format!(
                     "[DAT] Invalid sample number {} on line {}",
-                    data_values[0].trim(),
+                    ascii_field(sample_number_bytes),
                     i + 1
                 ))
             })?;
 
-            sample_numbers.push(sample_number);
-
-            let timestamp = match data_values[1].trim() {
-                "" => None, // TODO: Check whether there are any sampling rates. This is critical if there aren't any sampling rates.
-                v => Some(v.parse::<u32>().map_err(|_| {
-                    ParseError::new(format!(
-                        "[DAT] Invalid timestamp {} on line {}.",
-                        data_values[1].trim(),
-                        i
-                    ))
-                })?),
-            };
-
-            timestamps.push(self.real_time(sample_number, timestamp)?);
+        let timestamp = match next_value()? {
+            b"" => None, // TODO: Check whether there are any sampling rates. This is critical if there aren't any sampling rates.
+            v => Some(
+                parse_integer(v)
+                    .and_then(|v| u32::try_from(v).ok())
+                    .ok_or_else(|| {
+                        ParseError::new(format!(
+                            "[DAT] Invalid timestamp {} on line {}.",
+                            ascii_field(v),
+                            i
+                        ))
+                    })?,
+            ),
+        };
 
-            for channel_idx in 0..self.num_analog_channels {
-                let value_str = data_values[(channel_idx + 2) as usize].trim();
-                let value_raw = value_str.parse::<f64>().map_err(|_| {
+        scan.analog_values.clear();
+        for channel_idx in 0..self.num_analog_channels {
+            let value_bytes = next_value()?;
+            if value_bytes.iter().all(u8::is_ascii_whitespace) {
+                // Missing values are left blank.
+                scan.analog_values.push(f64::NAN);
+                continue;
+            }
+            let value_raw =
+                parse_ascii_value(value_bytes, self.decimal_comma).ok_or_else(|| {
                     ParseError::new(format!(
                         "[DAT] Invalid float value {} in analog channel {} on line {}.",
-                        value_str,
+                        ascii_field(value_bytes),
                         channel_idx + 1,
                         i + 1
                     ))
                 })?;
 
-                let adder = self.analog_channels[channel_idx as usize].offset_adder;
-                let multiplier = self.analog_channels[channel_idx as usize].multiplier;
-                let value = value_raw * multiplier + adder;
-
-                self.analog_channels[channel_idx as usize].push_datum(value);
-            }
+            scan.analog_values.push(value_raw);
+        }
 
-            for channel_idx in 0..self.num_status_channels {
-                let value_str =
-                    data_values[(channel_idx + self.num_analog_channels + 2) as usize].trim();
-                let value = value_str.parse::<u8>().map_err(|_| {
+        scan.status_values.clear();
+        for channel_idx in 0..self.num_status_channels {
+            let value_bytes = next_value()?;
+            let value = parse_integer(value_bytes)
+                .and_then(|v| u8::try_from(v).ok())
+                .ok_or_else(|| {
                     ParseError::new(format!(
                         "[DAT] Invalid status value {} in status channel {} on line {}",
-                        value_str,
+                        ascii_field(value_bytes),
                         channel_idx + 1,
                         i + 1
                     ))
                 })?;
-                self.status_channels[channel_idx as usize].push_datum(value);
-            }
+            scan.status_values.push(value);
+        }
+
+        if data_values.next().is_some() {
+            return Err(wrong_num_cols());
         }
 
-        self.builder.sample_numbers(sample_numbers);
-        self.builder.timestamps(timestamps);
+        scan.sample_number = sample_number;
+        scan.timestamp = self.real_time(sample_number, timestamp)?;
+        scan.raw_timestamp = timestamp;
 
         Ok(())
     }
 
-    fn parse_dat_binary(&mut self) -> ParseResult<()> {
-        // Status channels are binary (0 or 1) and combined into 16-bit bitfields.
-        // Each 16-bit bitfield is referred to as a status "group".
-        let num_status_groups = (self.num_status_channels as f32 / 16.0).ceil() as u8;
+    /// Find samples which don't follow on from the previous one and deal with them
+    /// according to the sample order policy.
+    fn check_sample_order(&mut self) -> ParseResult<()> {
+        let sample_numbers = core::mem::take(&mut self.data.sample_numbers);
+        let timestamps = core::mem::take(&mut self.data.timestamps);
+        let raw_timestamps = core::mem::take(&mut self.data.raw_timestamps);
+
+        let mut irregular_samples = vec![];
+        for i in 1..sample_numbers.len() {
+            let sample_number = sample_numbers[i];
+            let mut flag = |kind| {
+                irregular_samples.push(IrregularSample {
+                    index: i,
+                    sample_number,
+                    kind,
+                })
+            };
 
-        let mut cursor = Cursor::new(&self.binary_dat_contents);
+            if sample_number == sample_numbers[i - 1] {
+                flag(IrregularSampleKind::DuplicateSampleNumber);
+            } else if sample_number < sample_numbers[i - 1] {
+                flag(IrregularSampleKind::SampleNumberOutOfOrder);
+            }
 
-        let mut sample_numbers: Vec<u32> = Vec::with_capacity(self.total_num_samples as usize);
-        let mut timestamps: Vec<f64> = Vec::with_capacity(self.total_num_samples as usize);
+            if timestamps[i] == timestamps[i - 1] {
+                flag(IrregularSampleKind::DuplicateTimestamp);
+            } else if timestamps[i] < timestamps[i - 1] {
+                flag(IrregularSampleKind::TimestampBackwards);
+            }
+        }
 
-        let mut i = 0;
-        loop {
-            if i >= self.total_num_samples {
-                break;
+        if let Some(first) = irregular_samples.first() {
+            match self.sample_order_policy {
+                SampleOrderPolicy::Error => {
+                    return Err(ParseError::new(format!(
+                        "[DAT] sample number {} on line {} is out of order ({:?})",
+                        first.sample_number,
+                        first.index + 1,
+                        first.kind
+                    )))
+                }
+                SampleOrderPolicy::Keep => self.warnings.push(ParseWarning::new(format!(
+                    "{} samples are out of order in .dat file, starting with sample number {}",
+                    irregular_samples.len(),
+                    first.sample_number
+                ))),
+                SampleOrderPolicy::Reorder => {
+                    self.warnings.push(ParseWarning::new(format!(
+                        "{} samples are out of order in .dat file, so have been sorted by \
+                         sample number",
+                        irregular_samples.len()
+                    )));
+                }
             }
+        }
 
-            let sample_number = cursor.read_u32::<LittleEndian>().unwrap();
-            let timestamp = cursor.read_u32::<LittleEndian>().unwrap();
+        let is_sorted = irregular_samples
+            .iter()
+            .all(|s| s.kind != IrregularSampleKind::SampleNumberOutOfOrder);
+        if self.sample_order_policy == SampleOrderPolicy::Reorder && !is_sorted {
+            let mut order: Vec<usize> = (0..sample_numbers.len()).collect();
+            order.sort_by_key(|&i| sample_numbers[i]);
 
-            sample_numbers.push(sample_number);
-            timestamps.push(self.real_time(
-                sample_number,
-                if timestamp == TIMESTAMP_MISSING {
-                    None
-                } else {
-                    Some(timestamp)
-                },
-            )?);
+            for data in self.data.analog_data.iter_mut() {
+                *data = reorder(data, &order);
+            }
+            for data in self.data.status_data.iter_mut() {
+                *data = reorder(data, &order);
+            }
+            self.data.sample_numbers = reorder(&sample_numbers, &order);
+            self.data.timestamps = reorder(&timestamps, &order);
+            self.data.raw_timestamps = reorder(&raw_timestamps, &order);
+        } else {
+            self.data.sample_numbers = sample_numbers;
+            self.data.timestamps = timestamps;
+            self.data.raw_timestamps = raw_timestamps;
+        }
 
-            let analog_values = (0..self.num_analog_channels)
-                .map(|channel_idx| {
-                    let value = match self.data_format {
-                        Some(DataFormat::Binary16) => {
-                            cursor.read_i16::<LittleEndian>().unwrap() as f64
-                        }
-                        Some(DataFormat::Binary32) => {
-                            cursor.read_i32::<LittleEndian>().unwrap() as f64
-                        }
-                        Some(DataFormat::Float32) => {
-                            cursor.read_f32::<LittleEndian>().unwrap() as f64
-                        }
-                        _ => panic!(
-                            "tried to parse binary data for non-binary or invalid data format"
-                        ), // TODO: Turn into proper parse result.
-                    };
+        self.data.irregular_samples = irregular_samples;
 
-                    let adder = self.analog_channels[channel_idx as usize].offset_adder;
-                    let multiplier = self.analog_channels[channel_idx as usize].multiplier;
-                    value * multiplier + adder
-                })
-                .collect::<Vec<f64>>();
+        Ok(())
+    }
 
-            for (i, v) in analog_values.into_iter().enumerate() {
-                self.analog_channels[i].push_datum(v);
+    /// Apply each channel's multiplier and offset to its raw data values, in parallel
+    /// across channels if the `rayon` feature is enabled.
+    fn scale_analog_channels(&mut self) {
+        #[cfg(feature = "decimal")]
+        if self.exact_scaling {
+            let channels = self.analog_channels.iter();
+            for (i, (channel, data)) in channels.zip(self.data.analog_data.iter_mut()).enumerate() {
+                // Configurations put together elsewhere only have the parsed factors.
+                let factors = match self.exact_factors.get(i) {
+                    Some(factors) => *factors,
+                    None => Decimal::from_f64(channel.multiplier)
+                        .zip(Decimal::from_f64(channel.offset_adder)),
+                };
+                match factors {
+                    Some((multiplier, adder)) => {
+                        scale_values_exactly(data, channel, multiplier, adder)
+                    }
+                    None => {
+                        self.warnings.push(ParseWarning::new(format!(
+                            "multiplier or offset adder of analog channel {} can't be \
+                             represented as a decimal; scaling its values in floating point",
+                            channel.index
+                        )));
+                        scale_values(data, channel.multiplier, channel.offset_adder);
+                    }
+                }
             }
+            return;
+        }
 
-            let status_values = (0..num_status_groups)
-                .map(|_| cursor.read_u16::<LittleEndian>().unwrap())
-                .flat_map(|group| {
-                    (0..16)
-                        .map(|bit_idx| {
-                            // Least significant bit is first status channel.
-                            let bit_mask = 0b01 << bit_idx;
-                            let val = (group & bit_mask) >> bit_idx;
-                            val as u8
-                        })
-                        .collect::<Vec<u8>>()
-                })
-                // Groups are padded out with zeros - we want to ignore the padded values.
-                .take(self.num_status_channels as usize)
-                .collect::<Vec<u8>>();
+        let scale_channel = |(channel, data): (&AnalogChannel, &mut Vec<f64>)| {
+            scale_values(data, channel.multiplier, channel.offset_adder)
+        };
 
-            for (i, v) in status_values.into_iter().enumerate() {
-                self.status_channels[i].push_datum(v);
-            }
+        #[cfg(feature = "rayon")]
+        {
+            use rayon::iter::{
+                IndexedParallelIterator, IntoParallelRefIterator, IntoParallelRefMutIterator,
+                ParallelIterator,
+            };
+            self.analog_channels
+                .par_iter()
+                .zip(self.data.analog_data.par_iter_mut())
+                .for_each(scale_channel);
+        }
 
-            i += 1;
+        #[cfg(not(feature = "rayon"))]
+        self.analog_channels
+            .iter()
+            .zip(self.data.analog_data.iter_mut())
+            .for_each(scale_channel);
+    }
+
+    /// Number of bytes taken up by each sample in binary data.
+    pub(crate) fn binary_sample_size(&self) -> ParseResult<usize> {
+        self.data_format
+            .as_ref()
+            .and_then(|format| {
+                format.bytes_per_scan(self.num_analog_channels, self.num_status_channels)
+            })
+            .ok_or_else(non_binary_format_err)
+    }
+
+    /// Number of bytes taken up by each analog value in binary data.
+    fn analog_value_size(&self) -> ParseResult<usize> {
+        self.data_format
+            .as_ref()
+            .and_then(DataFormat::analog_value_size)
+            .ok_or_else(non_binary_format_err)
+    }
+
+    /// Decode a single sample of binary data, which must be exactly
+    /// `binary_sample_size()` bytes long.
+    pub(crate) fn parse_binary_scan(&self, sample: &[u8]) -> ParseResult<Scan> {
+        let mut scan = Scan::default();
+        self.read_binary_scan(sample, &mut scan)?;
+        Ok(scan)
+    }
+
+    /// Decode a single binary sample into `scan`, including scaling the analog values.
+    fn read_binary_scan(&self, sample: &[u8], scan: &mut Scan) -> ParseResult<()> {
+        let sample_size = self.binary_sample_size()?;
+        if sample.len() != sample_size {
+            return Err(ParseError::new(format!(
+                "[DAT] binary sample has {} bytes; expected {}",
+                sample.len(),
+                sample_size
+            )));
         }
 
-        self.builder.sample_numbers(sample_numbers);
-        self.builder.timestamps(timestamps);
+        let sample_number = LittleEndian::read_u32(&sample[0..4]) as u64;
+        let raw_timestamp = match LittleEndian::read_u32(&sample[4..8]) {
+            TIMESTAMP_MISSING => None,
+            timestamp => Some(timestamp),
+        };
+        let timestamp = self.real_time(sample_number, raw_timestamp)?;
+
+        let analog_value_size = self.analog_value_size()?;
+        let analog_bytes = &sample[8..8 + self.num_analog_channels as usize * analog_value_size];
+        scan.analog_values.clear();
+        scan.analog_values.extend(
+            analog_bytes
+                .chunks_exact(analog_value_size)
+                .zip(self.analog_channels.iter())
+                .map(|(bytes, channel)| {
+                    let value = match self.data_format {
+                        Some(DataFormat::Binary16) => binary16_value(bytes),
+                        Some(DataFormat::Binary32) => binary32_value(bytes),
+                        _ => LittleEndian::read_f32(bytes) as f64,
+                    };
+                    value * channel.multiplier + channel.offset_adder
+                }),
+        );
+
+        let status_bytes = &sample[8 + analog_bytes.len()..];
+        scan.status_values.clear();
+        scan.status_values.extend(
+            status_bytes
+                .chunks_exact(2)
+                .map(LittleEndian::read_u16)
+                .flat_map(|group| {
+                    (0..16).map(move |bit_idx| {
+                        // Least significant bit is first status channel.
+                        let bit_mask = 0b01 << bit_idx;
+                        let val = (group & bit_mask) >> bit_idx;
+                        val as u8
+                    })
+                })
+                // Groups are padded out with zeros - we want to ignore the padded values.
+                .take(self.num_status_channels as usize),
+        );
+
+        scan.sample_number = sample_number;
+        scan.timestamp = timestamp;
+        scan.raw_timestamp = raw_timestamp;
 
         Ok(())
     }
@@ -1125,17 +3132,17 @@ impl<T: BufRead> ComtradeParser<T> {
     /// sampling information if possible, otherwise the in-data timestamp values
     /// along with relevant multiplicative factors from configuration file. This
     /// does *not* include the skew, which needs to be done on a per-channel basis.
-    fn real_time(&self, sample_number: u32, timestamp: Option<u32>) -> ParseResult<f64> {
+    fn real_time(&self, sample_number: u64, timestamp: Option<u32>) -> ParseResult<i64> {
         if !self.is_timestamp_critical || timestamp.is_none() {
             let sampling_rate = self.sampling_rate_for_sample(sample_number);
-            return ParseResult::Ok((sample_number - 1) as f64 / sampling_rate);
+            let sample_index = sample_number.checked_sub(1).ok_or_else(|| {
+                ParseError::new("[DAT] invalid sample number 0; must start at 1".to_string())
+            })?;
+            return ParseResult::Ok(round_nanoseconds(sample_index as f64 * 1e9 / sampling_rate));
         }
 
         match timestamp {
-            Some(ts_value) => {
-                let multiplier = self.builder.timestamp_multiplication_factor.unwrap_or(1.0);
-                ParseResult::Ok(ts_value as f64 * self.ts_base_unit * multiplier)
-            }
+            Some(ts_value) => ParseResult::Ok(self.critical_time(ts_value as u64)),
             None => ParseResult::Err(ParseError::new(format!(
                 "timestamp is critical but not present in sample number {}",
                 sample_number
@@ -1143,10 +3150,65 @@ impl<T: BufRead> ComtradeParser<T> {
         }
     }
 
-    fn sampling_rate_for_sample(&self, sample_number: u32) -> f64 {
-        let sampling_rates: &Vec<SamplingRate> = self.builder.sampling_rates.as_ref().unwrap();
+    /// Time in nanoseconds of a timestamp from the data file. This takes a `u64` so that
+    /// timestamps which have rolled over can be counted on past the end of the range.
+    fn critical_time(&self, ts_value: u64) -> i64 {
+        let multiplier = self.timestamp_multiplication_factor;
+        let nanoseconds = (ts_value as i64).saturating_mul(self.ts_base_unit);
+        // Whole multipliers, which is nearly all of them, are kept exact.
+        if multiplier == multiplier as i64 as f64 {
+            nanoseconds.saturating_mul(multiplier as i64)
+        } else {
+            round_nanoseconds(nanoseconds as f64 * multiplier)
+        }
+    }
+
+    /// Timestamps in the data file are only 32 bits, so in microseconds they roll over
+    /// after about 71 minutes. Where the timestamps are critical and one is less than the
+    /// one before it by more than half the range, it's taken to have rolled over, and the
+    /// times from there on are counted on past the end of the range, with a warning.
+    fn unwrap_timestamps(&mut self) {
+        if !self.is_timestamp_critical {
+            return;
+        }
+        let raw_timestamps = core::mem::take(&mut self.data.raw_timestamps);
+        let mut timestamps = core::mem::take(&mut self.data.timestamps);
+
+        let mut rollovers: u64 = 0;
+        let mut first_rollover = None;
+        let mut previous = None;
+        for (i, (raw, timestamp)) in raw_timestamps.iter().zip(timestamps.iter_mut()).enumerate() {
+            let raw = match raw {
+                Some(raw) => *raw,
+                None => continue,
+            };
+            if previous.is_some_and(|previous| is_timestamp_rollover(previous, raw)) {
+                rollovers += 1;
+                first_rollover.get_or_insert(i);
+            }
+            previous = Some(raw);
+
+            if rollovers > 0 {
+                *timestamp = self.critical_time(raw as u64 + (rollovers << 32));
+            }
+        }
+
+        if let Some(index) = first_rollover {
+            self.warnings.push(ParseWarning::new(format!(
+                "[DAT] timestamps roll over {} time(s), first at sample {}; counting on past \
+                 the end of the range",
+                rollovers,
+                index + 1
+            )));
+        }
+
+        self.data.raw_timestamps = raw_timestamps;
+        self.data.timestamps = timestamps;
+    }
 
-        let maybe_rate = sampling_rates
+    fn sampling_rate_for_sample(&self, sample_number: u64) -> f64 {
+        let maybe_rate = self
+            .sampling_rates
             .iter()
             .find(|r| sample_number <= r.end_sample_number);
 
@@ -1157,10 +3219,301 @@ impl<T: BufRead> ComtradeParser<T> {
     }
 }
 
+/// Values rearranged so that the `i`th one is the `order[i]`th of the originals.
+fn reorder<T: Copy>(values: &[T], order: &[usize]) -> Vec<T> {
+    order
+        .iter()
+        .filter_map(|&i| values.get(i).copied())
+        .collect()
+}
+
+/// Calculate `value * multiplier + adder` for every value of the channel in place in
+/// decimal, falling back to the channel's floating point factors for values which can't be.
+#[cfg(feature = "decimal")]
+fn scale_values_exactly(
+    values: &mut [f64],
+    channel: &AnalogChannel,
+    multiplier: Decimal,
+    adder: Decimal,
+) {
+    for value in values.iter_mut() {
+        *value = Decimal::from_f64(*value)
+            .and_then(|v| v.checked_mul(multiplier))
+            .and_then(|v| v.checked_add(adder))
+            .and_then(|v| v.to_f64())
+            .unwrap_or(*value * channel.multiplier + channel.offset_adder);
+    }
+}
+
+/// Factor from the `.cfg` file as a decimal, or the parsed value if the text can't be
+/// read as one. `None` if neither fits in a decimal, e.g. because it's too large.
+#[cfg(feature = "decimal")]
+fn exact_factor(value: &str, parsed: f64, decimal_comma: bool) -> Option<Decimal> {
+    let value = value.trim();
+    let value = if decimal_comma {
+        value.replace(',', ".")
+    } else {
+        value.to_string()
+    };
+    Decimal::from_str(&value)
+        .or_else(|_| Decimal::from_scientific(&value))
+        .ok()
+        .or_else(|| Decimal::from_f64(parsed))
+}
+
+/// Calculate `value * multiplier + adder` for every value in place.
+fn scale_values(values: &mut [f64], multiplier: f64, adder: f64) {
+    if multiplier == 1.0 && adder == 0.0 {
+        return;
+    }
+
+    // Working through fixed-size chunks lets the compiler turn the inner loop into SIMD
+    // instructions without needing anything target-specific here.
+    const LANES: usize = 8;
+    let mut chunks = values.chunks_exact_mut(LANES);
+    for chunk in &mut chunks {
+        for value in chunk.iter_mut() {
+            *value = *value * multiplier + adder;
+        }
+    }
+    for value in chunks.into_remainder() {
+        *value = *value * multiplier + adder;
+    }
+}
+
+/// Parse a real number, which may have a comma instead of a period for the decimal
+/// separator if the file is written in that locale.
+fn parse_real(value: &str, decimal_comma: bool) -> Result<f64, core::num::ParseFloatError> {
+    let value = value.trim();
+    if decimal_comma && value.contains(',') {
+        value.replace(',', ".").parse::<f64>()
+    } else {
+        value.parse::<f64>()
+    }
+}
+
+/// Unscaled 16-bit binary analog value, with the missing value marker read as NaN.
+fn binary16_value(bytes: &[u8]) -> f64 {
+    match LittleEndian::read_i16(bytes) {
+        i16::MIN => f64::NAN,
+        value => value as f64,
+    }
+}
+
+/// As `binary16_value()`, for the 32-bit binary format.
+fn binary32_value(bytes: &[u8]) -> f64 {
+    match LittleEndian::read_i32(bytes) {
+        i32::MIN => f64::NAN,
+        value => value as f64,
+    }
+}
+
+/// Parse a value from an ASCII `.dat` file. These are nearly always integers, which are
+/// much quicker to parse by hand than going through the general float parser.
+fn parse_ascii_value(value: &[u8], decimal_comma: bool) -> Option<f64> {
+    match parse_integer(value) {
+        Some(v) => Some(v as f64),
+        None => parse_real(core::str::from_utf8(value).ok()?, decimal_comma).ok(),
+    }
+}
+
+/// Parse a plain decimal integer with an optional sign, returning `None` for anything
+/// else, including values too long to be sure of fitting in an `i64`.
+fn parse_integer(value: &[u8]) -> Option<i64> {
+    let (is_negative, digits) = match value.first()? {
+        b'-' => (true, &value[1..]),
+        b'+' => (false, &value[1..]),
+        _ => (false, value),
+    };
+    if digits.is_empty() || digits.len() > 18 {
+        return None;
+    }
+
+    let mut result: i64 = 0;
+    for b in digits {
+        if !b.is_ascii_digit() {
+            return None;
+        }
+        result = result * 10 + (b - b'0') as i64;
+    }
+
+    Some(if is_negative { -result } else { result })
+}
+
+/// Field from a line of ASCII data for use in error messages.
+fn ascii_field(value: &[u8]) -> &str {
+    core::str::from_utf8(value).unwrap_or("<invalid UTF-8>")
+}
+
+/// Values on a line split at `separator`, where a value in double quotes may contain the
+/// separator, as the 2013 revision allows for the station name and device ID. The quotes
+/// are removed, along with any padding outside them, and a doubled quote inside them is
+/// taken as a single quote. Unquoted values are left as they are.
+fn split_quoted(line: &str, separator: char) -> Vec<String> {
+    let mut values = vec![];
+    let mut rest = line;
+    loop {
+        let trimmed = rest.trim_start();
+        if let Some(quoted) = trimmed.strip_prefix('"') {
+            let mut value = String::new();
+            let mut chars = quoted.char_indices();
+            let mut end = quoted.len();
+            while let Some((i, c)) = chars.next() {
+                if c != '"' {
+                    value.push(c);
+                } else if quoted[i + 1..].starts_with('"') {
+                    value.push('"');
+                    chars.next();
+                } else {
+                    end = i + 1;
+                    break;
+                }
+            }
+            values.push(value);
+
+            // Anything between the closing quote and the next separator is dropped.
+            match quoted[end..].find(separator) {
+                Some(next) => rest = &quoted[end + next + separator.len_utf8()..],
+                None => return values,
+            }
+        } else {
+            match rest.find(separator) {
+                Some(next) => {
+                    values.push(rest[..next].to_string());
+                    rest = &rest[next + separator.len_utf8()..];
+                }
+                None => {
+                    values.push(rest.to_string());
+                    return values;
+                }
+            }
+        }
+    }
+}
+
+/// Date/time stamps in decimal comma files look like `dd/mm/yyyy;hh:mm:ss,ssssss`, so
+/// need converting back into the standard form before parsing.
+fn normalise_datetime_stamp(line: &str, decimal_comma: bool) -> String {
+    let stamp = line.trim();
+    if decimal_comma {
+        stamp
+            .replace(',', ".")
+            .replace(CFG_SEPARATOR_DECIMAL_COMMA, ",")
+    } else {
+        stamp.to_string()
+    }
+}
+
+/// Channel an `.inf` file section is for, by its 1-based index amongst the analog or
+/// status channels.
+enum InfChannel {
+    Analog(u32),
+    Status(u32),
+}
+
+/// Owner and channel of an `.inf` file section for a particular channel, e.g.
+/// `Public Analog_Channel_#3`.
+fn inf_channel_section(section: &str) -> Option<(&str, InfChannel)> {
+    let (owner, name) = section.rsplit_once(' ')?;
+    let (kind, index) = name.split_once("_Channel_#")?;
+    let index = index.parse::<u32>().ok()?;
+
+    match kind {
+        "Analog" => Some((owner.trim(), InfChannel::Analog(index))),
+        "Status" => Some((owner.trim(), InfChannel::Status(index))),
+        _ => None,
+    }
+}
+
+/// Channel text fields are kept as-is unless we've been told they're padded.
+fn channel_field<'a>(value: &'a str, quirks: &Quirks) -> &'a str {
+    if quirks.trim_channel_fields {
+        value.trim()
+    } else {
+        value
+    }
+}
+
+/// Split a line of the `.cfg` file into its values, reusing the `Vec` from the line
+/// before rather than allocating a new one for every line.
+fn split_line<'a>(values: &mut Vec<&'a str>, line: &'a str, separator: char) {
+    values.clear();
+    values.extend(line.split(separator));
+}
+
+/// Decode the contents of a `.cff` file, which is normally UTF-8 but which some Windows
+/// tools write as UTF-16 with a byte order mark.
+fn decode_cff(bytes: Vec<u8>) -> ParseResult<String> {
+    let invalid = || ParseError::new("unable to read specified .cff file".to_string());
+    let utf16 = |bytes: &[u8], from_bytes: fn([u8; 2]) -> u16| {
+        if !bytes.len().is_multiple_of(2) {
+            return Err(invalid());
+        }
+        let units = bytes
+            .chunks_exact(2)
+            .map(|pair| from_bytes([pair[0], pair[1]]));
+        char::decode_utf16(units)
+            .collect::<Result<String, _>>()
+            .map_err(|_| invalid())
+    };
+
+    match bytes.as_slice() {
+        [0xFF, 0xFE, rest @ ..] => utf16(rest, u16::from_le_bytes),
+        [0xFE, 0xFF, rest @ ..] => utf16(rest, u16::from_be_bytes),
+        [0xEF, 0xBB, 0xBF, rest @ ..] => Ok(core::str::from_utf8(rest)
+            .map_err(|_| invalid())?
+            .to_string()),
+        _ => String::from_utf8(bytes).map_err(|_| invalid()),
+    }
+}
+
+/// Parse data file type, accepting some common non-standard variations if configured.
+pub(crate) fn parse_data_format(value: &str, quirks: &Quirks) -> ParseResult<DataFormat> {
+    if quirks.nonstandard_tokens {
+        let token: String = value
+            .chars()
+            .filter(|c| !c.is_whitespace() && *c != '_' && *c != '-')
+            .collect::<String>()
+            .to_lowercase();
+
+        match token.as_str() {
+            "bin" | "bin16" | "binary16" => return Ok(DataFormat::Binary16),
+            "bin32" => return Ok(DataFormat::Binary32),
+            "float" | "real32" => return Ok(DataFormat::Float32),
+            _ => {}
+        }
+    }
+
+    DataFormat::from_str(value)
+}
+
+/// Parse analog channel scaling mode, accepting the full words if configured.
+fn parse_scaling_mode(value: &str, quirks: &Quirks) -> ParseResult<AnalogScalingMode> {
+    if quirks.nonstandard_tokens {
+        match value.trim().to_lowercase().as_str() {
+            "primary" => return Ok(AnalogScalingMode::Primary),
+            "secondary" => return Ok(AnalogScalingMode::Secondary),
+            _ => {}
+        }
+    }
+
+    AnalogScalingMode::from_str(value)
+}
+
+/// Any lines left over after the standard fields are vendor-specific extensions, which
+/// we keep hold of (minus blank lines) so they aren't lost when re-writing the record.
+fn extra_cfg_lines<'a>(lines: impl Iterator<Item = &'a str>) -> Vec<String> {
+    lines
+        .map(|l| l.trim_end_matches('\r'))
+        .filter(|l| !l.trim().is_empty())
+        .map(|l| l.to_string())
+        .collect()
+}
+
 /// If a timestamp is specified to 6 dp then the timestamps should be interpreted as
 /// in the base unit of microseconds. If the timestamp has 9 dp, the timestamps should
-/// be interpreted in nanoseconds.
-fn ts_base_unit(datetime_stamp: &str) -> ParseResult<f64> {
+/// be interpreted in nanoseconds. The base unit is returned as a number of nanoseconds.
+fn ts_base_unit(datetime_stamp: &str) -> ParseResult<i64> {
     let fraction = datetime_stamp.rsplit('.').next();
 
     if fraction.is_none() {
@@ -1170,9 +3523,19 @@ fn ts_base_unit(datetime_stamp: &str) -> ParseResult<f64> {
     }
 
     if fraction.unwrap().len() <= 6 {
-        Ok(1e-6)
+        Ok(1000)
     } else {
-        Ok(1e-9)
+        Ok(1)
+    }
+}
+
+/// Round a time in nanoseconds to the nearest whole nanosecond. `f64::round()` isn't
+/// available without `std`.
+pub(crate) fn round_nanoseconds(nanoseconds: f64) -> i64 {
+    if nanoseconds < 0.0 {
+        (nanoseconds - 0.5) as i64
+    } else {
+        (nanoseconds + 0.5) as i64
     }
 }
 
@@ -1184,50 +3547,58 @@ fn ts_base_unit(datetime_stamp: &str) -> ParseResult<f64> {
 ///   - "-7h15" meaning 7 hours and 15 minutes west of UTC.
 ///   - "0" meaning same as UTC.
 ///
+/// Some recorders write ISO 8601 style offsets instead, so "+05:30" and "+0530" are
+/// accepted too.
+///
 /// "Not applicable" is a valid value for this, represents in the COMTRADE file
 /// as `x` - this is given the value of `None` here.
 fn parse_time_offset(offset_str: &str) -> ParseResult<Option<FixedOffset>> {
     let time_value = offset_str.trim();
 
     // Special value indicating offset field does not apply.
-    if time_value.to_lowercase() == "x" {
+    if time_value.eq_ignore_ascii_case("x") {
         return Ok(None);
     }
 
-    let maybe_hours = time_value.parse::<i32>();
+    let invalid = || ParseError::new(format!("invalid time offset: '{}'", time_value));
 
-    if let Ok(hours) = maybe_hours {
-        // Offset specified just as number of hours, e.g. "-4", "+10", "0".
-        return Ok(Some(FixedOffset::east(hours * 3600)));
-    }
+    let (sign, rest) = match time_value.strip_prefix('-') {
+        Some(rest) => (-1, rest),
+        None => (1, time_value.strip_prefix('+').unwrap_or(time_value)),
+    };
+    let rest = rest.trim();
+    let (hours, minutes) = match rest.split_once(['h', 'H', ':']) {
+        Some((hours, minutes)) => (hours.trim(), minutes.trim()),
+        // Compact form with hours and minutes run together, e.g. "+0530".
+        None if rest.len() > 2 => rest.split_at(rest.len() - 2),
+        None => (rest, ""),
+    };
 
-    // Offset specified as number + minutes, e.g. "-7h15", "+9h45".
-    let time_split: Vec<&str> = time_value.split('h').collect();
-    if time_split.len() != 2 {
+    let is_number = |value: &str| !value.is_empty() && value.bytes().all(|b| b.is_ascii_digit());
+    if !is_number(hours) || hours.len() > 2 || minutes.len() > 2 {
+        return Err(invalid());
+    }
+    if !minutes.is_empty() && !is_number(minutes) {
+        return Err(invalid());
+    }
+    let hours: i32 = hours.parse().map_err(|_| invalid())?;
+    let minutes: i32 = match minutes {
+        "" => 0,
+        minutes => minutes.parse().map_err(|_| invalid())?,
+    };
+    if minutes >= 60 {
         return Err(ParseError::new(format!(
-            "invalid time offset on line: {}",
-            time_value,
+            "minutes out of range in time offset: '{}'",
+            time_value
         )));
     }
 
-    let hours = time_split[0].trim().parse::<i32>().map_err(|_| {
-        ParseError::new(format!(
-            "invalid hour offset in time offset: {} in {}",
-            time_split[0], time_value,
-        ))
-    })?;
-    let minutes = time_split[1].trim().parse::<i32>().map_err(|_| {
-        ParseError::new(format!(
-            "invalid minute offset in time offset: {} in {}",
-            time_split[1], time_value,
-        ))
-    })?;
-
-    let total_offset = if hours > 0 {
-        hours * 3600 + minutes * 60
-    } else {
-        hours * 3600 - minutes * 60
-    };
-
-    Ok(Some(FixedOffset::east(total_offset)))
+    FixedOffset::east_opt(sign * (hours * 3600 + minutes * 60))
+        .map(Some)
+        .ok_or_else(|| {
+            ParseError::new(format!(
+                "time offset out of range: '{}'; must be less than 24 hours",
+                time_value
+            ))
+        })
 }
@@ -0,0 +1,82 @@
+//! Keeping the files a record was parsed from, so that writing it back out unmodified
+//! gives exactly the same bytes, e.g. for archives which checksum records but pass them
+//! through this library. See `ComtradeParserBuilder::keep_source_files()` and
+//! `ComtradeWriter::passthrough()`.
+
+use alloc::vec::Vec;
+
+use crate::fingerprint::Fnv1a;
+use crate::{Comtrade, ComtradeConfig};
+
+/// Contents of the `.cfg` and `.dat` files a record was parsed from, byte for byte,
+/// along with a snapshot of the record as it was parsed so that changes to it since can
+/// be spotted.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SourceFiles {
+    /// Contents of the `.cfg` file, or `None` if the record was parsed against an
+    /// existing configuration rather than a `.cfg` file.
+    pub cfg: Option<Vec<u8>>,
+    pub dat: Vec<u8>,
+
+    config: ComtradeConfig,
+    data_hash: u64,
+}
+
+impl SourceFiles {
+    pub(crate) fn new(record: &Comtrade, cfg: Option<Vec<u8>>, dat: Vec<u8>) -> Self {
+        SourceFiles {
+            cfg,
+            dat,
+            config: record.config.clone(),
+            data_hash: data_hash(record),
+        }
+    }
+
+    /// Whether `record` is still exactly as it was parsed from these files, i.e. none of
+    /// its configuration, timestamps or values have changed.
+    pub fn matches(&self, record: &Comtrade) -> bool {
+        record.config == self.config && data_hash(record) == self.data_hash
+    }
+}
+
+impl Comtrade {
+    /// Whether the record has been changed since it was parsed. Only known if the parser
+    /// was asked to keep the source files; if not, this is always `false`.
+    pub fn is_unmodified(&self) -> bool {
+        self.data
+            .source_files
+            .as_ref()
+            .is_some_and(|source_files| source_files.matches(self))
+    }
+}
+
+/// Hash of every sample of the record, exactly, unlike `Comtrade::fingerprint()`.
+fn data_hash(record: &Comtrade) -> u64 {
+    let mut hasher = Fnv1a::new();
+
+    hasher.write_u64(record.data.sample_numbers.len() as u64);
+    for sample_number in record.data.sample_numbers.iter() {
+        hasher.write_u64(*sample_number);
+    }
+    hasher.write_u64(record.data.timestamps.len() as u64);
+    for timestamp in record.data.timestamps.iter() {
+        hasher.write_u64(*timestamp as u64);
+    }
+    hasher.write_u64(record.data.raw_timestamps.len() as u64);
+    for raw in record.data.raw_timestamps.iter() {
+        hasher.write_u64(raw.map_or(u64::MAX, u64::from));
+    }
+
+    for data in record.data.analog_data.iter() {
+        hasher.write_u64(data.len() as u64);
+        for value in data.iter() {
+            hasher.write_u64(value.to_bits());
+        }
+    }
+    for data in record.data.status_data.iter() {
+        hasher.write_u64(data.len() as u64);
+        hasher.write(data);
+    }
+
+    hasher.finish()
+}
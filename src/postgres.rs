@@ -0,0 +1,311 @@
+//! `DataSink` which writes records into Postgres, or TimescaleDB, tables. Samples are
+//! bulk loaded with `COPY` in batches rather than inserted one at a time, all in one
+//! transaction with the record's row, so a record is either loaded in full or not at
+//! all.
+//!
+//! The tables are created if they don't already exist:
+//!
+//! - `comtrade_records` - one row per record, with an `id` the other tables refer to.
+//! - `comtrade_analog_channels` / `comtrade_status_channels` - channel metadata.
+//! - `comtrade_analog_samples` / `comtrade_status_samples` - one row per value, with the
+//!   absolute time of the sample. These can be turned into TimescaleDB hypertables on
+//!   the `time` column.
+
+use std::fmt::Write as _;
+use std::io::Write as _;
+
+use ::postgres::{Client, Error};
+use chrono::{Duration, NaiveDateTime};
+
+use crate::{Comtrade, DataSink, Scan};
+
+const CREATE_TABLES: &str = "
+CREATE TABLE IF NOT EXISTS comtrade_records (
+    id BIGSERIAL PRIMARY KEY,
+    station_name TEXT NOT NULL,
+    recording_device_id TEXT NOT NULL,
+    start_time TIMESTAMP NOT NULL,
+    trigger_time TIMESTAMP NOT NULL,
+    line_frequency DOUBLE PRECISION NOT NULL
+);
+CREATE TABLE IF NOT EXISTS comtrade_analog_channels (
+    record_id BIGINT NOT NULL REFERENCES comtrade_records (id),
+    channel_index INTEGER NOT NULL,
+    name TEXT NOT NULL,
+    phase TEXT NOT NULL,
+    circuit_component_being_monitored TEXT NOT NULL,
+    units TEXT NOT NULL,
+    PRIMARY KEY (record_id, channel_index)
+);
+CREATE TABLE IF NOT EXISTS comtrade_status_channels (
+    record_id BIGINT NOT NULL REFERENCES comtrade_records (id),
+    channel_index INTEGER NOT NULL,
+    name TEXT NOT NULL,
+    phase TEXT NOT NULL,
+    circuit_component_being_monitored TEXT NOT NULL,
+    normal_status_value SMALLINT NOT NULL,
+    PRIMARY KEY (record_id, channel_index)
+);
+CREATE TABLE IF NOT EXISTS comtrade_analog_samples (
+    record_id BIGINT NOT NULL,
+    channel_index INTEGER NOT NULL,
+    time TIMESTAMP NOT NULL,
+    value DOUBLE PRECISION NOT NULL
+);
+CREATE TABLE IF NOT EXISTS comtrade_status_samples (
+    record_id BIGINT NOT NULL,
+    channel_index INTEGER NOT NULL,
+    time TIMESTAMP NOT NULL,
+    value SMALLINT NOT NULL
+);
+";
+
+const TIMESTAMP_FORMAT: &str = "%Y-%m-%d %H:%M:%S%.f";
+
+const DEFAULT_BATCH_SIZE: usize = 1000;
+
+/// Sink which writes a single record into the database. Errors can't be returned from
+/// the `DataSink` methods, so the first one is kept and returned by `finish()`, with
+/// nothing more written after it.
+///
+/// The record is written in a transaction which is only committed by `finish()`. It's
+/// rolled back if there's an error, or if the sink is dropped without calling
+/// `finish()`, e.g. because parsing failed part way through the `.dat` file, so no
+/// rows are left behind for a partly loaded record. The transaction is started with
+/// `BEGIN` on the client, so the client mustn't be used for anything else until then.
+///
+/// ```no_run
+/// use comtrade::postgres::PostgresSink;
+/// use comtrade::ComtradeParserBuilder;
+///
+/// let mut client = postgres::Client::connect("host=localhost user=postgres", postgres::NoTls)?;
+/// let mut sink = PostgresSink::new(&mut client);
+/// ComtradeParserBuilder::new()
+///     .cfg_file(std::fs::File::open("record.cfg")?)
+///     .dat_file(std::fs::File::open("record.dat")?)
+///     .build()
+///     .parse_into(&mut sink)?;
+/// let record_id = sink.finish()?;
+/// # Ok::<(), Box<dyn std::error::Error>>(())
+/// ```
+pub struct PostgresSink<'c> {
+    client: &'c mut Client,
+    batch_size: usize,
+    record_id: Option<i64>,
+    in_transaction: bool,
+    start_time: NaiveDateTime,
+
+    // Channel indices from the `.cfg` file, which the sample rows refer to.
+    analog_indices: Vec<i32>,
+    status_indices: Vec<i32>,
+    analog_rows: String,
+    status_rows: String,
+    num_buffered_scans: usize,
+    error: Option<Error>,
+}
+
+impl<'c> PostgresSink<'c> {
+    pub fn new(client: &'c mut Client) -> Self {
+        PostgresSink {
+            client,
+            batch_size: DEFAULT_BATCH_SIZE,
+            record_id: None,
+            in_transaction: false,
+            start_time: NaiveDateTime::from_timestamp(0, 0),
+            analog_indices: vec![],
+            status_indices: vec![],
+            analog_rows: String::new(),
+            status_rows: String::new(),
+            num_buffered_scans: 0,
+            error: None,
+        }
+    }
+
+    /// Number of samples to buffer before copying them into the database.
+    pub fn batch_size(mut self, batch_size: usize) -> Self {
+        self.batch_size = batch_size.max(1);
+        self
+    }
+
+    /// Write out any buffered samples and commit the record, returning its ID in
+    /// `comtrade_records`, or `None` if the sink was never given a record. If there was
+    /// an error, nothing is committed.
+    pub fn finish(mut self) -> Result<Option<i64>, Error> {
+        if self.error.is_none() {
+            self.flush();
+        }
+        if self.error.is_none() && self.in_transaction {
+            self.in_transaction = false;
+            if let Err(error) = self.client.batch_execute("COMMIT") {
+                self.error = Some(error);
+            }
+        }
+        match self.error.take() {
+            Some(error) => Err(error),
+            None => Ok(self.record_id),
+        }
+    }
+
+    fn insert_record(&mut self, config: &Comtrade) -> Result<i64, Error> {
+        self.client.batch_execute(CREATE_TABLES)?;
+
+        self.client.batch_execute("BEGIN")?;
+        self.in_transaction = true;
+        let row = self.client.query_one(
+            "INSERT INTO comtrade_records \
+             (station_name, recording_device_id, start_time, trigger_time, line_frequency) \
+             VALUES ($1, $2, $3::TEXT::TIMESTAMP, $4::TEXT::TIMESTAMP, $5) RETURNING id",
+            &[
+                &config.config.station_name,
+                &config.config.recording_device_id,
+                &config
+                    .config
+                    .start_time
+                    .format(TIMESTAMP_FORMAT)
+                    .to_string(),
+                &config
+                    .config
+                    .trigger_time
+                    .format(TIMESTAMP_FORMAT)
+                    .to_string(),
+                &config.config.line_frequency,
+            ],
+        )?;
+        let record_id: i64 = row.get(0);
+
+        for channel in config.config.analog_channels.iter() {
+            self.client.execute(
+                "INSERT INTO comtrade_analog_channels \
+                 (record_id, channel_index, name, phase, circuit_component_being_monitored, units) \
+                 VALUES ($1, $2, $3, $4, $5, $6)",
+                &[
+                    &record_id,
+                    &(channel.index as i32),
+                    &channel.name,
+                    &channel.phase.as_str(),
+                    &channel.circuit_component_being_monitored,
+                    &channel.units,
+                ],
+            )?;
+        }
+        for channel in config.config.status_channels.iter() {
+            self.client.execute(
+                "INSERT INTO comtrade_status_channels \
+                 (record_id, channel_index, name, phase, circuit_component_being_monitored, \
+                 normal_status_value) VALUES ($1, $2, $3, $4, $5, $6)",
+                &[
+                    &record_id,
+                    &(channel.index as i32),
+                    &channel.name,
+                    &channel.phase.as_str(),
+                    &channel.circuit_component_being_monitored,
+                    &(channel.normal_status_value as i16),
+                ],
+            )?;
+        }
+
+        Ok(record_id)
+    }
+
+    fn flush(&mut self) {
+        if self.num_buffered_scans == 0 {
+            return;
+        }
+
+        let result = copy_rows(self.client, "comtrade_analog_samples", &self.analog_rows)
+            .and_then(|_| copy_rows(self.client, "comtrade_status_samples", &self.status_rows));
+        if let Err(error) = result {
+            self.error = Some(error);
+        }
+
+        self.analog_rows.clear();
+        self.status_rows.clear();
+        self.num_buffered_scans = 0;
+    }
+}
+
+impl Drop for PostgresSink<'_> {
+    fn drop(&mut self) {
+        if self.in_transaction {
+            let _ = self.client.batch_execute("ROLLBACK");
+        }
+    }
+}
+
+impl DataSink for PostgresSink<'_> {
+    fn on_config(&mut self, config: &Comtrade) {
+        if self.error.is_some() {
+            return;
+        }
+        match self.insert_record(config) {
+            Ok(record_id) => {
+                self.record_id = Some(record_id);
+                self.start_time = config.config.start_time;
+                self.analog_indices = config
+                    .config
+                    .analog_channels
+                    .iter()
+                    .map(|c| c.index as i32)
+                    .collect();
+                self.status_indices = config
+                    .config
+                    .status_channels
+                    .iter()
+                    .map(|c| c.index as i32)
+                    .collect();
+            }
+            Err(error) => self.error = Some(error),
+        }
+    }
+
+    fn on_scan(&mut self, scan: &Scan) {
+        let record_id = match self.record_id {
+            Some(record_id) if self.error.is_none() => record_id,
+            _ => return,
+        };
+
+        let time = self.start_time + Duration::nanoseconds(scan.timestamp);
+        let time = time.format(TIMESTAMP_FORMAT);
+
+        for (index, value) in self.analog_indices.iter().zip(scan.analog_values.iter()) {
+            let _ = writeln!(
+                self.analog_rows,
+                "{}\t{}\t{}\t{}",
+                record_id, index, time, value
+            );
+        }
+        for (index, value) in self.status_indices.iter().zip(scan.status_values.iter()) {
+            let _ = writeln!(
+                self.status_rows,
+                "{}\t{}\t{}\t{}",
+                record_id, index, time, value
+            );
+        }
+
+        self.num_buffered_scans += 1;
+        if self.num_buffered_scans >= self.batch_size {
+            self.flush();
+        }
+    }
+}
+
+/// Copy rows in Postgres' text format into the table.
+fn copy_rows(client: &mut Client, table: &str, rows: &str) -> Result<(), Error> {
+    if rows.is_empty() {
+        return Ok(());
+    }
+
+    let mut writer = client.copy_in(
+        format!(
+            "COPY {} (record_id, channel_index, time, value) FROM STDIN",
+            table
+        )
+        .as_str(),
+    )?;
+    // A failure writing the rows makes the copy fail when it's finished, with a more
+    // informative error than the I/O one.
+    let _ = writer.write_all(rows.as_bytes());
+    writer.finish()?;
+
+    Ok(())
+}
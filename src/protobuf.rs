@@ -0,0 +1,544 @@
+//! Records and scans as protobuf messages, so that services can pass parsed records to
+//! each other over gRPC without each coming up with its own mapping. The messages are
+//! described by the proto3 schema in `PROTO_SCHEMA`, which other services can compile
+//! with `protoc` as normal, and follow the JSON format of the `json` module: times are
+//! ISO 8601 strings, timestamps are in nanoseconds from the start time and the revision,
+//! data format and scaling mode are as they're written in the `.cfg` file.
+//!
+//! Missing analog values are NaN, which protobuf doubles hold as they are. The header,
+//! extra `.cfg` lines and extensions go along with the record, so that e.g. the
+//! channels' anti-aliasing filters are still known at the other end.
+
+use alloc::collections::BTreeMap;
+use alloc::format;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use core::fmt;
+use core::str::FromStr;
+
+use chrono::{FixedOffset, NaiveDateTime};
+use prost::Message;
+
+use crate::writer::{channel_name, channel_phase};
+use crate::{
+    AnalogChannel, AnalogScalingMode, Comtrade, ComtradeConfig, ComtradeData, DataFormat,
+    FormatRevision, Phase, SamplingRate, Scan, StatusChannel,
+};
+
+/// Proto3 schema of the messages in this module.
+pub const PROTO_SCHEMA: &str = r#"syntax = "proto3";
+
+package comtrade;
+
+message Record {
+  string station_name = 1;
+  string recording_device_id = 2;
+  // "1991", "1999" or "2013".
+  string revision = 3;
+  double line_frequency = 4;
+  // ISO 8601 without an offset, e.g. "2020-02-01T00:00:00.001".
+  string start_time = 5;
+  string trigger_time = 6;
+  optional sint32 time_offset_minutes = 7;
+  optional sint32 local_offset_minutes = 8;
+  // "ASCII", "BINARY", "BINARY32" or "FLOAT32".
+  string data_format = 9;
+  double timestamp_multiplication_factor = 10;
+  repeated SamplingRate sampling_rates = 11;
+  // Defaults to 1, 2, 3 and so on if empty.
+  repeated uint64 sample_numbers = 12;
+  // Nanoseconds from the start time.
+  repeated sint64 timestamps = 13;
+  repeated AnalogChannel analog_channels = 14;
+  repeated StatusChannel status_channels = 15;
+  string header = 16;
+  // Lines after the end of the standard .cfg file.
+  repeated string extra_cfg_lines = 17;
+  // From the .inf file and the extra columns of the .cfg file.
+  map<string, string> extensions = 18;
+}
+
+message SamplingRate {
+  double rate_hz = 1;
+  uint64 end_sample_number = 2;
+}
+
+message AnalogChannel {
+  // As in the .cfg file, with any padding.
+  string name = 1;
+  string phase = 2;
+  string circuit_component_being_monitored = 3;
+  string units = 4;
+  double multiplier = 5;
+  double offset_adder = 6;
+  double skew = 7;
+  // Default to the range of the values as they'd be in the .dat file.
+  optional double min_value = 8;
+  optional double max_value = 9;
+  double primary_factor = 10;
+  double secondary_factor = 11;
+  // "P" or "S".
+  string scaling_mode = 12;
+  // Missing values are NaN.
+  repeated double values = 13;
+  map<string, string> extensions = 14;
+}
+
+message StatusChannel {
+  // As in the .cfg file, with any padding.
+  string name = 1;
+  string phase = 2;
+  string circuit_component_being_monitored = 3;
+  uint32 normal_status_value = 4;
+  // One byte per sample, 0 or 1.
+  bytes values = 5;
+  map<string, string> extensions = 6;
+}
+
+message Scan {
+  uint64 sample_number = 1;
+  sint64 timestamp = 2;
+  optional uint32 raw_timestamp = 3;
+  repeated double analog_values = 4;
+  bytes status_values = 5;
+}
+"#;
+
+/// Why a protobuf record couldn't be read.
+#[derive(Debug)]
+pub enum ProtoRecordError {
+    /// The bytes aren't an encoded `Record` message.
+    Decode(prost::DecodeError),
+
+    /// The message doesn't make a valid record, e.g. a channel doesn't have a value for
+    /// every timestamp.
+    Invalid(String),
+}
+
+impl fmt::Display for ProtoRecordError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ProtoRecordError::Decode(err) => write!(f, "invalid protobuf record: {}", err),
+            ProtoRecordError::Invalid(message) => {
+                write!(f, "invalid protobuf record: {}", message)
+            }
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for ProtoRecordError {}
+
+/// `Record` message, see `PROTO_SCHEMA`.
+#[derive(Clone, PartialEq, Message)]
+pub struct ProtoRecord {
+    #[prost(string, tag = "1")]
+    pub station_name: String,
+    #[prost(string, tag = "2")]
+    pub recording_device_id: String,
+    #[prost(string, tag = "3")]
+    pub revision: String,
+    #[prost(double, tag = "4")]
+    pub line_frequency: f64,
+    #[prost(string, tag = "5")]
+    pub start_time: String,
+    #[prost(string, tag = "6")]
+    pub trigger_time: String,
+    #[prost(sint32, optional, tag = "7")]
+    pub time_offset_minutes: Option<i32>,
+    #[prost(sint32, optional, tag = "8")]
+    pub local_offset_minutes: Option<i32>,
+    #[prost(string, tag = "9")]
+    pub data_format: String,
+    #[prost(double, tag = "10")]
+    pub timestamp_multiplication_factor: f64,
+    #[prost(message, repeated, tag = "11")]
+    pub sampling_rates: Vec<ProtoSamplingRate>,
+    #[prost(uint64, repeated, tag = "12")]
+    pub sample_numbers: Vec<u64>,
+    #[prost(sint64, repeated, tag = "13")]
+    pub timestamps: Vec<i64>,
+    #[prost(message, repeated, tag = "14")]
+    pub analog_channels: Vec<ProtoAnalogChannel>,
+    #[prost(message, repeated, tag = "15")]
+    pub status_channels: Vec<ProtoStatusChannel>,
+    #[prost(string, tag = "16")]
+    pub header: String,
+    #[prost(string, repeated, tag = "17")]
+    pub extra_cfg_lines: Vec<String>,
+    #[prost(btree_map = "string, string", tag = "18")]
+    pub extensions: BTreeMap<String, String>,
+}
+
+/// `SamplingRate` message, see `PROTO_SCHEMA`.
+#[derive(Clone, PartialEq, Message)]
+pub struct ProtoSamplingRate {
+    #[prost(double, tag = "1")]
+    pub rate_hz: f64,
+    #[prost(uint64, tag = "2")]
+    pub end_sample_number: u64,
+}
+
+/// `AnalogChannel` message, see `PROTO_SCHEMA`.
+#[derive(Clone, PartialEq, Message)]
+pub struct ProtoAnalogChannel {
+    #[prost(string, tag = "1")]
+    pub name: String,
+    #[prost(string, tag = "2")]
+    pub phase: String,
+    #[prost(string, tag = "3")]
+    pub circuit_component_being_monitored: String,
+    #[prost(string, tag = "4")]
+    pub units: String,
+    #[prost(double, tag = "5")]
+    pub multiplier: f64,
+    #[prost(double, tag = "6")]
+    pub offset_adder: f64,
+    #[prost(double, tag = "7")]
+    pub skew: f64,
+    #[prost(double, optional, tag = "8")]
+    pub min_value: Option<f64>,
+    #[prost(double, optional, tag = "9")]
+    pub max_value: Option<f64>,
+    #[prost(double, tag = "10")]
+    pub primary_factor: f64,
+    #[prost(double, tag = "11")]
+    pub secondary_factor: f64,
+    #[prost(string, tag = "12")]
+    pub scaling_mode: String,
+    #[prost(double, repeated, tag = "13")]
+    pub values: Vec<f64>,
+    #[prost(btree_map = "string, string", tag = "14")]
+    pub extensions: BTreeMap<String, String>,
+}
+
+/// `StatusChannel` message, see `PROTO_SCHEMA`.
+#[derive(Clone, PartialEq, Message)]
+pub struct ProtoStatusChannel {
+    #[prost(string, tag = "1")]
+    pub name: String,
+    #[prost(string, tag = "2")]
+    pub phase: String,
+    #[prost(string, tag = "3")]
+    pub circuit_component_being_monitored: String,
+    #[prost(uint32, tag = "4")]
+    pub normal_status_value: u32,
+    #[prost(bytes = "vec", tag = "5")]
+    pub values: Vec<u8>,
+    #[prost(btree_map = "string, string", tag = "6")]
+    pub extensions: BTreeMap<String, String>,
+}
+
+/// `Scan` message, see `PROTO_SCHEMA`, for streaming samples one at a time as they're
+/// parsed, e.g. from a `DataSink` or the push parser.
+#[derive(Clone, PartialEq, Message)]
+pub struct ProtoScan {
+    #[prost(uint64, tag = "1")]
+    pub sample_number: u64,
+    #[prost(sint64, tag = "2")]
+    pub timestamp: i64,
+    #[prost(uint32, optional, tag = "3")]
+    pub raw_timestamp: Option<u32>,
+    #[prost(double, repeated, tag = "4")]
+    pub analog_values: Vec<f64>,
+    #[prost(bytes = "vec", tag = "5")]
+    pub status_values: Vec<u8>,
+}
+
+/// The record encoded as a `Record` message.
+pub fn to_protobuf(record: &Comtrade) -> Vec<u8> {
+    ProtoRecord::from(record).encode_to_vec()
+}
+
+/// Record from an encoded `Record` message, checking that it makes a valid record.
+pub fn from_protobuf(bytes: &[u8]) -> Result<Comtrade, ProtoRecordError> {
+    let record = ProtoRecord::decode(bytes).map_err(ProtoRecordError::Decode)?;
+    Comtrade::try_from(record)
+}
+
+impl From<&Comtrade> for ProtoRecord {
+    fn from(record: &Comtrade) -> Self {
+        ProtoRecord {
+            station_name: record.config.station_name.clone(),
+            recording_device_id: record.config.recording_device_id.clone(),
+            revision: match record.config.revision {
+                FormatRevision::Revision1991 => "1991",
+                FormatRevision::Revision1999 => "1999",
+                FormatRevision::Revision2013 => "2013",
+            }
+            .to_string(),
+            line_frequency: record.config.line_frequency,
+            start_time: format_time(record.config.start_time),
+            trigger_time: format_time(record.config.trigger_time),
+            time_offset_minutes: record.config.time_offset.map(offset_minutes),
+            local_offset_minutes: record.config.local_offset.map(offset_minutes),
+            data_format: match record.config.data_format {
+                DataFormat::Ascii => "ASCII",
+                DataFormat::Binary16 => "BINARY",
+                DataFormat::Binary32 => "BINARY32",
+                DataFormat::Float32 => "FLOAT32",
+            }
+            .to_string(),
+            timestamp_multiplication_factor: record.config.timestamp_multiplication_factor,
+            sampling_rates: record
+                .config
+                .sampling_rates
+                .iter()
+                .map(|rate| ProtoSamplingRate {
+                    rate_hz: rate.rate_hz,
+                    end_sample_number: rate.end_sample_number,
+                })
+                .collect(),
+            sample_numbers: record.data.sample_numbers.clone(),
+            timestamps: record.data.timestamps.clone(),
+            analog_channels: record
+                .analog_channels_with_data()
+                .map(|(channel, data)| ProtoAnalogChannel {
+                    name: channel_name(&channel.name, &channel.name_raw).to_string(),
+                    phase: channel_phase(&channel.phase, &channel.phase_raw).to_string(),
+                    circuit_component_being_monitored: channel
+                        .circuit_component_being_monitored
+                        .clone(),
+                    units: channel.units.clone(),
+                    multiplier: channel.multiplier,
+                    offset_adder: channel.offset_adder,
+                    skew: channel.skew,
+                    min_value: Some(channel.min_value),
+                    max_value: Some(channel.max_value),
+                    primary_factor: channel.primary_factor,
+                    secondary_factor: channel.secondary_factor,
+                    scaling_mode: match channel.scaling_mode {
+                        AnalogScalingMode::Primary => "P",
+                        AnalogScalingMode::Secondary => "S",
+                    }
+                    .to_string(),
+                    values: data.to_vec(),
+                    extensions: channel.extensions.clone(),
+                })
+                .collect(),
+            status_channels: record
+                .status_channels_with_data()
+                .map(|(channel, data)| ProtoStatusChannel {
+                    name: channel_name(&channel.name, &channel.name_raw).to_string(),
+                    phase: channel_phase(&channel.phase, &channel.phase_raw).to_string(),
+                    circuit_component_being_monitored: channel
+                        .circuit_component_being_monitored
+                        .clone(),
+                    normal_status_value: channel.normal_status_value as u32,
+                    values: data.to_vec(),
+                    extensions: channel.extensions.clone(),
+                })
+                .collect(),
+            header: record.config.header.clone(),
+            extra_cfg_lines: record.config.extra_cfg_lines.clone(),
+            extensions: record.config.extensions.clone(),
+        }
+    }
+}
+
+impl TryFrom<ProtoRecord> for Comtrade {
+    type Error = ProtoRecordError;
+
+    fn try_from(proto: ProtoRecord) -> Result<Self, Self::Error> {
+        let invalid = |message: String| ProtoRecordError::Invalid(message);
+        let num_samples = proto.timestamps.len();
+
+        let revision =
+            FormatRevision::from_str(&proto.revision).map_err(|err| invalid(err.to_string()))?;
+        let data_format =
+            DataFormat::from_str(&proto.data_format).map_err(|err| invalid(err.to_string()))?;
+        let start_time = parse_time(&proto.start_time)
+            .ok_or_else(|| invalid(format!("invalid start time: '{}'", proto.start_time)))?;
+        let trigger_time = parse_time(&proto.trigger_time)
+            .ok_or_else(|| invalid(format!("invalid trigger time: '{}'", proto.trigger_time)))?;
+        let time_offset = parse_offset(proto.time_offset_minutes)
+            .map_err(|minutes| invalid(format!("invalid time offset: {} minutes", minutes)))?;
+        let local_offset = parse_offset(proto.local_offset_minutes)
+            .map_err(|minutes| invalid(format!("invalid local offset: {} minutes", minutes)))?;
+        if proto.timestamp_multiplication_factor <= 0.0 {
+            return Err(invalid(
+                "timestamp multiplication factor must be positive".to_string(),
+            ));
+        }
+
+        let sample_numbers = match proto.sample_numbers {
+            sample_numbers if sample_numbers.is_empty() => (1..=num_samples as u64).collect(),
+            sample_numbers if sample_numbers.len() != num_samples => {
+                return Err(invalid(format!(
+                    "{} sample numbers given for {} timestamps",
+                    sample_numbers.len(),
+                    num_samples
+                )))
+            }
+            sample_numbers => sample_numbers,
+        };
+
+        let mut analog_channels = Vec::with_capacity(proto.analog_channels.len());
+        let mut analog_data = Vec::with_capacity(proto.analog_channels.len());
+        for (i, channel) in proto.analog_channels.into_iter().enumerate() {
+            if channel.values.len() != num_samples {
+                return Err(invalid(format!(
+                    "analog channel '{}' has {} values but there are {} timestamps",
+                    channel.name,
+                    channel.values.len(),
+                    num_samples
+                )));
+            }
+            let scaling_mode = AnalogScalingMode::from_str(&channel.scaling_mode)
+                .map_err(|err| invalid(err.to_string()))?;
+
+            // Range of the values as they'd be in the `.dat` file.
+            let raw = |value: f64| (value - channel.offset_adder) / channel.multiplier;
+            let (min, max) = channel
+                .values
+                .iter()
+                .filter(|v| !v.is_nan())
+                .fold((f64::INFINITY, f64::NEG_INFINITY), |(min, max), &v| {
+                    (min.min(raw(v)), max.max(raw(v)))
+                });
+            let (min, max) = if min > max { (0.0, 0.0) } else { (min, max) };
+
+            analog_channels.push(AnalogChannel {
+                index: i as u32 + 1,
+                name_raw: channel.name.clone(),
+                name: channel.name.trim().to_string(),
+                phase: Phase::from(channel.phase.as_str()),
+                phase_raw: channel.phase.clone(),
+                circuit_component_being_monitored: channel.circuit_component_being_monitored,
+                units: channel.units,
+                min_value: channel.min_value.unwrap_or(min),
+                max_value: channel.max_value.unwrap_or(max),
+                multiplier: channel.multiplier,
+                offset_adder: channel.offset_adder,
+                skew: channel.skew,
+                primary_factor: channel.primary_factor,
+                secondary_factor: channel.secondary_factor,
+                scaling_mode,
+                extensions: channel.extensions,
+            });
+            analog_data.push(channel.values);
+        }
+
+        let mut status_channels = Vec::with_capacity(proto.status_channels.len());
+        let mut status_data = Vec::with_capacity(proto.status_channels.len());
+        for (i, channel) in proto.status_channels.into_iter().enumerate() {
+            if channel.values.len() != num_samples {
+                return Err(invalid(format!(
+                    "status channel '{}' has {} values but there are {} timestamps",
+                    channel.name,
+                    channel.values.len(),
+                    num_samples
+                )));
+            }
+            if channel.normal_status_value > 1 || channel.values.iter().any(|v| *v > 1) {
+                return Err(invalid(format!(
+                    "status channel '{}' has values other than 0 and 1",
+                    channel.name
+                )));
+            }
+
+            status_channels.push(StatusChannel {
+                index: i as u32 + 1,
+                name_raw: channel.name.clone(),
+                name: channel.name.trim().to_string(),
+                phase: Phase::from(channel.phase.as_str()),
+                phase_raw: channel.phase.clone(),
+                circuit_component_being_monitored: channel.circuit_component_being_monitored,
+                normal_status_value: channel.normal_status_value as u8,
+                extensions: channel.extensions,
+            });
+            status_data.push(channel.values);
+        }
+
+        let sampling_rates: Vec<SamplingRate> = proto
+            .sampling_rates
+            .iter()
+            .map(|rate| SamplingRate {
+                rate_hz: rate.rate_hz,
+                end_sample_number: rate.end_sample_number,
+            })
+            .collect();
+        let total_num_samples = sampling_rates
+            .last()
+            .map_or(num_samples as u64, |rate| rate.end_sample_number);
+
+        let mut config = ComtradeConfig {
+            station_name: proto.station_name,
+            recording_device_id: proto.recording_device_id,
+            revision,
+            num_total_channels: (analog_channels.len() + status_channels.len()) as u32,
+            num_analog_channels: analog_channels.len() as u32,
+            num_status_channels: status_channels.len() as u32,
+            analog_channels,
+            status_channels,
+            line_frequency: proto.line_frequency,
+            sampling_rates,
+            total_num_samples,
+            start_time,
+            trigger_time,
+            data_format,
+            timestamp_multiplication_factor: proto.timestamp_multiplication_factor,
+            time_offset,
+            local_offset,
+            extra_cfg_lines: proto.extra_cfg_lines,
+            header: proto.header,
+            extensions: proto.extensions,
+            ..Default::default()
+        };
+        config.timestamp_unit = config.implied_timestamp_unit();
+        let data = ComtradeData {
+            sample_numbers,
+            timestamps: proto.timestamps,
+            analog_data,
+            status_data,
+            ..Default::default()
+        };
+        Ok(Comtrade::from_parts(config, data))
+    }
+}
+
+impl From<&Scan> for ProtoScan {
+    fn from(scan: &Scan) -> Self {
+        ProtoScan {
+            sample_number: scan.sample_number,
+            timestamp: scan.timestamp,
+            raw_timestamp: scan.raw_timestamp,
+            analog_values: scan.analog_values.clone(),
+            status_values: scan.status_values.clone(),
+        }
+    }
+}
+
+impl From<ProtoScan> for Scan {
+    fn from(scan: ProtoScan) -> Self {
+        Scan {
+            sample_number: scan.sample_number,
+            timestamp: scan.timestamp,
+            raw_timestamp: scan.raw_timestamp,
+            analog_values: scan.analog_values,
+            status_values: scan.status_values,
+        }
+    }
+}
+
+fn format_time(time: NaiveDateTime) -> String {
+    time.format("%Y-%m-%dT%H:%M:%S%.f").to_string()
+}
+
+fn parse_time(value: &str) -> Option<NaiveDateTime> {
+    NaiveDateTime::parse_from_str(value, "%Y-%m-%dT%H:%M:%S%.f").ok()
+}
+
+fn offset_minutes(offset: FixedOffset) -> i32 {
+    offset.local_minus_utc() / 60
+}
+
+/// Offset of `minutes` from UTC, or the minutes back if they're out of range.
+fn parse_offset(minutes: Option<i32>) -> Result<Option<FixedOffset>, i32> {
+    match minutes {
+        Some(minutes) => FixedOffset::east_opt(minutes.saturating_mul(60))
+            .map(Some)
+            .ok_or(minutes),
+        None => Ok(None),
+    }
+}
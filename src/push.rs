@@ -0,0 +1,308 @@
+//! Incremental parsing of combined `.cff` files, for when the contents arrive in chunks
+//! (e.g. over a network connection) and the caller wants to handle samples as they are
+//! decoded rather than waiting for the whole record.
+//!
+//! The parser does no I/O itself - bytes are handed to `PushParser::feed()` in whatever
+//! sized chunks they arrive in, and parsed results are returned as `Event`s.
+
+use alloc::boxed::Box;
+use alloc::format;
+use alloc::string::{String, ToString};
+use alloc::vec;
+use alloc::vec::Vec;
+use core::str::FromStr;
+
+use crate::parser::{
+    is_ascii_scan_line, parse_cff_header, parse_data_format, ParseError, ParseLimits, ParseResult,
+    Scan,
+};
+use crate::{Comtrade, ComtradeParser, DataFormat, FileType, Quirks, VendorProfile};
+
+/// Something parsed from the stream fed into a `PushParser`.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Event {
+    /// The configuration section has been parsed. The record contains everything from
+    /// the `.cfg` file but no samples - these follow as `Scan` events.
+    Config(Box<Comtrade>),
+
+    /// A single sample from the data section.
+    Scan(Scan),
+
+    /// Contents of the header section.
+    Header(String),
+
+    /// Contents of the information section.
+    Info(String),
+
+    /// Content after the data section which isn't part of any section, e.g. a vendor
+    /// blob appended by the exporter.
+    Trailing(String),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Section {
+    /// Before the first section header, or in a section of unknown type which is being
+    /// skipped.
+    None,
+    Cfg,
+    AsciiDat,
+
+    /// Binary data section, with the number of bytes of it still to come.
+    BinaryDat(usize),
+    Hdr,
+    Inf,
+
+    /// After the data section, in content which isn't part of any section.
+    Trailing,
+}
+
+/// Parser for a combined `.cff` file which is fed the file contents as they become
+/// available.
+///
+/// ```no_run
+/// use comtrade::push::{Event, PushParser};
+///
+/// # fn handle(_: Event) {}
+/// # let chunks: Vec<&[u8]> = vec![];
+/// let mut parser = PushParser::new();
+/// for chunk in chunks {
+///     for event in parser.feed(chunk)? {
+///         handle(event);
+///     }
+/// }
+/// for event in parser.finish()? {
+///     handle(event);
+/// }
+/// # Ok::<(), comtrade::ParseError>(())
+/// ```
+pub struct PushParser {
+    parser: ComtradeParser<'static>,
+    strict: bool,
+    quirks: Quirks,
+    section: Section,
+    buffer: Vec<u8>,
+    section_lines: Vec<String>,
+    is_cfg_parsed: bool,
+    num_dat_lines: usize,
+}
+
+impl Default for PushParser {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl PushParser {
+    pub fn new() -> Self {
+        Self {
+            parser: ComtradeParser::new(None, None, None, None, None),
+            strict: false,
+            quirks: Quirks::default(),
+            section: Section::None,
+            buffer: vec![],
+            section_lines: vec![],
+            is_cfg_parsed: false,
+            num_dat_lines: 0,
+        }
+    }
+
+    /// See `ComtradeParserBuilder::strict()`.
+    pub fn strict(mut self, strict: bool) -> Self {
+        self.strict = strict;
+        self.parser = self.parser.strict(strict);
+        self
+    }
+
+    /// See `ComtradeParserBuilder::vendor_profile()`.
+    pub fn vendor_profile(mut self, profile: VendorProfile) -> Self {
+        self.quirks = profile.quirks();
+        self.parser = self.parser.vendor_profile(profile);
+        self
+    }
+
+    /// See `ComtradeParserBuilder::limits()`.
+    pub fn limits(mut self, limits: ParseLimits) -> Self {
+        self.parser = self.parser.limits(limits);
+        self
+    }
+
+    /// Feed the next chunk of the file into the parser, returning any events that can
+    /// be produced with the data received so far.
+    pub fn feed(&mut self, bytes: &[u8]) -> ParseResult<Vec<Event>> {
+        self.buffer.extend_from_slice(bytes);
+
+        let mut events = vec![];
+        let mut consumed = 0;
+
+        loop {
+            let remaining = &self.buffer[consumed..];
+
+            if let Section::BinaryDat(bytes_left) = self.section {
+                let sample_size = self.parser.binary_sample_size()?;
+                if bytes_left < sample_size {
+                    // Whatever's left can't be a whole sample, so must be padding.
+                    if remaining.len() < bytes_left {
+                        break;
+                    }
+                    consumed += bytes_left;
+                    self.section = Section::Trailing;
+                    continue;
+                }
+                if remaining.len() < sample_size {
+                    break;
+                }
+
+                let scan = self.parser.parse_binary_scan(&remaining[..sample_size])?;
+                events.push(Event::Scan(scan));
+                consumed += sample_size;
+                self.section = Section::BinaryDat(bytes_left - sample_size);
+                continue;
+            }
+
+            let line_end = match remaining.iter().position(|b| *b == b'\n') {
+                Some(idx) => idx,
+                None => break,
+            };
+            let line = decode_line(&remaining[..line_end])?;
+            consumed += line_end + 1;
+
+            self.handle_line(line, &mut events)?;
+        }
+
+        self.buffer.drain(..consumed);
+
+        Ok(events)
+    }
+
+    /// Signal that the end of the file has been reached, returning the events for any
+    /// data the parser was holding on to.
+    pub fn finish(mut self) -> ParseResult<Vec<Event>> {
+        let mut events = vec![];
+
+        if let Section::BinaryDat(bytes_left) = self.section {
+            if bytes_left >= self.parser.binary_sample_size()? {
+                return Err(ParseError::new(
+                    "unexpected end of binary data section in .cff file".to_string(),
+                ));
+            }
+        } else if !self.buffer.is_empty() {
+            let line = decode_line(&self.buffer)?;
+            self.buffer.clear();
+            self.handle_line(line, &mut events)?;
+        }
+
+        self.end_section(&mut events)?;
+
+        if !self.is_cfg_parsed {
+            return Err(ParseError::new(
+                "no configuration section found in .cff file".to_string(),
+            ));
+        }
+
+        Ok(events)
+    }
+
+    fn handle_line(&mut self, line: String, events: &mut Vec<Event>) -> ParseResult<()> {
+        if let Some(header) = parse_cff_header(line.as_str()) {
+            self.end_section(events)?;
+
+            let file_type = match FileType::from_str(header.file_type) {
+                Ok(file_type) => Some(file_type),
+                Err(_) if !self.strict => None,
+                Err(err) => return Err(err),
+            };
+
+            // The data format is normally given in the header, but otherwise must be
+            // the one from the configuration section.
+            let data_format = match header.data_format {
+                Some(token) => Some(parse_data_format(token, &self.quirks)?),
+                None => self.parser.data_format().cloned(),
+            };
+
+            self.section = match file_type {
+                Some(FileType::Cfg) => Section::Cfg,
+                Some(FileType::Dat) => {
+                    if !self.is_cfg_parsed {
+                        return Err(ParseError::new(
+                            "data section before configuration section in .cff file".to_string(),
+                        ));
+                    }
+                    match data_format {
+                        Some(DataFormat::Ascii) | None => Section::AsciiDat,
+                        Some(_) => {
+                            let size = header.data_size.ok_or_else(|| {
+                                ParseError::new(
+                                    "binary data section in .cff file must give its size"
+                                        .to_string(),
+                                )
+                            })?;
+                            let size = size.parse::<usize>().map_err(|_| {
+                                ParseError::new(format!("unable to parse .dat size: '{}'", size))
+                            })?;
+                            Section::BinaryDat(size)
+                        }
+                    }
+                }
+                Some(FileType::Hdr) => Section::Hdr,
+                Some(FileType::Inf) => Section::Inf,
+                None => Section::None,
+            };
+
+            return Ok(());
+        }
+
+        match self.section {
+            Section::AsciiDat if !is_ascii_scan_line(&line) => {
+                if self.strict {
+                    return Err(ParseError::new(
+                        "unexpected content after data in .cff file".to_string(),
+                    ));
+                }
+                self.section = Section::Trailing;
+                self.section_lines.push(line);
+            }
+            Section::AsciiDat => {
+                if !line.is_empty() {
+                    let scan = self.parser.parse_ascii_scan(&line, self.num_dat_lines)?;
+                    self.num_dat_lines += 1;
+                    events.push(Event::Scan(scan));
+                }
+            }
+            Section::Cfg | Section::Hdr | Section::Inf | Section::Trailing => {
+                self.section_lines.push(line)
+            }
+            Section::None => {}
+            Section::BinaryDat(_) => unreachable!("binary data is not read line by line"),
+        }
+
+        Ok(())
+    }
+
+    fn end_section(&mut self, events: &mut Vec<Event>) -> ParseResult<()> {
+        let contents = self.section_lines.join("\n");
+        self.section_lines.clear();
+
+        match self.section {
+            Section::Cfg => {
+                let record = self.parser.parse_cfg_contents(contents)?;
+                self.is_cfg_parsed = true;
+                events.push(Event::Config(Box::new(record)));
+            }
+            Section::Hdr => events.push(Event::Header(contents)),
+            Section::Inf => events.push(Event::Info(contents)),
+            Section::Trailing if !contents.trim().is_empty() => {
+                events.push(Event::Trailing(contents.trim_end().to_string()))
+            }
+            _ => {}
+        }
+        self.section = Section::None;
+
+        Ok(())
+    }
+}
+
+fn decode_line(bytes: &[u8]) -> ParseResult<String> {
+    core::str::from_utf8(bytes)
+        .map(|line| line.trim().to_string())
+        .map_err(|_| ParseError::new("invalid UTF-8 in text section of .cff file".to_string()))
+}
@@ -0,0 +1,66 @@
+//! Quality flags of analog values, for recorders which give the quality of each sample
+//! in status channels of their own, e.g. recorders taking sampled values from merging
+//! units, which come with IEC 61850 quality bits.
+
+use alloc::vec::Vec;
+
+use crate::status::StatusGroup;
+use crate::Comtrade;
+
+/// Status channels holding the quality flags of an analog channel, as a number with
+/// one bit per status channel.
+#[derive(Debug, Clone, PartialEq)]
+pub struct QualityMapping {
+    /// 0-based index into `ComtradeConfig::analog_channels` of the channel the flags are for.
+    pub analog_channel: usize,
+
+    /// 0-based indices into `ComtradeConfig::status_channels` of the channels holding each
+    /// flag, least significant bit first. At most 32 channels can be mapped.
+    pub status_channels: Vec<usize>,
+}
+
+impl QualityMapping {
+    pub fn new(analog_channel: usize, status_channels: Vec<usize>) -> Self {
+        QualityMapping {
+            analog_channel,
+            status_channels,
+        }
+    }
+}
+
+impl Comtrade {
+    /// Quality flags of the value at the 0-based `index` of the analog channel at the
+    /// 0-based `channel_idx`, or `None` if the channel has no quality flags or there's
+    /// no such value. The flags are set by `Comtrade::apply_quality_mapping()`, or
+    /// `ComtradeParser::quality_mapping()` when parsing.
+    pub fn quality(&self, channel_idx: usize, index: usize) -> Option<u32> {
+        self.data
+            .analog_quality
+            .get(channel_idx)?
+            .get(index)
+            .copied()
+    }
+
+    /// Decode the quality flags of an analog channel from the status channels given by
+    /// `mapping` into `ComtradeData::analog_quality`. Returns `false`, leaving the
+    /// record as it was, if any of the channels aren't in the record or there are too
+    /// many status channels to fit the flags in a `u32`.
+    pub fn apply_quality_mapping(&mut self, mapping: &QualityMapping) -> bool {
+        let n_channels = self.config.analog_channels.len();
+        if mapping.analog_channel >= n_channels {
+            return false;
+        }
+        let group = StatusGroup::new("", mapping.status_channels.clone());
+        match group.decode(self) {
+            Some(flags) => {
+                let quality = &mut self.data.analog_quality;
+                if quality.len() < n_channels {
+                    quality.resize(n_channels, Vec::new());
+                }
+                quality[mapping.analog_channel] = flags;
+                true
+            }
+            None => false,
+        }
+    }
+}
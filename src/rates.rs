@@ -0,0 +1,109 @@
+//! Inferring the sampling rate actually used from the timestamps in the `.dat` file, for
+//! recorders whose `.cfg` files give the wrong rate.
+
+use alloc::vec::Vec;
+use core::ops::Range;
+
+use crate::Comtrade;
+
+/// Sampling rate worked out from the timestamps of the samples declared to use one of
+/// the record's sampling rates, from `Comtrade::infer_sampling_rates()`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct InferredSamplingRate {
+    /// Index into `ComtradeConfig::sampling_rates` of the declared rate.
+    pub rate_index: usize,
+    pub declared_hz: f64,
+
+    /// Rate given by a least-squares fit of a straight line to the timestamps of the
+    /// samples, or `None` if fewer than two of them have timestamps or they don't go
+    /// forwards.
+    pub inferred_hz: Option<f64>,
+
+    /// 0-based indices of the samples declared to use the rate.
+    pub samples: Range<usize>,
+}
+
+impl InferredSamplingRate {
+    /// Difference between the inferred and declared rates, as a fraction of the
+    /// declared rate.
+    pub fn relative_error(&self) -> Option<f64> {
+        let inferred = self.inferred_hz?;
+        if self.declared_hz <= 0.0 {
+            return None;
+        }
+        Some((inferred - self.declared_hz).abs() / self.declared_hz)
+    }
+
+    /// Whether the inferred rate differs from the declared rate by more than
+    /// `tolerance`, as a fraction of the declared rate.
+    pub fn is_discrepancy(&self, tolerance: f64) -> bool {
+        self.relative_error().is_some_and(|error| error > tolerance)
+    }
+}
+
+impl Comtrade {
+    /// Sampling rate each run of samples was actually recorded at, going by the
+    /// timestamps in the `.dat` file rather than the rates in the `.cfg` file, which
+    /// some recorders get wrong. The timestamps are fitted by least squares so that
+    /// jitter in individual timestamps doesn't throw the rate off.
+    ///
+    /// The record doesn't say what unit the timestamps were in, so it's worked out from
+    /// the start and trigger times as `ComtradeConfig::from_record()` does. The parser
+    /// can check the rates as it goes with `ComtradeParser::infer_sampling_rates()`,
+    /// which knows the unit for sure.
+    pub fn infer_sampling_rates(&self) -> Vec<InferredSamplingRate> {
+        infer_sampling_rates(self, self.config.timestamp_unit)
+    }
+}
+
+/// See `Comtrade::infer_sampling_rates()`. `timestamp_unit` is the nanoseconds per unit
+/// of the raw timestamps.
+pub(crate) fn infer_sampling_rates(
+    record: &Comtrade,
+    timestamp_unit: i64,
+) -> Vec<InferredSamplingRate> {
+    let nanoseconds_per_unit =
+        timestamp_unit as f64 * record.config.timestamp_multiplication_factor;
+
+    record
+        .sampling_segments()
+        .into_iter()
+        .map(|segment| {
+            let points = segment.samples.clone().filter_map(|i| {
+                let raw = record.data.raw_timestamps.get(i).copied().flatten()?;
+                Some((i as f64, raw as f64 * nanoseconds_per_unit))
+            });
+            InferredSamplingRate {
+                rate_index: segment.rate_index,
+                declared_hz: segment.rate_hz,
+                inferred_hz: least_squares_slope(points)
+                    .filter(|slope| *slope > 0.0)
+                    .map(|nanoseconds_per_sample| 1e9 / nanoseconds_per_sample),
+                samples: segment.samples,
+            }
+        })
+        .collect()
+}
+
+/// Slope of the least-squares line through the points, or `None` if there are fewer
+/// than two distinct x values.
+fn least_squares_slope(points: impl Iterator<Item = (f64, f64)> + Clone) -> Option<f64> {
+    let (n, sum_x, sum_y) = points.clone().fold((0.0, 0.0, 0.0), |(n, sx, sy), (x, y)| {
+        (n + 1.0, sx + x, sy + y)
+    });
+    if n < 2.0 {
+        return None;
+    }
+    let (mean_x, mean_y) = (sum_x / n, sum_y / n);
+
+    let (covariance, variance) = points.fold((0.0, 0.0), |(c, v), (x, y)| {
+        (
+            c + (x - mean_x) * (y - mean_y),
+            v + (x - mean_x) * (x - mean_x),
+        )
+    });
+    if variance == 0.0 {
+        return None;
+    }
+    Some(covariance / variance)
+}
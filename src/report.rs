@@ -0,0 +1,202 @@
+//! Human-readable summaries of COMTRADE records, e.g. for fault reports.
+
+use std::fmt::Write;
+
+use chrono::NaiveDateTime;
+
+use crate::{AnalogChannel, Comtrade};
+
+// Post-trigger peak current must be at least this many times the pre-trigger peak for
+// the phase to be considered faulted.
+const FAULT_CURRENT_RATIO: f64 = 2.0;
+
+/// Peak current seen in a single current channel.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PeakCurrent {
+    pub channel_name: String,
+    pub units: String,
+    pub peak: f64,
+
+    /// Time of the peak in seconds relative to the start of the record.
+    pub time: f64,
+}
+
+/// Change in state of a status channel, e.g. a breaker opening or a protection element
+/// operating.
+#[derive(Debug, Clone, PartialEq)]
+pub struct StatusChange {
+    pub channel_name: String,
+
+    /// Index of the sample at which the new value first appears.
+    pub sample_index: usize,
+
+    /// Time of the change in seconds relative to the start of the record.
+    pub time: f64,
+    pub new_value: u8,
+}
+
+/// Key information about a record - the contents of a typical one-page fault summary.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RecordSummary {
+    pub station_name: String,
+    pub recording_device_id: String,
+    pub start_time: NaiveDateTime,
+    pub trigger_time: NaiveDateTime,
+    pub duration: f64,
+
+    /// Phases whose current rises significantly after the trigger point.
+    pub faulted_phases: Vec<String>,
+    pub max_currents: Vec<PeakCurrent>,
+    pub status_changes: Vec<StatusChange>,
+}
+
+impl RecordSummary {
+    pub fn new(record: &Comtrade) -> Self {
+        let trigger_idx = trigger_sample_index(record);
+
+        let current_channels: Vec<(&AnalogChannel, &[f64])> = record
+            .analog_channels_with_data()
+            .filter(|(c, _)| is_current_channel(c))
+            .collect();
+
+        let max_currents = current_channels
+            .iter()
+            .filter_map(|(c, data)| {
+                let (idx, peak) = peak_abs(data)?;
+                Some(PeakCurrent {
+                    channel_name: c.name.clone(),
+                    units: c.units.trim().to_string(),
+                    peak,
+                    time: record.timestamp_seconds(idx).unwrap_or_default(),
+                })
+            })
+            .collect();
+
+        let mut faulted_phases: Vec<String> = vec![];
+        for (c, data) in current_channels.iter() {
+            if trigger_idx == 0 || trigger_idx >= data.len() {
+                continue;
+            }
+            let pre_fault_peak = peak_abs(&data[..trigger_idx]).map(|(_, p)| p);
+            let post_fault_peak = peak_abs(&data[trigger_idx..]).map(|(_, p)| p);
+            if let (Some(pre), Some(post)) = (pre_fault_peak, post_fault_peak) {
+                let phase = channel_phase_label(c);
+                if post > pre * FAULT_CURRENT_RATIO && !faulted_phases.contains(&phase) {
+                    faulted_phases.push(phase);
+                }
+            }
+        }
+
+        let mut status_changes = vec![];
+        for (c, data) in record.status_channels_with_data() {
+            for (idx, pair) in data.windows(2).enumerate() {
+                if pair[0] != pair[1] {
+                    status_changes.push(StatusChange {
+                        channel_name: c.name.clone(),
+                        sample_index: idx + 1,
+                        time: record.timestamp_seconds(idx + 1).unwrap_or_default(),
+                        new_value: pair[1],
+                    });
+                }
+            }
+        }
+        status_changes.sort_by_key(|c| c.sample_index);
+
+        RecordSummary {
+            station_name: record.config.station_name.trim().to_string(),
+            recording_device_id: record.config.recording_device_id.trim().to_string(),
+            start_time: record.config.start_time,
+            trigger_time: record.config.trigger_time,
+            duration: record.duration(),
+            faulted_phases,
+            max_currents,
+            status_changes,
+        }
+    }
+}
+
+/// Generate a plain text summary of the record.
+pub fn summary(record: &Comtrade) -> String {
+    let summary = RecordSummary::new(record);
+    let mut out = String::new();
+
+    // Writing to a `String` can't fail, so the results are ignored throughout.
+    let _ = writeln!(out, "Station:         {}", summary.station_name);
+    let _ = writeln!(out, "Device:          {}", summary.recording_device_id);
+    let _ = writeln!(out, "Start time:      {}", summary.start_time);
+    let _ = writeln!(out, "Trigger time:    {}", summary.trigger_time);
+    let _ = writeln!(out, "Duration:        {:.6} s", summary.duration);
+
+    let faulted_phases = if summary.faulted_phases.is_empty() {
+        "none detected".to_string()
+    } else {
+        summary.faulted_phases.join(", ")
+    };
+    let _ = writeln!(out, "Faulted phases:  {}", faulted_phases);
+
+    let _ = writeln!(out, "\nMaximum currents:");
+    if summary.max_currents.is_empty() {
+        let _ = writeln!(out, "  none");
+    }
+    for c in summary.max_currents.iter() {
+        let _ = writeln!(
+            out,
+            "  {}: {:.3} {} at {:.6} s",
+            c.channel_name, c.peak, c.units, c.time
+        );
+    }
+
+    let _ = writeln!(out, "\nStatus changes:");
+    if summary.status_changes.is_empty() {
+        let _ = writeln!(out, "  none");
+    }
+    for c in summary.status_changes.iter() {
+        let _ = writeln!(
+            out,
+            "  {}: {} -> {} at {:.6} s",
+            c.channel_name,
+            1 - c.new_value.min(1),
+            c.new_value,
+            c.time
+        );
+    }
+
+    out
+}
+
+fn trigger_sample_index(record: &Comtrade) -> usize {
+    let trigger_offset = record.pre_fault_duration();
+    let first = record.data.timestamps.first().cloned().unwrap_or_default();
+    record
+        .data
+        .timestamps
+        .iter()
+        .position(|t| (t - first) as f64 / 1e9 >= trigger_offset)
+        .unwrap_or(record.data.timestamps.len())
+}
+
+fn is_current_channel(channel: &AnalogChannel) -> bool {
+    matches!(
+        channel.units.trim().to_lowercase().as_str(),
+        "a" | "ka" | "ma"
+    )
+}
+
+fn channel_phase_label(channel: &AnalogChannel) -> String {
+    let phase = channel.phase.as_str().trim();
+    if phase.is_empty() {
+        channel.name.clone()
+    } else {
+        phase.to_string()
+    }
+}
+
+fn peak_abs(data: &[f64]) -> Option<(usize, f64)> {
+    data.iter()
+        .map(|v| v.abs())
+        .enumerate()
+        .fold(None, |peak, (idx, v)| match peak {
+            Some((_, p)) if p >= v => peak,
+            _ => Some((idx, v)),
+        })
+}
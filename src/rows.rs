@@ -0,0 +1,60 @@
+//! Iterating over a record a sample at a time, as rows timestamped with the wall-clock
+//! time, which is the shape most time series databases expect to ingest.
+
+use alloc::vec::Vec;
+
+use chrono::{DateTime, FixedOffset, TimeZone};
+
+use crate::Comtrade;
+
+/// Iterator over the analog values of a record a sample at a time, from
+/// `Comtrade::analog_rows()`.
+#[derive(Debug, Clone)]
+pub struct AnalogRows<'a> {
+    record: &'a Comtrade,
+    offset: FixedOffset,
+    index: usize,
+}
+
+impl Comtrade {
+    /// Absolute time of each sample, as `absolute_time()` gives it, along with the value
+    /// of every analog channel at that sample, in channel order.
+    ///
+    /// The times are in the record's time code, i.e. `time_offset`, or UTC if it
+    /// doesn't have one.
+    pub fn analog_rows(&self) -> AnalogRows<'_> {
+        AnalogRows {
+            record: self,
+            offset: self
+                .config
+                .time_offset
+                .unwrap_or_else(|| FixedOffset::east(0)),
+            index: 0,
+        }
+    }
+}
+
+impl Iterator for AnalogRows<'_> {
+    type Item = (DateTime<FixedOffset>, Vec<f64>);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let time = self.record.absolute_time(self.index)?;
+        let time = self.offset.from_local_datetime(&time).single()?;
+        let values = self
+            .record
+            .data
+            .analog_data
+            .iter()
+            .map(|data| data.get(self.index).copied().unwrap_or(f64::NAN))
+            .collect();
+        self.index += 1;
+        Some((time, values))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let remaining = self.record.data.timestamps.len().saturating_sub(self.index);
+        (remaining, Some(remaining))
+    }
+}
+
+impl ExactSizeIterator for AnalogRows<'_> {}
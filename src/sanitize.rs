@@ -0,0 +1,125 @@
+//! Removal of identifying information from records, e.g. before sharing fault records
+//! outside the utility they came from. The waveform data is left as it is.
+
+use alloc::format;
+use alloc::string::String;
+
+use crate::fingerprint::Fnv1a;
+use crate::Comtrade;
+
+/// What to do with a piece of identifying information.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum SanitizeAction {
+    /// Leave it as it is.
+    Keep,
+
+    /// Replace it with an empty string.
+    #[default]
+    Clear,
+
+    /// Replace it with a hash of the original value, so that records from the same
+    /// station or device can still be matched up with each other. The hash is not
+    /// cryptographic - it only stops the names being read at a glance.
+    Hash,
+}
+
+/// Which identifying information to remove from a record, see `Comtrade::sanitize()`.
+/// By default everything is cleared. Channel names are always kept.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct SanitizeOptions {
+    pub station_name: SanitizeAction,
+    pub recording_device_id: SanitizeAction,
+
+    /// Circuit component being monitored for each of the analog and status channels.
+    pub circuit_components: SanitizeAction,
+
+    /// Contents of the `.hdr` file.
+    pub header: SanitizeAction,
+
+    /// Vendor-specific lines after the standard fields of the `.cfg` file, which can
+    /// name the station or device too. Cleared lines are removed rather than left
+    /// blank.
+    pub extra_cfg_lines: SanitizeAction,
+
+    /// Values in the extensions of the record and its channels, i.e. everything read
+    /// from the `.inf` file and the extra columns of the `.cfg` file's channel lines.
+    /// The keys are kept so that it's clear what was there.
+    pub extensions: SanitizeAction,
+
+    /// Mixed in with every hashed value, so that the original values can't be found by
+    /// hashing likely candidates without knowing the salt.
+    pub salt: String,
+}
+
+impl Comtrade {
+    /// Remove identifying information from the record as set out in `options`. Any
+    /// files the record was parsed from are dropped, as they'd give the original values
+    /// away, unless everything is kept.
+    pub fn sanitize(&mut self, options: SanitizeOptions) {
+        let apply = |action: SanitizeAction, value: &mut String| match action {
+            SanitizeAction::Keep => {}
+            SanitizeAction::Clear => value.clear(),
+            SanitizeAction::Hash if value.is_empty() => {}
+            SanitizeAction::Hash => *value = hash(&options.salt, value),
+        };
+
+        apply(options.station_name, &mut self.config.station_name);
+        apply(
+            options.recording_device_id,
+            &mut self.config.recording_device_id,
+        );
+        apply(options.header, &mut self.config.header);
+        if options.extra_cfg_lines == SanitizeAction::Clear {
+            self.config.extra_cfg_lines.clear();
+        }
+        self.config
+            .extra_cfg_lines
+            .iter_mut()
+            .for_each(|line| apply(options.extra_cfg_lines, line));
+
+        for channel in self.config.analog_channels.iter_mut() {
+            apply(
+                options.circuit_components,
+                &mut channel.circuit_component_being_monitored,
+            );
+            channel
+                .extensions
+                .values_mut()
+                .for_each(|v| apply(options.extensions, v));
+        }
+        for channel in self.config.status_channels.iter_mut() {
+            apply(
+                options.circuit_components,
+                &mut channel.circuit_component_being_monitored,
+            );
+            channel
+                .extensions
+                .values_mut()
+                .for_each(|v| apply(options.extensions, v));
+        }
+        self.config
+            .extensions
+            .values_mut()
+            .for_each(|v| apply(options.extensions, v));
+
+        let actions = [
+            options.station_name,
+            options.recording_device_id,
+            options.circuit_components,
+            options.header,
+            options.extra_cfg_lines,
+            options.extensions,
+        ];
+        if actions.iter().any(|action| *action != SanitizeAction::Keep) {
+            self.data.source_files = None;
+        }
+    }
+}
+
+/// 64-bit FNV-1a hash of the salt and value, as hex.
+fn hash(salt: &str, value: &str) -> String {
+    let mut hasher = Fnv1a::new();
+    hasher.write(salt.as_bytes());
+    hasher.write(value.as_bytes());
+    format!("{:016x}", hasher.finish())
+}
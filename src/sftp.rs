@@ -0,0 +1,286 @@
+//! `RecordSource` which fetches records from a directory on an SFTP server, which is
+//! how most disturbance recorders make their records available. This needs to be run
+//! on a Tokio runtime.
+//!
+//! The server's host key is checked against the `known_hosts` file, so the server needs
+//! to have been added to it (e.g. by connecting with `ssh` once) beforehand.
+
+use std::fmt;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use russh::client::{self, Handle};
+use russh::keys::{self, key};
+use russh_sftp::client::SftpSession;
+
+use crate::source::{RecordFiles, RecordSource};
+
+/// How to authenticate with the SFTP server.
+#[derive(Debug, Clone, PartialEq)]
+pub enum SftpAuth {
+    Password(String),
+
+    /// Private key file, e.g. `~/.ssh/id_ed25519`, with the passphrase if it's
+    /// encrypted.
+    KeyFile {
+        path: PathBuf,
+        passphrase: Option<String>,
+    },
+}
+
+/// Where to connect to and how, for `SftpSource::connect()`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SftpOptions {
+    pub host: String,
+    pub port: u16,
+    pub username: String,
+    pub auth: SftpAuth,
+
+    /// Directory on the server the records are in.
+    pub dir: String,
+
+    /// File to check the server's host key against, rather than `~/.ssh/known_hosts`.
+    pub known_hosts: Option<PathBuf>,
+}
+
+impl SftpOptions {
+    /// Options for connecting to port 22 of `host` and fetching records from the
+    /// user's home directory.
+    pub fn new<H: Into<String>, U: Into<String>>(host: H, username: U, auth: SftpAuth) -> Self {
+        SftpOptions {
+            host: host.into(),
+            port: 22,
+            username: username.into(),
+            auth,
+            dir: ".".to_string(),
+            known_hosts: None,
+        }
+    }
+
+    pub fn port(mut self, port: u16) -> Self {
+        self.port = port;
+        self
+    }
+
+    pub fn dir<D: Into<String>>(mut self, dir: D) -> Self {
+        self.dir = dir.into();
+        self
+    }
+
+    pub fn known_hosts<P: Into<PathBuf>>(mut self, path: P) -> Self {
+        self.known_hosts = Some(path.into());
+        self
+    }
+}
+
+#[derive(Debug)]
+pub enum SftpError {
+    Ssh(russh::Error),
+    Key(keys::Error),
+    Sftp(russh_sftp::client::error::Error),
+
+    /// Server's host key isn't in the `known_hosts` file.
+    UnknownHostKey,
+    AuthenticationFailed,
+
+    /// Record is missing one of the files it needs, e.g. a `.cfg` file without a
+    /// `.dat` file.
+    MissingFile(String),
+}
+
+impl fmt::Display for SftpError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SftpError::Ssh(error) => write!(f, "SSH error: {}", error),
+            SftpError::Key(error) => write!(f, "SSH key error: {}", error),
+            SftpError::Sftp(error) => write!(f, "SFTP error: {}", error),
+            SftpError::UnknownHostKey => write!(f, "server's host key is not in known_hosts"),
+            SftpError::AuthenticationFailed => write!(f, "unable to authenticate with server"),
+            SftpError::MissingFile(name) => write!(f, "record is missing {}", name),
+        }
+    }
+}
+
+impl std::error::Error for SftpError {}
+
+impl From<russh::Error> for SftpError {
+    fn from(error: russh::Error) -> Self {
+        SftpError::Ssh(error)
+    }
+}
+
+impl From<keys::Error> for SftpError {
+    fn from(error: keys::Error) -> Self {
+        SftpError::Key(error)
+    }
+}
+
+impl From<russh_sftp::client::error::Error> for SftpError {
+    fn from(error: russh_sftp::client::error::Error) -> Self {
+        SftpError::Sftp(error)
+    }
+}
+
+/// Records in a directory on an SFTP server.
+///
+/// ```no_run
+/// use comtrade::sftp::{SftpAuth, SftpOptions, SftpSource};
+/// use comtrade::source::RecordSource;
+///
+/// # async fn run() -> Result<(), Box<dyn std::error::Error>> {
+/// let options = SftpOptions::new("dfr1.example.com", "comtrade", SftpAuth::Password("secret".into()))
+///     .dir("/records");
+/// let source = SftpSource::connect(options).await?;
+/// for name in source.list().await? {
+///     let record = source.fetch(&name).await?.parse()?;
+///     println!("{}: {} samples", name, record.data.sample_numbers.len());
+/// }
+/// # Ok(())
+/// # }
+/// ```
+pub struct SftpSource {
+    sftp: SftpSession,
+    dir: String,
+
+    // The connection is closed when this is dropped.
+    _session: Handle<HostKeyCheck>,
+}
+
+impl SftpSource {
+    pub async fn connect(options: SftpOptions) -> Result<Self, SftpError> {
+        let handler = HostKeyCheck {
+            host: options.host.clone(),
+            port: options.port,
+            known_hosts: options.known_hosts.clone(),
+        };
+        let config = Arc::new(client::Config::default());
+        let mut session =
+            client::connect(config, (options.host.as_str(), options.port), handler).await?;
+
+        let is_authenticated = match options.auth {
+            SftpAuth::Password(password) => {
+                session
+                    .authenticate_password(options.username, password)
+                    .await?
+            }
+            SftpAuth::KeyFile { path, passphrase } => {
+                let key = keys::load_secret_key(path, passphrase.as_deref())?;
+                session
+                    .authenticate_publickey(options.username, Arc::new(key))
+                    .await?
+            }
+        };
+        if !is_authenticated {
+            return Err(SftpError::AuthenticationFailed);
+        }
+
+        let channel = session.channel_open_session().await?;
+        channel.request_subsystem(true, "sftp").await?;
+        let sftp = SftpSession::new(channel.into_stream()).await?;
+
+        Ok(SftpSource {
+            sftp,
+            dir: options.dir,
+            _session: session,
+        })
+    }
+
+    async fn file_names(&self) -> Result<Vec<String>, SftpError> {
+        Ok(self
+            .sftp
+            .read_dir(self.dir.as_str())
+            .await?
+            .map(|entry| entry.file_name())
+            .collect())
+    }
+
+    async fn read(&self, file_name: &str) -> Result<Vec<u8>, SftpError> {
+        let path = format!("{}/{}", self.dir.trim_end_matches('/'), file_name);
+        Ok(self.sftp.read(path).await?)
+    }
+}
+
+impl RecordSource for SftpSource {
+    type Error = SftpError;
+
+    async fn list(&self) -> Result<Vec<String>, SftpError> {
+        let file_names = self.file_names().await?;
+        let mut names: Vec<String> = file_names
+            .iter()
+            .filter(|f| has_extension(f, "cfg") || has_extension(f, "cff"))
+            .map(|f| f[..f.len() - 4].to_string())
+            .collect();
+        names.sort();
+        names.dedup();
+        Ok(names)
+    }
+
+    async fn fetch(&self, name: &str) -> Result<RecordFiles, SftpError> {
+        let file_names = self.file_names().await?;
+        // Recorders differ on whether the extensions are upper or lower case.
+        let find = |extension: &str| {
+            file_names.iter().find(|f| {
+                f.len() == name.len() + extension.len() + 1
+                    && f.starts_with(name)
+                    && has_extension(f, extension)
+            })
+        };
+
+        if let Some(cff) = find("cff") {
+            return Ok(RecordFiles::Combined(self.read(cff).await?));
+        }
+
+        let missing = |extension: &str| SftpError::MissingFile(format!("{}.{}", name, extension));
+        let cfg = find("cfg").ok_or_else(|| missing("cfg"))?;
+        let dat = find("dat").ok_or_else(|| missing("dat"))?;
+        let hdr = match find("hdr") {
+            Some(hdr) => Some(self.read(hdr).await?),
+            None => None,
+        };
+        let inf = match find("inf") {
+            Some(inf) => Some(self.read(inf).await?),
+            None => None,
+        };
+
+        Ok(RecordFiles::Separate {
+            cfg: self.read(cfg).await?,
+            dat: self.read(dat).await?,
+            hdr,
+            inf,
+        })
+    }
+}
+
+/// Whether the file name ends with `.` and the extension, in any case.
+fn has_extension(file_name: &str, extension: &str) -> bool {
+    file_name.len() > extension.len() + 1
+        && file_name.is_char_boundary(file_name.len() - extension.len() - 1)
+        && file_name[file_name.len() - extension.len() - 1..]
+            .strip_prefix('.')
+            .is_some_and(|e| e.eq_ignore_ascii_case(extension))
+}
+
+/// Checks the server's host key against the `known_hosts` file.
+struct HostKeyCheck {
+    host: String,
+    port: u16,
+    known_hosts: Option<PathBuf>,
+}
+
+#[async_trait]
+impl client::Handler for HostKeyCheck {
+    type Error = SftpError;
+
+    async fn check_server_key(&mut self, server_key: &key::PublicKey) -> Result<bool, SftpError> {
+        let is_known = match &self.known_hosts {
+            Some(path) => keys::check_known_hosts_path(&self.host, self.port, server_key, path)?,
+            None => keys::check_known_hosts(&self.host, self.port, server_key)?,
+        };
+        if is_known {
+            Ok(true)
+        } else {
+            Err(SftpError::UnknownHostKey)
+        }
+    }
+}
@@ -0,0 +1,247 @@
+//! Compressed sidecar files holding a record's samples in place of its `.dat` file, for
+//! archives where the `.dat` files take up most of the space. The `.cfg` file is kept
+//! as it is alongside, and the standard `.dat` file can be regenerated from the two at
+//! any time.
+//!
+//! Write a sidecar with `ComtradeWriter::sidecar()` and read the record back with
+//! `read_sidecar()`. The samples are those of the binary `.dat` file for the record's
+//! data format, or of a `BINARY32` one for ASCII records, so the `.dat` file of a binary
+//! record comes back byte for byte. ASCII values are rounded to whole numbers, which
+//! they nearly always are anyway.
+//!
+//! The format is a header, all numbers little-endian:
+//!
+//! - The magic bytes `CTDZ` and the version of the format, currently 1.
+//! - The data format of the samples: 1 for `BINARY`, 2 for `BINARY32` and 3 for
+//!   `FLOAT32`.
+//! - The number of analog channels, status channels and scans, as `u32`, `u32` and
+//!   `u64`.
+//!
+//! followed by a deflate stream of the columns of the binary `.dat` file one after the
+//! other: the sample numbers, the timestamps, each analog channel and each 16-bit word
+//! of status channels. Each value in a column is written as the difference from the
+//! one before it, zigzag encoded so small negative differences stay small, or for
+//! `FLOAT32` values the bits which differ from the one before, as an unsigned LEB128
+//! varint. Values which change slowly from scan to scan, as most do, take up a byte or
+//! two and compress well.
+
+use alloc::format;
+use alloc::vec;
+use alloc::vec::Vec;
+use std::io::{Read, Write};
+
+use byteorder::{ByteOrder, LittleEndian};
+use flate2::read::DeflateDecoder;
+use flate2::write::DeflateEncoder;
+use flate2::Compression;
+
+use crate::{Comtrade, ComtradeParser, DataFormat, ParseError, ParseResult};
+
+const MAGIC: &[u8; 4] = b"CTDZ";
+const VERSION: u8 = 1;
+const HEADER_SIZE: usize = 4 + 1 + 1 + 4 + 4 + 8;
+
+/// Column of a binary `.dat` file: its size in bytes and whether it holds `FLOAT32`
+/// values, which are encoded by their bits rather than their differences.
+#[derive(Clone, Copy)]
+struct Column {
+    offset: usize,
+    size: usize,
+    float: bool,
+}
+
+fn columns(format: &DataFormat, num_analog: u32, num_status: u32) -> Vec<Column> {
+    let mut columns = Vec::new();
+    let mut add = |size: usize, float: bool| {
+        let offset = columns.last().map_or(0, |c: &Column| c.offset + c.size);
+        columns.push(Column {
+            offset,
+            size,
+            float,
+        });
+    };
+    add(4, false);
+    add(4, false);
+    let analog_size = format.analog_value_size().unwrap_or(4);
+    for _ in 0..num_analog {
+        add(analog_size, *format == DataFormat::Float32);
+    }
+    for _ in 0..(num_status as usize).div_ceil(16) {
+        add(2, false);
+    }
+    columns
+}
+
+/// Value of a column in a scan, sign-extended for integers so that differences between
+/// negative values stay small.
+fn read_value(scan: &[u8], column: Column) -> u64 {
+    let bytes = &scan[column.offset..column.offset + column.size];
+    match (column.size, column.float) {
+        (2, _) => LittleEndian::read_i16(bytes) as i64 as u64,
+        (_, true) => LittleEndian::read_u32(bytes) as u64,
+        _ => LittleEndian::read_i32(bytes) as i64 as u64,
+    }
+}
+
+fn write_value(scan: &mut [u8], column: Column, value: u64) {
+    let bytes = &mut scan[column.offset..column.offset + column.size];
+    match column.size {
+        2 => LittleEndian::write_u16(bytes, value as u16),
+        _ => LittleEndian::write_u32(bytes, value as u32),
+    }
+}
+
+fn format_code(format: &DataFormat) -> u8 {
+    match format {
+        DataFormat::Ascii | DataFormat::Binary32 => 2,
+        DataFormat::Binary16 => 1,
+        DataFormat::Float32 => 3,
+    }
+}
+
+/// Sidecar holding the scans of a binary `.dat` file in the given format, see
+/// `ComtradeWriter::sidecar()`.
+pub(crate) fn compress(
+    format: &DataFormat,
+    num_analog: u32,
+    num_status: u32,
+    dat: &[u8],
+) -> Vec<u8> {
+    let columns = columns(format, num_analog, num_status);
+    let scan_size = format
+        .bytes_per_scan(num_analog, num_status)
+        .expect("sidecars are always in a binary format");
+    let num_scans = dat.len() / scan_size;
+
+    let mut sidecar = Vec::with_capacity(HEADER_SIZE + dat.len() / 4);
+    sidecar.extend_from_slice(MAGIC);
+    sidecar.push(VERSION);
+    sidecar.push(format_code(format));
+    let mut header = [0; 16];
+    LittleEndian::write_u32(&mut header[0..4], num_analog);
+    LittleEndian::write_u32(&mut header[4..8], num_status);
+    LittleEndian::write_u64(&mut header[8..16], num_scans as u64);
+    sidecar.extend_from_slice(&header);
+
+    let mut encoded = Vec::with_capacity(dat.len());
+    for column in columns {
+        let mut previous = 0;
+        for scan in dat.chunks_exact(scan_size) {
+            let value = read_value(scan, column);
+            let difference = if column.float {
+                value ^ previous
+            } else {
+                let difference = value.wrapping_sub(previous) as i64;
+                ((difference << 1) ^ (difference >> 63)) as u64
+            };
+            write_varint(&mut encoded, difference);
+            previous = value;
+        }
+    }
+
+    let mut encoder = DeflateEncoder::new(sidecar, Compression::default());
+    encoder
+        .write_all(&encoded)
+        .and_then(|_| encoder.finish())
+        .expect("writing to a Vec can't fail")
+}
+
+/// Data format of the samples in a sidecar and the binary `.dat` file they make up.
+pub fn decompress(sidecar: &[u8]) -> ParseResult<(DataFormat, Vec<u8>)> {
+    let invalid = |message: &str| ParseError::new(format!("invalid sidecar: {}", message));
+    if sidecar.len() < HEADER_SIZE || &sidecar[0..4] != MAGIC {
+        return Err(invalid("missing header"));
+    }
+    if sidecar[4] != VERSION {
+        return Err(invalid(&format!("unsupported version {}", sidecar[4])));
+    }
+    let format = match sidecar[5] {
+        1 => DataFormat::Binary16,
+        2 => DataFormat::Binary32,
+        3 => DataFormat::Float32,
+        code => return Err(invalid(&format!("unknown data format {}", code))),
+    };
+    let num_analog = LittleEndian::read_u32(&sidecar[6..10]);
+    let num_status = LittleEndian::read_u32(&sidecar[10..14]);
+    let num_scans = LittleEndian::read_u64(&sidecar[14..22]);
+
+    let mut encoded = Vec::new();
+    DeflateDecoder::new(&sidecar[HEADER_SIZE..])
+        .read_to_end(&mut encoded)
+        .map_err(|err| invalid(&err.to_string()))?;
+
+    // Every value takes at least a byte, which bounds the size of the `.dat` file
+    // before it's allocated.
+    let columns = columns(&format, num_analog, num_status);
+    let scan_size = format
+        .bytes_per_scan(num_analog, num_status)
+        .expect("binary formats have a scan size");
+    let num_values = (num_scans as u128) * columns.len() as u128;
+    if num_values > encoded.len() as u128 {
+        return Err(invalid("fewer values than scans"));
+    }
+    let num_scans = num_scans as usize;
+
+    let mut dat = vec![0; num_scans * scan_size];
+    let mut values = encoded.as_slice();
+    for column in columns {
+        let mut previous: u64 = 0;
+        for scan in dat.chunks_exact_mut(scan_size) {
+            let difference = read_varint(&mut values).ok_or_else(|| invalid("truncated"))?;
+            let value = if column.float {
+                difference ^ previous
+            } else {
+                let difference = ((difference >> 1) as i64) ^ -((difference & 1) as i64);
+                previous.wrapping_add(difference as u64)
+            };
+            write_value(scan, column, value);
+            previous = value;
+        }
+    }
+    if !values.is_empty() {
+        return Err(invalid("more values than scans"));
+    }
+
+    Ok((format, dat))
+}
+
+/// Record from its `.cfg` file and the sidecar written in place of its `.dat` file by
+/// `ComtradeWriter::sidecar()`, as it would be parsed from the standard files. The
+/// standard `.dat` file can be regenerated from the record with `ComtradeWriter::dat()`.
+pub fn read_sidecar(cfg: &[u8], sidecar: &[u8]) -> ParseResult<Comtrade> {
+    let (format, dat) = decompress(sidecar)?;
+    let mut config = ComtradeParser::from_bytes(cfg, &[]).parse_comtrade_config()?;
+    let cfg_format = config.data_format.clone();
+    if config.num_analog_channels != LittleEndian::read_u32(&sidecar[6..10])
+        || config.num_status_channels != LittleEndian::read_u32(&sidecar[10..14])
+    {
+        return Err(ParseError::new(
+            "sidecar doesn't have the channels in the .cfg file".into(),
+        ));
+    }
+
+    config.data_format = format;
+    let mut record = ComtradeParser::from_config(&config, dat.as_slice()).parse()?;
+    record.config.data_format = cfg_format;
+    Ok(record)
+}
+
+fn write_varint(out: &mut Vec<u8>, mut value: u64) {
+    while value >= 0x80 {
+        out.push(value as u8 | 0x80);
+        value >>= 7;
+    }
+    out.push(value as u8);
+}
+
+fn read_varint(bytes: &mut &[u8]) -> Option<u64> {
+    let mut value = 0u64;
+    for (i, byte) in bytes.iter().enumerate().take(10) {
+        value |= ((byte & 0x7f) as u64) << (7 * i);
+        if byte & 0x80 == 0 {
+            *bytes = &bytes[i + 1..];
+            return Some(value);
+        }
+    }
+    None
+}
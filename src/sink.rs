@@ -0,0 +1,44 @@
+//! Receiving parsed samples directly rather than collecting them into a `Comtrade`
+//! record, e.g. to write them straight into a ring buffer or database.
+
+use crate::{Comtrade, Scan};
+
+/// Receiver for the results of `ComtradeParser::parse_into()`.
+///
+/// ```
+/// use comtrade::{Comtrade, ComtradeParserBuilder, DataSink, Scan};
+///
+/// /// Keeps track of the peak value of the first analog channel.
+/// #[derive(Default)]
+/// struct Peak(f64);
+///
+/// impl DataSink for Peak {
+///     fn on_config(&mut self, _config: &Comtrade) {}
+///
+///     fn on_scan(&mut self, scan: &Scan) {
+///         self.0 = self.0.max(scan.analog_values[0].abs());
+///     }
+/// }
+///
+/// let cfg = "STATION,DEVICE,1999\n1,1A,0D\n1,IA,A,,A,0.5,0,0,-32768,32767,1,1,S\n\
+///            50\n1\n1000,2\n01/01/2020,00:00:00\n01/01/2020,00:00:00\nASCII\n1\n";
+/// let dat = "1,0,10\n2,1000,-30\n";
+///
+/// let mut peak = Peak::default();
+/// ComtradeParserBuilder::new()
+///     .cfg_file(cfg.as_bytes())
+///     .dat_file(dat.as_bytes())
+///     .build()
+///     .parse_into(&mut peak)?;
+/// assert_eq!(peak.0, 15.0);
+/// # Ok::<(), comtrade::ParseError>(())
+/// ```
+pub trait DataSink {
+    /// Called once the configuration has been parsed, before any samples. The record
+    /// has everything apart from the samples.
+    fn on_config(&mut self, config: &Comtrade);
+
+    /// Called for each sample in the order they appear in the data file, with the analog
+    /// values already scaled.
+    fn on_scan(&mut self, scan: &Scan);
+}
@@ -0,0 +1,299 @@
+//! Borrowed views over part of a record, for running analysis over a time window or a
+//! few channels of a large record without copying the sample data.
+
+use alloc::vec::Vec;
+use core::ops::Range;
+
+use crate::{
+    AnalogChannel, Comtrade, ComtradeConfig, ComtradeData, IrregularSample, StatusChannel,
+};
+
+/// View of a range of samples and a subset of the channels in a record.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ComtradeSlice<'a> {
+    record: &'a Comtrade,
+    samples: Range<usize>,
+    analog_channels: Vec<usize>,
+    status_channels: Vec<usize>,
+}
+
+/// Analog channel in a `ComtradeSlice`, with just the data for the samples in the slice.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct AnalogChannelSlice<'a> {
+    pub channel: &'a AnalogChannel,
+    pub data: &'a [f64],
+
+    /// Quality flags of the samples in the slice, or empty if the channel doesn't have
+    /// any.
+    pub quality: &'a [u32],
+}
+
+/// Status channel in a `ComtradeSlice`, with just the data for the samples in the slice.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct StatusChannelSlice<'a> {
+    pub channel: &'a StatusChannel,
+    pub data: &'a [u8],
+}
+
+impl Comtrade {
+    /// Views of each whole power system cycle in the record, see
+    /// `ComtradeSlice::cycles()`.
+    pub fn cycles(&self) -> impl Iterator<Item = ComtradeSlice<'_>> {
+        self.slice().cycles()
+    }
+
+    /// View of the whole record, which can then be narrowed down.
+    pub fn slice(&self) -> ComtradeSlice<'_> {
+        ComtradeSlice {
+            record: self,
+            samples: 0..self.data.sample_numbers.len(),
+            analog_channels: (0..self.config.analog_channels.len()).collect(),
+            status_channels: (0..self.config.status_channels.len()).collect(),
+        }
+    }
+}
+
+impl<'a> ComtradeSlice<'a> {
+    /// Narrow the view down to the samples in `range`, which is relative to the samples
+    /// already in the view. Anything past the end of the view is left out.
+    pub fn with_samples(mut self, range: Range<usize>) -> Self {
+        let start = (self.samples.start + range.start).min(self.samples.end);
+        let end = (self.samples.start + range.end).clamp(start, self.samples.end);
+        self.samples = start..end;
+        self
+    }
+
+    /// Narrow the view down to the samples with timestamps from `start` up to but not
+    /// including `end`, in nanoseconds. The timestamps are assumed to be in order.
+    pub fn with_time_range(mut self, start: i64, end: i64) -> Self {
+        let timestamps = self.timestamps();
+        let first = timestamps.partition_point(|t| *t < start);
+        let last = timestamps.partition_point(|t| *t < end).max(first);
+        self.samples = self.samples.start + first..self.samples.start + last;
+        self
+    }
+
+    /// Narrow the view down to the analog channels with these 0-based indices into
+    /// `ComtradeConfig::analog_channels`. Channels not in the view are ignored.
+    pub fn with_analog_channels(mut self, indices: &[usize]) -> Self {
+        self.analog_channels.retain(|i| indices.contains(i));
+        self
+    }
+
+    /// Narrow the view down to the status channels with these 0-based indices into
+    /// `ComtradeConfig::status_channels`. Channels not in the view are ignored.
+    pub fn with_status_channels(mut self, indices: &[usize]) -> Self {
+        self.status_channels.retain(|i| indices.contains(i));
+        self
+    }
+
+    /// Views of each whole power system cycle in the view, going by the record's line
+    /// frequency, which is the natural window for working out RMS values and phasors
+    /// cycle by cycle. The cycles are timed from the first sample in the view, so they
+    /// line up even where the sampling rate changes or isn't a whole multiple of the
+    /// line frequency. A part cycle left at the end is left out.
+    pub fn cycles(self) -> impl Iterator<Item = ComtradeSlice<'a>> {
+        let timestamps = self.timestamps();
+        let period = 1e9 / self.record.config.line_frequency;
+
+        // The last sample covers a sample period of its own, taken to be the same as
+        // the one before.
+        let num_cycles = match timestamps {
+            [first, .., before, last] if period > 0.0 && period.is_finite() => {
+                let covered = (last - first + last - before) as f64;
+                (covered / period + 1e-9) as usize
+            }
+            _ => 0,
+        };
+        let first = timestamps.first().copied().unwrap_or(0);
+
+        (0..num_cycles).map(move |k| {
+            let start = first + (k as f64 * period + 0.5) as i64;
+            let end = first + ((k + 1) as f64 * period + 0.5) as i64;
+            self.clone().with_time_range(start, end)
+        })
+    }
+
+    /// Record the view is over.
+    pub fn record(&self) -> &'a Comtrade {
+        self.record
+    }
+
+    /// Indices of the samples in the view.
+    pub fn sample_range(&self) -> Range<usize> {
+        self.samples.clone()
+    }
+
+    pub fn len(&self) -> usize {
+        self.samples.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.samples.is_empty()
+    }
+
+    pub fn sample_numbers(&self) -> &'a [u64] {
+        &self.record.data.sample_numbers[self.samples.clone()]
+    }
+
+    pub fn timestamps(&self) -> &'a [i64] {
+        &self.record.data.timestamps[self.samples.clone()]
+    }
+
+    pub fn raw_timestamps(&self) -> &'a [Option<u32>] {
+        self.record
+            .data
+            .raw_timestamps
+            .get(self.samples.clone())
+            .unwrap_or(&[])
+    }
+
+    pub fn analog_channels(&self) -> impl Iterator<Item = AnalogChannelSlice<'a>> + '_ {
+        self.analog_channels.iter().map(|i| {
+            let data = self
+                .record
+                .data
+                .analog_data
+                .get(*i)
+                .map_or(&[][..], |data| &data[..]);
+            let quality = self
+                .record
+                .data
+                .analog_quality
+                .get(*i)
+                .map_or(&[][..], |quality| &quality[..]);
+            AnalogChannelSlice {
+                channel: &self.record.config.analog_channels[*i],
+                data: data.get(self.samples.clone()).unwrap_or(&[]),
+                quality: quality.get(self.samples.clone()).unwrap_or(&[]),
+            }
+        })
+    }
+
+    pub fn status_channels(&self) -> impl Iterator<Item = StatusChannelSlice<'a>> + '_ {
+        self.status_channels.iter().map(|i| {
+            let data = self
+                .record
+                .data
+                .status_data
+                .get(*i)
+                .map_or(&[][..], |data| &data[..]);
+            StatusChannelSlice {
+                channel: &self.record.config.status_channels[*i],
+                data: data.get(self.samples.clone()).unwrap_or(&[]),
+            }
+        })
+    }
+
+    /// Copy the view out into a record of its own, e.g. for writing out a shorter
+    /// record. The channels are renumbered from 1 in the new record, but the sample
+    /// numbers and timestamps are kept as they were.
+    pub fn to_comtrade(&self) -> Comtrade {
+        let source = self.record;
+
+        let analog_channels: Vec<AnalogChannel> = self
+            .analog_channels()
+            .enumerate()
+            .map(|(i, slice)| AnalogChannel {
+                index: i as u32 + 1,
+                name: slice.channel.name.clone(),
+                name_raw: slice.channel.name_raw.clone(),
+                phase: slice.channel.phase.clone(),
+                phase_raw: slice.channel.phase_raw.clone(),
+                circuit_component_being_monitored: slice
+                    .channel
+                    .circuit_component_being_monitored
+                    .clone(),
+                units: slice.channel.units.clone(),
+                min_value: slice.channel.min_value,
+                max_value: slice.channel.max_value,
+                multiplier: slice.channel.multiplier,
+                offset_adder: slice.channel.offset_adder,
+                skew: slice.channel.skew,
+                primary_factor: slice.channel.primary_factor,
+                secondary_factor: slice.channel.secondary_factor,
+                scaling_mode: slice.channel.scaling_mode.clone(),
+                extensions: slice.channel.extensions.clone(),
+            })
+            .collect();
+        let status_channels: Vec<StatusChannel> = self
+            .status_channels()
+            .enumerate()
+            .map(|(i, slice)| StatusChannel {
+                index: i as u32 + 1,
+                name: slice.channel.name.clone(),
+                name_raw: slice.channel.name_raw.clone(),
+                phase: slice.channel.phase.clone(),
+                phase_raw: slice.channel.phase_raw.clone(),
+                circuit_component_being_monitored: slice
+                    .channel
+                    .circuit_component_being_monitored
+                    .clone(),
+                normal_status_value: slice.channel.normal_status_value,
+                extensions: slice.channel.extensions.clone(),
+            })
+            .collect();
+
+        let irregular_samples = source
+            .data
+            .irregular_samples
+            .iter()
+            .filter(|s| self.samples.contains(&s.index))
+            .map(|s| IrregularSample {
+                index: s.index - self.samples.start,
+                ..s.clone()
+            })
+            .collect();
+
+        let config = ComtradeConfig {
+            station_name: source.config.station_name.clone(),
+            recording_device_id: source.config.recording_device_id.clone(),
+            revision: source.config.revision,
+            num_total_channels: (analog_channels.len() + status_channels.len()) as u32,
+            num_analog_channels: analog_channels.len() as u32,
+            num_status_channels: status_channels.len() as u32,
+            analog_channels,
+            status_channels,
+            line_frequency: source.config.line_frequency,
+            sampling_rates: source.config.sampling_rates.clone(),
+            total_num_samples: self.len() as u64,
+            start_time: source.config.start_time,
+            trigger_time: source.config.trigger_time,
+            data_format: source.config.data_format.clone(),
+            timestamp_multiplication_factor: source.config.timestamp_multiplication_factor,
+            time_offset: source.config.time_offset,
+            local_offset: source.config.local_offset,
+            time_quality: source.config.time_quality.clone(),
+            leap_second_status: source.config.leap_second_status.clone(),
+            extra_cfg_lines: source.config.extra_cfg_lines.clone(),
+            header: source.config.header.clone(),
+            extensions: source.config.extensions.clone(),
+            warnings: source.config.warnings.clone(),
+            timestamp_unit: source.config.timestamp_unit,
+            decimal_comma: source.config.decimal_comma,
+        };
+        let data = ComtradeData {
+            sample_numbers: self.sample_numbers().to_vec(),
+            timestamps: self.timestamps().to_vec(),
+            raw_timestamps: self.raw_timestamps().to_vec(),
+            analog_data: self
+                .analog_channels()
+                .map(|slice| slice.data.to_vec())
+                .collect(),
+            analog_quality: if source.data.analog_quality.is_empty() {
+                Vec::new()
+            } else {
+                self.analog_channels()
+                    .map(|slice| slice.quality.to_vec())
+                    .collect()
+            },
+            status_data: self
+                .status_channels()
+                .map(|slice| slice.data.to_vec())
+                .collect(),
+            irregular_samples,
+            source_files: None,
+        };
+        Comtrade::from_parts(config, data)
+    }
+}
@@ -0,0 +1,83 @@
+//! Fetching records from wherever recording devices make them available, e.g. over
+//! SFTP, so that they can then be parsed. See `sftp::SftpSource` (with the `sftp`
+//! feature) for an implementation.
+
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::future::Future;
+
+use crate::{Comtrade, ComtradeParserBuilder, ParseResult};
+
+/// Contents of the files making up a single record, as fetched from a `RecordSource`.
+#[derive(Debug, Clone, PartialEq)]
+pub enum RecordFiles {
+    /// Separate `.cfg` and `.dat` files, along with the `.hdr` and `.inf` files if the
+    /// record has them.
+    Separate {
+        cfg: Vec<u8>,
+        dat: Vec<u8>,
+        hdr: Option<Vec<u8>>,
+        inf: Option<Vec<u8>>,
+    },
+
+    /// Single combined `.cff` file.
+    Combined(Vec<u8>),
+}
+
+impl RecordFiles {
+    pub fn parse(&self) -> ParseResult<Comtrade> {
+        let builder = ComtradeParserBuilder::new();
+        let builder = match self {
+            RecordFiles::Separate { cfg, dat, hdr, inf } => {
+                let mut builder = builder.cfg_file(cfg.as_slice()).dat_file(dat.as_slice());
+                if let Some(hdr) = hdr {
+                    builder = builder.hdr_file(hdr.as_slice());
+                }
+                if let Some(inf) = inf {
+                    builder = builder.inf_file(inf.as_slice());
+                }
+                builder
+            }
+            RecordFiles::Combined(cff) => builder.cff_file(cff.as_slice()),
+        };
+
+        builder.build().parse()
+    }
+}
+
+/// Somewhere records can be fetched from, e.g. a directory on a recording device's
+/// (S)FTP server.
+///
+/// ```
+/// use std::collections::BTreeMap;
+/// use std::future::Future;
+///
+/// use comtrade::source::{RecordFiles, RecordSource};
+///
+/// /// Records already held in memory, keyed by name.
+/// struct InMemory(BTreeMap<String, RecordFiles>);
+///
+/// impl RecordSource for InMemory {
+///     type Error = String;
+///
+///     fn list(&self) -> impl Future<Output = Result<Vec<String>, String>> + Send {
+///         let names = self.0.keys().cloned().collect();
+///         async move { Ok(names) }
+///     }
+///
+///     fn fetch(&self, name: &str) -> impl Future<Output = Result<RecordFiles, String>> + Send {
+///         let files = self.0.get(name).cloned().ok_or(format!("no record {}", name));
+///         async move { files }
+///     }
+/// }
+/// ```
+pub trait RecordSource {
+    type Error;
+
+    /// Names of the records available, which can be passed to `fetch()`. These are the
+    /// names of the record's files without the extension.
+    fn list(&self) -> impl Future<Output = Result<Vec<String>, Self::Error>> + Send;
+
+    /// Fetch the files making up the record with this name.
+    fn fetch(&self, name: &str) -> impl Future<Output = Result<RecordFiles, Self::Error>> + Send;
+}
@@ -0,0 +1,239 @@
+//! Decoding of logical signals which are spread over several status channels, e.g. a
+//! tap changer position given as a 3-bit number, and packing status channels into bits
+//! for combining long channels quickly.
+
+use alloc::string::String;
+use alloc::vec;
+use alloc::vec::Vec;
+use core::ops::{BitAnd, BitOr, BitXor, Not};
+
+use crate::Comtrade;
+
+/// Multi-bit signal made up of several status channels.
+#[derive(Debug, Clone, PartialEq)]
+pub struct StatusGroup {
+    pub name: String,
+
+    /// 0-based indices into `ComtradeConfig::status_channels` of the channels holding each
+    /// bit of the signal, least significant bit first. At most 32 channels can be
+    /// grouped together.
+    pub channels: Vec<usize>,
+}
+
+/// Point at which the value of a `StatusGroup` changes.
+#[derive(Debug, Clone, PartialEq)]
+pub struct StatusTransition {
+    /// 0-based index of the first sample with the new value.
+    pub index: usize,
+    pub from: u32,
+    pub to: u32,
+}
+
+impl StatusGroup {
+    pub fn new(name: &str, channels: Vec<usize>) -> Self {
+        StatusGroup {
+            name: name.into(),
+            channels,
+        }
+    }
+
+    /// Value of the signal at every sample in the record. Returns `None` if any of the
+    /// channels aren't in the record or there are too many to fit the value in a `u32`.
+    pub fn decode(&self, record: &Comtrade) -> Option<Vec<u32>> {
+        if self.channels.len() > 32 {
+            return None;
+        }
+
+        let mut values = vec![0u32; record.data.sample_numbers.len()];
+        for (bit, channel_index) in self.channels.iter().enumerate() {
+            let data = record.data.status_data.get(*channel_index)?;
+            for (value, datum) in values.iter_mut().zip(data.iter()) {
+                *value |= ((*datum & 1) as u32) << bit;
+            }
+        }
+
+        Some(values)
+    }
+
+    /// Every change in the value of the signal over the record. The value at the first
+    /// sample isn't counted as a change.
+    pub fn transitions(&self, record: &Comtrade) -> Option<Vec<StatusTransition>> {
+        let values = self.decode(record)?;
+
+        Some(
+            values
+                .windows(2)
+                .enumerate()
+                .filter(|(_, pair)| pair[0] != pair[1])
+                .map(|(i, pair)| StatusTransition {
+                    index: i + 1,
+                    from: pair[0],
+                    to: pair[1],
+                })
+                .collect(),
+        )
+    }
+}
+
+/// Values of a status channel packed 64 to a word, from `Comtrade::status_bits()`.
+/// This takes an eighth of the memory of the channel's data, and channels can be
+/// combined with `&`, `|` and `^` a word at a time, e.g. to find the samples where
+/// either of two trip signals is set.
+///
+/// Combining channels of different lengths gives the length of the shorter one.
+///
+/// ```rust
+/// use comtrade::StatusBits;
+///
+/// let trip_a = StatusBits::from_values(&[0, 1, 1, 0]);
+/// let trip_b = StatusBits::from_values(&[0, 0, 1, 1]);
+///
+/// assert_eq!((&trip_a | &trip_b).to_values(), vec![0, 1, 1, 1]);
+/// assert_eq!((&trip_a & &trip_b).count_ones(), 1);
+/// assert_eq!((&trip_a ^ &trip_b).ones().collect::<Vec<_>>(), vec![1, 3]);
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct StatusBits {
+    // Bits past `len` in the last word are always 0.
+    words: Vec<u64>,
+    len: usize,
+}
+
+impl StatusBits {
+    /// Pack status values, where anything other than 0 is taken to be set.
+    pub fn from_values(values: &[u8]) -> Self {
+        let words = values
+            .chunks(64)
+            .map(|chunk| {
+                chunk.iter().enumerate().fold(0u64, |word, (bit, value)| {
+                    word | (((*value != 0) as u64) << bit)
+                })
+            })
+            .collect();
+        StatusBits {
+            words,
+            len: values.len(),
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Value of the 0-based `index`th sample, or `None` if there's no such sample.
+    pub fn get(&self, index: usize) -> Option<bool> {
+        if index >= self.len {
+            return None;
+        }
+        Some((self.words[index / 64] >> (index % 64)) & 1 == 1)
+    }
+
+    /// Number of samples which are set.
+    pub fn count_ones(&self) -> usize {
+        self.words.iter().map(|w| w.count_ones() as usize).sum()
+    }
+
+    /// Number of samples which aren't set.
+    pub fn count_zeros(&self) -> usize {
+        self.len - self.count_ones()
+    }
+
+    /// 0-based indices of the samples which are set, in order.
+    pub fn ones(&self) -> impl Iterator<Item = usize> + '_ {
+        self.words.iter().enumerate().flat_map(|(i, word)| {
+            let mut word = *word;
+            core::iter::from_fn(move || {
+                if word == 0 {
+                    return None;
+                }
+                let bit = word.trailing_zeros() as usize;
+                word &= word - 1;
+                Some(i * 64 + bit)
+            })
+        })
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = bool> + '_ {
+        (0..self.len).map(|i| (self.words[i / 64] >> (i % 64)) & 1 == 1)
+    }
+
+    /// Unpack into status values of 0 or 1, as in `ComtradeData::status_data`.
+    pub fn to_values(&self) -> Vec<u8> {
+        self.iter().map(|bit| bit as u8).collect()
+    }
+
+    fn combine(&self, other: &StatusBits, op: impl Fn(u64, u64) -> u64) -> StatusBits {
+        let len = self.len.min(other.len);
+        let mut bits = StatusBits {
+            words: self
+                .words
+                .iter()
+                .zip(other.words.iter())
+                .map(|(a, b)| op(*a, *b))
+                .take(len.div_ceil(64))
+                .collect(),
+            len,
+        };
+        bits.clear_unused();
+        bits
+    }
+
+    fn clear_unused(&mut self) {
+        if let Some(last) = self.words.last_mut() {
+            if !self.len.is_multiple_of(64) {
+                *last &= (1 << (self.len % 64)) - 1;
+            }
+        }
+    }
+}
+
+impl BitAnd for &StatusBits {
+    type Output = StatusBits;
+
+    fn bitand(self, other: &StatusBits) -> StatusBits {
+        self.combine(other, |a, b| a & b)
+    }
+}
+
+impl BitOr for &StatusBits {
+    type Output = StatusBits;
+
+    fn bitor(self, other: &StatusBits) -> StatusBits {
+        self.combine(other, |a, b| a | b)
+    }
+}
+
+impl BitXor for &StatusBits {
+    type Output = StatusBits;
+
+    fn bitxor(self, other: &StatusBits) -> StatusBits {
+        self.combine(other, |a, b| a ^ b)
+    }
+}
+
+impl Not for &StatusBits {
+    type Output = StatusBits;
+
+    fn not(self) -> StatusBits {
+        let mut bits = StatusBits {
+            words: self.words.iter().map(|w| !w).collect(),
+            len: self.len,
+        };
+        bits.clear_unused();
+        bits
+    }
+}
+
+impl Comtrade {
+    /// Values of the status channel at `index` packed into bits, see `StatusBits`.
+    pub fn status_bits(&self, index: usize) -> Option<StatusBits> {
+        self.data
+            .status_data
+            .get(index)
+            .map(|data| StatusBits::from_values(data))
+    }
+}
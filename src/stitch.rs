@@ -0,0 +1,240 @@
+//! Joining up back-to-back records from the same device, e.g. when a long disturbance
+//! set off several triggers in a row, into one long record.
+
+use alloc::vec;
+use core::fmt;
+
+use crate::{Comtrade, IrregularSample, SamplingRate};
+
+/// Why records couldn't be stitched together. `index` is the position in the records
+/// passed to `stitch()` of the record that didn't follow on from the one before.
+#[derive(Debug, Clone, PartialEq)]
+pub enum StitchError {
+    NoRecords,
+
+    /// The station name or recording device ID is different.
+    DifferentDevice {
+        index: usize,
+    },
+
+    /// The channels aren't the same, going by their names and units.
+    DifferentChannels {
+        index: usize,
+    },
+
+    /// The record doesn't start one sample period after the record before ends, give or
+    /// take half a sample. `gap` is the time in nanoseconds between the last sample of
+    /// the record before and the first of this one, so negative if they overlap.
+    NotContiguous {
+        index: usize,
+        gap: i64,
+    },
+}
+
+impl fmt::Display for StitchError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            StitchError::NoRecords => write!(f, "no records to stitch"),
+            StitchError::DifferentDevice { index } => {
+                write!(f, "record {} is from a different device", index)
+            }
+            StitchError::DifferentChannels { index } => {
+                write!(f, "record {} has different channels", index)
+            }
+            StitchError::NotContiguous { index, gap } => write!(
+                f,
+                "record {} doesn't follow on from the record before, with a gap of {} ns",
+                index, gap
+            ),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for StitchError {}
+
+/// Join records from the same device which follow on from each other into one long
+/// record, in the order given.
+///
+/// Each record must start one sample period after the one before ends, going by their
+/// start times and timestamps, and taking their offsets from UTC into account if they
+/// both have them. The sample period is from the last sampling rate of the record
+/// before, or the time between its last two samples if it doesn't have a fixed rate.
+///
+/// The stitched record takes its header fields, start and trigger time from the first
+/// record. Samples are renumbered from 1, their timestamps are worked out relative to
+/// the first record's start time, and the sampling rates of each record are joined up
+/// into one table. Raw timestamps aren't kept, as they're relative to the start of each
+/// record.
+pub fn stitch(records: &[Comtrade]) -> Result<Comtrade, StitchError> {
+    let first = records.first().ok_or(StitchError::NoRecords)?;
+    let mut stitched = first.clone();
+    stitched.data.raw_timestamps = vec![];
+
+    for (index, record) in records.iter().enumerate().skip(1) {
+        if record.config.station_name.trim() != first.config.station_name.trim()
+            || record.config.recording_device_id.trim() != first.config.recording_device_id.trim()
+        {
+            return Err(StitchError::DifferentDevice { index });
+        }
+        if !same_channels(first, record) {
+            return Err(StitchError::DifferentChannels { index });
+        }
+
+        let offset = start_offset(first, record).ok_or(StitchError::NotContiguous {
+            index,
+            gap: i64::MAX,
+        })?;
+        let previous = &records[index - 1];
+        check_contiguous(&stitched, previous, record, offset)
+            .map_err(|gap| StitchError::NotContiguous { index, gap })?;
+
+        append(&mut stitched, record, offset);
+    }
+
+    stitched.data.sample_numbers = (1..=stitched.data.timestamps.len() as u64).collect();
+    stitched.config.total_num_samples = stitched.data.timestamps.len() as u64;
+    Ok(stitched)
+}
+
+fn same_channels(left: &Comtrade, right: &Comtrade) -> bool {
+    left.config.analog_channels.len() == right.config.analog_channels.len()
+        && left.config.status_channels.len() == right.config.status_channels.len()
+        && left
+            .config
+            .analog_channels
+            .iter()
+            .zip(right.config.analog_channels.iter())
+            .all(|(l, r)| l.name.trim() == r.name.trim() && l.units.trim() == r.units.trim())
+        && left
+            .config
+            .status_channels
+            .iter()
+            .zip(right.config.status_channels.iter())
+            .all(|(l, r)| l.name.trim() == r.name.trim())
+}
+
+/// Nanoseconds to add to the record's timestamps to make them relative to the first
+/// record's start time.
+fn start_offset(first: &Comtrade, record: &Comtrade) -> Option<i64> {
+    let utc_difference = match (first.config.time_offset, record.config.time_offset) {
+        (Some(f), Some(r)) => (f.local_minus_utc() - r.local_minus_utc()) as i64 * 1_000_000_000,
+        _ => 0,
+    };
+    Some((record.config.start_time - first.config.start_time).num_nanoseconds()? + utc_difference)
+}
+
+/// Check that the record starts a sample period after the end of what's been stitched
+/// so far, giving the gap between them if it doesn't.
+fn check_contiguous(
+    stitched: &Comtrade,
+    previous: &Comtrade,
+    record: &Comtrade,
+    offset: i64,
+) -> Result<(), i64> {
+    let (last, first) = match (
+        stitched.data.timestamps.last(),
+        record.data.timestamps.first(),
+    ) {
+        (Some(last), Some(first)) => (*last, first + offset),
+        _ => return Ok(()),
+    };
+    let gap = first - last;
+
+    let period = match previous.config.sampling_rates.last() {
+        Some(rate) if rate.rate_hz > 0.0 => 1e9 / rate.rate_hz,
+        _ => match previous.data.timestamps.as_slice() {
+            [.., before, last] => (last - before) as f64,
+            _ => return if gap > 0 { Ok(()) } else { Err(gap) },
+        },
+    };
+    if ((gap as f64) - period).abs() <= period / 2.0 {
+        Ok(())
+    } else {
+        Err(gap)
+    }
+}
+
+fn append(stitched: &mut Comtrade, record: &Comtrade, offset: i64) {
+    let n_before = stitched.data.timestamps.len();
+
+    stitched
+        .data
+        .timestamps
+        .extend(record.data.timestamps.iter().map(|t| t + offset));
+    for (channel, other) in stitched
+        .config
+        .analog_channels
+        .iter_mut()
+        .zip(record.config.analog_channels.iter())
+    {
+        channel.min_value = channel.min_value.min(other.min_value);
+        channel.max_value = channel.max_value.max(other.max_value);
+    }
+    for (data, other) in stitched
+        .data
+        .analog_data
+        .iter_mut()
+        .zip(record.data.analog_data.iter())
+    {
+        data.extend_from_slice(other);
+    }
+    for (i, quality) in stitched.data.analog_quality.iter_mut().enumerate() {
+        // Quality flags only line up with the samples if both records have them.
+        let other = record
+            .data
+            .analog_quality
+            .get(i)
+            .map_or(&[][..], |other| &other[..]);
+        if quality.len() == n_before && !other.is_empty() {
+            quality.extend_from_slice(other);
+        } else {
+            quality.clear();
+        }
+    }
+    for (data, other) in stitched
+        .data
+        .status_data
+        .iter_mut()
+        .zip(record.data.status_data.iter())
+    {
+        data.extend_from_slice(other);
+    }
+
+    // Records without fixed sampling rates can't be described by a rate table, so the
+    // stitched record can't either.
+    if stitched.config.sampling_rates.is_empty() || record.config.sampling_rates.is_empty() {
+        stitched.config.sampling_rates.clear();
+    } else {
+        for rate in record.config.sampling_rates.iter() {
+            let end_sample_number = rate.end_sample_number + n_before as u64;
+            match stitched.config.sampling_rates.last_mut() {
+                Some(last) if last.rate_hz == rate.rate_hz => {
+                    last.end_sample_number = end_sample_number
+                }
+                _ => stitched.config.sampling_rates.push(SamplingRate {
+                    rate_hz: rate.rate_hz,
+                    end_sample_number,
+                }),
+            }
+        }
+    }
+
+    stitched
+        .data
+        .irregular_samples
+        .extend(
+            record
+                .data
+                .irregular_samples
+                .iter()
+                .map(|s| IrregularSample {
+                    index: s.index + n_before,
+                    ..s.clone()
+                }),
+        );
+    stitched
+        .config
+        .warnings
+        .extend(record.config.warnings.iter().cloned());
+}
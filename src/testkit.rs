@@ -0,0 +1,269 @@
+//! Small, valid COMTRADE files for each revision and data format, generated on the fly
+//! so that crates using this one can test against real inputs without checking binary
+//! fixtures into their repositories.
+//!
+//! ```
+//! use comtrade::testkit::TestRecord;
+//!
+//! for test_record in TestRecord::all() {
+//!     let files = test_record.files();
+//!     let record = files.parser().parse().expect("test record should parse");
+//!     assert_eq!(record.data.analog_data[0][2], test_record.analog_value(0, 2));
+//! }
+//! ```
+
+use alloc::format;
+use alloc::string::String;
+use alloc::vec::Vec;
+
+use byteorder::{ByteOrder, LittleEndian};
+
+use crate::parser::round_nanoseconds;
+use crate::{ComtradeParser, DataFormat, FormatRevision};
+
+/// Record to generate files for. Start from `TestRecord::new()` and change whichever
+/// fields matter for the test.
+///
+/// Each analog channel is called `A1`, `A2` and so on, with a multiplier of 1 and no
+/// offset so that the values in the data file are the values the parser gives back,
+/// and each status channel `D1`, `D2` and so on. The values are given by
+/// `analog_value()` and `status_value()`. Samples are evenly spaced at `sample_rate_hz`
+/// from midnight on 1st January 2020, which is also the trigger time.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TestRecord {
+    pub revision: FormatRevision,
+    pub data_format: DataFormat,
+    pub num_analog_channels: u32,
+    pub num_status_channels: u32,
+    pub num_samples: u32,
+    pub sample_rate_hz: f64,
+    pub line_frequency: f64,
+}
+
+impl TestRecord {
+    /// Record with a single analog and status channel and 4 samples at 1 kHz on a 50 Hz
+    /// system.
+    ///
+    /// The 32-bit binary and floating point formats only exist in the 2013 revision,
+    /// but files are generated for any combination as the parser doesn't check.
+    pub fn new(revision: FormatRevision, data_format: DataFormat) -> Self {
+        TestRecord {
+            revision,
+            data_format,
+            num_analog_channels: 1,
+            num_status_channels: 1,
+            num_samples: 4,
+            sample_rate_hz: 1000.0,
+            line_frequency: 50.0,
+        }
+    }
+
+    /// Default record for each combination of revision and data format allowed by the
+    /// standard.
+    pub fn all() -> Vec<TestRecord> {
+        [
+            (FormatRevision::Revision1991, DataFormat::Ascii),
+            (FormatRevision::Revision1991, DataFormat::Binary16),
+            (FormatRevision::Revision1999, DataFormat::Ascii),
+            (FormatRevision::Revision1999, DataFormat::Binary16),
+            (FormatRevision::Revision2013, DataFormat::Ascii),
+            (FormatRevision::Revision2013, DataFormat::Binary16),
+            (FormatRevision::Revision2013, DataFormat::Binary32),
+            (FormatRevision::Revision2013, DataFormat::Float32),
+        ]
+        .into_iter()
+        .map(|(revision, data_format)| TestRecord::new(revision, data_format))
+        .collect()
+    }
+
+    /// Value of analog channel `channel` (counting from 0) at sample `sample` (also
+    /// from 0). These are whole numbers between -1000 and 1000, so they're exact in
+    /// every data format.
+    pub fn analog_value(&self, channel: u32, sample: u32) -> f64 {
+        ((sample as u64 * 7 + channel as u64 * 100) % 2001) as f64 - 1000.0
+    }
+
+    /// Value of status channel `channel` (counting from 0) at sample `sample` (also from
+    /// 0). Channel `n` switches every `n + 1` samples.
+    pub fn status_value(&self, channel: u32, sample: u32) -> u8 {
+        ((sample / (channel + 1)) % 2) as u8
+    }
+
+    /// Timestamp of sample `sample` (counting from 0) in nanoseconds since the start of
+    /// the record, as the parser gives it.
+    pub fn timestamp(&self, sample: u32) -> i64 {
+        round_nanoseconds(sample as f64 * 1e9 / self.sample_rate_hz)
+    }
+
+    /// Generate the `.cfg` and `.dat` files for the record.
+    pub fn files(&self) -> TestFiles {
+        TestFiles {
+            data_format: self.data_format.clone(),
+            cfg: self.cfg().into_bytes(),
+            dat: match self.data_format {
+                DataFormat::Ascii => self.ascii_dat().into_bytes(),
+                _ => self.binary_dat(),
+            },
+        }
+    }
+
+    // The files use CR/LF line endings, as the standard asks for. 1991 files have the
+    // same channel lines as later revisions, as that's what the parser reads.
+    fn cfg(&self) -> String {
+        let mut cfg = String::new();
+        let mut line = |line: String| {
+            cfg.push_str(&line);
+            cfg.push_str("\r\n");
+        };
+
+        line(match self.revision {
+            FormatRevision::Revision1991 => "TEST STATION,TEST DEVICE".into(),
+            FormatRevision::Revision1999 => "TEST STATION,TEST DEVICE,1999".into(),
+            FormatRevision::Revision2013 => "TEST STATION,TEST DEVICE,2013".into(),
+        });
+        line(format!(
+            "{},{}A,{}D",
+            self.num_analog_channels + self.num_status_channels,
+            self.num_analog_channels,
+            self.num_status_channels
+        ));
+
+        let (min, max) = match self.data_format {
+            DataFormat::Ascii | DataFormat::Binary16 => (-32767, 32767),
+            _ => (-99999, 99999),
+        };
+        for i in 1..=self.num_analog_channels {
+            line(format!("{i},A{i},,Line1,V,1,0,0,{min},{max},1,1,P"));
+        }
+        for i in 1..=self.num_status_channels {
+            line(format!("{i},D{i},,Line1,0"));
+        }
+
+        line(format!("{}", self.line_frequency));
+        line("1".into());
+        line(format!("{},{}", self.sample_rate_hz, self.num_samples));
+
+        // The 1991 revision has the month first, later ones the day, so use a date where
+        // it doesn't matter.
+        line("01/01/2020,00:00:00.000000".into());
+        line("01/01/2020,00:00:00.000000".into());
+
+        line(
+            match self.data_format {
+                DataFormat::Ascii => "ASCII",
+                DataFormat::Binary16 => "BINARY",
+                DataFormat::Binary32 => "BINARY32",
+                DataFormat::Float32 => "FLOAT32",
+            }
+            .into(),
+        );
+        if self.revision != FormatRevision::Revision1991 {
+            line("1".into());
+        }
+        if self.revision == FormatRevision::Revision2013 {
+            line("0,0".into());
+            line("0,0".into());
+        }
+
+        cfg
+    }
+
+    /// Timestamp in the data file, in microseconds.
+    fn raw_timestamp(&self, sample: u32) -> u32 {
+        (self.timestamp(sample) / 1000) as u32
+    }
+
+    fn ascii_dat(&self) -> String {
+        let mut dat = String::new();
+        for sample in 0..self.num_samples {
+            dat.push_str(&format!("{},{}", sample + 1, self.raw_timestamp(sample)));
+            for channel in 0..self.num_analog_channels {
+                dat.push_str(&format!(",{}", self.analog_value(channel, sample)));
+            }
+            for channel in 0..self.num_status_channels {
+                dat.push_str(&format!(",{}", self.status_value(channel, sample)));
+            }
+            dat.push_str("\r\n");
+        }
+        dat
+    }
+
+    fn binary_dat(&self) -> Vec<u8> {
+        let mut dat = Vec::new();
+        let mut word = [0; 4];
+        for sample in 0..self.num_samples {
+            LittleEndian::write_u32(&mut word, sample + 1);
+            dat.extend_from_slice(&word);
+            LittleEndian::write_u32(&mut word, self.raw_timestamp(sample));
+            dat.extend_from_slice(&word);
+
+            for channel in 0..self.num_analog_channels {
+                let value = self.analog_value(channel, sample);
+                match self.data_format {
+                    DataFormat::Binary16 => {
+                        LittleEndian::write_i16(&mut word, value as i16);
+                        dat.extend_from_slice(&word[..2]);
+                    }
+                    DataFormat::Binary32 => {
+                        LittleEndian::write_i32(&mut word, value as i32);
+                        dat.extend_from_slice(&word);
+                    }
+                    _ => {
+                        LittleEndian::write_f32(&mut word, value as f32);
+                        dat.extend_from_slice(&word);
+                    }
+                }
+            }
+
+            // Status channels are packed 16 to a word, least significant bit first.
+            for group in 0..self.num_status_channels.div_ceil(16) {
+                let bits = (group * 16..self.num_status_channels.min(group * 16 + 16))
+                    .map(|channel| (self.status_value(channel, sample) as u16) << (channel % 16))
+                    .fold(0, |bits, bit| bits | bit);
+                LittleEndian::write_u16(&mut word, bits);
+                dat.extend_from_slice(&word[..2]);
+            }
+        }
+        dat
+    }
+}
+
+/// Contents of the files generated for a `TestRecord`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TestFiles {
+    pub data_format: DataFormat,
+    pub cfg: Vec<u8>,
+    pub dat: Vec<u8>,
+}
+
+impl TestFiles {
+    /// Parser for the files.
+    pub fn parser(&self) -> ComtradeParser<'_> {
+        ComtradeParser::from_bytes(&self.cfg, &self.dat)
+    }
+
+    /// The same record as a combined `.cff` file, with empty `.inf` and `.hdr`
+    /// sections. Note that the parser can't yet read binary data in `.cff` files.
+    pub fn cff(&self) -> Vec<u8> {
+        let mut cff = Vec::new();
+        cff.extend_from_slice(b"--- file type: CFG ---\r\n");
+        cff.extend_from_slice(&self.cfg);
+        cff.extend_from_slice(b"--- file type: INF ---\r\n");
+        cff.extend_from_slice(b"--- file type: HDR ---\r\n");
+        let data_header = match self.data_format {
+            DataFormat::Ascii => "--- file type: DAT ASCII ---\r\n".into(),
+            DataFormat::Binary16 => {
+                format!("--- file type: DAT BINARY: {} ---\r\n", self.dat.len())
+            }
+            DataFormat::Binary32 => {
+                format!("--- file type: DAT BINARY32: {} ---\r\n", self.dat.len())
+            }
+            DataFormat::Float32 => {
+                format!("--- file type: DAT FLOAT32: {} ---\r\n", self.dat.len())
+            }
+        };
+        cff.extend_from_slice(data_header.as_bytes());
+        cff.extend_from_slice(&self.dat);
+        cff
+    }
+}
@@ -0,0 +1,52 @@
+//! Named time zones for records, for rendering the start and trigger times as local
+//! wall-clock times in human-facing reports. The `.cfg` file only gives fixed offsets
+//! from UTC, so the zone the recorder was in can only be narrowed down, not known.
+
+use alloc::vec::Vec;
+
+use chrono::{DateTime, Duration, NaiveDateTime, Offset, TimeZone};
+use chrono_tz::{Tz, TZ_VARIANTS};
+
+use crate::Comtrade;
+
+impl Comtrade {
+    /// Named time zones whose offset from UTC at the start of the record is the record's
+    /// `local_offset`, in alphabetical order. Empty if the record doesn't have a local
+    /// offset.
+    ///
+    /// Daylight saving is taken into account, so e.g. a `+1h` record from July matches
+    /// `Europe/London` but one from January doesn't.
+    pub fn local_time_zones(&self) -> Vec<Tz> {
+        let local_offset = match self.config.local_offset {
+            Some(local_offset) => local_offset,
+            None => return Vec::new(),
+        };
+        let start_utc = self.to_utc(self.config.start_time);
+
+        TZ_VARIANTS
+            .iter()
+            .filter(|tz| tz.offset_from_utc_datetime(&start_utc).fix() == local_offset)
+            .copied()
+            .collect()
+    }
+
+    /// Start time of the record as wall-clock time in the time zone `tz`.
+    pub fn start_time_in(&self, tz: Tz) -> DateTime<Tz> {
+        tz.from_utc_datetime(&self.to_utc(self.config.start_time))
+    }
+
+    /// Trigger time of the record as wall-clock time in the time zone `tz`.
+    pub fn trigger_time_in(&self, tz: Tz) -> DateTime<Tz> {
+        tz.from_utc_datetime(&self.to_utc(self.config.trigger_time))
+    }
+
+    /// Time in the record's time code as UTC. If there isn't a time code, the time is
+    /// assumed to be in UTC already.
+    fn to_utc(&self, time: NaiveDateTime) -> NaiveDateTime {
+        time - Duration::seconds(
+            self.config
+                .time_offset
+                .map_or(0, |offset| offset.local_minus_utc() as i64),
+        )
+    }
+}
@@ -0,0 +1,136 @@
+//! Detection of analog channels whose units don't match their values, e.g. a channel in
+//! kV whose values are in the hundreds of thousands because the device actually wrote
+//! them in volts. Plenty of devices get this wrong in their `.cfg` files.
+//!
+//! This only catches values which are far too large for the units to be right, as no
+//! real voltage or current gets anywhere near them. Values which are merely on the small
+//! side could just as well be a quiet channel, so are left alone.
+
+use alloc::format;
+use alloc::string::String;
+use alloc::vec::Vec;
+
+use crate::{AnalogChannel, Comtrade};
+
+/// Largest voltage in volts that a channel could plausibly see, well above the peak of
+/// the highest transmission voltages.
+const MAX_PLAUSIBLE_VOLTS: f64 = 2e6;
+
+/// Largest current in amps that a channel could plausibly see, well above the largest
+/// fault currents.
+const MAX_PLAUSIBLE_AMPS: f64 = 5e5;
+
+/// SI prefixes the units can be rescaled between, as they're written in units.
+const PREFIXES: &[(&str, f64)] = &[("m", 1e-3), ("", 1.0), ("k", 1e3), ("M", 1e6)];
+
+/// Analog channel whose units look wrong for its values.
+#[derive(Debug, Clone, PartialEq)]
+pub struct UnitFix {
+    pub channel_index: u32,
+    pub channel_name: String,
+
+    /// Units as they were in the record.
+    pub units: String,
+
+    /// Units which fit the values, e.g. `V` rather than `kV`. The values are assumed to
+    /// be right, so only the units need changing.
+    pub suggested_units: String,
+
+    /// Largest magnitude of the channel's values.
+    pub peak: f64,
+}
+
+/// Analog channels whose units were found to be wrong, see `Comtrade::check_units()`.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct UnitFixReport {
+    pub fixes: Vec<UnitFix>,
+}
+
+impl UnitFixReport {
+    /// Whether all the channels' units look right.
+    pub fn is_empty(&self) -> bool {
+        self.fixes.is_empty()
+    }
+}
+
+impl Comtrade {
+    /// Find analog voltage and current channels whose units can't be right for their
+    /// values, and suggest units which fit. The record isn't changed - see
+    /// `fix_units()` to apply the suggestions.
+    pub fn check_units(&self) -> UnitFixReport {
+        UnitFixReport {
+            fixes: self
+                .analog_channels_with_data()
+                .filter_map(|(channel, data)| check_channel(channel, data))
+                .collect(),
+        }
+    }
+
+    /// Change the units of any channels found by `check_units()` to the suggested
+    /// units, returning what was changed. The values are left as they are.
+    pub fn fix_units(&mut self) -> UnitFixReport {
+        let report = self.check_units();
+        for fix in report.fixes.iter() {
+            if let Some(channel) = self
+                .config
+                .analog_channels
+                .iter_mut()
+                .find(|c| c.index == fix.channel_index)
+            {
+                channel.units = fix.suggested_units.clone();
+            }
+        }
+        report
+    }
+}
+
+fn check_channel(channel: &AnalogChannel, data: &[f64]) -> Option<UnitFix> {
+    let units = channel.units.trim();
+    let (multiplier, base) = parse_units(units)?;
+    let max_plausible = if base.eq_ignore_ascii_case("V") {
+        MAX_PLAUSIBLE_VOLTS
+    } else {
+        MAX_PLAUSIBLE_AMPS
+    };
+
+    let peak = data
+        .iter()
+        .filter(|v| v.is_finite())
+        .fold(0.0, |peak: f64, v| peak.max(v.abs()));
+    if peak * multiplier <= max_plausible {
+        return None;
+    }
+
+    // Largest prefix smaller than the current one which makes the values plausible.
+    let (prefix, _) = PREFIXES
+        .iter()
+        .rev()
+        .filter(|(_, m)| *m < multiplier)
+        .find(|(_, m)| peak * m <= max_plausible)?;
+
+    Some(UnitFix {
+        channel_index: channel.index,
+        channel_name: channel.name.clone(),
+        units: channel.units.clone(),
+        suggested_units: format!("{}{}", prefix, base),
+        peak,
+    })
+}
+
+/// Multiplier of the prefix and the base unit, for units of voltage or current like
+/// `kV`, `KV` or `A`. `None` for anything else.
+fn parse_units(units: &str) -> Option<(f64, &str)> {
+    let (prefix, base) = units.split_at(units.char_indices().last()?.0);
+    if !base.eq_ignore_ascii_case("V") && !base.eq_ignore_ascii_case("A") {
+        return None;
+    }
+
+    let multiplier = match prefix {
+        "" => 1.0,
+        "m" => 1e-3,
+        "k" | "K" => 1e3,
+        "M" => 1e6,
+        _ => return None,
+    };
+    Some((multiplier, base))
+}
@@ -0,0 +1,117 @@
+/// Deviations from the COMTRADE standard that the parser can be told to work around.
+///
+/// These are normally picked via a `VendorProfile`, but can be set individually using
+/// `VendorProfile::Custom` if your recording devices don't fit any of the known
+/// profiles. More quirks may be added, so they're set with the methods below rather
+/// than written out:
+///
+/// ```rust
+/// use comtrade::{Quirks, VendorProfile};
+///
+/// let profile = VendorProfile::Custom(Quirks::new().month_first_dates(true));
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+#[non_exhaustive]
+pub struct Quirks {
+    /// Strip leading and trailing whitespace from channel phases, circuit components
+    /// and units, which some devices pad out to a fixed width. Channel names are always
+    /// trimmed, with the original kept in `name_raw`.
+    pub trim_channel_fields: bool,
+
+    /// Dates in 1999 and 2013 files are given as mm/dd/yyyy (as in the 1991 revision)
+    /// rather than the dd/mm/yyyy required by the standard.
+    pub month_first_dates: bool,
+
+    /// Accept common variations on the standard tokens, e.g. `BINARY16` or `BIN` for
+    /// the `BINARY` data format and `PRIMARY` / `SECONDARY` for the scaling mode.
+    pub nonstandard_tokens: bool,
+
+    /// Fields are separated by semicolons and real numbers use a comma as the decimal
+    /// separator, e.g. `0,5;1,25`. This is detected automatically (with a warning) if
+    /// the file is consistently semicolon-separated, but can be forced on here.
+    pub decimal_comma: bool,
+}
+
+impl Quirks {
+    /// No quirks enabled, i.e. follow the standard.
+    pub fn new() -> Self {
+        Quirks::default()
+    }
+
+    pub fn trim_channel_fields(mut self, enabled: bool) -> Self {
+        self.trim_channel_fields = enabled;
+        self
+    }
+
+    pub fn month_first_dates(mut self, enabled: bool) -> Self {
+        self.month_first_dates = enabled;
+        self
+    }
+
+    pub fn nonstandard_tokens(mut self, enabled: bool) -> Self {
+        self.nonstandard_tokens = enabled;
+        self
+    }
+
+    pub fn decimal_comma(mut self, enabled: bool) -> Self {
+        self.decimal_comma = enabled;
+        self
+    }
+}
+
+/// Profiles for recording device manufacturers whose files are known to deviate from
+/// the COMTRADE standard in some way, for use with
+/// `ComtradeParserBuilder::vendor_profile()`. Vendor-specific metadata in the `.inf`
+/// file and extra `.cfg` columns ends up in `ComtradeConfig::extensions` whichever
+/// profile is used.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum VendorProfile {
+    /// Follow the standard - no quirks enabled.
+    #[default]
+    Standard,
+
+    /// SEL relays pad the fields of the channel lines out to a fixed width, so this
+    /// turns on `Quirks::trim_channel_fields`.
+    Sel,
+
+    /// GE recorders write dates month first in 1999 and 2013 files, carried over from
+    /// the 1991 revision, so this turns on `Quirks::month_first_dates`.
+    Ge,
+
+    /// Siemens recorders pad the fields of the channel lines like SEL relays, so this
+    /// turns on `Quirks::trim_channel_fields`.
+    Siemens,
+
+    /// OMICRON test sets write variations on the standard tokens, e.g. `BINARY16` or
+    /// `PRIMARY`, so this turns on `Quirks::nonstandard_tokens`.
+    Omicron,
+
+    /// Pick and choose exactly which quirks to work around.
+    Custom(Quirks),
+}
+
+impl VendorProfile {
+    /// The set of quirks handled when parsing with this profile.
+    pub fn quirks(&self) -> Quirks {
+        match self {
+            VendorProfile::Standard => Quirks::default(),
+            VendorProfile::Sel => Quirks {
+                trim_channel_fields: true,
+                ..Quirks::default()
+            },
+            VendorProfile::Ge => Quirks {
+                month_first_dates: true,
+                ..Quirks::default()
+            },
+            VendorProfile::Siemens => Quirks {
+                trim_channel_fields: true,
+                ..Quirks::default()
+            },
+            VendorProfile::Omicron => Quirks {
+                nonstandard_tokens: true,
+                ..Quirks::default()
+            },
+            VendorProfile::Custom(quirks) => *quirks,
+        }
+    }
+}
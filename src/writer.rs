@@ -0,0 +1,750 @@
+//! Writing records back out as `.cfg` and `.dat` files, e.g. after adding computed
+//! channels for viewers that can't work them out for themselves.
+
+use alloc::borrow::Cow;
+use alloc::collections::BTreeMap;
+use alloc::format;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use core::fmt;
+
+use byteorder::{ByteOrder, LittleEndian};
+use chrono::{FixedOffset, NaiveDateTime, Timelike};
+
+#[cfg(feature = "zip")]
+use crate::ComName;
+use crate::{
+    AnalogChannel, AnalogScalingMode, Comtrade, DataFormat, FormatRevision, LeapSecondStatus,
+    Phase, SourceFiles, StatusChannel, TimeQuality,
+};
+
+/// Why a channel couldn't be added to a record.
+#[derive(Debug, Clone, PartialEq)]
+pub enum AppendChannelError {
+    /// The channel doesn't have a value for every sample in the record.
+    WrongLength { expected: usize, found: usize },
+}
+
+impl fmt::Display for AppendChannelError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AppendChannelError::WrongLength { expected, found } => write!(
+                f,
+                "channel has {} values but the record has {} samples",
+                found, expected
+            ),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for AppendChannelError {}
+
+impl Comtrade {
+    /// Add an analog channel and its values after the existing ones, e.g. a residual
+    /// current computed from the phase currents, returning the index it was given. The
+    /// channel counts are updated to match.
+    ///
+    /// The channel's `min_value` and `max_value` are set to the range of its values as
+    /// they'll be written to the `.dat` file. For the 16 and 32-bit binary formats, its
+    /// `multiplier` and `offset_adder` are also worked out so that its values span the
+    /// whole range of the integers in the file, as there's no telling whether the ones
+    /// it came with suit the binary format.
+    pub fn append_analog_channel(
+        &mut self,
+        mut channel: AnalogChannel,
+        data: Vec<f64>,
+    ) -> Result<u32, AppendChannelError> {
+        self.check_channel_length(data.len())?;
+
+        if let Some(max_raw) = max_raw_value(&self.config.data_format) {
+            let (min, max) = value_range(&data);
+            let (multiplier, offset_adder) = if min > max {
+                (1.0, 0.0)
+            } else if min == max {
+                (1.0, min)
+            } else {
+                ((max - min) / (2.0 * max_raw), (max + min) / 2.0)
+            };
+            channel.multiplier = multiplier;
+            channel.offset_adder = offset_adder;
+        }
+
+        let (min, max) = value_range(&data);
+        let (min, max) = if min > max { (0.0, 0.0) } else { (min, max) };
+        channel.min_value = raw_value(&channel, min);
+        channel.max_value = raw_value(&channel, max);
+        if max_raw_value(&self.config.data_format).is_some() {
+            channel.min_value = round(channel.min_value);
+            channel.max_value = round(channel.max_value);
+        }
+
+        channel.index = self.config.analog_channels.len() as u32 + 1;
+        let index = channel.index;
+        self.config.analog_channels.push(channel);
+        self.data.analog_data.push(data);
+        self.update_channel_counts();
+        Ok(index)
+    }
+
+    /// Add a status channel and its values after the existing ones, returning the index
+    /// it was given. The channel counts are updated to match.
+    pub fn append_status_channel(
+        &mut self,
+        mut channel: StatusChannel,
+        data: Vec<u8>,
+    ) -> Result<u32, AppendChannelError> {
+        self.check_channel_length(data.len())?;
+
+        channel.index = self.config.status_channels.len() as u32 + 1;
+        let index = channel.index;
+        self.config.status_channels.push(channel);
+        self.data.status_data.push(data);
+        self.update_channel_counts();
+        Ok(index)
+    }
+
+    fn check_channel_length(&self, found: usize) -> Result<(), AppendChannelError> {
+        let expected = self.data.timestamps.len();
+        if found == expected {
+            Ok(())
+        } else {
+            Err(AppendChannelError::WrongLength { expected, found })
+        }
+    }
+
+    fn update_channel_counts(&mut self) {
+        self.config.num_analog_channels = self.config.analog_channels.len() as u32;
+        self.config.num_status_channels = self.config.status_channels.len() as u32;
+        self.config.num_total_channels =
+            self.config.num_analog_channels + self.config.num_status_channels;
+    }
+}
+
+/// Writes a record out as `.cfg` and `.dat` files, in the revision and data format
+/// given in the record.
+///
+/// Analog values are scaled back to what they were in the `.dat` file using each
+/// channel's `multiplier` and `offset_adder`, with missing values written as blanks in
+/// ASCII files and the most negative integer in binary files. Timestamps are written as
+/// they were in the original `.dat` file where they're known, otherwise in microseconds
+/// or nanoseconds as the record's timestamps need. Extra columns
+/// kept from the channel lines of the original `.cfg` file and its vendor-specific
+/// lines are written back too. The `.inf` file can be rebuilt from the extensions with
+/// `inf()`.
+///
+/// ```rust
+/// use comtrade::writer::ComtradeWriter;
+/// use comtrade::ComtradeParser;
+/// # let (cfg, dat) = (
+/// #     "STATION,DEVICE,1999\n1,1A,0D\n1,IA,A,,A,1,0,0,-99,99,1,1,P\n50\n1\n1000,2\n\
+/// #      01/01/2020,00:00:00.000000\n01/01/2020,00:00:00.000000\nASCII\n1\n",
+/// #     "1,0,5\n2,1000,6\n",
+/// # );
+///
+/// let record = ComtradeParser::from_bytes(cfg.as_bytes(), dat.as_bytes()).parse()?;
+/// let writer = ComtradeWriter::new(&record);
+/// let (cfg, dat) = (writer.cfg(), writer.dat());
+///
+/// let written = ComtradeParser::from_bytes(cfg.as_bytes(), &dat).parse()?;
+/// assert_eq!(written.config.analog_channels, record.config.analog_channels);
+/// assert_eq!(written.data.analog_data, record.data.analog_data);
+/// # Ok::<(), comtrade::ParseError>(())
+/// ```
+pub struct ComtradeWriter<'a> {
+    record: &'a Comtrade,
+
+    /// Nanoseconds in the unit the timestamps are written in.
+    timestamp_unit: i64,
+    primary_values: bool,
+    passthrough: bool,
+    name_padding: bool,
+
+    /// Decimal places to round non-integer values in ASCII `.dat` files to, if any.
+    precision: Option<usize>,
+}
+
+impl<'a> ComtradeWriter<'a> {
+    pub fn new(record: &'a Comtrade) -> Self {
+        // Timestamps are in nanoseconds if the start or trigger time has a fraction of a
+        // microsecond, as in the file they came from, or if the timestamps are all
+        // there is to go on and they need it. Otherwise they're in microseconds.
+        let sub_microsecond = |time: &NaiveDateTime| !time.nanosecond().is_multiple_of(1000);
+        let multiplier = record.config.timestamp_multiplication_factor;
+        let needs_nanoseconds = record.config.sampling_rates.is_empty()
+            && record.data.timestamps.iter().any(|t| {
+                let raw = *t as f64 / (1000.0 * multiplier);
+                (raw - round(raw)).abs() > 1e-6
+            });
+        let nanoseconds = sub_microsecond(&record.config.start_time)
+            || sub_microsecond(&record.config.trigger_time)
+            || needs_nanoseconds;
+        ComtradeWriter {
+            record,
+            timestamp_unit: if nanoseconds { 1 } else { 1000 },
+            primary_values: false,
+            passthrough: false,
+            name_padding: true,
+            precision: None,
+        }
+    }
+
+    /// Write every analog channel in primary values, e.g. to standardise an archive
+    /// which mixes records scaled either way. Channels in secondary values have their
+    /// multiplier and offset scaled by the ratio of their primary and secondary factors
+    /// and are flagged as primary, so the values in the `.dat` file stay the same.
+    pub fn primary_values(mut self, primary_values: bool) -> Self {
+        self.primary_values = primary_values;
+        self
+    }
+
+    /// Write the files the record was parsed from exactly as they were, if the parser
+    /// kept them and the record hasn't been changed since, see
+    /// `ComtradeParser::keep_source_files()`. Otherwise, and when writing in primary
+    /// values, the files are written out as normal. A record parsed against an existing
+    /// configuration has no `.cfg` file to pass through, so only its `.dat` file is.
+    pub fn passthrough(mut self, passthrough: bool) -> Self {
+        self.passthrough = passthrough;
+        self
+    }
+
+    /// Write channel names padded as they were in the file they were parsed from, i.e.
+    /// as in `name_raw`, which is the default. Otherwise they're written trimmed. A
+    /// channel whose `name` no longer matches its `name_raw`, e.g. because it's been
+    /// renamed, is always written with its `name`.
+    pub fn name_padding(mut self, name_padding: bool) -> Self {
+        self.name_padding = name_padding;
+        self
+    }
+
+    /// Round values in ASCII `.dat` files which aren't whole numbers to at most this
+    /// many decimal places, dropping trailing zeros, e.g. to keep diffs between
+    /// versions of a record clean of noise from scaling. By default, values are written
+    /// with the fewest digits that read back as exactly the same value.
+    pub fn precision(mut self, precision: Option<usize>) -> Self {
+        self.precision = precision;
+        self
+    }
+
+    /// Files to write out as they are, if passing them through.
+    fn source_files(&self) -> Option<&SourceFiles> {
+        if !self.passthrough || self.primary_values || !self.record.is_unmodified() {
+            return None;
+        }
+        self.record.data.source_files.as_ref()
+    }
+
+    /// Name the channel is written with, see `name_padding()`.
+    fn channel_name<'c>(&self, name: &'c str, name_raw: &'c str) -> &'c str {
+        if self.name_padding {
+            channel_name(name, name_raw)
+        } else {
+            name
+        }
+    }
+
+    /// Multiplier, offset and scaling mode the channel is written with.
+    fn scaling(&self, channel: &AnalogChannel) -> (f64, f64, AnalogScalingMode) {
+        match channel.scaling_mode {
+            AnalogScalingMode::Secondary
+                if self.primary_values && channel.secondary_factor != 0.0 =>
+            {
+                let ratio = channel.primary_factor / channel.secondary_factor;
+                (
+                    channel.multiplier * ratio,
+                    channel.offset_adder * ratio,
+                    AnalogScalingMode::Primary,
+                )
+            }
+            _ => (
+                channel.multiplier,
+                channel.offset_adder,
+                channel.scaling_mode.clone(),
+            ),
+        }
+    }
+
+    /// Contents of the `.cfg` file.
+    pub fn cfg(&self) -> String {
+        let source_cfg = self.source_files().and_then(|source| source.cfg.as_ref());
+        // The parser only accepts `.cfg` files in UTF-8, so this is never lossy.
+        if let Some(cfg) = source_cfg.and_then(|cfg| core::str::from_utf8(cfg).ok()) {
+            return cfg.to_string();
+        }
+
+        let record = self.record;
+        let mut cfg = String::new();
+        let mut line = |line: String| {
+            cfg.push_str(&line);
+            cfg.push_str("\r\n");
+        };
+
+        line(match record.config.revision {
+            FormatRevision::Revision1991 => {
+                format!(
+                    "{},{}",
+                    quoted(&record.config.station_name),
+                    quoted(&record.config.recording_device_id)
+                )
+            }
+            FormatRevision::Revision1999 => {
+                format!(
+                    "{},{},1999",
+                    quoted(&record.config.station_name),
+                    quoted(&record.config.recording_device_id)
+                )
+            }
+            FormatRevision::Revision2013 => {
+                format!(
+                    "{},{},2013",
+                    quoted(&record.config.station_name),
+                    quoted(&record.config.recording_device_id)
+                )
+            }
+        });
+        line(format!(
+            "{},{}A,{}D",
+            record.config.analog_channels.len() + record.config.status_channels.len(),
+            record.config.analog_channels.len(),
+            record.config.status_channels.len()
+        ));
+
+        for channel in record.config.analog_channels.iter() {
+            let (multiplier, offset_adder, scaling_mode) = self.scaling(channel);
+            line(format!(
+                "{},{},{},{},{},{},{},{},{},{},{},{},{}{}",
+                channel.index,
+                self.channel_name(&channel.name, &channel.name_raw),
+                channel_phase(&channel.phase, &channel.phase_raw),
+                channel.circuit_component_being_monitored,
+                channel.units,
+                multiplier,
+                offset_adder,
+                channel.skew,
+                channel.min_value,
+                channel.max_value,
+                channel.primary_factor,
+                channel.secondary_factor,
+                match scaling_mode {
+                    AnalogScalingMode::Primary => "P",
+                    AnalogScalingMode::Secondary => "S",
+                },
+                extra_columns(&channel.extensions),
+            ));
+        }
+        for channel in record.config.status_channels.iter() {
+            line(format!(
+                "{},{},{},{},{}{}",
+                channel.index,
+                self.channel_name(&channel.name, &channel.name_raw),
+                channel_phase(&channel.phase, &channel.phase_raw),
+                channel.circuit_component_being_monitored,
+                channel.normal_status_value,
+                extra_columns(&channel.extensions),
+            ));
+        }
+
+        line(format!("{}", record.config.line_frequency));
+        if record.config.sampling_rates.is_empty() {
+            line("0".to_string());
+            line(format!("0,{}", record.data.timestamps.len()));
+        } else {
+            line(format!("{}", record.config.sampling_rates.len()));
+            for rate in record.config.sampling_rates.iter() {
+                line(format!("{},{}", rate.rate_hz, rate.end_sample_number));
+            }
+        }
+
+        // The precision of the start and trigger times sets the unit of the timestamps.
+        let datetime_format = match (record.config.revision, self.timestamp_unit) {
+            (FormatRevision::Revision1991, 1000) => "%m/%d/%Y,%H:%M:%S%.6f",
+            (FormatRevision::Revision1991, _) => "%m/%d/%Y,%H:%M:%S%.9f",
+            (_, 1000) => "%d/%m/%Y,%H:%M:%S%.6f",
+            _ => "%d/%m/%Y,%H:%M:%S%.9f",
+        };
+        line(record.config.start_time.format(datetime_format).to_string());
+        line(
+            record
+                .config
+                .trigger_time
+                .format(datetime_format)
+                .to_string(),
+        );
+
+        line(
+            match record.config.data_format {
+                DataFormat::Ascii => "ASCII",
+                DataFormat::Binary16 => "BINARY",
+                DataFormat::Binary32 => "BINARY32",
+                DataFormat::Float32 => "FLOAT32",
+            }
+            .to_string(),
+        );
+
+        if record.config.revision != FormatRevision::Revision1991 {
+            line(format!("{}", record.config.timestamp_multiplication_factor));
+        }
+        if record.config.revision == FormatRevision::Revision2013 {
+            line(format!(
+                "{},{}",
+                time_offset(record.config.time_offset),
+                time_offset(record.config.local_offset)
+            ));
+            line(format!(
+                "{},{}",
+                time_quality(record.config.time_quality.as_ref()),
+                leap_second_status(record.config.leap_second_status.as_ref())
+            ));
+        }
+
+        for extra in record.config.extra_cfg_lines.iter() {
+            line(extra.clone());
+        }
+
+        cfg
+    }
+
+    /// Contents of the `.dat` file.
+    pub fn dat(&self) -> Vec<u8> {
+        if let Some(source) = self.source_files() {
+            return source.dat.clone();
+        }
+
+        match &self.record.config.data_format {
+            DataFormat::Ascii => self.ascii_dat().into_bytes(),
+            format => self.binary_dat(format),
+        }
+    }
+
+    /// Contents of the `.inf` file, rebuilt from the record's `extensions` and those of
+    /// its channels, or an empty string if there's nothing to go in it. Extensions which
+    /// came from elsewhere, i.e. the `COMNAME/` and `CFG/` ones, are left out.
+    pub fn inf(&self) -> String {
+        let mut sections: BTreeMap<String, Vec<(&str, &str)>> = BTreeMap::new();
+        let mut add = |extensions: &'a BTreeMap<String, String>, channel: Option<String>| {
+            for (name, value) in extensions.iter() {
+                let (owner, key) = match name.split_once('/') {
+                    Some((owner, _)) if owner == "COMNAME" || owner == "CFG" => continue,
+                    Some(entry) => entry,
+                    None => continue,
+                };
+                let section = match &channel {
+                    Some(channel) => format!("{} {}", owner, channel),
+                    None => owner.to_string(),
+                };
+                sections.entry(section).or_default().push((key, value));
+            }
+        };
+
+        add(&self.record.config.extensions, None);
+        for channel in self.record.config.analog_channels.iter() {
+            let name = format!("Analog_Channel_#{}", channel.index);
+            add(&channel.extensions, Some(name));
+        }
+        for channel in self.record.config.status_channels.iter() {
+            let name = format!("Status_Channel_#{}", channel.index);
+            add(&channel.extensions, Some(name));
+        }
+
+        let mut inf = String::new();
+        for (section, entries) in sections.iter() {
+            if !inf.is_empty() {
+                inf.push_str("\r\n");
+            }
+            inf.push_str(&format!("[{}]\r\n", section));
+            for (key, value) in entries.iter() {
+                inf.push_str(&format!("{}={}\r\n", key, value));
+            }
+        }
+        inf
+    }
+
+    /// Compressed sidecar to keep in place of the `.dat` file, from which the record can
+    /// be read back with `sidecar::read_sidecar()` along with the `.cfg` file. See the
+    /// `sidecar` module for the format.
+    #[cfg(feature = "sidecar")]
+    pub fn sidecar(&self) -> Vec<u8> {
+        let record = self.record;
+        let (format, dat) = match record.config.data_format {
+            DataFormat::Ascii => (DataFormat::Binary32, self.binary_dat(&DataFormat::Binary32)),
+            ref format => (format.clone(), self.dat()),
+        };
+        crate::sidecar::compress(
+            &format,
+            record.config.analog_channels.len() as u32,
+            record.config.status_channels.len() as u32,
+            &dat,
+        )
+    }
+
+    /// Write the record into a zip archive, as the `.cfg` and `.dat` files, along with
+    /// the `.hdr` and `.inf` files if there's anything to go in them. The files are named
+    /// after `name`, e.g. from `ComName::from_record()`, and `name.file_name("zip")` is
+    /// the conventional name for the archive itself.
+    #[cfg(feature = "zip")]
+    pub fn write_zip<W: std::io::Write + std::io::Seek>(
+        &self,
+        out: W,
+        name: &ComName,
+    ) -> zip::result::ZipResult<W> {
+        use std::io::Write;
+
+        let mut zip = zip::ZipWriter::new(out);
+        let options = zip::write::SimpleFileOptions::default()
+            .compression_method(zip::CompressionMethod::Deflated);
+
+        zip.start_file(name.file_name("cfg"), options)?;
+        zip.write_all(self.cfg().as_bytes())?;
+        zip.start_file(name.file_name("dat"), options)?;
+        zip.write_all(&self.dat())?;
+        if !self.record.config.header.is_empty() {
+            zip.start_file(name.file_name("hdr"), options)?;
+            zip.write_all(self.record.config.header.as_bytes())?;
+        }
+        let inf = self.inf();
+        if !inf.is_empty() {
+            zip.start_file(name.file_name("inf"), options)?;
+            zip.write_all(inf.as_bytes())?;
+        }
+
+        zip.finish()
+    }
+
+    fn ascii_dat(&self) -> String {
+        let record = self.record;
+        let mut dat = String::new();
+        for i in 0..record.data.timestamps.len() {
+            dat.push_str(&format!("{}", self.sample_number(i)));
+            dat.push(',');
+            if let Some(timestamp) = self.raw_timestamp(i) {
+                dat.push_str(&format!("{}", timestamp));
+            }
+            for (channel, data) in record.analog_channels_with_data() {
+                dat.push(',');
+                let value = data[i];
+                if !value.is_nan() {
+                    // Values which were whole numbers before scaling are written as
+                    // such, rather than with the rounding error from scaling them.
+                    let raw = raw_value(channel, value);
+                    if (raw - round(raw)).abs() < 1e-6 {
+                        dat.push_str(&format!("{}", round(raw)));
+                    } else {
+                        push_real(&mut dat, raw, self.precision);
+                    }
+                }
+            }
+            for data in record.data.status_data.iter() {
+                dat.push_str(&format!(",{}", data[i]));
+            }
+            dat.push_str("\r\n");
+        }
+        dat
+    }
+
+    /// Contents of the `.dat` file in the given binary format.
+    fn binary_dat(&self, format: &DataFormat) -> Vec<u8> {
+        let record = self.record;
+        let status_words = record.status_words();
+        let mut dat = Vec::new();
+        let mut word = [0; 4];
+        for (i, sample_words) in status_words.iter().enumerate() {
+            LittleEndian::write_u32(&mut word, self.sample_number(i) as u32);
+            dat.extend_from_slice(&word);
+            LittleEndian::write_u32(&mut word, self.raw_timestamp(i).unwrap_or(u32::MAX));
+            dat.extend_from_slice(&word);
+
+            for (channel, data) in record.analog_channels_with_data() {
+                let value = data[i];
+                let raw = round(raw_value(channel, value));
+                match format {
+                    DataFormat::Binary16 => {
+                        let raw = if value.is_nan() {
+                            i16::MIN
+                        } else {
+                            raw.clamp(-(i16::MAX as f64), i16::MAX as f64) as i16
+                        };
+                        LittleEndian::write_i16(&mut word, raw);
+                        dat.extend_from_slice(&word[..2]);
+                    }
+                    DataFormat::Binary32 => {
+                        let raw = if value.is_nan() {
+                            i32::MIN
+                        } else {
+                            raw.clamp(-(i32::MAX as f64), i32::MAX as f64) as i32
+                        };
+                        LittleEndian::write_i32(&mut word, raw);
+                        dat.extend_from_slice(&word);
+                    }
+                    _ => {
+                        LittleEndian::write_f32(&mut word, raw_value(channel, value) as f32);
+                        dat.extend_from_slice(&word);
+                    }
+                }
+            }
+
+            for status_word in sample_words.iter() {
+                LittleEndian::write_u16(&mut word, *status_word);
+                dat.extend_from_slice(&word[..2]);
+            }
+        }
+        dat
+    }
+
+    fn sample_number(&self, i: usize) -> u64 {
+        self.record
+            .data
+            .sample_numbers
+            .get(i)
+            .copied()
+            .unwrap_or(i as u64 + 1)
+    }
+
+    /// Timestamp of the sample as it goes in the `.dat` file: as it was in the file the
+    /// record came from if there's one, otherwise worked out from the sample's time.
+    /// `None` if it was missing or doesn't fit.
+    fn raw_timestamp(&self, i: usize) -> Option<u32> {
+        let record = self.record;
+        if record.data.raw_timestamps.len() == record.data.timestamps.len() {
+            return record.data.raw_timestamps[i];
+        }
+
+        let unit = self.timestamp_unit as f64 * record.config.timestamp_multiplication_factor;
+        let raw = round(record.data.timestamps[i] as f64 / unit);
+        if raw >= 0.0 && raw < u32::MAX as f64 {
+            Some(raw as u32)
+        } else {
+            None
+        }
+    }
+}
+
+/// Station name or device ID, quoted if it contains a comma or starts with a quote so
+/// that it reads back the same.
+fn quoted(value: &str) -> Cow<'_, str> {
+    if value.contains(',') || value.trim_start().starts_with('"') {
+        Cow::Owned(format!("\"{}\"", value.replace('"', "\"\"")))
+    } else {
+        Cow::Borrowed(value)
+    }
+}
+
+/// Channel name as it was in the file, so any padding is kept, unless the channel has
+/// been renamed since or is new and has no name from a file.
+pub(crate) fn channel_name<'a>(name: &'a str, name_raw: &'a str) -> &'a str {
+    if !name_raw.is_empty() && name_raw.trim() == name {
+        name_raw
+    } else {
+        name
+    }
+}
+
+/// Phase as it was in the file, e.g. `L1` rather than `A`, unless the channel's phase
+/// has been changed since or it's new and has no phase from a file.
+pub(crate) fn channel_phase<'a>(phase: &'a Phase, phase_raw: &'a str) -> &'a str {
+    let unchanged = match phase {
+        Phase::Other(value) => phase_raw.trim() == value.trim(),
+        _ => Phase::from(phase_raw) == *phase,
+    };
+    if unchanged {
+        phase_raw
+    } else {
+        phase.as_str()
+    }
+}
+
+/// Extra columns from the channel line of the original `.cfg` file, each with a
+/// leading comma.
+fn extra_columns(extensions: &BTreeMap<String, String>) -> String {
+    (1..)
+        .map_while(|n| extensions.get(&format!("CFG/Extra_Column_{}", n)))
+        .map(|value| format!(",{}", value))
+        .collect()
+}
+
+/// Largest integer the data format holds, or `None` if it isn't an integer format.
+/// The most negative integer is left out as it marks missing values.
+fn max_raw_value(data_format: &DataFormat) -> Option<f64> {
+    match data_format {
+        DataFormat::Binary16 => Some(i16::MAX as f64),
+        DataFormat::Binary32 => Some(i32::MAX as f64),
+        DataFormat::Ascii | DataFormat::Float32 => None,
+    }
+}
+
+/// Smallest and largest value, ignoring missing values, so the smallest is larger than
+/// the largest if there aren't any values.
+fn value_range(data: &[f64]) -> (f64, f64) {
+    data.iter()
+        .fold((f64::INFINITY, f64::NEG_INFINITY), |(min, max), &value| {
+            (min.min(value), max.max(value))
+        })
+}
+
+/// Nearest whole number, as `f64::round()` needs `std`.
+fn round(value: f64) -> f64 {
+    if value < 0.0 {
+        (value - 0.5) as i64 as f64
+    } else {
+        (value + 0.5) as i64 as f64
+    }
+}
+
+/// Write a real number the same way whatever the platform or locale, either rounded to
+/// `precision` decimal places or with the shortest digits which round-trip, without
+/// trailing zeros or a sign on zero.
+fn push_real(out: &mut String, value: f64, precision: Option<usize>) {
+    let mut ryu_buffer = ryu::Buffer::new();
+    let formatted;
+    let mut digits = match precision {
+        Some(precision) => {
+            formatted = format!("{:.*}", precision, value);
+            formatted.as_str()
+        }
+        None => ryu_buffer.format(value),
+    };
+    if digits.contains('.') && !digits.contains('e') {
+        digits = digits.trim_end_matches('0').trim_end_matches('.');
+    }
+    if digits.trim_start_matches('-') == "0" {
+        digits = "0";
+    }
+    out.push_str(digits);
+}
+
+/// Value as it was in the `.dat` file, before scaling.
+fn raw_value(channel: &AnalogChannel, value: f64) -> f64 {
+    (value - channel.offset_adder) / channel.multiplier
+}
+
+fn time_offset(offset: Option<FixedOffset>) -> String {
+    let seconds = match offset {
+        Some(offset) => offset.local_minus_utc(),
+        None => return "x".to_string(),
+    };
+    let sign = if seconds < 0 { "-" } else { "" };
+    let (hours, minutes) = (seconds.abs() / 3600, seconds.abs() % 3600 / 60);
+    if minutes == 0 {
+        format!("{}{}", sign, hours)
+    } else {
+        format!("{}{}h{:02}", sign, hours, minutes)
+    }
+}
+
+pub(crate) fn time_quality(quality: Option<&TimeQuality>) -> String {
+    match quality {
+        None | Some(TimeQuality::ClockLocked) => "0".to_string(),
+        Some(TimeQuality::ClockFailure) => "F".to_string(),
+        Some(TimeQuality::ClockUnlocked(exponent)) => {
+            format!("{:X}", (exponent + 10).clamp(1, 11))
+        }
+    }
+}
+
+pub(crate) fn leap_second_status(status: Option<&LeapSecondStatus>) -> &'static str {
+    match status {
+        Some(LeapSecondStatus::NoCapability) | None => "3",
+        Some(LeapSecondStatus::Subtracted) => "2",
+        Some(LeapSecondStatus::Added) => "1",
+        Some(LeapSecondStatus::NotPresent) => "0",
+    }
+}
@@ -0,0 +1,411 @@
+//! Configuration of a record as XML, for systems which want XML metadata alongside the
+//! data files rather than a `.cfg` file. Everything in the `.cfg` file is kept, so a
+//! configuration read back with `config_from_xml()` parses `.dat` files the same as the
+//! one written with `config_to_xml()`:
+//!
+//! ```xml
+//! <?xml version="1.0" encoding="UTF-8"?>
+//! <ComtradeConfig revision="1999" stationName="..." recordingDeviceId="..."
+//!     lineFrequency="50" startTime="2020-02-01T00:00:00"
+//!     triggerTime="2020-02-01T00:00:00.001" dataFormat="ASCII"
+//!     timestampMultiplicationFactor="1" totalSamples="4" timestampUnit="1000">
+//!   <AnalogChannel index="1" name="IA" phase="A" circuitComponent="Line1" units="A"
+//!       multiplier="0.5" offsetAdder="0" skew="0" minValue="-32768" maxValue="32767"
+//!       primaryFactor="1" secondaryFactor="1" scalingMode="S"/>
+//!   <StatusChannel index="1" name="TRIP" phase="" circuitComponent="Line1"
+//!       normalStatusValue="0"/>
+//!   <SamplingRate rateHz="1000" endSampleNumber="4"/>
+//! </ComtradeConfig>
+//! ```
+//!
+//! For the 2013 revision, `ComtradeConfig` also has `timeOffsetMinutes`,
+//! `localOffsetMinutes`, `timeQuality` and `leapSecond` attributes where they're known,
+//! the last two with the codes used in the `.cfg` file. `decimalComma="true"` marks a
+//! configuration read from a semicolon-separated `.cfg` file. Any vendor-specific lines
+//! after the standard fields are kept in `<ExtraLine text="..."/>` elements. The header,
+//! extensions and warnings aren't part of the configuration, so aren't exported.
+
+use std::collections::BTreeMap;
+use std::fmt;
+use std::str::FromStr;
+
+use chrono::{FixedOffset, NaiveDateTime};
+use quick_xml::escape::escape;
+use quick_xml::events::{BytesStart, Event};
+use quick_xml::Reader;
+
+use crate::writer::{channel_name, channel_phase, leap_second_status, time_quality};
+use crate::{
+    AnalogChannel, AnalogScalingMode, ComtradeConfig, DataFormat, FormatRevision, LeapSecondStatus,
+    Phase, SamplingRate, StatusChannel, TimeQuality,
+};
+
+/// Why an XML configuration couldn't be read.
+#[derive(Debug)]
+pub enum XmlConfigError {
+    /// The XML is malformed.
+    Xml(quick_xml::Error),
+
+    /// The XML is well-formed but isn't a valid configuration, e.g. an attribute is
+    /// missing or has an invalid value.
+    Invalid(String),
+}
+
+impl fmt::Display for XmlConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            XmlConfigError::Xml(err) => write!(f, "invalid XML configuration: {}", err),
+            XmlConfigError::Invalid(message) => {
+                write!(f, "invalid XML configuration: {}", message)
+            }
+        }
+    }
+}
+
+impl std::error::Error for XmlConfigError {}
+
+/// The configuration as XML, in the format described in the module documentation.
+pub fn config_to_xml(config: &ComtradeConfig) -> String {
+    let mut xml = String::from("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+
+    xml.push_str("<ComtradeConfig");
+    attribute(&mut xml, "revision", revision(config.revision));
+    attribute(&mut xml, "stationName", &config.station_name);
+    attribute(&mut xml, "recordingDeviceId", &config.recording_device_id);
+    attribute(
+        &mut xml,
+        "lineFrequency",
+        &config.line_frequency.to_string(),
+    );
+    attribute(&mut xml, "startTime", &format_time(config.start_time));
+    attribute(&mut xml, "triggerTime", &format_time(config.trigger_time));
+    attribute(&mut xml, "dataFormat", data_format(&config.data_format));
+    attribute(
+        &mut xml,
+        "timestampMultiplicationFactor",
+        &config.timestamp_multiplication_factor.to_string(),
+    );
+    attribute(
+        &mut xml,
+        "totalSamples",
+        &config.total_num_samples.to_string(),
+    );
+    attribute(
+        &mut xml,
+        "timestampUnit",
+        &config.timestamp_unit.to_string(),
+    );
+    if let Some(offset) = config.time_offset {
+        attribute(&mut xml, "timeOffsetMinutes", &offset_minutes(offset));
+    }
+    if let Some(offset) = config.local_offset {
+        attribute(&mut xml, "localOffsetMinutes", &offset_minutes(offset));
+    }
+    if let Some(quality) = &config.time_quality {
+        attribute(&mut xml, "timeQuality", &time_quality(Some(quality)));
+    }
+    if let Some(status) = &config.leap_second_status {
+        attribute(&mut xml, "leapSecond", leap_second_status(Some(status)));
+    }
+    if config.decimal_comma {
+        attribute(&mut xml, "decimalComma", "true");
+    }
+    xml.push_str(">\n");
+
+    for channel in config.analog_channels.iter() {
+        xml.push_str("  <AnalogChannel");
+        attribute(&mut xml, "index", &channel.index.to_string());
+        attribute(
+            &mut xml,
+            "name",
+            channel_name(&channel.name, &channel.name_raw),
+        );
+        attribute(
+            &mut xml,
+            "phase",
+            channel_phase(&channel.phase, &channel.phase_raw),
+        );
+        attribute(
+            &mut xml,
+            "circuitComponent",
+            &channel.circuit_component_being_monitored,
+        );
+        attribute(&mut xml, "units", &channel.units);
+        attribute(&mut xml, "multiplier", &channel.multiplier.to_string());
+        attribute(&mut xml, "offsetAdder", &channel.offset_adder.to_string());
+        attribute(&mut xml, "skew", &channel.skew.to_string());
+        attribute(&mut xml, "minValue", &channel.min_value.to_string());
+        attribute(&mut xml, "maxValue", &channel.max_value.to_string());
+        attribute(
+            &mut xml,
+            "primaryFactor",
+            &channel.primary_factor.to_string(),
+        );
+        attribute(
+            &mut xml,
+            "secondaryFactor",
+            &channel.secondary_factor.to_string(),
+        );
+        attribute(
+            &mut xml,
+            "scalingMode",
+            match channel.scaling_mode {
+                AnalogScalingMode::Primary => "P",
+                AnalogScalingMode::Secondary => "S",
+            },
+        );
+        xml.push_str("/>\n");
+    }
+
+    for channel in config.status_channels.iter() {
+        xml.push_str("  <StatusChannel");
+        attribute(&mut xml, "index", &channel.index.to_string());
+        attribute(
+            &mut xml,
+            "name",
+            channel_name(&channel.name, &channel.name_raw),
+        );
+        attribute(
+            &mut xml,
+            "phase",
+            channel_phase(&channel.phase, &channel.phase_raw),
+        );
+        attribute(
+            &mut xml,
+            "circuitComponent",
+            &channel.circuit_component_being_monitored,
+        );
+        attribute(
+            &mut xml,
+            "normalStatusValue",
+            &channel.normal_status_value.to_string(),
+        );
+        xml.push_str("/>\n");
+    }
+
+    for rate in config.sampling_rates.iter() {
+        xml.push_str("  <SamplingRate");
+        attribute(&mut xml, "rateHz", &rate.rate_hz.to_string());
+        attribute(
+            &mut xml,
+            "endSampleNumber",
+            &rate.end_sample_number.to_string(),
+        );
+        xml.push_str("/>\n");
+    }
+
+    for line in config.extra_cfg_lines.iter() {
+        xml.push_str("  <ExtraLine");
+        attribute(&mut xml, "text", line);
+        xml.push_str("/>\n");
+    }
+
+    xml.push_str("</ComtradeConfig>\n");
+    xml
+}
+
+/// Configuration from XML in the format written by `config_to_xml()`.
+pub fn config_from_xml(xml: &str) -> Result<ComtradeConfig, XmlConfigError> {
+    let mut reader = Reader::from_str(xml);
+    let mut config: Option<ComtradeConfig> = None;
+
+    loop {
+        let element = match reader.read_event().map_err(XmlConfigError::Xml)? {
+            Event::Start(element) | Event::Empty(element) => element,
+            Event::Eof => break,
+            _ => continue,
+        };
+        let name = element.name();
+        let attributes = Attributes::of(&element)?;
+
+        if name.as_ref() == b"ComtradeConfig" {
+            config = Some(attributes.config()?);
+            continue;
+        }
+        let config = config.as_mut().ok_or_else(|| {
+            XmlConfigError::Invalid("root element must be ComtradeConfig".to_string())
+        })?;
+        match name.as_ref() {
+            b"AnalogChannel" => config.analog_channels.push(attributes.analog_channel()?),
+            b"StatusChannel" => config.status_channels.push(attributes.status_channel()?),
+            b"SamplingRate" => config.sampling_rates.push(SamplingRate {
+                rate_hz: attributes.parse("rateHz")?,
+                end_sample_number: attributes.parse("endSampleNumber")?,
+            }),
+            b"ExtraLine" => config
+                .extra_cfg_lines
+                .push(attributes.get("text")?.to_string()),
+            other => {
+                return Err(XmlConfigError::Invalid(format!(
+                    "unexpected element {}",
+                    String::from_utf8_lossy(other)
+                )))
+            }
+        }
+    }
+
+    let mut config =
+        config.ok_or_else(|| XmlConfigError::Invalid("no ComtradeConfig element".to_string()))?;
+    config.num_analog_channels = config.analog_channels.len() as u32;
+    config.num_status_channels = config.status_channels.len() as u32;
+    config.num_total_channels = config.num_analog_channels + config.num_status_channels;
+    Ok(config)
+}
+
+/// Attributes of an element, unescaped.
+struct Attributes(BTreeMap<String, String>);
+
+impl Attributes {
+    fn of(element: &BytesStart) -> Result<Self, XmlConfigError> {
+        let mut attributes = BTreeMap::new();
+        for attribute in element.attributes() {
+            let attribute = attribute.map_err(|err| XmlConfigError::Xml(err.into()))?;
+            let value = attribute.unescape_value().map_err(XmlConfigError::Xml)?;
+            attributes.insert(
+                String::from_utf8_lossy(attribute.key.as_ref()).into_owned(),
+                value.into_owned(),
+            );
+        }
+        Ok(Attributes(attributes))
+    }
+
+    fn get(&self, name: &str) -> Result<&str, XmlConfigError> {
+        self.0
+            .get(name)
+            .map(String::as_str)
+            .ok_or_else(|| XmlConfigError::Invalid(format!("missing attribute {}", name)))
+    }
+
+    fn get_or_empty(&self, name: &str) -> String {
+        self.0.get(name).cloned().unwrap_or_default()
+    }
+
+    fn parse<T: FromStr>(&self, name: &str) -> Result<T, XmlConfigError> {
+        let value = self.get(name)?;
+        value.trim().parse().map_err(|_| invalid_value(name, value))
+    }
+
+    fn parse_optional<T: FromStr>(&self, name: &str) -> Result<Option<T>, XmlConfigError> {
+        match self.0.contains_key(name) {
+            true => self.parse(name).map(Some),
+            false => Ok(None),
+        }
+    }
+
+    fn config(&self) -> Result<ComtradeConfig, XmlConfigError> {
+        let time = |name: &str| {
+            let value = self.get(name)?;
+            NaiveDateTime::parse_from_str(value, "%Y-%m-%dT%H:%M:%S%.f")
+                .map_err(|_| invalid_value(name, value))
+        };
+        let offset = |name: &str| match self.parse_optional::<i32>(name)? {
+            Some(minutes) => FixedOffset::east_opt(minutes.saturating_mul(60))
+                .map(Some)
+                .ok_or_else(|| invalid_value(name, self.0[name].as_str())),
+            None => Ok(None),
+        };
+
+        Ok(ComtradeConfig {
+            station_name: self.get("stationName")?.to_string(),
+            recording_device_id: self.get("recordingDeviceId")?.to_string(),
+            revision: self.parse::<FormatRevision>("revision")?,
+            num_total_channels: 0,
+            num_analog_channels: 0,
+            num_status_channels: 0,
+            analog_channels: vec![],
+            status_channels: vec![],
+            line_frequency: self.parse("lineFrequency")?,
+            sampling_rates: vec![],
+            total_num_samples: self.parse("totalSamples")?,
+            start_time: time("startTime")?,
+            trigger_time: time("triggerTime")?,
+            data_format: self.parse::<DataFormat>("dataFormat")?,
+            timestamp_multiplication_factor: self.parse("timestampMultiplicationFactor")?,
+            time_offset: offset("timeOffsetMinutes")?,
+            local_offset: offset("localOffsetMinutes")?,
+            time_quality: self.parse_optional::<TimeQuality>("timeQuality")?,
+            leap_second_status: self.parse_optional::<LeapSecondStatus>("leapSecond")?,
+            extra_cfg_lines: vec![],
+            header: String::new(),
+            extensions: BTreeMap::new(),
+            warnings: vec![],
+            timestamp_unit: self.parse("timestampUnit")?,
+            decimal_comma: self.parse_optional("decimalComma")?.unwrap_or(false),
+        })
+    }
+
+    fn analog_channel(&self) -> Result<AnalogChannel, XmlConfigError> {
+        let name_raw = self.get("name")?.to_string();
+        let phase_raw = self.get_or_empty("phase");
+        Ok(AnalogChannel {
+            index: self.parse("index")?,
+            name: name_raw.trim().to_string(),
+            name_raw,
+            phase: Phase::from(phase_raw.as_str()),
+            phase_raw,
+            circuit_component_being_monitored: self.get_or_empty("circuitComponent"),
+            units: self.get_or_empty("units"),
+            min_value: self.parse("minValue")?,
+            max_value: self.parse("maxValue")?,
+            multiplier: self.parse("multiplier")?,
+            offset_adder: self.parse("offsetAdder")?,
+            skew: self.parse("skew")?,
+            primary_factor: self.parse("primaryFactor")?,
+            secondary_factor: self.parse("secondaryFactor")?,
+            scaling_mode: self.parse::<AnalogScalingMode>("scalingMode")?,
+            extensions: BTreeMap::new(),
+        })
+    }
+
+    fn status_channel(&self) -> Result<StatusChannel, XmlConfigError> {
+        let name_raw = self.get("name")?.to_string();
+        let phase_raw = self.get_or_empty("phase");
+        Ok(StatusChannel {
+            index: self.parse("index")?,
+            name: name_raw.trim().to_string(),
+            name_raw,
+            phase: Phase::from(phase_raw.as_str()),
+            phase_raw,
+            circuit_component_being_monitored: self.get_or_empty("circuitComponent"),
+            normal_status_value: self.parse("normalStatusValue")?,
+            extensions: BTreeMap::new(),
+        })
+    }
+}
+
+fn invalid_value(name: &str, value: &str) -> XmlConfigError {
+    XmlConfigError::Invalid(format!("invalid value for {}: '{}'", name, value))
+}
+
+fn attribute(xml: &mut String, name: &str, value: &str) {
+    xml.push(' ');
+    xml.push_str(name);
+    xml.push_str("=\"");
+    xml.push_str(&escape(value));
+    xml.push('"');
+}
+
+fn revision(revision: FormatRevision) -> &'static str {
+    match revision {
+        FormatRevision::Revision1991 => "1991",
+        FormatRevision::Revision1999 => "1999",
+        FormatRevision::Revision2013 => "2013",
+    }
+}
+
+fn data_format(data_format: &DataFormat) -> &'static str {
+    match data_format {
+        DataFormat::Ascii => "ASCII",
+        DataFormat::Binary16 => "BINARY",
+        DataFormat::Binary32 => "BINARY32",
+        DataFormat::Float32 => "FLOAT32",
+    }
+}
+
+fn format_time(time: NaiveDateTime) -> String {
+    time.format("%Y-%m-%dT%H:%M:%S%.f").to_string()
+}
+
+fn offset_minutes(offset: FixedOffset) -> String {
+    (offset.local_minus_utc() / 60).to_string()
+}
@@ -1,6 +1,8 @@
+#![allow(dead_code)]
+
 use float_cmp::approx_eq;
 
-use comtrade::Comtrade;
+use comtrade::{AnalogChannel, Comtrade, StatusChannel};
 
 pub const SAMPLE_COMTRADE_DIR: &str = "./tests/comtrade_files";
 pub const MINUTE: i32 = 60;
@@ -11,33 +13,20 @@ pub fn assert_comtrades_eq(left: &Comtrade, right: &Comtrade) {
     // normal one, so we do that below. To not have to manually write out the rest of
     // the normal comparisons, we just bypass the float comparisons by cloning the
     // floating point arrays and writing over the values in the struct, then performing
-    // the floating point appro. equal comparison manually.
+    // the floating point appro. equal comparison manually. Timestamps are whole
+    // nanoseconds so they're compared exactly.
     let mut right_clone = right.clone();
-    right_clone.timestamps = left.timestamps.clone();
-    for (i, c) in left.analog_channels.iter().enumerate() {
-        right_clone.analog_channels[i].data = c.data.clone();
-    }
+    right_clone.data.analog_data = left.data.analog_data.clone();
 
     assert_eq!(*left, right_clone);
 
-    // Timestamps and analog data values are floats that involve some calculations to
-    // get the actual values, so direct floating point comparisons don't work. The actual
-    // type of the data is f64 but the underlying data being read in from file is either
-    // i16 / i32 (converted to float via calculations) or f32.
-    for (i, tl) in left.timestamps.iter().enumerate() {
-        let tr = right.timestamps[i];
-        assert!(
-            approx_eq!(f64, *tl, tr),
-            "timestamp {} different: {} !≈ {}",
-            i,
-            tl,
-            tr,
-        );
-    }
-
-    for (i, c) in left.analog_channels.iter().enumerate() {
-        for (j, vl) in c.data.iter().enumerate() {
-            let vr = right.analog_channels[i].data[j];
+    // Analog data values are floats that involve some calculations to get the actual
+    // values, so direct floating point comparisons don't work. The actual type of the
+    // data is f64 but the underlying data being read in from file is either i16 / i32
+    // (converted to float via calculations) or f32.
+    for (i, data) in left.data.analog_data.iter().enumerate() {
+        for (j, vl) in data.iter().enumerate() {
+            let vr = right.data.analog_data[i][j];
             assert!(
                 approx_eq!(f32, *vl as f32, vr as f32),
                 "analog channel {} value {} different: {} !≈ {}",
@@ -49,3 +38,25 @@ pub fn assert_comtrades_eq(left: &Comtrade, right: &Comtrade) {
         }
     }
 }
+
+/// Analog channel with values as they are in the `.dat` file, for records put together
+/// by hand. Anything else can be set with struct update syntax.
+pub fn analog_channel(index: u32, name: &str, units: &str) -> AnalogChannel {
+    AnalogChannel {
+        index,
+        name: name.to_string(),
+        name_raw: name.to_string(),
+        units: units.to_string(),
+        ..Default::default()
+    }
+}
+
+/// Status channel that's normally 0, for records put together by hand.
+pub fn status_channel(index: u32, name: &str) -> StatusChannel {
+    StatusChannel {
+        index,
+        name: name.to_string(),
+        name_raw: name.to_string(),
+        ..Default::default()
+    }
+}
@@ -3,11 +3,13 @@ use std::io::BufReader;
 use std::path::Path;
 
 use chrono::{FixedOffset, NaiveDate};
+#[allow(unused_imports)]
 use float_cmp::approx_eq;
 
 use comtrade::{
-    AnalogChannel, AnalogScalingMode, Comtrade, ComtradeParserBuilder, DataFormat, FormatRevision,
-    LeapSecondStatus, SamplingRate, StatusChannel, TimeQuality,
+    AnalogChannel, AnalogScalingMode, Comtrade, ComtradeConfig, ComtradeData,
+    ComtradeParserBuilder, DataFormat, FormatRevision, LeapSecondStatus, Phase, SamplingRate,
+    StatusChannel, TimeQuality,
 };
 
 mod common;
@@ -15,6 +17,7 @@ mod common;
 use common::{assert_comtrades_eq, HOUR, MINUTE, SAMPLE_COMTRADE_DIR};
 
 #[test]
+#[allow(clippy::zero_prefixed_literal)]
 fn it_correctly_parses_sample_2013_files_with_ascii_data_using_utf8() {
     let dir = Path::new(SAMPLE_COMTRADE_DIR);
     let cfg_path = dir.join("sample_2013_ascii_utf8.cfg");
@@ -33,45 +36,164 @@ fn it_correctly_parses_sample_2013_files_with_ascii_data_using_utf8() {
     let expected_sample_rate = 1200.0;
 
     let expected = Comtrade {
-        station_name: "SMARTSTATION testing text encoding: hgvcj터파크387".to_string(),
-        recording_device_id: "IED123".to_string(),
-        revision: FormatRevision::Revision2013,
-        line_frequency: 60.0,
-        sampling_rates: vec![SamplingRate {
-            rate_hz: expected_sample_rate,
-            end_sample_number: 40,
-        }],
-        start_time: NaiveDate::from_ymd(2011, 01, 12).and_hms_micro(5, 55, 30, 750_110),
-        trigger_time: NaiveDate::from_ymd(2011, 01, 12).and_hms_micro(5, 55, 30, 782_610),
-        data_format: DataFormat::Ascii,
-        timestamp_multiplication_factor: 1.0,
-        time_offset: Some(FixedOffset::west(5 * HOUR + 30 * MINUTE)),
-        local_offset: Some(FixedOffset::west(5 * HOUR + 30 * MINUTE)),
-        time_quality: Some(TimeQuality::ClockUnlocked(1)),
-        leap_second_status: Some(LeapSecondStatus::NoCapability),
-        num_analog_channels: 4,
-        num_status_channels: 4,
-        num_total_channels: 8,
-
-        sample_numbers: (1..=40).collect(),
-        timestamps: (0..40).map(|i| i as f64 / expected_sample_rate).collect(),
-
-        analog_channels: vec![
-            AnalogChannel {
-                index: 1,
-                name: "IA ".to_string(),
-                phase: "".to_string(),
-                circuit_component_being_monitored: "Line123".to_string(),
-                units: " A".into(),
-                multiplier: 0.1138916015625,
-                offset_adder: 0.05694580078125,
-                skew: 0.0,
-                min_value: -32768.0,
-                max_value: 32767.0,
-                primary_factor: 933.0,
-                secondary_factor: 1.0,
-                scaling_mode: AnalogScalingMode::Secondary,
-                data: vec![
+        config: ComtradeConfig {
+            station_name: "SMARTSTATION testing text encoding: hgvcj터파크387".to_string(),
+            recording_device_id: "IED123".to_string(),
+            revision: FormatRevision::Revision2013,
+            line_frequency: 60.0,
+            sampling_rates: vec![SamplingRate {
+                rate_hz: expected_sample_rate,
+                end_sample_number: 40,
+            }],
+            total_num_samples: 40,
+            start_time: NaiveDate::from_ymd(2011, 01, 12).and_hms_micro(5, 55, 30, 750_110),
+            trigger_time: NaiveDate::from_ymd(2011, 01, 12).and_hms_micro(5, 55, 30, 782_610),
+            data_format: DataFormat::Ascii,
+            timestamp_multiplication_factor: 1.0,
+            time_offset: Some(FixedOffset::west(5 * HOUR + 30 * MINUTE)),
+            local_offset: Some(FixedOffset::west(5 * HOUR + 30 * MINUTE)),
+            time_quality: Some(TimeQuality::ClockUnlocked(1)),
+            leap_second_status: Some(LeapSecondStatus::NoCapability),
+            extra_cfg_lines: vec![],
+            header: "".to_string(),
+            extensions: Default::default(),
+            warnings: vec![],
+            num_analog_channels: 4,
+            num_status_channels: 4,
+            num_total_channels: 8,
+            analog_channels: vec![
+                AnalogChannel {
+                    index: 1,
+                    name: "IA".to_string(),
+                    name_raw: "IA ".to_string(),
+                    phase: Phase::default(),
+                    phase_raw: "".to_string(),
+                    circuit_component_being_monitored: "Line123".to_string(),
+                    units: " A".into(),
+                    multiplier: 0.1138916015625,
+                    offset_adder: 0.05694580078125,
+                    skew: 0.0,
+                    min_value: -32768.0,
+                    max_value: 32767.0,
+                    primary_factor: 933.0,
+                    secondary_factor: 1.0,
+                    scaling_mode: AnalogScalingMode::Secondary,
+                    extensions: Default::default(),
+                },
+                AnalogChannel {
+                    index: 2,
+                    name: "IB".to_string(),
+                    name_raw: "IB ".to_string(),
+                    phase: Phase::default(),
+                    phase_raw: "".to_string(),
+                    circuit_component_being_monitored: "Line123".to_string(),
+                    units: " A".into(),
+                    multiplier: 0.1138916015625,
+                    offset_adder: 0.05694580078125,
+                    skew: 0.0,
+                    min_value: -32768.0,
+                    max_value: 32767.0,
+                    primary_factor: 933.0,
+                    secondary_factor: 1.0,
+                    scaling_mode: AnalogScalingMode::Secondary,
+                    extensions: Default::default(),
+                },
+                AnalogChannel {
+                    index: 3,
+                    name: "IC".to_string(),
+                    name_raw: "IC ".to_string(),
+                    phase: Phase::default(),
+                    phase_raw: "".to_string(),
+                    circuit_component_being_monitored: "Line123".to_string(),
+                    units: " A".into(),
+                    multiplier: 0.1138916015625,
+                    offset_adder: 0.05694580078125,
+                    skew: 0.0,
+                    min_value: -32768.0,
+                    max_value: 32767.0,
+                    primary_factor: 933.0,
+                    secondary_factor: 1.0,
+                    scaling_mode: AnalogScalingMode::Secondary,
+                    extensions: Default::default(),
+                },
+                AnalogChannel {
+                    index: 4,
+                    name: "3I0".to_string(),
+                    name_raw: "3I0".to_string(),
+                    phase: Phase::default(),
+                    phase_raw: "".to_string(),
+                    circuit_component_being_monitored: "Line123".to_string(),
+                    units: " A".into(),
+                    multiplier: 0.1138916015625,
+                    offset_adder: 0.05694580078125,
+                    skew: 0.0,
+                    min_value: -32768.0,
+                    max_value: 32767.0,
+                    primary_factor: 933.0,
+                    secondary_factor: 1.0,
+                    scaling_mode: AnalogScalingMode::Secondary,
+                    extensions: Default::default(),
+                },
+            ],
+            status_channels: vec![
+                StatusChannel {
+                    index: 1,
+                    name: "51A".to_string(),
+                    name_raw: "51A".to_string(),
+                    phase: Phase::default(),
+                    phase_raw: "".to_string(),
+                    circuit_component_being_monitored: "Line123".into(),
+                    normal_status_value: 0,
+                    extensions: Default::default(),
+                },
+                StatusChannel {
+                    index: 2,
+                    name: "51B".to_string(),
+                    name_raw: "51B".to_string(),
+                    phase: Phase::default(),
+                    phase_raw: "".to_string(),
+                    circuit_component_being_monitored: "Line123".into(),
+                    normal_status_value: 0,
+                    extensions: Default::default(),
+                },
+                StatusChannel {
+                    index: 3,
+                    name: "51C".to_string(),
+                    name_raw: "51C".to_string(),
+                    phase: Phase::default(),
+                    phase_raw: "".to_string(),
+                    circuit_component_being_monitored: "Line123".into(),
+                    normal_status_value: 0,
+                    extensions: Default::default(),
+                },
+                StatusChannel {
+                    index: 4,
+                    name: "51N".to_string(),
+                    name_raw: "51N".to_string(),
+                    phase: Phase::default(),
+                    phase_raw: "".to_string(),
+                    circuit_component_being_monitored: "Line123".into(),
+                    normal_status_value: 0,
+                    extensions: Default::default(),
+                },
+            ],
+            timestamp_unit: 1000,
+            decimal_comma: false,
+        },
+        data: ComtradeData {
+            irregular_samples: vec![],
+            analog_quality: vec![],
+            source_files: None,
+            sample_numbers: (1..=40).collect(),
+            timestamps: (0..40)
+                .map(|i| (i as f64 * 1e9 / expected_sample_rate).round() as i64)
+                .collect(),
+            // Timestamps in the data file are in microseconds, starting at 72.5ms.
+            raw_timestamps: (0..40)
+                .map(|i| Some(72500 + (i as f64 * 2500.0 / 3.0).round() as u32))
+                .collect(),
+            analog_data: vec![
+                vec![
                     -9.39605712890625,
                     -1.65142822265625,
                     6.32098388671875,
@@ -113,22 +235,7 @@ fn it_correctly_parses_sample_2013_files_with_ascii_data_using_utf8() {
                     -22.60748291015625,
                     -19.19073486328125,
                 ],
-            },
-            AnalogChannel {
-                index: 2,
-                name: "IB ".to_string(),
-                phase: "".to_string(),
-                circuit_component_being_monitored: "Line123".to_string(),
-                units: " A".into(),
-                multiplier: 0.1138916015625,
-                offset_adder: 0.05694580078125,
-                skew: 0.0,
-                min_value: -32768.0,
-                max_value: 32767.0,
-                primary_factor: 933.0,
-                secondary_factor: 1.0,
-                scaling_mode: AnalogScalingMode::Secondary,
-                data: vec![
+                vec![
                     7.80157470703125,
                     0.62640380859375,
                     -5.97930908203125,
@@ -170,22 +277,7 @@ fn it_correctly_parses_sample_2013_files_with_ascii_data_using_utf8() {
                     11.33221435546875,
                     4.72650146484375,
                 ],
-            },
-            AnalogChannel {
-                index: 3,
-                name: "IC ".to_string(),
-                phase: "".to_string(),
-                circuit_component_being_monitored: "Line123".to_string(),
-                units: " A".into(),
-                multiplier: 0.1138916015625,
-                offset_adder: 0.05694580078125,
-                skew: 0.0,
-                min_value: -32768.0,
-                max_value: 32767.0,
-                primary_factor: 933.0,
-                secondary_factor: 1.0,
-                scaling_mode: AnalogScalingMode::Secondary,
-                data: vec![
+                vec![
                     0.85418701171875,
                     0.51251220703125,
                     0.05694580078125,
@@ -227,22 +319,7 @@ fn it_correctly_parses_sample_2013_files_with_ascii_data_using_utf8() {
                     1.99310302734375,
                     2.10699462890625,
                 ],
-            },
-            AnalogChannel {
-                index: 4,
-                name: "3I0".to_string(),
-                phase: "".to_string(),
-                circuit_component_being_monitored: "Line123".to_string(),
-                units: " A".into(),
-                multiplier: 0.1138916015625,
-                offset_adder: 0.05694580078125,
-                skew: 0.0,
-                min_value: -32768.0,
-                max_value: 32767.0,
-                primary_factor: 933.0,
-                secondary_factor: 1.0,
-                scaling_mode: AnalogScalingMode::Secondary,
-                data: vec![
+                vec![
                     -0.85418701171875,
                     -0.62640380859375,
                     0.28472900390625,
@@ -284,55 +361,26 @@ fn it_correctly_parses_sample_2013_files_with_ascii_data_using_utf8() {
                     -9.39605712890625,
                     -12.47113037109375,
                 ],
-            },
-        ],
-
-        status_channels: vec![
-            StatusChannel {
-                index: 1,
-                name: "51A".into(),
-                phase: "".into(),
-                circuit_component_being_monitored: "Line123".into(),
-                normal_status_value: 0,
-                data: vec![
+            ],
+            status_data: vec![
+                vec![
                     0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1,
                     1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1,
                 ],
-            },
-            StatusChannel {
-                index: 2,
-                name: "51B".into(),
-                phase: "".into(),
-                circuit_component_being_monitored: "Line123".into(),
-                normal_status_value: 0,
-                data: vec![
+                vec![
                     0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1,
                     1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1,
                 ],
-            },
-            StatusChannel {
-                index: 3,
-                name: "51C".into(),
-                phase: "".into(),
-                circuit_component_being_monitored: "Line123".into(),
-                normal_status_value: 0,
-                data: vec![
+                vec![
                     0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
                     0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
                 ],
-            },
-            StatusChannel {
-                index: 4,
-                name: "51N".into(),
-                phase: "".into(),
-                circuit_component_being_monitored: "Line123".into(),
-                normal_status_value: 0,
-                data: vec![
+                vec![
                     0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1,
                     1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1,
                 ],
-            },
-        ],
+            ],
+        },
     };
 
     assert_comtrades_eq(&expected, &record);
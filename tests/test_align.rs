@@ -0,0 +1,123 @@
+mod common;
+
+use std::f64::consts::PI;
+
+use chrono::{Duration, FixedOffset, NaiveDate, NaiveDateTime};
+
+use comtrade::analysis::{align_records, Interpolation};
+use comtrade::{AnalogChannel, Comtrade, ComtradeConfig, ComtradeData};
+
+use common::analog_channel;
+
+const FREQUENCY: f64 = 50.0;
+const SAMPLE_RATE: f64 = 1000.0;
+
+fn fault_time() -> NaiveDateTime {
+    NaiveDate::from_ymd(2020, 2, 1).and_hms(12, 0, 0)
+}
+
+/// Record of the same 50 Hz waveform starting `start_ns` after the fault time, in the
+/// time zone `offset_hours` from UTC (with the fault time being in UTC).
+fn record(start_ns: i64, n_samples: usize, offset_hours: Option<i32>) -> Comtrade {
+    let timestamps: Vec<i64> = (0..n_samples)
+        .map(|i| (i as f64 * 1e9 / SAMPLE_RATE).round() as i64)
+        .collect();
+    let data = timestamps
+        .iter()
+        .map(|t| (2.0 * PI * FREQUENCY * (start_ns + t) as f64 / 1e9).sin())
+        .collect();
+    let local = Duration::hours(offset_hours.unwrap_or(0) as i64);
+
+    Comtrade {
+        config: ComtradeConfig {
+            analog_channels: vec![AnalogChannel {
+                min_value: -1.0,
+                max_value: 1.0,
+                multiplier: 0.001,
+                ..analog_channel(1, "VA", "kV")
+            }],
+            start_time: fault_time() + Duration::nanoseconds(start_ns) + local,
+            trigger_time: fault_time() + Duration::milliseconds(10) + local,
+            time_offset: offset_hours.map(|h| FixedOffset::east(h * 3600)),
+            ..Default::default()
+        },
+        data: ComtradeData {
+            sample_numbers: (1..=n_samples as u64).collect(),
+            timestamps,
+            analog_data: vec![data],
+            ..Default::default()
+        },
+    }
+}
+
+fn max_difference(left: &[f64], right: &[f64]) -> f64 {
+    left.iter()
+        .zip(right.iter())
+        .fold(0.0, |max: f64, (l, r)| max.max((l - r).abs()))
+}
+
+#[test]
+fn it_aligns_records_with_sub_sample_offset() {
+    // Right-hand record starts 0.3 of a sample later and runs for longer.
+    let left = record(0, 100, None);
+    let right = record(300_000, 120, None);
+
+    let linear = align_records(&left, &right, SAMPLE_RATE, Interpolation::Linear)
+        .expect("records should overlap");
+    let sinc = align_records(
+        &left,
+        &right,
+        SAMPLE_RATE,
+        Interpolation::Sinc { half_width: 4 },
+    )
+    .expect("records should overlap");
+
+    // Overlap starts at the first of the left record's samples after the right record
+    // starts, i.e. its second sample, 9 ms before the trigger.
+    assert_eq!(sinc.times.len(), 99);
+    assert!((sinc.times[0] + 0.009).abs() < 1e-12);
+    assert_eq!(sinc.start_time, fault_time() + Duration::milliseconds(1));
+    assert_eq!(sinc.left[0].multiplier, 1.0);
+
+    // The left record is sampled at the aligned instants already.
+    assert_eq!(sinc.left_data[0][..], left.data.analog_data[0][1..]);
+
+    let linear_error = max_difference(&linear.left_data[0], &linear.right_data[0]);
+    let sinc_error = max_difference(&sinc.left_data[0][5..90], &sinc.right_data[0][5..90]);
+    assert!(linear_error < 0.05, "linear error {}", linear_error);
+    assert!(sinc_error < 5e-3, "sinc error {}", sinc_error);
+    assert!(sinc_error < linear_error / 2.0);
+}
+
+#[test]
+fn it_takes_utc_offsets_into_account() {
+    let left = record(0, 100, Some(0));
+    let right = record(300_000, 100, Some(2));
+
+    let aligned = align_records(
+        &left,
+        &right,
+        SAMPLE_RATE,
+        Interpolation::Sinc { half_width: 4 },
+    )
+    .expect("records should overlap");
+    assert_eq!(aligned.times.len(), 99);
+
+    let error = max_difference(&aligned.left_data[0][5..90], &aligned.right_data[0][5..90]);
+    assert!(error < 5e-3, "error {}", error);
+}
+
+#[test]
+fn it_gives_nothing_for_records_which_dont_overlap() {
+    let left = record(0, 100, None);
+    let right = record(200_000_000, 100, None);
+
+    assert_eq!(
+        align_records(&left, &right, SAMPLE_RATE, Interpolation::Linear),
+        None
+    );
+    assert_eq!(
+        align_records(&left, &left, 0.0, Interpolation::Linear),
+        None
+    );
+}
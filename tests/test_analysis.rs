@@ -0,0 +1,439 @@
+mod common;
+
+use std::f64::consts::{PI, SQRT_2};
+
+use float_cmp::approx_eq;
+
+use comtrade::analysis::{
+    classify_fault, derive_channel, double_ended_fault_location, fault_locator, phasor_power,
+    phasors, power, select_windows, sequence_components, DerivedKind, FaultClassifierConfig,
+    FaultLocatorConfig, FaultLoop, FaultType, LineImpedance, LineTerminal, Phasor,
+    SequenceComponents, WindowSelectionConfig,
+};
+use comtrade::Phase;
+
+use common::analog_channel;
+
+#[test]
+fn it_derives_rms_channel_over_sliding_window() {
+    // Square wave with amplitude 2 has RMS of 2 everywhere.
+    let channel = analog_channel(1, "IA", "A");
+    let data = vec![2.0, -2.0, 2.0, -2.0, 2.0, -2.0];
+    let (rms, rms_data) = derive_channel(DerivedKind::Rms { window: 4 }, &channel, &data);
+
+    assert_eq!(rms.name, "RMS(IA)");
+    assert_eq!(rms.units, "A");
+    assert_eq!(rms_data.len(), data.len());
+    for v in rms_data.iter() {
+        assert!(approx_eq!(f64, *v, 2.0, ulps = 4));
+    }
+    assert!(approx_eq!(f64, rms.max_value, 2.0, ulps = 4));
+}
+
+#[test]
+fn it_derives_derivative_absolute_and_envelope_channels() {
+    let channel = analog_channel(1, "VA", "kV");
+    let data = vec![0.0, 1.0, 4.0, 9.0, -16.0];
+
+    let (ddt, ddt_data) = derive_channel(
+        DerivedKind::Derivative {
+            sample_rate_hz: 10.0,
+        },
+        &channel,
+        &data,
+    );
+    assert_eq!(ddt.units, "kV/s");
+    assert_eq!(ddt_data, vec![10.0, 20.0, 40.0, -100.0, -250.0]);
+
+    let (_, abs_data) = derive_channel(DerivedKind::Absolute, &channel, &data);
+    assert_eq!(abs_data, vec![0.0, 1.0, 4.0, 9.0, 16.0]);
+
+    let (env, env_data) = derive_channel(DerivedKind::Envelope { window: 2 }, &channel, &data);
+    assert_eq!(env_data, vec![0.0, 1.0, 4.0, 9.0, 16.0]);
+    assert_eq!(env.min_value, 0.0);
+    assert_eq!(env.max_value, 16.0);
+}
+
+fn sine(amplitude: f64, phase: f64, samples_per_cycle: usize, n_samples: usize) -> Vec<f64> {
+    (0..n_samples)
+        .map(|k| {
+            let theta = 2.0 * PI * k as f64 / samples_per_cycle as f64;
+            amplitude * (theta + phase).cos()
+        })
+        .collect()
+}
+
+#[test]
+fn it_estimates_steady_state_phasors() {
+    let channel = sine(100.0 * SQRT_2, 0.5, 20, 60);
+    let estimates = phasors(&channel, 20.0);
+
+    assert_eq!(estimates.len(), 60);
+    assert!(estimates[..19].iter().all(|p| p.is_none()));
+    for p in estimates[19..].iter() {
+        let p = p.expect("expected phasor after first cycle");
+        assert!(approx_eq!(f64, p.magnitude(), 100.0, epsilon = 1e-9));
+        assert!(approx_eq!(f64, p.angle(), 0.5, epsilon = 1e-9));
+    }
+}
+
+#[test]
+fn it_calculates_instantaneous_and_phasor_power() {
+    // Current lags voltage by 60 degrees, so power factor is 0.5.
+    let v = sine(100.0 * SQRT_2, 0.0, 20, 40);
+    let i = sine(10.0 * SQRT_2, -PI / 3.0, 20, 40);
+
+    let p = power(&v, &i);
+    assert_eq!(p.len(), 40);
+    assert!(approx_eq!(f64, p[0], v[0] * i[0]));
+
+    // Mean instantaneous power over a cycle is the active power.
+    let mean_power = p[..20].iter().sum::<f64>() / 20.0;
+    assert!(approx_eq!(f64, mean_power, 500.0, epsilon = 1e-9));
+
+    let quantities = phasor_power(&v, &i, 20.0);
+    let q = quantities[39].expect("expected power after first cycle");
+    assert!(approx_eq!(f64, q.active, 500.0, epsilon = 1e-9));
+    assert!(approx_eq!(
+        f64,
+        q.reactive,
+        1000.0 * (PI / 3.0).sin(),
+        epsilon = 1e-9
+    ));
+    assert!(approx_eq!(f64, q.apparent, 1000.0, epsilon = 1e-9));
+    assert!(approx_eq!(f64, q.power_factor, 0.5, epsilon = 1e-9));
+}
+
+fn sine_from_phasor(p: Phasor, samples_per_cycle: usize, n_samples: usize) -> Vec<f64> {
+    sine(
+        p.magnitude() * SQRT_2,
+        p.angle(),
+        samples_per_cycle,
+        n_samples,
+    )
+}
+
+#[test]
+fn it_calculates_sequence_components() {
+    let a = Phasor::from_polar(1.0, 0.0);
+    let b = Phasor::from_polar(1.0, -2.0 * PI / 3.0);
+    let c = Phasor::from_polar(1.0, 2.0 * PI / 3.0);
+
+    let balanced = sequence_components(a, b, c);
+    assert!(approx_eq!(
+        f64,
+        balanced.positive.magnitude(),
+        1.0,
+        epsilon = 1e-12
+    ));
+    assert!(balanced.negative.magnitude() < 1e-12);
+    assert!(balanced.zero.magnitude() < 1e-12);
+}
+
+#[test]
+fn it_locates_single_phase_to_ground_fault() {
+    let line = LineImpedance {
+        z1: Phasor::new(0.03, 0.3),
+        z0: Phasor::new(0.1, 1.0),
+        line_length: 100.0,
+    };
+    let distance = 42.0;
+
+    // Only faulted phase A carries current, so the residual current is the same as the
+    // phase A current and the loop impedance is m (Z1 + (Z0 - Z1) / 3).
+    let ia = Phasor::from_polar(1000.0, -1.4);
+    let zero = Phasor::default();
+    let va = ia * (line.z1 + (line.z0 - line.z1).scale(1.0 / 3.0)).scale(distance);
+    let vb = Phasor::from_polar(63500.0, -2.0 * PI / 3.0);
+    let vc = Phasor::from_polar(63500.0, 2.0 * PI / 3.0);
+
+    let channels: Vec<Vec<f64>> = [va, vb, vc, ia, zero, zero]
+        .iter()
+        .map(|p| sine_from_phasor(*p, 20, 80))
+        .collect();
+
+    let location = fault_locator(&FaultLocatorConfig {
+        voltages: [&channels[0], &channels[1], &channels[2]],
+        currents: [&channels[3], &channels[4], &channels[5]],
+        samples_per_cycle: 20.0,
+        fault_sample: 0,
+        fault_loop: FaultLoop::Ag,
+        line,
+    })
+    .expect("expected fault location");
+
+    assert!(approx_eq!(f64, location.distance, distance, epsilon = 1e-6));
+    assert!(location.confidence > 0.99);
+}
+
+/// Phase A, B and C phasors with the given sequence components.
+fn from_sequence(components: SequenceComponents) -> [Phasor; 3] {
+    let op = Phasor::from_polar(1.0, 2.0 * PI / 3.0);
+    let op2 = op * op;
+    let SequenceComponents {
+        zero,
+        positive,
+        negative,
+    } = components;
+    [
+        zero + positive + negative,
+        zero + op2 * positive + op * negative,
+        zero + op * positive + op2 * negative,
+    ]
+}
+
+/// Sine wave channels for phases A, B and C at both ends of a line with a fault at
+/// `distance`, given the sequence components of the fault current and of the local end's
+/// current. Voltages are worked out back from the fault, whose own voltage is just
+/// across the fault resistance for phase A.
+fn two_terminal_channels(
+    line: &LineImpedance,
+    distance: f64,
+    fault_current: SequenceComponents,
+    local_current: SequenceComponents,
+) -> Vec<Vec<f64>> {
+    let sub = |l: SequenceComponents, r: SequenceComponents| SequenceComponents {
+        zero: l.zero - r.zero,
+        positive: l.positive - r.positive,
+        negative: l.negative - r.negative,
+    };
+    let drop = |current: SequenceComponents, length: f64| SequenceComponents {
+        zero: line.z0.scale(length) * current.zero,
+        positive: line.z1.scale(length) * current.positive,
+        negative: line.z1.scale(length) * current.negative,
+    };
+    let add = |l: SequenceComponents, r: SequenceComponents| SequenceComponents {
+        zero: l.zero + r.zero,
+        positive: l.positive + r.positive,
+        negative: l.negative + r.negative,
+    };
+
+    let fault_resistance = 5.0;
+    let fault_a = from_sequence(fault_current)[0].scale(fault_resistance);
+    let fault_negative = Phasor::from_polar(2000.0, 0.3);
+    let fault_zero = Phasor::from_polar(3000.0, -0.2);
+    let fault_voltage = SequenceComponents {
+        zero: fault_zero,
+        positive: fault_a - fault_negative - fault_zero,
+        negative: fault_negative,
+    };
+
+    let remote_current = sub(fault_current, local_current);
+    let local_voltage = add(fault_voltage, drop(local_current, distance));
+    let remote_voltage = add(
+        fault_voltage,
+        drop(remote_current, line.line_length - distance),
+    );
+
+    [local_voltage, local_current, remote_voltage, remote_current]
+        .iter()
+        .flat_map(|c| from_sequence(*c))
+        .map(|p| sine_from_phasor(p, 20, 80))
+        .collect()
+}
+
+fn terminal(channels: &[Vec<f64>]) -> LineTerminal<'_> {
+    LineTerminal {
+        voltages: [&channels[0], &channels[1], &channels[2]],
+        currents: [&channels[3], &channels[4], &channels[5]],
+        samples_per_cycle: 20.0,
+        fault_sample: 0,
+    }
+}
+
+#[test]
+fn it_locates_fault_from_both_ends_of_line() {
+    let line = LineImpedance {
+        z1: Phasor::new(0.03, 0.3),
+        z0: Phasor::new(0.1, 1.0),
+        line_length: 100.0,
+    };
+    let distance = 63.0;
+
+    // Phase A to ground fault, fed unequally from both ends, with load flowing from the
+    // local end to the remote end.
+    let share = Phasor::from_polar(1000.0, -1.3);
+    let fault_current = SequenceComponents {
+        zero: share,
+        positive: share,
+        negative: share,
+    };
+    let local_current = SequenceComponents {
+        zero: share.scale(0.3),
+        positive: share.scale(0.6) + Phasor::from_polar(400.0, -0.1),
+        negative: share.scale(0.6),
+    };
+    let channels = two_terminal_channels(&line, distance, fault_current, local_current);
+
+    let location =
+        double_ended_fault_location(&terminal(&channels[..6]), &terminal(&channels[6..]), &line)
+            .expect("expected fault location");
+
+    assert_eq!(location.fault_type, FaultType::Ag);
+    assert!(approx_eq!(f64, location.distance, distance, epsilon = 1e-6));
+    assert!(location.confidence > 0.99);
+}
+
+#[test]
+fn it_locates_three_phase_fault_from_both_ends_of_line() {
+    let line = LineImpedance {
+        z1: Phasor::new(0.03, 0.3),
+        z0: Phasor::new(0.1, 1.0),
+        line_length: 100.0,
+    };
+    let distance = 20.0;
+
+    let fault_current = SequenceComponents {
+        positive: Phasor::from_polar(5000.0, -1.4),
+        ..Default::default()
+    };
+    let local_current = SequenceComponents {
+        positive: Phasor::from_polar(3500.0, -1.2),
+        ..Default::default()
+    };
+    let channels = two_terminal_channels(&line, distance, fault_current, local_current);
+
+    let location =
+        double_ended_fault_location(&terminal(&channels[..6]), &terminal(&channels[6..]), &line)
+            .expect("expected fault location");
+
+    assert_eq!(location.fault_type, FaultType::Abc);
+    assert!(approx_eq!(f64, location.distance, distance, epsilon = 1e-6));
+
+    let mut remote = terminal(&channels[6..]);
+    remote.fault_sample = 1;
+    assert!(double_ended_fault_location(&terminal(&channels[..6]), &remote, &line).is_none());
+}
+
+/// Phase A, B and C current channels carrying balanced load current throughout, plus the
+/// given fault currents from `fault_sample` onwards.
+fn faulted_currents(load: f64, fault: [Phasor; 3], fault_sample: usize) -> Vec<Vec<f64>> {
+    let angles = [0.0, -2.0 * PI / 3.0, 2.0 * PI / 3.0];
+    angles
+        .iter()
+        .zip(fault.iter())
+        .map(|(angle, fault)| {
+            let load = sine_from_phasor(Phasor::from_polar(load, *angle - 0.2), 20, 80);
+            let fault = sine_from_phasor(*fault, 20, 80);
+            (0..80)
+                .map(|k| load[k] + if k >= fault_sample { fault[k] } else { 0.0 })
+                .collect()
+        })
+        .collect()
+}
+
+#[test]
+fn it_classifies_faults() {
+    let zero = Phasor::default();
+    let i = |magnitude: f64, angle: f64| Phasor::from_polar(magnitude, angle);
+    let cases = [
+        ([i(2000.0, -1.3), zero, zero], FaultType::Ag),
+        ([zero, i(1500.0, -1.3), i(1500.0, -1.3 + PI)], FaultType::Bc),
+        ([i(800.0, 0.5), zero, i(900.0, 2.0)], FaultType::Cag),
+        (
+            [
+                i(3000.0, -1.3),
+                i(3000.0, -1.3 - 2.0 * PI / 3.0),
+                i(3000.0, -1.3 + 2.0 * PI / 3.0),
+            ],
+            FaultType::Abc,
+        ),
+    ];
+
+    for (fault, expected) in cases {
+        // The load current is large next to the fault current in the healthy phases, but
+        // it's there before the fault too so is taken away.
+        let channels = faulted_currents(700.0, fault, 30);
+        let classification = classify_fault(&FaultClassifierConfig {
+            currents: [&channels[0], &channels[1], &channels[2]],
+            samples_per_cycle: 20.0,
+            fault_sample: 30,
+        })
+        .expect("expected fault classification");
+
+        assert_eq!(classification.fault_type, expected);
+        assert!(classification.confidence > 0.9, "{:?}", classification);
+    }
+}
+
+#[test]
+fn it_classifies_fault_without_pre_fault_data() {
+    let fault = [
+        Phasor::from_polar(1000.0, -1.3),
+        Phasor::default(),
+        Phasor::default(),
+    ];
+    let channels = faulted_currents(150.0, fault, 0);
+    let classification = classify_fault(&FaultClassifierConfig {
+        currents: [&channels[0], &channels[1], &channels[2]],
+        samples_per_cycle: 20.0,
+        fault_sample: 0,
+    })
+    .expect("expected fault classification");
+
+    // Without the load current to take away, the healthy phases are closer to looking
+    // faulted, so it's less sure.
+    assert_eq!(classification.fault_type, FaultType::Ag);
+    assert_eq!(classification.involved_phases(), vec![Phase::A]);
+    assert!(classification.phase_confidence[0] > 0.99);
+    assert!(classification.confidence < 0.9);
+    assert!(classification.confidence > 0.0);
+
+    assert!(classify_fault(&FaultClassifierConfig {
+        currents: [&channels[0], &channels[1], &channels[2]],
+        samples_per_cycle: 20.0,
+        fault_sample: 70,
+    })
+    .is_none());
+}
+
+#[test]
+fn it_selects_windows_either_side_of_fault() {
+    let fault = [
+        Phasor::from_polar(2000.0, -1.3),
+        Phasor::default(),
+        Phasor::default(),
+    ];
+    let channels = faulted_currents(700.0, fault, 30);
+    let config = WindowSelectionConfig::new(channels.iter().map(Vec::as_slice).collect(), 20.0);
+
+    let windows = select_windows(&config).expect("expected fault windows");
+
+    assert_eq!(windows.change_sample, 30);
+    assert_eq!(windows.pre_fault, Some(5..25));
+    assert_eq!(windows.post_fault, Some(30..50));
+}
+
+#[test]
+fn it_selects_post_fault_window_once_offset_decays() {
+    // Fault current with a decaying offset, which takes a few cycles to settle.
+    let mut channel = sine(100.0, 0.0, 20, 200);
+    for (k, value) in channel.iter_mut().enumerate().skip(40) {
+        let t = (k - 40) as f64;
+        *value = 1000.0 * (2.0 * PI * t / 20.0).sin() + 1000.0 * (-t / 10.0).exp();
+    }
+    let config = WindowSelectionConfig::new(vec![channel.as_slice()], 20.0);
+
+    let windows = select_windows(&config).expect("expected fault windows");
+
+    assert_eq!(windows.change_sample, 40);
+    assert_eq!(windows.pre_fault, Some(15..35));
+    let post_fault = windows.post_fault.expect("expected post-fault window");
+    assert!(post_fault.start > 40, "{:?}", post_fault);
+    assert_eq!(post_fault.len(), 20);
+}
+
+#[test]
+fn it_selects_no_windows_without_a_fault() {
+    let steady = sine(100.0, 0.0, 20, 100);
+    assert!(select_windows(&WindowSelectionConfig::new(vec![steady.as_slice()], 20.0)).is_none());
+
+    // Fault cleared after a cycle and a half never settles.
+    let mut cleared = steady.clone();
+    for value in cleared[50..80].iter_mut() {
+        *value *= 10.0;
+    }
+    let windows = select_windows(&WindowSelectionConfig::new(vec![cleared.as_slice()], 20.0))
+        .expect("expected fault windows");
+    assert_eq!(windows.post_fault, None);
+}
@@ -0,0 +1,70 @@
+#![cfg(feature = "approx")]
+
+use std::path::Path;
+
+use approx::{abs_diff_eq, assert_abs_diff_eq, assert_relative_eq, relative_eq};
+
+use comtrade::analysis::{sequence_components, Phasor};
+use comtrade::Comtrade;
+
+mod common;
+
+use common::SAMPLE_COMTRADE_DIR;
+
+fn sample_record() -> Comtrade {
+    Comtrade::try_from(
+        Path::new(SAMPLE_COMTRADE_DIR)
+            .join("sample_2013_ascii.cff")
+            .as_path(),
+    )
+    .expect("unable to parse sample record")
+}
+
+#[test]
+fn it_compares_records_within_tolerance() {
+    let record = sample_record();
+    let mut nudged = record.clone();
+    for data in nudged.data.analog_data.iter_mut() {
+        for value in data.iter_mut() {
+            *value += 1e-9;
+        }
+    }
+    nudged.config.line_frequency += 1e-12;
+
+    assert_ne!(record, nudged);
+    assert_abs_diff_eq!(record, nudged, epsilon = 1e-6);
+    assert_relative_eq!(record, nudged, epsilon = 1e-6, max_relative = 1e-6);
+    assert!(!abs_diff_eq!(record, nudged, epsilon = 1e-12));
+}
+
+#[test]
+fn it_requires_non_float_fields_to_match_exactly() {
+    let record = sample_record();
+
+    let mut renamed = record.clone();
+    renamed.config.analog_channels[0].name = "IX".to_string();
+    assert!(!abs_diff_eq!(record, renamed, epsilon = 1.0));
+
+    let mut shifted = record.clone();
+    shifted.data.timestamps[1] += 1;
+    assert!(!relative_eq!(
+        record,
+        shifted,
+        epsilon = 1.0,
+        max_relative = 1.0
+    ));
+
+    let mut flipped = record.clone();
+    flipped.data.status_data[0][0] ^= 1;
+    assert!(!abs_diff_eq!(record, flipped));
+}
+
+#[test]
+fn it_compares_analysis_results_within_tolerance() {
+    let a = Phasor::from_polar(1.0, 0.0);
+    let components = sequence_components(a, a, a);
+
+    assert_relative_eq!(components.zero, a, epsilon = 1e-12);
+    assert_abs_diff_eq!(components.positive, Phasor::new(0.0, 0.0), epsilon = 1e-12);
+    assert_abs_diff_eq!(components.negative, Phasor::new(0.0, 0.0), epsilon = 1e-12);
+}
@@ -0,0 +1,78 @@
+#![cfg(feature = "cache")]
+
+mod common;
+
+use std::fs;
+use std::path::Path;
+
+use comtrade::cache::{decode_record, encode_record, CacheError, DirectoryCache, RecordCache};
+use comtrade::source::RecordFiles;
+
+use common::{assert_comtrades_eq, SAMPLE_COMTRADE_DIR};
+
+fn sample_files(name: &str) -> RecordFiles {
+    let dir = Path::new(SAMPLE_COMTRADE_DIR);
+    RecordFiles::Separate {
+        cfg: fs::read(dir.join(format!("{}.cfg", name))).unwrap(),
+        dat: fs::read(dir.join(format!("{}.dat", name))).unwrap(),
+        hdr: None,
+        inf: None,
+    }
+}
+
+#[test]
+fn it_round_trips_records_through_cache_blobs() {
+    let record = sample_files("sample_2013_bin").parse().unwrap();
+
+    let blob = encode_record(&record);
+    let read = decode_record::<()>(&blob).expect("unable to decode cached record");
+
+    assert_eq!(read.config.station_name, record.config.station_name);
+    assert_eq!(read.data.timestamps, record.data.timestamps);
+    assert_eq!(read.config.analog_channels, record.config.analog_channels);
+    assert_eq!(read.config.status_channels, record.config.status_channels);
+
+    assert!(matches!(
+        decode_record::<()>(b"not a cached record"),
+        Err(CacheError::Corrupt(_))
+    ));
+}
+
+#[test]
+fn it_parses_records_through_a_directory_cache() {
+    let dir = std::env::temp_dir().join(format!("comtrade-cache-{}", std::process::id()));
+    let mut cache = DirectoryCache::new(&dir).expect("unable to create cache");
+    let files = sample_files("sample_2013_ascii");
+    let key = files.fingerprint();
+
+    let missing = cache.get_record(key);
+    let parsed = cache.parse(&files);
+    let cached = cache.get_record(key);
+    let parsed_again = cache.parse(&files);
+
+    // A blob which can't be read is replaced by parsing the files again.
+    fs::write(dir.join(format!("{:016x}.ctrc", key)), b"corrupt").unwrap();
+    let reparsed = cache.parse(&files);
+    fs::remove_dir_all(&dir).expect("unable to remove cache directory");
+
+    assert!(missing.unwrap().is_none());
+    let parsed = parsed.expect("unable to parse record");
+    let cached = cached.unwrap().expect("record wasn't cached");
+    assert_comtrades_eq(&cached.clone(), &parsed_again.unwrap());
+    assert_eq!(cached.config.analog_channels, parsed.config.analog_channels);
+    assert_eq!(cached.config.sampling_rates, parsed.config.sampling_rates);
+    assert_eq!(cached.config.start_time, parsed.config.start_time);
+    assert_eq!(reparsed.unwrap().data.timestamps, parsed.data.timestamps);
+}
+
+#[test]
+fn it_keys_records_by_the_contents_of_their_files() {
+    let files = sample_files("sample_2013_ascii");
+    let other = sample_files("sample_2013_bin");
+
+    assert_eq!(
+        files.fingerprint(),
+        sample_files("sample_2013_ascii").fingerprint()
+    );
+    assert_ne!(files.fingerprint(), other.fingerprint());
+}
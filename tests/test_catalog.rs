@@ -0,0 +1,82 @@
+mod common;
+
+use std::path::Path;
+
+use chrono::NaiveDate;
+
+use common::SAMPLE_COMTRADE_DIR;
+use comtrade::ComtradeCatalog;
+
+fn sample_catalog() -> ComtradeCatalog {
+    let mut catalog = ComtradeCatalog::new();
+    let failures = catalog
+        .scan_dir(SAMPLE_COMTRADE_DIR)
+        .expect("unable to read sample directory");
+    assert!(failures.is_empty(), "failed to index {:?}", failures);
+    catalog
+}
+
+#[test]
+fn it_indexes_records_in_directory() {
+    let catalog = sample_catalog();
+    assert_eq!(catalog.len(), 6);
+
+    let entry = catalog
+        .entries()
+        .iter()
+        .find(|e| e.path == Path::new(SAMPLE_COMTRADE_DIR).join("sample_2013_ascii.cfg"))
+        .expect("sample_2013_ascii.cfg not indexed");
+    assert_eq!(entry.station_name, "SMARTSTATION");
+    assert_eq!(entry.recording_device_id, "IED123");
+    assert_eq!(
+        entry.trigger_time,
+        NaiveDate::from_ymd(2011, 1, 12).and_hms_micro(5, 55, 30, 782610)
+    );
+    assert_eq!(entry.duration, 0.0325);
+    assert_eq!(entry.analog_channel_names, vec!["IA", "IB", "IC", "3I0"]);
+    assert_eq!(entry.status_channel_names, vec!["51A", "51B", "51C", "51N"]);
+}
+
+#[test]
+fn it_queries_records_by_station_and_time() {
+    let catalog = sample_catalog();
+
+    // Separate and combined files of the same record - the UTF-8 copy has a longer name.
+    assert_eq!(catalog.by_station("smartstation ").count(), 2);
+    assert_eq!(catalog.by_station("station").count(), 2);
+
+    let start = NaiveDate::from_ymd(2021, 1, 1).and_hms(0, 0, 0);
+    let end = NaiveDate::from_ymd(2022, 1, 1).and_hms(0, 0, 0);
+    let entries: Vec<_> = catalog.by_time_range(start, end).collect();
+    assert_eq!(entries.len(), 1);
+    assert_eq!(entries[0].station_name, "Relay 1");
+    assert_eq!(catalog.by_time_range(end, start).count(), 0);
+}
+
+#[test]
+fn it_replaces_entries_when_rescanning() {
+    let mut catalog = sample_catalog();
+    catalog
+        .scan_dir(SAMPLE_COMTRADE_DIR)
+        .expect("unable to read sample directory");
+    assert_eq!(catalog.len(), 6);
+
+    let removed = catalog.remove(Path::new(SAMPLE_COMTRADE_DIR).join("sample_1999_bin.cfg"));
+    assert!(removed.is_some());
+    assert_eq!(catalog.len(), 5);
+
+    catalog.remove_missing();
+    assert_eq!(catalog.len(), 5);
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn it_round_trips_catalog_through_json() {
+    let catalog = sample_catalog();
+
+    let json = serde_json::to_string(&catalog).expect("unable to serialize catalog");
+    let loaded: ComtradeCatalog =
+        serde_json::from_str(&json).expect("unable to deserialize catalog");
+
+    assert_eq!(loaded, catalog);
+}
@@ -0,0 +1,615 @@
+use std::io::Cursor;
+
+use comtrade::{Comtrade, ComtradeParser, ComtradeParserBuilder, ParseResult, TimeQuality};
+
+// Minimal 1999 record with a single analog and status channel, with the time
+// multiplication factor left for each test to fill in.
+const CFG_1999_TEMPLATE: &str = "\
+QUIRKY STATION,DEVICE 1,1999
+2,1A,1D
+1,IA,A,Line1,A,0.5,0.0,0,-32768,32767,1,1,S
+1,TRIP,,Line1,0
+50
+1
+1000,4
+01/02/2020,00:00:00.000000
+01/02/2020,00:00:00.001000
+ASCII
+{timemult}
+";
+
+const DAT_1999: &str = "\
+1,0,10,0
+2,1000,20,0
+3,2000,30,1
+4,3000,40,1
+";
+
+fn parse_1999(timemult: &str, strict: bool) -> ParseResult<Comtrade> {
+    let cfg = CFG_1999_TEMPLATE.replace("{timemult}", timemult);
+
+    ComtradeParserBuilder::new()
+        .cfg_file(Cursor::new(cfg.into_bytes()))
+        .dat_file(Cursor::new(DAT_1999.as_bytes().to_vec()))
+        .strict(strict)
+        .build()
+        .parse()
+}
+
+#[test]
+fn it_treats_zero_or_blank_timemult_as_one_with_warning() {
+    for timemult in ["0", "0.0", ""] {
+        let record = parse_1999(timemult, false).expect("unable to parse COMTRADE files");
+
+        assert_eq!(record.config.timestamp_multiplication_factor, 1.0);
+        assert_eq!(
+            record.data.timestamps,
+            vec![0, 1_000_000, 2_000_000, 3_000_000]
+        );
+        assert_eq!(record.data.analog_data[0], vec![5.0, 10.0, 15.0, 20.0]);
+        assert_eq!(
+            record.config.warnings.len(),
+            1,
+            "expected single warning for timemult '{}'",
+            timemult
+        );
+        assert!(record.config.warnings[0]
+            .message()
+            .contains("multiplication factor"));
+    }
+}
+
+#[test]
+fn it_rejects_zero_timemult_in_strict_mode() {
+    assert!(parse_1999("0", true).is_err());
+    assert!(parse_1999("", true).is_err());
+
+    let record = parse_1999("1", true).expect("unable to parse COMTRADE files");
+    assert_eq!(record.config.timestamp_multiplication_factor, 1.0);
+    assert!(record.config.warnings.is_empty());
+}
+
+#[test]
+fn it_preserves_vendor_specific_extra_cfg_lines() {
+    let cfg = CFG_1999_TEMPLATE.replace("{timemult}", "1\r\nVENDOR,EXTRA,1\r\n\r\nANOTHER LINE");
+
+    let record = ComtradeParserBuilder::new()
+        .cfg_file(Cursor::new(cfg.into_bytes()))
+        .dat_file(Cursor::new(DAT_1999.as_bytes().to_vec()))
+        .build()
+        .parse()
+        .expect("unable to parse COMTRADE files");
+
+    assert_eq!(
+        record.config.extra_cfg_lines,
+        vec!["VENDOR,EXTRA,1".to_string(), "ANOTHER LINE".to_string()]
+    );
+}
+
+const CFG_1999_DECIMAL_COMMA: &str = "\
+STATION;DEVICE;1999
+2;1A;1D
+1;IA;A;Line1;A;0,5;0,25;1,5;-32768;32767;1;1;S
+1;TRIP;;Line1;0
+50,0
+1
+1000,5;4
+01/02/2020;00:00:00,000000
+01/02/2020;00:00:00,001000
+ASCII
+1,0
+";
+
+const DAT_1999_DECIMAL_COMMA: &str = "\
+1;0;10;0
+2;1000;20;0
+3;2000;30;1
+4;3000;40;1
+";
+
+#[test]
+fn it_detects_semicolon_separated_cfg_with_decimal_commas() {
+    let record = ComtradeParserBuilder::new()
+        .cfg_file(Cursor::new(CFG_1999_DECIMAL_COMMA.as_bytes()))
+        .dat_file(Cursor::new(DAT_1999_DECIMAL_COMMA.as_bytes()))
+        .build()
+        .parse()
+        .expect("unable to parse COMTRADE files");
+
+    let channel = &record.config.analog_channels[0];
+    assert_eq!(channel.multiplier, 0.5);
+    assert_eq!(channel.offset_adder, 0.25);
+    assert_eq!(channel.skew, 1.5);
+    assert_eq!(record.data.analog_data[0], vec![5.25, 10.25, 15.25, 20.25]);
+    assert_eq!(record.config.line_frequency, 50.0);
+    assert_eq!(record.config.sampling_rates[0].rate_hz, 1000.5);
+    assert_eq!(record.data.status_data[0], vec![0, 0, 1, 1]);
+    assert_eq!(record.config.warnings.len(), 1);
+
+    let strict_result = ComtradeParserBuilder::new()
+        .cfg_file(Cursor::new(CFG_1999_DECIMAL_COMMA.as_bytes()))
+        .dat_file(Cursor::new(DAT_1999_DECIMAL_COMMA.as_bytes()))
+        .strict(true)
+        .build()
+        .parse();
+    assert!(strict_result.is_err());
+}
+
+#[test]
+fn it_parses_dat_files_with_decimal_commas_against_a_parsed_config() {
+    let config = ComtradeParserBuilder::new()
+        .cfg_file(Cursor::new(CFG_1999_DECIMAL_COMMA.as_bytes()))
+        .build()
+        .parse_comtrade_config()
+        .expect("unable to parse .cfg file");
+    assert!(config.decimal_comma);
+
+    let record = ComtradeParser::from_config(&config, DAT_1999_DECIMAL_COMMA.as_bytes())
+        .parse()
+        .expect("unable to parse .dat file");
+    assert!(record.config.decimal_comma);
+    assert_eq!(record.data.analog_data[0], vec![5.25, 10.25, 15.25, 20.25]);
+    assert_eq!(record.data.status_data[0], vec![0, 0, 1, 1]);
+}
+
+fn parse_1999_dat(dat: &str) -> ParseResult<Comtrade> {
+    let cfg = CFG_1999_TEMPLATE.replace("{timemult}", "1");
+
+    ComtradeParserBuilder::new()
+        .cfg_file(Cursor::new(cfg.into_bytes()))
+        .dat_file(Cursor::new(dat.as_bytes().to_vec()))
+        .build()
+        .parse()
+}
+
+#[test]
+fn it_parses_non_integer_ascii_data_values() {
+    let record = parse_1999_dat("1, 0, +10 ,0\n2,1000,-2.5,0\n3,2000,1e2,1\n4,3000, 40.0 ,1\n")
+        .expect("unable to parse COMTRADE files");
+
+    assert_eq!(record.data.analog_data[0], vec![5.0, -1.25, 50.0, 20.0]);
+    assert_eq!(record.data.status_data[0], vec![0, 0, 1, 1]);
+}
+
+#[test]
+fn it_rejects_ascii_data_with_wrong_number_of_columns() {
+    assert!(parse_1999_dat("1,0,10,0\n2,1000,20\n").is_err());
+    assert!(parse_1999_dat("1,0,10,0\n2,1000,20,0,5\n").is_err());
+    assert!(parse_1999_dat("1,0,10,0\n2,1000,20,x\n").is_err());
+}
+
+// Timestamp-critical 1999 record with no fixed sampling rate, with the total
+// number of samples left for each test to fill in.
+const CFG_1999_NO_RATES_TEMPLATE: &str = "\
+QUIRKY STATION,DEVICE 1,1999
+2,1A,1D
+1,IA,A,Line1,A,0.5,0.0,0,-32768,32767,1,1,S
+1,TRIP,,Line1,0
+50
+0
+0,{total}
+01/02/2020,00:00:00.000000
+01/02/2020,00:00:00.001000
+ASCII
+1
+";
+
+fn parse_1999_no_rates(total: Option<&str>, strict: bool) -> ParseResult<Comtrade> {
+    let cfg = match total {
+        Some(total) => CFG_1999_NO_RATES_TEMPLATE.replace("{total}", total),
+        None => CFG_1999_NO_RATES_TEMPLATE.replace("0,{total}\n", ""),
+    };
+
+    ComtradeParserBuilder::new()
+        .cfg_file(Cursor::new(cfg.into_bytes()))
+        .dat_file(Cursor::new(DAT_1999.as_bytes().to_vec()))
+        .strict(strict)
+        .build()
+        .parse()
+}
+
+#[test]
+fn it_reads_total_samples_when_no_sampling_rates() {
+    for total in ["4", " 4 ", "4.0"] {
+        let record =
+            parse_1999_no_rates(Some(total), true).expect("unable to parse COMTRADE files");
+
+        assert!(record.config.sampling_rates.is_empty());
+        assert_eq!(record.config.total_num_samples, 4);
+        assert_eq!(record.data.sample_numbers, vec![1, 2, 3, 4]);
+        assert_eq!(
+            record.data.timestamps,
+            vec![0, 1_000_000, 2_000_000, 3_000_000]
+        );
+        assert!(record.config.warnings.is_empty());
+    }
+}
+
+#[test]
+fn it_rounds_down_fractional_total_samples_with_warning() {
+    let record = parse_1999_no_rates(Some("4.5"), false).expect("unable to parse COMTRADE files");
+
+    assert_eq!(record.config.total_num_samples, 4);
+    assert_eq!(record.config.warnings.len(), 1);
+    assert!(record.config.warnings[0]
+        .message()
+        .contains("total number of samples"));
+
+    assert!(parse_1999_no_rates(Some("4.5"), true).is_err());
+    assert!(parse_1999_no_rates(Some("four"), false).is_err());
+}
+
+#[test]
+fn it_counts_samples_in_dat_when_total_not_given() {
+    let record = parse_1999_no_rates(Some("0"), true).expect("unable to parse COMTRADE files");
+    assert_eq!(record.config.total_num_samples, 4);
+    assert_eq!(record.data.sample_numbers, vec![1, 2, 3, 4]);
+    assert!(record.config.warnings.is_empty());
+
+    let record = parse_1999_no_rates(None, false).expect("unable to parse COMTRADE files");
+    assert_eq!(record.config.total_num_samples, 4);
+    assert_eq!(
+        record.data.timestamps,
+        vec![0, 1_000_000, 2_000_000, 3_000_000]
+    );
+    assert_eq!(record.config.warnings.len(), 1);
+    assert!(record.config.warnings[0]
+        .message()
+        .contains("total number of samples"));
+
+    assert!(parse_1999_no_rates(None, true).is_err());
+}
+
+#[test]
+fn it_reports_cfg_line_numbers_around_sampling_rates() {
+    let message = |cfg: String, strict: bool| -> String {
+        let result = ComtradeParserBuilder::new()
+            .cfg_file(Cursor::new(cfg.into_bytes()))
+            .dat_file(Cursor::new(DAT_1999.as_bytes().to_vec()))
+            .strict(strict)
+            .build()
+            .parse();
+        match result {
+            Ok(record) => record.config.warnings[0].message().to_string(),
+            Err(err) => err.to_string(),
+        }
+    };
+    let with_total = CFG_1999_NO_RATES_TEMPLATE.replace("{total}", "4");
+    let without_total = CFG_1999_NO_RATES_TEMPLATE.replace("0,{total}\n", "");
+
+    let cfg = CFG_1999_NO_RATES_TEMPLATE.replace("{total}", "4,4");
+    assert!(message(cfg, false).contains("on line 7"));
+    let cfg = CFG_1999_NO_RATES_TEMPLATE.replace("{total}", "four");
+    assert!(message(cfg, false).contains("on line 7"));
+    assert!(message(without_total.clone(), true).contains("after line 6"));
+
+    // Lines after the total number of samples are numbered from where it was, or
+    // would have been.
+    let bad_start = |cfg: &str| cfg.replacen("01/02/2020,00:00:00.000000", "start", 1);
+    assert!(message(bad_start(&with_total), false).contains("on line 8"));
+    assert!(message(bad_start(&without_total), false).contains("on line 7"));
+
+    let zero_timemult = |cfg: &str| cfg.replace("ASCII\n1\n", "ASCII\n0\n");
+    assert!(message(zero_timemult(&with_total), false).contains("on line 11"));
+    let record = ComtradeParserBuilder::new()
+        .cfg_file(Cursor::new(zero_timemult(&without_total).into_bytes()))
+        .dat_file(Cursor::new(DAT_1999.as_bytes().to_vec()))
+        .build()
+        .parse()
+        .expect("unable to parse COMTRADE files");
+    assert!(record.config.warnings[1].message().contains("on line 10"));
+
+    let cfg = CFG_1999_TEMPLATE
+        .replace("{timemult}", "1")
+        .replace("1000,4", "1000,4,4");
+    assert!(message(cfg, false).contains("on line 7"));
+}
+
+#[test]
+fn it_keeps_extra_channel_columns_in_lenient_mode() {
+    let cfg = CFG_1999_TEMPLATE
+        .replace("{timemult}", "1")
+        .replace("32767,1,1,S\n", "32767,1,1,S,CT1,  spare \n")
+        .replace("Line1,0\n", "Line1,0,NO\n");
+    let parse = |strict: bool| {
+        ComtradeParserBuilder::new()
+            .cfg_file(Cursor::new(cfg.as_bytes().to_vec()))
+            .dat_file(Cursor::new(DAT_1999.as_bytes().to_vec()))
+            .strict(strict)
+            .build()
+            .parse()
+    };
+
+    let record = parse(false).expect("unable to parse COMTRADE files");
+    let analog = &record.config.analog_channels[0];
+    assert_eq!(analog.scaling_mode, comtrade::AnalogScalingMode::Secondary);
+    assert_eq!(record.data.analog_data[0], vec![5.0, 10.0, 15.0, 20.0]);
+    assert_eq!(
+        analog
+            .extensions
+            .get("CFG/Extra_Column_1")
+            .map(String::as_str),
+        Some("CT1")
+    );
+    assert_eq!(
+        analog
+            .extensions
+            .get("CFG/Extra_Column_2")
+            .map(String::as_str),
+        Some("spare")
+    );
+    let status = &record.config.status_channels[0];
+    assert_eq!(status.normal_status_value, 0);
+    assert_eq!(
+        status
+            .extensions
+            .get("CFG/Extra_Column_1")
+            .map(String::as_str),
+        Some("NO")
+    );
+    assert_eq!(record.config.warnings.len(), 2);
+    assert!(record.config.warnings[0]
+        .message()
+        .contains("extra values on line 3"));
+
+    assert!(parse(true).is_err());
+
+    // Too few columns is still an error.
+    let cfg = CFG_1999_TEMPLATE
+        .replace("{timemult}", "1")
+        .replace("Line1,0\n", "Line1\n");
+    let result = ComtradeParserBuilder::new()
+        .cfg_file(Cursor::new(cfg.into_bytes()))
+        .dat_file(Cursor::new(DAT_1999.as_bytes().to_vec()))
+        .build()
+        .parse();
+    assert!(result.is_err());
+}
+
+#[test]
+fn it_keeps_commas_in_quoted_station_and_device_names() {
+    let cfg = CFG_1999_TEMPLATE.replace("{timemult}", "1").replace(
+        "QUIRKY STATION,DEVICE 1,1999",
+        "\"SUBSTATION 4, NORTH\", \"RELAY \"\"A\"\", BAY 2\",1999",
+    );
+
+    let record = ComtradeParserBuilder::new()
+        .cfg_file(Cursor::new(cfg.into_bytes()))
+        .dat_file(Cursor::new(DAT_1999.as_bytes().to_vec()))
+        .build()
+        .parse()
+        .expect("unable to parse COMTRADE files");
+
+    assert_eq!(record.config.station_name, "SUBSTATION 4, NORTH");
+    assert_eq!(record.config.recording_device_id, "RELAY \"A\", BAY 2");
+    assert_eq!(
+        record.config.revision,
+        comtrade::FormatRevision::Revision1999
+    );
+
+    let written = comtrade::ComtradeWriter::new(&record).cfg();
+    assert!(written.starts_with("\"SUBSTATION 4, NORTH\",\"RELAY \"\"A\"\", BAY 2\",1999\r\n"));
+}
+
+// Minimal 2013 record with the time codes and time quality left for each test to fill
+// in.
+const CFG_2013_TEMPLATE: &str = "\
+QUIRKY STATION,DEVICE 1,2013
+2,1A,1D
+1,IA,A,Line1,A,0.5,0.0,0,-32768,32767,1,1,S
+1,TRIP,,Line1,0
+50
+1
+1000,4
+01/02/2020,00:00:00.000000
+01/02/2020,00:00:00.001000
+ASCII
+1
+{time_codes}
+{time_quality}
+";
+
+fn parse_2013(time_codes: &str, time_quality: &str, strict: bool) -> ParseResult<Comtrade> {
+    let cfg = CFG_2013_TEMPLATE
+        .replace("{time_codes}", time_codes)
+        .replace("{time_quality}", time_quality);
+    parse_2013_cfg(cfg, strict)
+}
+
+fn parse_2013_cfg(cfg: String, strict: bool) -> ParseResult<Comtrade> {
+    ComtradeParserBuilder::new()
+        .cfg_file(Cursor::new(cfg.into_bytes()))
+        .dat_file(Cursor::new(DAT_1999.as_bytes().to_vec()))
+        .strict(strict)
+        .build()
+        .parse()
+}
+
+fn parse_2013_time_codes(time_codes: &str) -> ParseResult<Comtrade> {
+    parse_2013(time_codes, "0,0", false)
+}
+
+#[test]
+fn it_parses_time_codes_in_each_form() {
+    let minutes = |m: i32| Some(m * 60);
+    for (time_code, expected) in [
+        ("-4", minutes(-4 * 60)),
+        ("+10h30", minutes(10 * 60 + 30)),
+        ("-7h15", minutes(-(7 * 60 + 15))),
+        ("+0h30", minutes(30)),
+        ("-0h30", minutes(-30)),
+        ("5h", minutes(5 * 60)),
+        ("+05:30", minutes(5 * 60 + 30)),
+        ("-03:00", minutes(-3 * 60)),
+        ("+0545", minutes(5 * 60 + 45)),
+        ("-0930", minutes(-(9 * 60 + 30))),
+        ("0", minutes(0)),
+        ("x", None),
+        ("X", None),
+    ] {
+        let record = parse_2013_time_codes(&format!("{},{}", time_code, time_code))
+            .unwrap_or_else(|err| panic!("unable to parse time code '{}': {}", time_code, err));
+
+        let offset = |o: Option<chrono::FixedOffset>| o.map(|o| o.local_minus_utc());
+        assert_eq!(offset(record.config.time_offset), expected, "{}", time_code);
+        assert_eq!(
+            offset(record.config.local_offset),
+            expected,
+            "{}",
+            time_code
+        );
+    }
+}
+
+#[test]
+fn it_rejects_invalid_time_codes_without_panicking() {
+    for (time_code, message) in [
+        ("+24", "out of range"),
+        ("-99h00", "out of range"),
+        ("+2147483647", "invalid time offset"),
+        ("+5h60", "minutes out of range"),
+        ("+05:75", "minutes out of range"),
+        ("+5h3x", "invalid time offset"),
+        ("5:30:00", "invalid time offset"),
+        ("++5", "invalid time offset"),
+        ("", "invalid time offset"),
+    ] {
+        let err = parse_2013_time_codes(&format!("{},0", time_code))
+            .expect_err(&format!("time code '{}' should be rejected", time_code));
+        assert!(
+            err.to_string().contains(message),
+            "unexpected error for '{}': {}",
+            time_code,
+            err
+        );
+    }
+}
+
+#[test]
+fn it_leaves_blank_or_x_time_quality_unknown_in_lenient_mode() {
+    for time_quality in ["", ",", "x,x", "X,", " , ", "x"] {
+        let record = parse_2013("0,0", time_quality, false).unwrap_or_else(|err| {
+            panic!("unable to parse time quality '{}': {}", time_quality, err)
+        });
+
+        assert_eq!(record.config.time_quality, None, "{}", time_quality);
+        assert_eq!(record.config.leap_second_status, None, "{}", time_quality);
+        assert_eq!(record.config.warnings.len(), 2, "{}", time_quality);
+        assert_eq!(record.data.analog_data[0], vec![5.0, 10.0, 15.0, 20.0]);
+
+        assert!(parse_2013("0,0", time_quality, true).is_err());
+    }
+
+    let record = parse_2013("0,0", "A,x", false).expect("unable to parse COMTRADE files");
+    assert_eq!(
+        record.config.time_quality,
+        Some(TimeQuality::ClockUnlocked(0))
+    );
+    assert_eq!(record.config.leap_second_status, None);
+    assert_eq!(record.config.warnings.len(), 1);
+
+    assert!(parse_2013("0,0", "Q,0", false).is_err());
+}
+
+#[test]
+fn it_accepts_2013_cfg_files_missing_the_final_lines_in_lenient_mode() {
+    let up_to_timemult = CFG_2013_TEMPLATE.split("{time_codes}").next().unwrap();
+    for (cfg, num_warnings) in [
+        (up_to_timemult.to_string(), 1),
+        (up_to_timemult.trim_end().to_string(), 1),
+        (format!("{}\n\n", up_to_timemult), 1),
+        (format!("{}-5h30,-5h30", up_to_timemult), 1),
+        (format!("{}-5h30,-5h30\n", up_to_timemult), 2),
+    ] {
+        let record = parse_2013_cfg(cfg.clone(), false)
+            .unwrap_or_else(|err| panic!("unable to parse {:?}: {}", cfg, err));
+
+        assert_eq!(record.config.time_quality, None);
+        assert_eq!(record.config.leap_second_status, None);
+        assert_eq!(record.config.warnings.len(), num_warnings, "{:?}", cfg);
+        assert_eq!(record.data.analog_data[0], vec![5.0, 10.0, 15.0, 20.0]);
+
+        assert!(parse_2013_cfg(cfg, true).is_err());
+    }
+
+    let record = parse_2013_cfg(up_to_timemult.to_string(), false).unwrap();
+    assert_eq!(record.config.time_offset, None);
+    let record = parse_2013_cfg(format!("{}-5h30,-5h30", up_to_timemult), false).unwrap();
+    assert_eq!(
+        record.config.time_offset.map(|o| o.local_minus_utc()),
+        Some(-(5 * 3600 + 30 * 60))
+    );
+}
+
+fn parse_1999_channel_counts(counts: &str, strict: bool) -> ParseResult<Comtrade> {
+    let cfg = CFG_1999_TEMPLATE
+        .replace("2,1A,1D", counts)
+        .replace("{timemult}", "1");
+    ComtradeParserBuilder::new()
+        .cfg_file(Cursor::new(cfg.into_bytes()))
+        .dat_file(Cursor::new(DAT_1999.as_bytes().to_vec()))
+        .strict(strict)
+        .build()
+        .parse()
+}
+
+#[test]
+fn it_accepts_variations_on_channel_count_suffixes() {
+    for counts in ["2,1A,1D", "2,1a,1d", " 2 ,\t1A , 1 D\t", "2,01A,001D"] {
+        let record = parse_1999_channel_counts(counts, true)
+            .unwrap_or_else(|err| panic!("unable to parse {:?}: {}", counts, err));
+        assert_eq!(record.config.num_analog_channels, 1, "{:?}", counts);
+        assert_eq!(record.config.num_status_channels, 1, "{:?}", counts);
+        assert!(record.config.warnings.is_empty(), "{:?}", counts);
+    }
+
+    let record = parse_1999_channel_counts("2,1,1D", false).expect("unable to parse");
+    assert_eq!(record.config.num_analog_channels, 1);
+    assert_eq!(record.config.warnings.len(), 1);
+    assert!(record.config.warnings[0].message().contains("no 'A'"));
+    assert!(parse_1999_channel_counts("2,1,1D", true).is_err());
+
+    for counts in ["2,1D,1D", "2,A,1D", "2,1A,-1D", "2,1A,1X"] {
+        assert!(
+            parse_1999_channel_counts(counts, false).is_err(),
+            "{:?}",
+            counts
+        );
+    }
+}
+
+#[test]
+fn it_parses_records_with_thousands_of_channels() {
+    let (num_analog, num_status) = (1200, 3400);
+    let mut cfg = format!(
+        "WIDE STATION,DEVICE 1,1999\n{},{}A,{}D\n",
+        num_analog + num_status,
+        num_analog,
+        num_status
+    );
+    for i in 1..=num_analog {
+        cfg += &format!("{},A{},,,V,1,0,0,-99999,99999,1,1,P\n", i, i);
+    }
+    for i in 1..=num_status {
+        cfg += &format!("{},S{},,,0\n", i, i);
+    }
+    cfg += "50\n1\n1000,1\n01/02/2020,00:00:00.000000\n01/02/2020,00:00:00.000000\nASCII\n1\n";
+    let dat = format!(
+        "1,0{}{}\n",
+        ",7".repeat(num_analog),
+        ",1".repeat(num_status)
+    );
+
+    let record = ComtradeParserBuilder::new()
+        .cfg_file(Cursor::new(cfg.into_bytes()))
+        .dat_file(Cursor::new(dat.into_bytes()))
+        .strict(true)
+        .build()
+        .parse()
+        .expect("unable to parse wide record");
+
+    assert_eq!(record.config.num_analog_channels, num_analog as u32);
+    assert_eq!(record.config.num_status_channels, num_status as u32);
+    assert_eq!(record.data.analog_data[num_analog - 1], vec![7.0]);
+    assert_eq!(record.data.status_data[num_status - 1], vec![1]);
+}
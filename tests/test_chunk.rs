@@ -0,0 +1,136 @@
+mod common;
+
+use std::path::Path;
+
+use comtrade::chunk::{split_record, ChunkError, RecordMessage, RecordReassembler, HEADER_SIZE};
+use comtrade::{Comtrade, ComtradeParser, ComtradeWriter};
+
+use common::{assert_comtrades_eq, SAMPLE_COMTRADE_DIR};
+
+fn parse_sample(name: &str) -> Comtrade {
+    let dir = Path::new(SAMPLE_COMTRADE_DIR);
+    Comtrade::from_cfg_dat_paths(
+        dir.join(format!("{}.cfg", name)),
+        dir.join(format!("{}.dat", name)),
+    )
+    .expect("unable to parse sample files")
+}
+
+fn write_and_parse(record: &Comtrade) -> Comtrade {
+    let writer = ComtradeWriter::new(record);
+    let (cfg, dat) = (writer.cfg(), writer.dat());
+    ComtradeParser::from_bytes(cfg.as_bytes(), &dat)
+        .parse()
+        .expect("unable to parse written files")
+}
+
+#[test]
+fn it_splits_records_into_bounded_messages_and_reassembles_them() {
+    for (name, max_size) in [("sample_2013_ascii", 600), ("real_1999_bin", 65536)] {
+        let record = parse_sample(name);
+        let messages = split_record(&record, max_size).expect("unable to split record");
+
+        assert!(messages.len() > 2, "{} split into too few messages", name);
+        assert!(messages[0].is_config());
+        for (i, message) in messages.iter().enumerate() {
+            assert_eq!(message.sequence, i as u32);
+            assert_eq!(message.num_messages, messages.len() as u32);
+            assert_eq!(message.record_id, record.fingerprint());
+            assert!(message.to_bytes().len() <= max_size);
+        }
+
+        // Out of order and with a duplicate, as a message bus might deliver them.
+        let mut reassembler = RecordReassembler::new();
+        let mut reassembled = None;
+        let mut delivered: Vec<&RecordMessage> = messages.iter().rev().collect();
+        delivered.insert(1, &messages[1]);
+        for message in delivered {
+            let bytes = message.to_bytes();
+            let received = RecordMessage::from_bytes(&bytes).expect("unable to decode message");
+            assert!(reassembled.is_none());
+            reassembled = reassembler.push(received).expect("unable to reassemble");
+        }
+
+        let reassembled = reassembled.expect("record not reassembled");
+        assert_comtrades_eq(&reassembled, &write_and_parse(&record));
+        assert_eq!(reassembler.num_pending(), 0);
+    }
+}
+
+#[test]
+fn it_ignores_messages_delivered_again_after_the_record_is_complete() {
+    let record = parse_sample("sample_1999_bin");
+    let messages = split_record(&record, 1024).unwrap();
+
+    let mut reassembler = RecordReassembler::new();
+    let reassembled: Vec<Comtrade> = messages
+        .iter()
+        .filter_map(|message| reassembler.push(message.clone()).unwrap())
+        .collect();
+    assert_eq!(reassembled.len(), 1);
+
+    for message in messages.iter().rev() {
+        assert!(reassembler.push(message.clone()).unwrap().is_none());
+    }
+    assert_eq!(reassembler.num_pending(), 0);
+}
+
+#[test]
+fn it_reassembles_interleaved_records() {
+    let first = parse_sample("sample_1999_bin");
+    let second = parse_sample("sample_2013_ascii");
+    let first_messages = split_record(&first, 1024).unwrap();
+    let second_messages = split_record(&second, 600).unwrap();
+
+    let mut reassembler = RecordReassembler::new();
+    let mut reassembled = Vec::new();
+    let longest = first_messages.len().max(second_messages.len());
+    for i in 0..longest {
+        for messages in [&first_messages, &second_messages] {
+            if let Some(message) = messages.get(i) {
+                reassembled.extend(reassembler.push(message.clone()).unwrap());
+            }
+        }
+    }
+
+    assert_eq!(reassembled.len(), 2);
+    assert!(reassembled
+        .iter()
+        .any(|r| r.config.station_name == first.config.station_name));
+    assert!(reassembled
+        .iter()
+        .any(|r| r.config.station_name == second.config.station_name));
+}
+
+#[test]
+fn it_returns_error_for_messages_too_small_for_the_config() {
+    let record = parse_sample("sample_2013_bin");
+    let cfg_size = ComtradeWriter::new(&record).cfg().len();
+
+    match split_record(&record, 256) {
+        Err(ChunkError::MessageTooSmall { needed }) => assert_eq!(needed, HEADER_SIZE + cfg_size),
+        other => panic!("expected message too small, got {:?}", other),
+    }
+    assert!(split_record(&record, HEADER_SIZE + cfg_size).is_ok());
+}
+
+#[test]
+fn it_returns_error_for_invalid_messages() {
+    assert!(RecordMessage::from_bytes(b"not a message").is_err());
+
+    let record = parse_sample("sample_1999_bin");
+    let mut messages = split_record(&record, 1024).unwrap();
+    let mut reassembler = RecordReassembler::new();
+    reassembler.push(messages.remove(0)).unwrap();
+
+    let mut inconsistent = messages.remove(0);
+    inconsistent.num_messages += 1;
+    assert!(matches!(
+        reassembler.push(inconsistent),
+        Err(ChunkError::InvalidMessage(_))
+    ));
+
+    assert_eq!(reassembler.num_pending(), 1);
+    assert!(reassembler.discard(record.fingerprint()));
+    assert_eq!(reassembler.num_pending(), 0);
+}
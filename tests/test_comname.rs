@@ -0,0 +1,118 @@
+use std::fs;
+use std::path::Path;
+
+use chrono::{FixedOffset, NaiveDate};
+use comtrade::{ComName, Comtrade};
+
+mod common;
+
+use common::SAMPLE_COMTRADE_DIR;
+
+#[test]
+fn it_parses_comname_file_names() {
+    let comname = ComName::parse("/archive/990915,124316394,-5,Tiger Station,Rel 721,Entergy.cfg")
+        .expect("unable to parse COMNAME file name");
+
+    assert_eq!(
+        comname,
+        ComName {
+            start_time: NaiveDate::from_ymd(1999, 9, 15).and_hms_milli(12, 43, 16, 394),
+            time_code: "-5".to_string(),
+            station_name: "Tiger Station".to_string(),
+            device_id: "Rel 721".to_string(),
+            company_name: "Entergy".to_string(),
+            duration: None,
+            user_fields: vec![],
+        }
+    );
+    assert_eq!(comname.offset(), Some(FixedOffset::west(5 * 3600)));
+}
+
+#[test]
+fn it_parses_duration_and_user_fields() {
+    let comname = ComName::parse("20200201,000000000123,+5h30,STATION,IED,ACME,1.5s,AG,Line 4")
+        .expect("unable to parse COMNAME file name");
+
+    assert_eq!(
+        comname.start_time,
+        NaiveDate::from_ymd(2020, 2, 1).and_hms_micro(0, 0, 0, 123)
+    );
+    assert_eq!(
+        comname.offset(),
+        Some(FixedOffset::east(5 * 3600 + 30 * 60))
+    );
+    assert_eq!(comname.duration, Some(1.5));
+    assert_eq!(comname.user_fields, vec!["AG", "Line 4"]);
+}
+
+#[test]
+fn it_rejects_names_not_following_convention() {
+    assert!(ComName::parse("sample_2013_ascii.cfg").is_err());
+    assert!(ComName::parse("991315,124316394,-5,Station,Device,Company.cfg").is_err());
+    assert!(ComName::parse("990915,124316394,local,Station,Device,Company.cfg").is_err());
+}
+
+#[test]
+fn it_composes_file_name_from_record() {
+    let record = Comtrade::try_from(
+        Path::new(SAMPLE_COMTRADE_DIR)
+            .join("sample_2013_ascii.cff")
+            .as_path(),
+    )
+    .expect("unable to parse sample record");
+
+    let mut comname = ComName::from_record(&record);
+    comname.company_name = "ACME, Inc.".to_string();
+    let file_name = comname.file_name("cff");
+    assert_eq!(
+        file_name,
+        "110112,055530750110,-5h30,SMARTSTATION,IED123,ACME_ Inc.,0.0325s.cff"
+    );
+
+    let parsed = ComName::parse(&file_name).expect("unable to parse composed file name");
+    assert_eq!(parsed.start_time, record.config.start_time);
+    assert_eq!(parsed.offset(), record.config.time_offset);
+    assert_eq!(parsed.duration, Some(0.0325));
+}
+
+#[test]
+fn it_attaches_comname_fields_when_parsing_from_path() {
+    let dir = std::env::temp_dir().join(format!("comtrade-comname-{}", std::process::id()));
+    fs::create_dir_all(&dir).expect("unable to create temporary directory");
+    let path = dir.join("110112,055530750110,-5h30,SMARTSTATION,IED123,ACME.cff");
+    fs::copy(
+        Path::new(SAMPLE_COMTRADE_DIR).join("sample_2013_ascii.cff"),
+        &path,
+    )
+    .expect("unable to copy sample record");
+
+    let record = Comtrade::try_from(path.as_path());
+    fs::remove_dir_all(&dir).expect("unable to remove temporary directory");
+    let record = record.expect("unable to parse renamed record");
+
+    assert_eq!(
+        record
+            .config
+            .extensions
+            .get("COMNAME/Station_Name")
+            .map(String::as_str),
+        Some("SMARTSTATION")
+    );
+    assert_eq!(
+        record
+            .config
+            .extensions
+            .get("COMNAME/Company_Name")
+            .map(String::as_str),
+        Some("ACME")
+    );
+    assert_eq!(
+        record
+            .config
+            .extensions
+            .get("COMNAME/Start_Time")
+            .map(String::as_str),
+        Some("2011-01-12T05:55:30.750110")
+    );
+    assert!(!record.config.extensions.contains_key("COMNAME/Duration"));
+}
@@ -0,0 +1,99 @@
+#![cfg(all(feature = "gzip", feature = "zstd"))]
+
+mod common;
+
+use std::convert::TryFrom;
+use std::fs;
+use std::io::Write;
+use std::path::Path;
+
+use comtrade::compression::Compression;
+use comtrade::{Comtrade, ComtradeParserBuilder, ParseLimits};
+
+use common::{assert_comtrades_eq, SAMPLE_COMTRADE_DIR};
+
+const COMPRESSED_DIR: &str = "./tests/compressed_files";
+
+fn parse_sample(file_name: &str) -> Comtrade {
+    Comtrade::try_from(Path::new(SAMPLE_COMTRADE_DIR).join(file_name).as_path())
+        .expect("unable to parse sample")
+}
+
+fn gzip(bytes: &[u8]) -> Vec<u8> {
+    let mut encoder = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default());
+    encoder.write_all(bytes).unwrap();
+    encoder.finish().unwrap()
+}
+
+#[test]
+fn it_detects_compression_from_magic_bytes() {
+    let dir = Path::new(COMPRESSED_DIR);
+    let gz = fs::read(dir.join("sample_2013_bin.dat.gz")).unwrap();
+    let zst = fs::read(dir.join("sample_2013_ascii.cff.zst")).unwrap();
+    let cfg = fs::read(dir.join("sample_2013_bin.cfg")).unwrap();
+
+    assert_eq!(Compression::detect(&gz), Some(Compression::Gzip));
+    assert_eq!(Compression::detect(&zst), Some(Compression::Zstd));
+    assert_eq!(Compression::detect(&cfg), None);
+    assert_eq!(Compression::detect(&[]), None);
+}
+
+#[test]
+fn it_finds_compressed_dat_files_next_to_cfg_files() {
+    let record = Comtrade::try_from(
+        Path::new(COMPRESSED_DIR)
+            .join("sample_2013_bin.cfg")
+            .as_path(),
+    )
+    .expect("unable to parse record with compressed .dat file");
+    assert_comtrades_eq(&record, &parse_sample("sample_2013_bin.cfg"));
+}
+
+#[test]
+fn it_parses_compressed_cfg_and_cff_paths() {
+    let dir = Path::new(COMPRESSED_DIR);
+
+    let record = Comtrade::try_from(dir.join("sample_1999_bin.cfg.zst").as_path())
+        .expect("unable to parse compressed .cfg file");
+    assert_comtrades_eq(&record, &parse_sample("sample_1999_bin.cfg"));
+
+    let record = Comtrade::try_from(dir.join("sample_2013_ascii.cff.zst").as_path())
+        .expect("unable to parse compressed .cff file");
+    assert_comtrades_eq(&record, &parse_sample("sample_2013_ascii.cff"));
+}
+
+#[test]
+fn it_decompresses_files_given_to_the_parser() {
+    let dir = Path::new(SAMPLE_COMTRADE_DIR);
+    let cfg = fs::read(dir.join("sample_2013_bin.cfg")).unwrap();
+    let dat = fs::read(dir.join("sample_2013_bin.dat")).unwrap();
+
+    let record = ComtradeParserBuilder::new()
+        .cfg_file(gzip(&cfg).as_slice())
+        .dat_file(dat.as_slice())
+        .build()
+        .parse()
+        .expect("unable to parse gzipped .cfg file");
+    assert_comtrades_eq(&record, &parse_sample("sample_2013_bin.cfg"));
+}
+
+#[test]
+fn it_applies_limits_to_decompressed_sizes() {
+    let dir = Path::new(SAMPLE_COMTRADE_DIR);
+    let cfg = fs::read(dir.join("sample_2013_bin.cfg")).unwrap();
+    let dat = fs::read(dir.join("sample_2013_bin.dat")).unwrap();
+    let compressed = gzip(&cfg);
+    assert!(compressed.len() < 500);
+
+    let limits = ParseLimits {
+        max_cfg_bytes: 500,
+        ..ParseLimits::default()
+    };
+    let result = ComtradeParserBuilder::new()
+        .cfg_file(compressed.as_slice())
+        .dat_file(dat.as_slice())
+        .build()
+        .limits(limits)
+        .parse();
+    assert!(result.is_err());
+}
@@ -0,0 +1,201 @@
+use std::fs;
+use std::path::Path;
+
+use comtrade::{Comtrade, ComtradeConfig, ComtradeParser, ComtradeParserBuilder, ConfigMismatch};
+
+mod common;
+
+use common::{assert_comtrades_eq, SAMPLE_COMTRADE_DIR};
+
+fn read_sample(name: &str) -> (Vec<u8>, Vec<u8>) {
+    let dir = Path::new(SAMPLE_COMTRADE_DIR);
+    (
+        fs::read(dir.join(format!("{}.cfg", name))).expect("unable to read sample file"),
+        fs::read(dir.join(format!("{}.dat", name))).expect("unable to read sample file"),
+    )
+}
+
+#[test]
+fn it_parses_dat_files_against_a_parsed_config() {
+    for name in ["sample_2013_bin", "sample_2013_ascii", "sample_1999_bin"] {
+        let (cfg, dat) = read_sample(name);
+        let expected = ComtradeParser::from_bytes(&cfg, &dat)
+            .parse()
+            .expect("unable to parse COMTRADE files");
+
+        let config = ComtradeParserBuilder::new()
+            .cfg_file(cfg.as_slice())
+            .build()
+            .parse_comtrade_config()
+            .expect("unable to parse .cfg file");
+        assert_eq!(config, expected.config);
+
+        // The same config can be used for any number of data files.
+        for _ in 0..2 {
+            let record = ComtradeParser::from_config(&config, dat.as_slice())
+                .parse()
+                .expect("unable to parse .dat file");
+            assert_comtrades_eq(&expected, &record);
+        }
+    }
+}
+
+#[test]
+fn it_parses_dat_files_against_a_records_config() {
+    let (cfg, dat) = read_sample("sample_2013_bin");
+    let expected = ComtradeParser::from_bytes(&cfg, &dat)
+        .parse()
+        .expect("unable to parse COMTRADE files");
+
+    let config = expected.config.clone();
+    assert_eq!(config.timestamp_unit, 1000);
+    assert_eq!(config.station_name, expected.config.station_name);
+    assert_eq!(config.sampling_rates, expected.config.sampling_rates);
+
+    let record = ComtradeParserBuilder::new()
+        .config(&config)
+        .dat_file(dat.as_slice())
+        .build()
+        .parse()
+        .expect("unable to parse .dat file");
+    assert_comtrades_eq(&expected, &record);
+}
+
+#[test]
+fn it_splits_a_record_into_config_and_data() {
+    let (cfg, dat) = read_sample("sample_2013_ascii");
+    let record = ComtradeParser::from_bytes(&cfg, &dat)
+        .keep_source_files(true)
+        .parse()
+        .expect("unable to parse COMTRADE files");
+
+    let (config, data) = record.clone().into_parts();
+    assert_eq!(config, record.config);
+    assert_eq!(data.analog_data.len(), config.analog_channels.len());
+    assert_eq!(data.status_data.len(), config.status_channels.len());
+    assert!(data.source_files.is_some());
+
+    // Nothing is lost in splitting the record up, so it's still known to be unmodified.
+    let joined = Comtrade::from_parts(config, data);
+    assert_eq!(joined, record);
+    assert!(joined.is_unmodified());
+}
+
+#[test]
+fn it_predicts_the_size_of_binary_dat_files() {
+    for name in ["sample_2013_bin", "sample_1999_bin"] {
+        let (cfg, dat) = read_sample(name);
+        let config = ComtradeParserBuilder::new()
+            .cfg_file(cfg.as_slice())
+            .build()
+            .parse_comtrade_config()
+            .expect("unable to parse .cfg file");
+
+        assert_eq!(config.expected_dat_size(), Some(dat.len() as u64));
+        assert_eq!(
+            config.bytes_per_scan().unwrap() as u64 * config.total_num_samples,
+            dat.len() as u64
+        );
+
+        let record = ComtradeParser::from_bytes(&cfg, &dat)
+            .parse()
+            .expect("unable to parse COMTRADE files");
+        assert_eq!(record.bytes_per_scan(), config.bytes_per_scan());
+        assert_eq!(record.expected_dat_size(), config.expected_dat_size());
+    }
+
+    let (cfg, _) = read_sample("sample_2013_ascii");
+    let config = ComtradeParserBuilder::new()
+        .cfg_file(cfg.as_slice())
+        .build()
+        .parse_comtrade_config()
+        .expect("unable to parse .cfg file");
+    assert_eq!(config.bytes_per_scan(), None);
+    assert_eq!(config.expected_dat_size(), None);
+}
+
+fn parse_config(name: &str) -> ComtradeConfig {
+    let (cfg, _) = read_sample(name);
+    ComtradeParserBuilder::new()
+        .cfg_file(cfg.as_slice())
+        .build()
+        .parse_comtrade_config()
+        .expect("unable to parse .cfg file")
+}
+
+#[test]
+fn it_accepts_configs_compatible_with_a_template() {
+    let template = parse_config("sample_2013_bin");
+
+    // Later records from the same device differ in their times and lengths.
+    let mut config = template.clone();
+    config.total_num_samples += 100;
+    config.sampling_rates.last_mut().unwrap().end_sample_number += 100;
+    config.start_time += chrono::Duration::days(1);
+    config.analog_channels[0].name = format!(" {} ", config.analog_channels[0].name);
+
+    assert_eq!(config.compatible_with(&template), Ok(()));
+}
+
+#[test]
+fn it_lists_every_mismatch_with_a_template() {
+    let template = parse_config("sample_2013_bin");
+
+    let mut config = template.clone();
+    config.analog_channels.swap(0, 1);
+    config.analog_channels[2].multiplier *= 2.0;
+    config.status_channels.pop();
+    config.sampling_rates[0].rate_hz /= 2.0;
+
+    let mismatches = config.compatible_with(&template).unwrap_err();
+
+    // The swapped channels have different names and scaling.
+    assert_eq!(mismatches.len(), 7, "{:?}", mismatches);
+    assert_eq!(
+        mismatches[0],
+        ConfigMismatch::AnalogChannelName {
+            channel_index: 1,
+            expected: "VA".to_string(),
+            found: "VB".to_string(),
+        }
+    );
+    assert!(matches!(
+        mismatches[1],
+        ConfigMismatch::AnalogChannelScaling {
+            channel_index: 1,
+            ..
+        }
+    ));
+    assert!(matches!(
+        mismatches[2],
+        ConfigMismatch::AnalogChannelName {
+            channel_index: 2,
+            ..
+        }
+    ));
+    assert!(matches!(
+        mismatches[4],
+        ConfigMismatch::AnalogChannelScaling {
+            channel_index: 3,
+            ..
+        }
+    ));
+    assert_eq!(
+        mismatches[5],
+        ConfigMismatch::StatusChannelCount {
+            expected: 16,
+            found: 15,
+        }
+    );
+    assert_eq!(
+        mismatches[6],
+        ConfigMismatch::SamplingRates {
+            expected: vec![15360.0],
+            found: vec![7680.0],
+        }
+    );
+    assert_eq!(
+        mismatches[0].to_string(),
+        "expected analog channel 1 to be 'VA', found 'VB'"
+    );
+}
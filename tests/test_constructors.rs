@@ -0,0 +1,82 @@
+use std::fs;
+use std::path::Path;
+
+use comtrade::{Comtrade, ComtradeParser};
+
+mod common;
+
+use common::{assert_comtrades_eq, SAMPLE_COMTRADE_DIR};
+
+#[test]
+fn it_parses_records_from_paths() {
+    let dir = Path::new(SAMPLE_COMTRADE_DIR);
+
+    let from_cfg_dat = Comtrade::from_cfg_dat_paths(
+        dir.join("sample_2013_ascii.cfg"),
+        dir.join("sample_2013_ascii.dat"),
+    )
+    .expect("unable to parse COMTRADE files");
+    let from_cfg_path = Comtrade::try_from(dir.join("sample_2013_ascii.cfg").as_path())
+        .expect("unable to parse COMTRADE files");
+    let from_cff_path =
+        Comtrade::from_cff_path(dir.join("sample_2013_ascii.cff")).expect("unable to parse cff");
+    let from_cff_try = Comtrade::try_from(dir.join("sample_2013_ascii.cff").as_path())
+        .expect("unable to parse cff");
+
+    assert_comtrades_eq(&from_cfg_path, &from_cfg_dat);
+    assert_comtrades_eq(&from_cff_try, &from_cff_path);
+    assert_eq!(
+        from_cff_path.config.station_name,
+        from_cfg_dat.config.station_name
+    );
+    assert_eq!(
+        from_cff_path.config.analog_channels.len(),
+        from_cfg_dat.config.analog_channels.len()
+    );
+}
+
+#[test]
+fn it_parses_records_from_cff_string() {
+    let dir = Path::new(SAMPLE_COMTRADE_DIR);
+    let contents =
+        fs::read_to_string(dir.join("sample_2013_ascii.cff")).expect("unable to find cff file");
+
+    let record: Comtrade = contents.parse().expect("unable to parse cff");
+    let expected =
+        Comtrade::from_cff_path(dir.join("sample_2013_ascii.cff")).expect("unable to parse cff");
+
+    assert_comtrades_eq(&record, &expected);
+}
+
+#[test]
+fn it_fails_to_parse_from_unknown_or_missing_paths() {
+    let dir = Path::new(SAMPLE_COMTRADE_DIR);
+
+    assert!(Comtrade::try_from(dir.join("real_1999_bin.hdr").as_path()).is_err());
+    assert!(Comtrade::try_from(dir.join("missing.cfg").as_path()).is_err());
+}
+
+#[test]
+fn it_parses_records_from_bytes() {
+    let dir = Path::new(SAMPLE_COMTRADE_DIR);
+    let cfg = fs::read(dir.join("sample_2013_bin.cfg")).expect("unable to find cfg file");
+    let dat = fs::read(dir.join("sample_2013_bin.dat")).expect("unable to find dat file");
+    let cff = fs::read(dir.join("sample_2013_ascii.cff")).expect("unable to find cff file");
+
+    let from_bytes = ComtradeParser::from_bytes(&cfg, &dat)
+        .parse()
+        .expect("unable to parse COMTRADE files");
+    let expected = Comtrade::from_cfg_dat_paths(
+        dir.join("sample_2013_bin.cfg"),
+        dir.join("sample_2013_bin.dat"),
+    )
+    .expect("unable to parse COMTRADE files");
+    assert_comtrades_eq(&from_bytes, &expected);
+
+    let from_cff_bytes = ComtradeParser::from_cff_bytes(&cff)
+        .parse()
+        .expect("unable to parse cff");
+    let expected =
+        Comtrade::from_cff_path(dir.join("sample_2013_ascii.cff")).expect("unable to parse cff");
+    assert_comtrades_eq(&from_cff_bytes, &expected);
+}
@@ -0,0 +1,104 @@
+use std::fs::File;
+use std::io::BufReader;
+use std::path::Path;
+
+use chrono::FixedOffset;
+
+use comtrade::{Comtrade, ComtradeParserBuilder, DataFormat, FormatRevision, Loss};
+
+mod common;
+
+use common::SAMPLE_COMTRADE_DIR;
+
+fn sample_2013_record() -> Comtrade {
+    let dir = Path::new(SAMPLE_COMTRADE_DIR);
+    let cfg_file = BufReader::new(
+        File::open(dir.join("sample_2013_ascii.cfg")).expect("unable to find sample cfg file"),
+    );
+    let dat_file = BufReader::new(
+        File::open(dir.join("sample_2013_ascii.dat")).expect("unable to find sample dat file"),
+    );
+
+    ComtradeParserBuilder::new()
+        .cfg_file(cfg_file)
+        .dat_file(dat_file)
+        .build()
+        .parse()
+        .expect("unable to parse COMTRADE files")
+}
+
+#[test]
+fn it_reports_dropped_2013_fields_when_converting_to_1999() {
+    let original = sample_2013_record();
+    let (record, report) = original.to_1999();
+
+    assert_eq!(record.config.revision, FormatRevision::Revision1999);
+    assert_eq!(record.config.time_offset, None);
+    assert_eq!(record.config.local_offset, None);
+    assert_eq!(record.config.time_quality, None);
+    assert_eq!(record.config.leap_second_status, None);
+    assert_eq!(
+        record.config.analog_channels,
+        original.config.analog_channels
+    );
+    assert_eq!(record.data.timestamps, original.data.timestamps);
+
+    assert!(!report.is_lossless());
+    assert!(report.losses.contains(&Loss::TimeOffset(
+        original
+            .config
+            .time_offset
+            .expect("sample should have time offset")
+    )));
+    assert!(report
+        .losses
+        .iter()
+        .all(|l| !matches!(l, Loss::ScalingFactors { .. } | Loss::DataFormat(_))));
+}
+
+#[test]
+fn it_drops_scaling_factors_and_wide_formats_when_converting_to_1991() {
+    let mut original = sample_2013_record();
+    original.config.data_format = DataFormat::Float32;
+    original.config.timestamp_multiplication_factor = 2.0;
+    original.config.time_offset = Some(FixedOffset::east(3600));
+
+    let (record, report) = original.to_1991();
+
+    assert_eq!(record.config.revision, FormatRevision::Revision1991);
+    assert_eq!(record.config.data_format, DataFormat::Binary16);
+    assert_eq!(record.config.timestamp_multiplication_factor, 1.0);
+    assert!(record
+        .config
+        .analog_channels
+        .iter()
+        .all(|c| c.primary_factor == 1.0 && c.secondary_factor == 1.0));
+
+    assert!(report
+        .losses
+        .contains(&Loss::DataFormat(DataFormat::Float32)));
+    assert!(report
+        .losses
+        .contains(&Loss::TimestampMultiplicationFactor(2.0)));
+    let num_scaling_losses = report
+        .losses
+        .iter()
+        .filter(|l| matches!(l, Loss::ScalingFactors { .. }))
+        .count();
+    let num_scaled_channels = original
+        .config
+        .analog_channels
+        .iter()
+        .filter(|c| c.primary_factor != 1.0 || c.secondary_factor != 1.0)
+        .count();
+    assert_eq!(num_scaling_losses, num_scaled_channels);
+}
+
+#[test]
+fn it_converts_to_2013_without_loss() {
+    let (record_1999, _) = sample_2013_record().to_1999();
+    let (record, report) = record_1999.to_2013();
+
+    assert_eq!(record.config.revision, FormatRevision::Revision2013);
+    assert!(report.is_lossless());
+}
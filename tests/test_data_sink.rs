@@ -0,0 +1,74 @@
+use std::fs;
+use std::path::Path;
+
+use comtrade::{Comtrade, ComtradeParserBuilder, DataSink, Scan};
+
+mod common;
+
+use common::SAMPLE_COMTRADE_DIR;
+
+#[derive(Default)]
+struct CollectingSink {
+    configs: Vec<Comtrade>,
+    scans: Vec<Scan>,
+}
+
+impl DataSink for CollectingSink {
+    fn on_config(&mut self, config: &Comtrade) {
+        self.configs.push(config.clone());
+    }
+
+    fn on_scan(&mut self, scan: &Scan) {
+        self.scans.push(scan.clone());
+    }
+}
+
+fn assert_sink_matches_parse(cfg_name: &str, dat_name: &str) {
+    let dir = Path::new(SAMPLE_COMTRADE_DIR);
+    let cfg = fs::read(dir.join(cfg_name)).expect("unable to find sample cfg file");
+    let dat = fs::read(dir.join(dat_name)).expect("unable to find sample dat file");
+
+    let parser = || {
+        ComtradeParserBuilder::new()
+            .cfg_file(cfg.as_slice())
+            .dat_file(dat.as_slice())
+            .build()
+    };
+
+    let record = parser().parse().expect("unable to parse COMTRADE files");
+    let mut sink = CollectingSink::default();
+    parser()
+        .parse_into(&mut sink)
+        .expect("unable to parse COMTRADE files");
+
+    assert_eq!(sink.configs.len(), 1);
+    assert_eq!(
+        sink.configs[0].config.station_name,
+        record.config.station_name
+    );
+    assert!(sink.configs[0].data.sample_numbers.is_empty());
+    assert!(sink.configs[0].data.analog_data.iter().all(Vec::is_empty));
+
+    assert_eq!(sink.scans.len(), record.data.sample_numbers.len());
+    for (i, scan) in sink.scans.iter().enumerate() {
+        assert_eq!(scan.sample_number, record.data.sample_numbers[i]);
+        assert_eq!(scan.timestamp, record.data.timestamps[i]);
+        for (j, data) in record.data.analog_data.iter().enumerate() {
+            assert_eq!(scan.analog_values[j], data[i]);
+        }
+        for (j, data) in record.data.status_data.iter().enumerate() {
+            assert_eq!(scan.status_values[j], data[i]);
+        }
+    }
+}
+
+#[test]
+fn it_feeds_ascii_samples_into_sink() {
+    assert_sink_matches_parse("sample_2013_ascii.cfg", "sample_2013_ascii.dat");
+}
+
+#[test]
+fn it_feeds_binary_samples_into_sink() {
+    assert_sink_matches_parse("sample_2013_bin.cfg", "sample_2013_bin.dat");
+    assert_sink_matches_parse("real_1999_bin.cfg", "real_1999_bin.dat");
+}
@@ -0,0 +1,132 @@
+mod common;
+
+use chrono::NaiveDate;
+
+use comtrade::{AnalogChannel, Comtrade, ComtradeConfig, ComtradeData, SamplingRate, StatusEvent};
+
+use common::{analog_channel, status_channel};
+
+/// Record of 10 samples at 1 kHz, with a one-sample pulse on `PICKUP` that's shorter
+/// than the decimation windows.
+fn record() -> Comtrade {
+    let start_time = NaiveDate::from_ymd(2021, 6, 3).and_hms(8, 15, 0);
+    Comtrade {
+        config: ComtradeConfig {
+            analog_channels: vec![AnalogChannel {
+                max_value: 10.0,
+                ..analog_channel(1, "IA", "A")
+            }],
+            status_channels: vec![status_channel(1, "PICKUP"), status_channel(2, "TRIP")],
+            num_total_channels: 3,
+            num_analog_channels: 1,
+            num_status_channels: 2,
+            line_frequency: 50.0,
+            sampling_rates: vec![SamplingRate {
+                rate_hz: 1000.0,
+                end_sample_number: 10,
+            }],
+            total_num_samples: 10,
+            start_time,
+            trigger_time: start_time,
+            ..Default::default()
+        },
+        data: ComtradeData {
+            sample_numbers: (1..=10).collect(),
+            timestamps: (0..10).map(|i| i * 1_000_000).collect(),
+            raw_timestamps: (0..10).map(|i| Some(i * 1000)).collect(),
+            analog_data: vec![vec![0.0, 1.0, 2.0, 3.0, 4.0, 5.0, f64::NAN, 7.0, 8.0, 9.0]],
+            status_data: vec![
+                vec![0, 0, 0, 0, 0, 1, 0, 0, 0, 0],
+                vec![0, 0, 0, 1, 1, 1, 1, 1, 1, 1],
+            ],
+            ..Default::default()
+        },
+    }
+}
+
+#[test]
+fn it_decimates_keeping_every_status_change() {
+    let decimated = record().decimate(4);
+    let record = &decimated.record;
+
+    assert_eq!(record.data.sample_numbers, vec![1, 2, 3]);
+    assert_eq!(record.data.timestamps, vec![0, 4_000_000, 8_000_000]);
+    assert!(record.data.raw_timestamps.is_empty());
+    assert_eq!(record.config.total_num_samples, 3);
+    assert_eq!(
+        record.config.sampling_rates,
+        vec![SamplingRate {
+            rate_hz: 250.0,
+            end_sample_number: 3
+        }]
+    );
+
+    // Means over each window, leaving out the missing value.
+    assert_eq!(record.data.analog_data[0], vec![1.5, 16.0 / 3.0, 8.5]);
+
+    // The pulse is longer but still there.
+    assert_eq!(record.data.status_data[0], vec![0, 1, 0]);
+    assert_eq!(record.data.status_data[1], vec![1, 1, 1]);
+
+    assert_eq!(
+        decimated.events,
+        vec![
+            StatusEvent {
+                channel: 1,
+                index: 3,
+                timestamp: 3_000_000,
+                value: 1
+            },
+            StatusEvent {
+                channel: 0,
+                index: 5,
+                timestamp: 5_000_000,
+                value: 1
+            },
+            StatusEvent {
+                channel: 0,
+                index: 6,
+                timestamp: 6_000_000,
+                value: 0
+            },
+        ]
+    );
+}
+
+#[test]
+fn it_keeps_windows_within_sampling_rates() {
+    let mut original = record();
+    original.config.sampling_rates = vec![
+        SamplingRate {
+            rate_hz: 1000.0,
+            end_sample_number: 6,
+        },
+        SamplingRate {
+            rate_hz: 1000.0 / 2.0,
+            end_sample_number: 10,
+        },
+    ];
+    let decimated = original.decimate(4);
+
+    assert_eq!(
+        decimated.record.data.timestamps,
+        vec![0, 4_000_000, 6_000_000]
+    );
+    assert_eq!(
+        decimated.record.config.sampling_rates,
+        vec![
+            SamplingRate {
+                rate_hz: 250.0,
+                end_sample_number: 2
+            },
+            SamplingRate {
+                rate_hz: 125.0,
+                end_sample_number: 3
+            }
+        ]
+    );
+    // A factor of 1 changes nothing but the raw timestamps.
+    let same = record().decimate(1).record;
+    assert_eq!(same.config.status_channels, record().config.status_channels);
+    assert_eq!(same.data.timestamps, record().data.timestamps);
+}
@@ -0,0 +1,56 @@
+use std::fs::File;
+use std::io::{BufRead, BufReader, Cursor};
+use std::path::Path;
+
+use comtrade::{detect_file_type, DetectedFileType};
+
+mod common;
+
+use common::SAMPLE_COMTRADE_DIR;
+
+fn detect(filename: &str) -> DetectedFileType {
+    let path = Path::new(SAMPLE_COMTRADE_DIR).join(filename);
+    let mut reader = BufReader::new(File::open(path).expect("unable to find sample file"));
+    detect_file_type(&mut reader).expect("unable to read sample file")
+}
+
+#[test]
+fn it_detects_type_of_sample_files() {
+    assert_eq!(detect("sample_2013_ascii.cfg"), DetectedFileType::Cfg);
+    assert_eq!(detect("sample_2013_ascii_utf8.cfg"), DetectedFileType::Cfg);
+    assert_eq!(detect("real_1999_bin.cfg"), DetectedFileType::Cfg);
+    assert_eq!(detect("sample_2013_ascii.dat"), DetectedFileType::AsciiDat);
+    assert_eq!(detect("sample_2013_bin.dat"), DetectedFileType::BinaryDat);
+    assert_eq!(detect("real_1999_bin.dat"), DetectedFileType::BinaryDat);
+    assert_eq!(detect("sample_2013_ascii.cff"), DetectedFileType::Cff);
+    assert_eq!(detect("real_1999_bin.hdr"), DetectedFileType::Unknown);
+}
+
+#[test]
+fn it_does_not_consume_reader_when_detecting() {
+    let contents = b"1,0,10,20\n2,1000,11,21\n";
+    let mut reader = Cursor::new(contents.to_vec());
+
+    assert_eq!(
+        detect_file_type(&mut reader).expect("unable to read data"),
+        DetectedFileType::AsciiDat
+    );
+    assert_eq!(reader.fill_buf().unwrap(), contents);
+}
+
+#[test]
+fn it_detects_combined_files_with_byte_order_marks() {
+    let cff = "--- file type: CFG ---\r\nSTATION,DEVICE,2013\r\n1,1A,0D\r\n";
+    let utf16_le: Vec<u8> = [0xFF, 0xFE]
+        .into_iter()
+        .chain(cff.encode_utf16().flat_map(|u| u.to_le_bytes()))
+        .collect();
+    let utf8: Vec<u8> = [0xEF, 0xBB, 0xBF].into_iter().chain(cff.bytes()).collect();
+
+    for bytes in [utf16_le, utf8] {
+        assert_eq!(
+            detect_file_type(&mut Cursor::new(bytes)).expect("unable to read data"),
+            DetectedFileType::Cff
+        );
+    }
+}
@@ -0,0 +1,52 @@
+use std::path::Path;
+
+use comtrade::{Comtrade, Phase};
+
+mod common;
+
+use common::SAMPLE_COMTRADE_DIR;
+
+fn sample_record() -> Comtrade {
+    Comtrade::try_from(
+        Path::new(SAMPLE_COMTRADE_DIR)
+            .join("sample_2013_ascii.cff")
+            .as_path(),
+    )
+    .expect("unable to parse sample record")
+}
+
+#[test]
+fn it_summarises_record() {
+    let expected = "\
+SMARTSTATION / IED123 (2013 revision)
+Started 2011-01-12 05:55:30.750110, triggered 2011-01-12 05:55:30.782610
+40 samples at 1200 Hz over 0.0325 s, 60 Hz line frequency
+4 analog channels
+  IA (A): 40 samples from -23.633 to 30.922
+  IB (A): 40 samples from -18.052 to 28.416
+  IC (A): 40 samples from -2.107 to 2.221
+  3I0 (A): 40 samples from -12.471 to 29.669
+4 status channels
+  51A (normally 0): 40 samples, 1 change
+  51B (normally 0): 40 samples, 1 change
+  51C (normally 0): 40 samples, 0 changes
+  51N (normally 0): 40 samples, 1 change";
+
+    assert_eq!(sample_record().to_string(), expected);
+}
+
+#[test]
+fn it_summarises_channels() {
+    let mut record = sample_record();
+
+    let channel = &mut record.config.analog_channels[0];
+    assert_eq!(channel.to_string(), "IA (A)");
+
+    channel.phase = Phase::A;
+    assert_eq!(channel.to_string(), "IA (A, phase A)");
+
+    record.data.analog_data[0].clear();
+    assert!(record
+        .to_string()
+        .contains("\n  IA (A, phase A): 0 samples\n"));
+}
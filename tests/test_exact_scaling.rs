@@ -0,0 +1,67 @@
+#![cfg(feature = "decimal")]
+
+use std::io::Cursor;
+
+use comtrade::{Comtrade, ComtradeParserBuilder};
+
+// Multiplier which picks up floating point error when applied: 3 * 0.1 gives
+// 0.30000000000000004 rather than 0.3.
+const CFG: &str = "\
+EXACT STATION,DEVICE 1,1999
+1,1A,0D
+1,VA,A,Line1,kV,0.1,0.0,0,-32768,32767,1,1,P
+50
+1
+1000,3
+01/02/2020,00:00:00.000000
+01/02/2020,00:00:00.001000
+ASCII
+1
+";
+
+const DAT: &str = "\
+1,0,3
+2,1000,7
+3,2000,
+";
+
+fn parse(exact_scaling: bool) -> Comtrade {
+    parse_cfg(CFG, exact_scaling)
+}
+
+fn parse_cfg(cfg: &str, exact_scaling: bool) -> Comtrade {
+    ComtradeParserBuilder::new()
+        .cfg_file(Cursor::new(cfg.as_bytes().to_vec()))
+        .dat_file(Cursor::new(DAT.as_bytes().to_vec()))
+        .exact_scaling(exact_scaling)
+        .build()
+        .parse()
+        .expect("unable to parse COMTRADE files")
+}
+
+#[test]
+fn it_scales_analog_values_exactly() {
+    let inexact = parse(false);
+    assert_eq!(inexact.data.analog_data[0][0], 3.0 * 0.1);
+    assert_ne!(inexact.data.analog_data[0][0], 0.3);
+
+    let exact = parse(true);
+    assert_eq!(exact.data.analog_data[0][0], 0.3);
+    assert_eq!(exact.data.analog_data[0][1], 0.7);
+    assert!(exact.data.analog_data[0][2].is_nan());
+    assert_eq!(exact.config.analog_channels[0].multiplier, 0.1);
+}
+
+#[test]
+fn it_scales_in_floating_point_if_factors_dont_fit_in_a_decimal() {
+    let cfg = CFG.replace("kV,0.1,0.0,", "kV,1e30,0.5,");
+    let exact = parse_cfg(&cfg, true);
+
+    assert_eq!(exact.data.analog_data[0][0], 3.0 * 1e30 + 0.5);
+    assert_eq!(exact.data.analog_data[0][1], 7.0 * 1e30 + 0.5);
+    assert!(exact
+        .config
+        .warnings
+        .iter()
+        .any(|w| w.to_string().contains("analog channel 1")));
+}
@@ -0,0 +1,69 @@
+use comtrade::export::preview_json;
+use comtrade::{Comtrade, ComtradeParserBuilder};
+use serde_json::{json, Value};
+
+const CFG_1999: &str = "\
+PREVIEW \"STATION\",DEVICE 1,1999
+2,1A,1D
+1,IA,A,Line1,A,0.5,0.0,0,-32768,32767,1,1,S
+1,TRIP,,Line1,0
+50
+1
+1000,4
+01/02/2020,00:00:00.000000
+01/02/2020,00:00:00.001000
+ASCII
+1
+";
+
+const DAT_1999: &str = "\
+1,0,10,0
+2,1000,20,0
+3,2000,30,1
+4,3000,40,1
+";
+
+fn parse() -> Comtrade {
+    ComtradeParserBuilder::new()
+        .cfg_file(CFG_1999.as_bytes())
+        .dat_file(DAT_1999.as_bytes())
+        .build()
+        .parse()
+        .expect("unable to parse COMTRADE files")
+}
+
+#[test]
+fn it_exports_downsampled_preview_json() {
+    let preview: Value =
+        serde_json::from_str(&preview_json(&parse(), 2)).expect("preview should be valid JSON");
+
+    assert_eq!(
+        preview,
+        json!({
+            "station_name": "PREVIEW \"STATION\"",
+            "recording_device_id": "DEVICE 1",
+            "revision": "1999",
+            "start_time": "2020-02-01T00:00:00",
+            "trigger_time": "2020-02-01T00:00:00.001",
+            "line_frequency": 50,
+            "num_samples": 4,
+            "duration": 0.003,
+            "times": [0, 0.002],
+            "analog_channels": [
+                {"index": 1, "name": "IA", "phase": "A", "units": "A", "min": [5, 15], "max": [10, 20]}
+            ],
+            "status_channels": [
+                {"index": 1, "name": "TRIP", "phase": "", "min": [0, 1], "max": [0, 1]}
+            ]
+        })
+    );
+}
+
+#[test]
+fn it_exports_every_sample_when_fewer_than_max_points() {
+    let preview: Value =
+        serde_json::from_str(&preview_json(&parse(), 100)).expect("preview should be valid JSON");
+
+    assert_eq!(preview["times"].as_array().map(|t| t.len()), Some(4));
+    assert_eq!(preview["analog_channels"][0]["max"], json!([5, 10, 15, 20]));
+}
@@ -0,0 +1,99 @@
+use comtrade::{AnalogFilter, Comtrade, ComtradeParserBuilder};
+
+// Two currents sampled at the same instants, the second 20 µs late and through a filter
+// with a longer delay.
+const CFG_2013: &str = "\
+FILTER STATION,DEVICE 1,2013
+2,2A,0D
+1,IA,A,Line1,A,1.0,0.0,0,-32768,32767,1,1,S
+2,IB,B,Line1,A,1.0,0.0,20,-32768,32767,1,1,S
+50
+1
+1000,2
+01/02/2020,00:00:00.000000
+01/02/2020,00:00:00.001000
+ASCII
+1
+0,0
+F,0
+";
+
+const DAT_2013: &str = "\
+1,0,10,20
+2,1000,30,40
+";
+
+const INF: &str = "\
+[Public Analog_Channel_#1]
+Filter_Type=Butterworth
+Filter_Cutoff_Frequency=400
+Filter_Order=4
+Filter_Group_Delay=100
+
+[ACME Corp Analog_Channel_#2]
+filter_group_delay=250.5
+";
+
+fn parse(inf: &str) -> Comtrade {
+    ComtradeParserBuilder::new()
+        .cfg_file(CFG_2013.as_bytes())
+        .dat_file(DAT_2013.as_bytes())
+        .inf_file(inf.as_bytes())
+        .build()
+        .parse()
+        .expect("unable to parse COMTRADE files")
+}
+
+#[test]
+fn it_reads_filters_from_channel_extensions() {
+    let record = parse(INF);
+
+    assert_eq!(
+        record.config.analog_channels[0].filter(),
+        Some(AnalogFilter {
+            filter_type: Some("Butterworth".to_string()),
+            cutoff_hz: Some(400.0),
+            order: Some(4),
+            group_delay: Some(100.0),
+        })
+    );
+    assert_eq!(record.config.analog_channels[1].group_delay(), Some(250.5));
+    assert_eq!(
+        record.config.analog_channels[1]
+            .filter()
+            .unwrap()
+            .filter_type,
+        None
+    );
+}
+
+#[test]
+fn it_has_no_filter_without_filter_extensions() {
+    let record = parse("[Public Analog_Channel_#1]\nChannel_ID=IA\n");
+
+    assert_eq!(record.config.analog_channels[0].filter(), None);
+    assert_eq!(record.config.analog_channels[0].group_delay(), None);
+}
+
+#[test]
+fn it_compensates_channel_timestamps_for_skew_and_group_delay() {
+    let record = parse(INF);
+
+    assert_eq!(
+        record.channel_timestamps(0, false),
+        Some(vec![0, 1_000_000])
+    );
+    assert_eq!(
+        record.channel_timestamps(0, true),
+        Some(vec![-100_000, 900_000])
+    );
+    assert_eq!(
+        record.channel_timestamps(1, false),
+        Some(vec![20_000, 1_020_000])
+    );
+    assert_eq!(
+        record.channel_timestamps(1, true),
+        Some(vec![-230_500, 769_500])
+    );
+    assert_eq!(record.channel_timestamps(2, true), None);
+}
@@ -0,0 +1,71 @@
+use std::path::Path;
+
+use comtrade::{Comtrade, DataFormat};
+
+mod common;
+
+use common::SAMPLE_COMTRADE_DIR;
+
+fn parse(file_name: &str) -> Comtrade {
+    Comtrade::try_from(Path::new(SAMPLE_COMTRADE_DIR).join(file_name).as_path())
+        .expect("unable to parse sample record")
+}
+
+#[test]
+fn it_gives_same_fingerprint_whatever_the_files() {
+    let separate = parse("sample_2013_ascii.cfg");
+    let combined = parse("sample_2013_ascii.cff");
+
+    assert_eq!(separate.fingerprint(), combined.fingerprint());
+    // Fingerprints are stored by ingestion pipelines, so mustn't change.
+    assert_eq!(separate.fingerprint(), 10869160611799500011);
+}
+
+#[test]
+fn it_ignores_how_the_record_is_stored() {
+    let record = parse("sample_2013_ascii.cff");
+
+    let (mut converted, _) = record.to_1999();
+    converted.config.data_format = DataFormat::Float32;
+    converted.config.header = "Retrieved from substation gateway".to_string();
+    converted.config.station_name = "  SMARTSTATION ".to_string();
+    converted
+        .config
+        .extensions
+        .insert("COMNAME/Company_Name".to_string(), "ACME".to_string());
+    for data in converted.data.analog_data.iter_mut() {
+        for value in data.iter_mut() {
+            *value = *value as f32 as f64;
+        }
+    }
+
+    assert_eq!(record.fingerprint(), converted.fingerprint());
+
+    // Same recording saved in different revisions.
+    assert_eq!(
+        parse("sample_1999_bin.cfg").fingerprint(),
+        parse("sample_2013_bin.cfg").fingerprint()
+    );
+}
+
+#[test]
+fn it_distinguishes_different_records() {
+    let record = parse("sample_2013_ascii.cff");
+
+    let mut changed_value = record.clone();
+    changed_value.data.analog_data[0][10] += 1.0;
+    assert_ne!(record.fingerprint(), changed_value.fingerprint());
+
+    let mut changed_time = record.clone();
+    changed_time.config.trigger_time += chrono::Duration::microseconds(1);
+    assert_ne!(record.fingerprint(), changed_time.fingerprint());
+
+    let mut changed_status = record.clone();
+    changed_status.data.status_data[0][0] ^= 1;
+    assert_ne!(record.fingerprint(), changed_status.fingerprint());
+
+    assert_ne!(
+        record.fingerprint(),
+        parse("sample_2013_bin.cfg").fingerprint()
+    );
+}
@@ -0,0 +1,106 @@
+use comtrade::{Comtrade, ComtradeParserBuilder, ParseResult};
+
+const CFG_1999: &str = "\
+INF STATION,DEVICE 1,1999
+2,1A,1D
+1,IA,A,Line1,A,1.0,0.0,0,-32768,32767,1,1,S
+1,TRIP,,Line1,0
+50
+1
+1000,2
+01/02/2020,00:00:00.000000
+01/02/2020,00:00:00.001000
+ASCII
+1
+";
+
+const DAT_1999: &str = "\
+1,0,10,0
+2,1000,20,1
+";
+
+const INF: &str = "\
+; Written by the recorder.
+[Public Record_Information]
+Source=Relay 1
+Record_Information=Fault
+
+[Public Analog_Channel_#1]
+Channel_ID=IA
+Phase_ID = A
+
+[ACME Corp Status_Channel_#1]
+Contact=52a
+
+[ACME Corp Settings]
+Group=2
+";
+
+fn parse(inf: &str, strict: bool) -> ParseResult<Comtrade> {
+    ComtradeParserBuilder::new()
+        .cfg_file(CFG_1999.as_bytes())
+        .dat_file(DAT_1999.as_bytes())
+        .inf_file(inf.as_bytes())
+        .strict(strict)
+        .build()
+        .parse()
+}
+
+#[test]
+fn it_reads_inf_sections_into_extensions() {
+    let record = parse(INF, true).expect("unable to parse COMTRADE files");
+
+    let extensions: Vec<(&str, &str)> = record
+        .config
+        .extensions
+        .iter()
+        .map(|(k, v)| (k.as_str(), v.as_str()))
+        .collect();
+    assert_eq!(
+        extensions,
+        vec![
+            ("ACME Corp Settings/Group", "2"),
+            ("Public Record_Information/Record_Information", "Fault"),
+            ("Public Record_Information/Source", "Relay 1"),
+        ]
+    );
+
+    let analog = &record.config.analog_channels[0].extensions;
+    assert_eq!(analog.len(), 2);
+    assert_eq!(analog["Public/Channel_ID"], "IA");
+    assert_eq!(analog["Public/Phase_ID"], "A");
+
+    let status = &record.config.status_channels[0].extensions;
+    assert_eq!(status.len(), 1);
+    assert_eq!(status["ACME Corp/Contact"], "52a");
+
+    assert!(record.config.warnings.is_empty());
+}
+
+#[test]
+fn it_ignores_invalid_inf_entries_with_warning() {
+    let inf = "Orphan=1\n[Public Record_Information]\nnot an entry\nSource=Relay 1\n";
+
+    let record = parse(inf, false).expect("unable to parse COMTRADE files");
+    assert_eq!(record.config.extensions.len(), 1);
+    assert_eq!(record.config.warnings.len(), 2);
+
+    assert!(parse(inf, true).is_err());
+}
+
+#[test]
+fn it_writes_inf_file_back_from_extensions() {
+    let record = parse(INF, true).expect("unable to parse COMTRADE files");
+    let inf = comtrade::ComtradeWriter::new(&record).inf();
+
+    let reparsed = parse(&inf, true).expect("unable to parse written .inf file");
+    assert_eq!(reparsed.config.extensions, record.config.extensions);
+    assert_eq!(
+        reparsed.config.analog_channels[0].extensions,
+        record.config.analog_channels[0].extensions
+    );
+    assert_eq!(
+        reparsed.config.status_channels[0].extensions,
+        record.config.status_channels[0].extensions
+    );
+}
@@ -0,0 +1,106 @@
+#![cfg(feature = "json")]
+
+use comtrade::json::{from_json, to_json, JsonRecordError, JSON_SCHEMA};
+use comtrade::{ComtradeParserBuilder, ComtradeWriter, DataFormat, FormatRevision};
+use serde_json::{json, Value};
+
+const CFG_1999: &str = "\
+JSON STATION,DEVICE 1,1999
+2,1A,1D
+1,IA,A,Line1,A,0.5,0.0,0,-32768,32767,1,1,S
+1,TRIP,,Line1,0
+50
+1
+1000,4
+01/02/2020,00:00:00.000000
+01/02/2020,00:00:00.001000
+ASCII
+1
+";
+
+const DAT_1999: &str = "\
+1,0,10,0
+2,1000,,0
+3,2000,30,1
+4,3000,40,1
+";
+
+#[test]
+fn it_round_trips_records_through_json() {
+    let record = ComtradeParserBuilder::new()
+        .cfg_file(CFG_1999.as_bytes())
+        .dat_file(DAT_1999.as_bytes())
+        .build()
+        .parse()
+        .expect("unable to parse COMTRADE files");
+
+    let json = to_json(&record);
+    let value: Value = serde_json::from_str(&json).unwrap();
+    assert_eq!(
+        value["analog_channels"][0]["values"],
+        json!([5.0, null, 15.0, 20.0])
+    );
+    assert_eq!(value["start_time"], "2020-02-01T00:00:00");
+
+    let read = from_json(&json).expect("unable to read JSON record");
+    assert_eq!(read.config.station_name, record.config.station_name);
+    assert_eq!(read.data.timestamps, record.data.timestamps);
+    assert_eq!(read.config.sampling_rates, record.config.sampling_rates);
+    assert_eq!(read.config.status_channels, record.config.status_channels);
+    assert_eq!(read.config.analog_channels[0].multiplier, 0.5);
+    assert_eq!(read.data.analog_data[0][0], 5.0);
+    assert!(read.data.analog_data[0][1].is_nan());
+}
+
+#[test]
+fn it_fills_in_defaults_for_minimal_json() {
+    let record = from_json(
+        r#"{
+            "station_name": "WEB", "recording_device_id": "APP", "revision": "2013",
+            "line_frequency": 60,
+            "start_time": "2021-03-04T05:06:07.5", "trigger_time": "2021-03-04T05:06:07.5",
+            "timestamps": [0, 500000],
+            "analog_channels": [{"name": "VA", "values": [1.5, -2.5]}],
+            "status_channels": [{"name": "TRIP", "values": [0, 1]}]
+        }"#,
+    )
+    .expect("unable to read JSON record");
+
+    assert_eq!(record.config.revision, FormatRevision::Revision2013);
+    assert_eq!(record.config.data_format, DataFormat::Ascii);
+    assert_eq!(record.data.sample_numbers, vec![1, 2]);
+    assert_eq!(record.config.num_total_channels, 2);
+    let channel = &record.config.analog_channels[0];
+    assert_eq!(channel.index, 1);
+    assert_eq!((channel.min_value, channel.max_value), (-2.5, 1.5));
+
+    // It can be written straight out as COMTRADE files.
+    let writer = ComtradeWriter::new(&record);
+    let written = comtrade::ComtradeParser::from_bytes(writer.cfg().as_bytes(), &writer.dat())
+        .parse()
+        .expect("unable to parse written files");
+    assert_eq!(written.data.analog_data[0], vec![1.5, -2.5]);
+}
+
+#[test]
+fn it_rejects_invalid_json_records() {
+    let unknown_field = from_json(r#"{"station_name": "A", "colour": "red"}"#);
+    assert!(matches!(unknown_field, Err(JsonRecordError::Json(_))));
+
+    let wrong_length = from_json(
+        r#"{
+            "station_name": "WEB", "recording_device_id": "APP", "revision": "1999",
+            "line_frequency": 50,
+            "start_time": "2021-03-04T05:06:07", "trigger_time": "2021-03-04T05:06:07",
+            "timestamps": [0, 1000],
+            "analog_channels": [{"name": "VA", "values": [1.0]}]
+        }"#,
+    );
+    assert!(matches!(wrong_length, Err(JsonRecordError::Invalid(_))));
+}
+
+#[test]
+fn it_provides_valid_json_schema() {
+    let schema: Value = serde_json::from_str(JSON_SCHEMA).expect("schema should be valid JSON");
+    assert_eq!(schema["title"], "COMTRADE record");
+}
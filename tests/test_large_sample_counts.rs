@@ -0,0 +1,94 @@
+use comtrade::{Comtrade, ComtradeParserBuilder, ParseResult};
+
+// Long capture whose sample numbers run past what fits in a `u32`, with the end sample
+// number left for each test to fill in.
+const CFG_2013_TEMPLATE: &str = "\
+LONG STATION,DEVICE 1,2013
+2,1A,1D
+1,IA,A,Line1,A,1.0,0.0,0,-32768,32767,1,1,S
+1,TRIP,,Line1,0
+50
+1
+1000,{end_sample}
+01/02/2020,00:00:00.000000
+01/02/2020,00:00:00.001000
+{format}
+1
+0,0
+F,0
+";
+
+fn parse(end_sample: &str, format: &str, dat: &[u8]) -> ParseResult<Comtrade> {
+    let cfg = CFG_2013_TEMPLATE
+        .replace("{end_sample}", end_sample)
+        .replace("{format}", format);
+
+    ComtradeParserBuilder::new()
+        .cfg_file(cfg.as_bytes())
+        .dat_file(dat)
+        .build()
+        .parse()
+}
+
+#[test]
+fn it_parses_sample_numbers_larger_than_u32() {
+    let first = u32::MAX as u64 + 1;
+    let dat = format!("{},0,10,0\n{},1000,20,1\n", first, first + 1);
+
+    let record = parse(&(first + 1).to_string(), "ASCII", dat.as_bytes())
+        .expect("unable to parse COMTRADE files");
+
+    assert_eq!(record.config.total_num_samples, first + 1);
+    assert_eq!(record.config.sampling_rates[0].end_sample_number, first + 1);
+    assert_eq!(record.data.sample_numbers, vec![first, first + 1]);
+    assert_eq!(
+        record.data.timestamps,
+        vec![(first - 1) as i64 * 1_000_000, first as i64 * 1_000_000]
+    );
+    assert_eq!(record.data.analog_data[0], vec![10.0, 20.0]);
+}
+
+#[test]
+fn it_keeps_nanosecond_timestamps_exact() {
+    // Timestamps in the data file are in nanoseconds, scaled up to cover a long capture.
+    let cfg = "\
+LONG STATION,DEVICE 1,2013
+1,1A,0D
+1,IA,A,Line1,A,1.0,0.0,0,-32768,32767,1,1,S
+50
+0
+0,2
+01/02/2020,00:00:00.000000000
+01/02/2020,00:00:00.001000000
+ASCII
+1000
+0,0
+F,0
+";
+    let dat = "1,4000000000,10\n2,4000000001,20\n";
+
+    let record = ComtradeParserBuilder::new()
+        .cfg_file(cfg.as_bytes())
+        .dat_file(dat.as_bytes())
+        .build()
+        .parse()
+        .expect("unable to parse COMTRADE files");
+
+    assert_eq!(
+        record.data.timestamps,
+        vec![4_000_000_000_000, 4_000_000_001_000]
+    );
+    assert_eq!(record.timestamp_seconds(1), Some(4000.000001));
+    assert_eq!(record.duration(), 0.000001);
+}
+
+#[test]
+fn it_errors_on_binary_data_shorter_than_huge_total() {
+    let mut dat = vec![];
+    dat.extend_from_slice(&1u32.to_le_bytes());
+    dat.extend_from_slice(&0u32.to_le_bytes());
+    dat.extend_from_slice(&10i16.to_le_bytes());
+    dat.extend_from_slice(&0u16.to_le_bytes());
+
+    assert!(parse(&u64::MAX.to_string(), "BINARY", &dat).is_err());
+}
@@ -0,0 +1,105 @@
+use chrono::{FixedOffset, NaiveDate, NaiveDateTime};
+
+use comtrade::{Comtrade, ComtradeConfig, ComtradeData, LeapSecondStatus};
+
+// Four seconds of samples at 2 Hz either side of the leap second at the end of 2016.
+fn record(leap_second_status: LeapSecondStatus, utc_offset_hours: i32) -> Comtrade {
+    let start_time = NaiveDate::from_ymd(2016, 12, 31).and_hms(23, 59, 58)
+        + chrono::Duration::hours(utc_offset_hours as i64);
+
+    Comtrade {
+        config: ComtradeConfig {
+            start_time,
+            trigger_time: start_time,
+            time_offset: Some(FixedOffset::east(utc_offset_hours * 3600)),
+            leap_second_status: Some(leap_second_status),
+            ..Default::default()
+        },
+        data: ComtradeData {
+            timestamps: (0..8).map(|i| i * 500_000_000).collect(),
+            ..Default::default()
+        },
+    }
+}
+
+fn absolute_times(record: &Comtrade) -> Vec<NaiveDateTime> {
+    (0..record.data.timestamps.len())
+        .map(|i| record.absolute_time(i).expect("sample should exist"))
+        .collect()
+}
+
+fn utc(hour: u32, min: u32, sec: u32, milli: u32) -> NaiveDateTime {
+    let date = if hour == 0 {
+        NaiveDate::from_ymd(2017, 1, 1)
+    } else {
+        NaiveDate::from_ymd(2016, 12, 31)
+    };
+    date.and_hms_milli(hour, min, sec, milli)
+}
+
+#[test]
+fn it_gives_unadjusted_times_without_leap_second() {
+    let record = record(LeapSecondStatus::NotPresent, 0);
+
+    assert_eq!(record.leap_second_time(), None);
+    assert_eq!(record.leap_second_span(), None);
+    assert_eq!(absolute_times(&record)[4], utc(0, 0, 0, 0));
+}
+
+#[test]
+fn it_adjusts_times_after_added_leap_second() {
+    let record = record(LeapSecondStatus::Added, 0);
+
+    assert_eq!(record.leap_second_time(), Some(utc(0, 0, 0, 0)));
+    assert_eq!(record.leap_second_span(), Some(4..8));
+    assert_eq!(
+        absolute_times(&record),
+        vec![
+            utc(23, 59, 58, 0),
+            utc(23, 59, 58, 500),
+            utc(23, 59, 59, 0),
+            utc(23, 59, 59, 500),
+            // 23:59:60.000 and 23:59:60.500.
+            utc(23, 59, 59, 1000),
+            utc(23, 59, 59, 1500),
+            utc(0, 0, 0, 0),
+            utc(0, 0, 0, 500),
+        ]
+    );
+}
+
+#[test]
+fn it_adjusts_times_after_subtracted_leap_second() {
+    let record = record(LeapSecondStatus::Subtracted, 0);
+
+    assert_eq!(record.leap_second_span(), Some(2..8));
+    assert_eq!(
+        absolute_times(&record),
+        vec![
+            utc(23, 59, 58, 0),
+            utc(23, 59, 58, 500),
+            utc(0, 0, 0, 0),
+            utc(0, 0, 0, 500),
+            utc(0, 0, 1, 0),
+            utc(0, 0, 1, 500),
+            utc(0, 0, 2, 0),
+            utc(0, 0, 2, 500),
+        ]
+    );
+}
+
+#[test]
+fn it_finds_utc_midnight_using_time_code() {
+    // Record is in UTC+2, so the leap second is at 02:00 local time.
+    let record = record(LeapSecondStatus::Added, 2);
+
+    assert_eq!(
+        record.leap_second_time(),
+        Some(NaiveDate::from_ymd(2017, 1, 1).and_hms(2, 0, 0))
+    );
+    assert_eq!(record.leap_second_span(), Some(4..8));
+    assert_eq!(
+        record.absolute_time(6),
+        Some(NaiveDate::from_ymd(2017, 1, 1).and_hms(2, 0, 0))
+    );
+}
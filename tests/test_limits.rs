@@ -0,0 +1,148 @@
+use std::io::Cursor;
+
+use comtrade::{Comtrade, ComtradeParser, ComtradeParserBuilder, ParseLimits, ParseResult};
+
+const CFG: &str = "\
+LIMITED STATION,DEVICE 1,1999
+2,1A,1D
+1,IA,A,Line1,A,0.5,0.0,0,-32768,32767,1,1,S
+1,TRIP,,Line1,0
+50
+1
+1000,4
+01/02/2020,00:00:00.000000
+01/02/2020,00:00:00.001000
+ASCII
+1
+";
+
+const DAT: &str = "\
+1,0,10,0
+2,1000,20,0
+3,2000,30,1
+4,3000,40,1
+";
+
+fn parse(cfg: &str, limits: ParseLimits) -> ParseResult<Comtrade> {
+    ComtradeParserBuilder::new()
+        .cfg_file(Cursor::new(cfg.as_bytes().to_vec()))
+        .dat_file(Cursor::new(DAT.as_bytes().to_vec()))
+        .limits(limits)
+        .build()
+        .parse()
+}
+
+#[test]
+fn it_parses_records_within_limits() {
+    let limits = ParseLimits {
+        max_cfg_bytes: CFG.len(),
+        max_channels: 2,
+        max_sampling_rates: 1,
+        max_samples: 4,
+    };
+    let record = parse(CFG, limits).expect("record should be within limits");
+    assert_eq!(record.data.analog_data[0].len(), 4);
+}
+
+#[test]
+fn it_rejects_huge_channel_counts_by_default() {
+    let cfg = CFG.replace("2,1A,1D", "4000000000,3999999999A,1D");
+    let error = parse(&cfg, ParseLimits::default()).unwrap_err();
+    assert_eq!(
+        error.to_string(),
+        "record has 4000000000 channels, more than the limit of 999999"
+    );
+}
+
+#[test]
+fn it_rejects_records_over_limits() {
+    let limits = ParseLimits::default();
+
+    let error = parse(
+        CFG,
+        ParseLimits {
+            max_channels: 1,
+            ..limits
+        },
+    )
+    .unwrap_err();
+    assert_eq!(
+        error.to_string(),
+        "record has 2 channels, more than the limit of 1"
+    );
+
+    let cfg = CFG.replace("\n1\n1000,4\n", "\n2\n1000,2\n500,4\n");
+    let error = parse(
+        &cfg,
+        ParseLimits {
+            max_sampling_rates: 1,
+            ..limits
+        },
+    )
+    .unwrap_err();
+    assert_eq!(
+        error.to_string(),
+        "record has 2 sampling rates, more than the limit of 1"
+    );
+
+    let error = parse(
+        CFG,
+        ParseLimits {
+            max_samples: 3,
+            ..limits
+        },
+    )
+    .unwrap_err();
+    assert_eq!(
+        error.to_string(),
+        "record has more than the limit of 3 samples"
+    );
+
+    let error = parse(
+        CFG,
+        ParseLimits {
+            max_cfg_bytes: 100,
+            ..limits
+        },
+    )
+    .unwrap_err();
+    assert_eq!(
+        error.to_string(),
+        ".cfg file is larger than the limit of 100 bytes"
+    );
+}
+
+#[test]
+fn it_limits_samples_counted_from_data() {
+    // No fixed sampling rate or total, so the samples are only counted in the data.
+    let cfg = CFG.replace("\n1\n1000,4\n", "\n0\n");
+    let limits = ParseLimits {
+        max_samples: 2,
+        ..ParseLimits::default()
+    };
+    let error = parse(&cfg, limits).unwrap_err();
+    assert_eq!(
+        error.to_string(),
+        "record has more than the limit of 2 samples"
+    );
+}
+
+#[test]
+fn it_limits_configuration_section_of_cff_file() {
+    let cff = format!(
+        "--- file type: CFG ---\n{}--- file type: DAT ASCII ---\n{}",
+        CFG, DAT
+    );
+    let limits = ParseLimits {
+        max_cfg_bytes: 100,
+        ..ParseLimits::default()
+    };
+    let error = ComtradeParser::from_cff_bytes(cff.as_bytes())
+        .limits(limits)
+        .parse()
+        .unwrap_err();
+    assert_eq!(
+        error.to_string(),
+        ".cfg file is larger than the limit of 100 bytes"
+    );
+}
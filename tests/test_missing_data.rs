@@ -0,0 +1,167 @@
+use comtrade::analysis::{
+    derive_channel, derive_channel_with_policy, phasors, phasors_with_policy, DerivedKind,
+};
+use comtrade::export::{preview_json, preview_json_with_policy};
+use comtrade::{Comtrade, ComtradeParserBuilder, MissingDataError, MissingDataPolicy};
+
+const CFG: &str = "\
+MISSING STATION,DEVICE 1,1999
+1,1A,0D
+1,VA,A,Line1,V,2.0,0.0,0,-32767,32767,1,1,S
+50
+1
+1000,6
+01/02/2020,00:00:00.000000
+01/02/2020,00:00:00.001000
+{format}
+1
+";
+
+fn parse(format: &str, dat: &[u8]) -> Comtrade {
+    let cfg = CFG.replace("{format}", format);
+    ComtradeParserBuilder::new()
+        .cfg_file(cfg.as_bytes())
+        .dat_file(dat)
+        .build()
+        .parse()
+        .expect("unable to parse COMTRADE files")
+}
+
+fn ascii_record() -> Comtrade {
+    parse(
+        "ASCII",
+        b"1,0,1\n2,1000,\n3,2000,3\n4,3000,4\n5,4000, \n6,5000,6\n",
+    )
+}
+
+fn assert_values(actual: &[f64], expected: &[f64]) {
+    assert_eq!(
+        actual.len(),
+        expected.len(),
+        "{:?} != {:?}",
+        actual,
+        expected
+    );
+    for (a, e) in actual.iter().zip(expected.iter()) {
+        assert!(
+            (a.is_nan() && e.is_nan()) || a == e,
+            "{:?} != {:?}",
+            actual,
+            expected
+        );
+    }
+}
+
+#[test]
+fn it_parses_missing_values_as_nan() {
+    let nan = f64::NAN;
+    assert_values(
+        &ascii_record().data.analog_data[0],
+        &[2.0, nan, 6.0, 8.0, nan, 12.0],
+    );
+
+    let mut dat = vec![];
+    for (i, value) in [5i16, i16::MIN, -7, 1, 2, 3].iter().enumerate() {
+        dat.extend_from_slice(&(i as u32 + 1).to_le_bytes());
+        dat.extend_from_slice(&(i as u32 * 1000).to_le_bytes());
+        dat.extend_from_slice(&value.to_le_bytes());
+    }
+    let record = parse("BINARY", &dat);
+    assert_values(
+        &record.data.analog_data[0],
+        &[10.0, nan, -14.0, 2.0, 4.0, 6.0],
+    );
+}
+
+#[test]
+fn it_applies_missing_data_policies() {
+    let record = ascii_record();
+    let data = &record.data.analog_data[0];
+
+    assert_values(
+        &MissingDataPolicy::Interpolate.apply(data).unwrap(),
+        &[2.0, 4.0, 6.0, 8.0, 10.0, 12.0],
+    );
+    assert_values(
+        &MissingDataPolicy::Zero.apply(data).unwrap(),
+        &[2.0, 0.0, 6.0, 8.0, 0.0, 12.0],
+    );
+    assert_values(&MissingDataPolicy::Skip.apply(data).unwrap(), data);
+    assert_eq!(
+        MissingDataPolicy::Error.apply(data),
+        Err(MissingDataError { sample_index: 1 })
+    );
+
+    // Nothing to interpolate between at the ends, so the nearest value is used.
+    let nan = f64::NAN;
+    assert_values(
+        &MissingDataPolicy::Interpolate
+            .apply(&[nan, 1.0, nan, nan, 4.0, nan])
+            .unwrap(),
+        &[1.0, 1.0, 2.0, 3.0, 4.0, 4.0],
+    );
+    assert_values(
+        &MissingDataPolicy::Interpolate.apply(&[nan, nan]).unwrap(),
+        &[0.0, 0.0],
+    );
+}
+
+#[test]
+fn it_skips_missing_values_in_analysis() {
+    let record = ascii_record();
+    let channel = &record.config.analog_channels[0];
+    let data = &record.data.analog_data[0];
+    let nan = f64::NAN;
+
+    let (_, rms) = derive_channel(DerivedKind::Rms { window: 2 }, channel, data);
+    assert_values(&rms, &[2.0, 2.0, 6.0, 50f64.sqrt(), 8.0, 12.0]);
+
+    let (_, abs) = derive_channel(DerivedKind::Absolute, channel, data);
+    assert_values(&abs, &[2.0, nan, 6.0, 8.0, nan, 12.0]);
+
+    let (_, interpolated) = derive_channel_with_policy(
+        DerivedKind::Absolute,
+        channel,
+        data,
+        MissingDataPolicy::Interpolate,
+    )
+    .unwrap();
+    assert_values(&interpolated, &[2.0, 4.0, 6.0, 8.0, 10.0, 12.0]);
+
+    // Every cycle of 2 samples includes a missing value, apart from samples 2 and 3.
+    let skipped: Vec<bool> = phasors(data, 2.0).iter().map(Option::is_some).collect();
+    assert_eq!(skipped, vec![false, false, false, true, false, false]);
+
+    let zeroed = phasors_with_policy(data, 2.0, MissingDataPolicy::Zero).unwrap();
+    assert!(zeroed[1..].iter().all(Option::is_some));
+
+    assert_eq!(
+        phasors_with_policy(data, 2.0, MissingDataPolicy::Error),
+        Err(MissingDataError { sample_index: 1 })
+    );
+}
+
+#[test]
+fn it_skips_missing_values_in_exports() {
+    let record = ascii_record();
+
+    let skipped = preview_json(&record, 6);
+    assert!(
+        skipped.contains(r#""min":[2,null,6,8,null,12]"#),
+        "{}",
+        skipped
+    );
+
+    let interpolated = preview_json_with_policy(&record, 6, MissingDataPolicy::Interpolate)
+        .expect("unable to export record");
+    assert!(
+        interpolated.contains(r#""min":[2,4,6,8,10,12]"#),
+        "{}",
+        interpolated
+    );
+
+    assert_eq!(
+        preview_json_with_policy(&record, 6, MissingDataPolicy::Error),
+        Err(MissingDataError { sample_index: 1 })
+    );
+}
@@ -0,0 +1,52 @@
+use std::fs;
+use std::path::Path;
+
+use comtrade::{ComtradeParser, ComtradeParserBuilder};
+
+mod common;
+
+use common::SAMPLE_COMTRADE_DIR;
+
+#[test]
+fn it_reports_parse_statistics() {
+    let dir = Path::new(SAMPLE_COMTRADE_DIR);
+    let cfg = fs::read(dir.join("sample_2013_bin.cfg")).expect("unable to read sample file");
+    let dat = fs::read(dir.join("sample_2013_bin.dat")).expect("unable to read sample file");
+    let inf = b"[Public Record_Information]\nSource=Test\n";
+
+    let (record, stats) = ComtradeParserBuilder::new()
+        .cfg_file(cfg.as_slice())
+        .dat_file(dat.as_slice())
+        .inf_file(&inf[..])
+        .build()
+        .parse_with_stats()
+        .expect("unable to parse COMTRADE files");
+
+    assert_eq!(stats.cfg_bytes, cfg.len());
+    assert_eq!(stats.dat_bytes, dat.len());
+    assert_eq!(stats.hdr_bytes, 0);
+    assert_eq!(stats.inf_bytes, inf.len());
+    assert_eq!(stats.num_samples, record.data.timestamps.len());
+    // Sample number, timestamp, 4 analog values and one word of status channels.
+    assert_eq!(stats.scan_size, Some(4 + 4 + 4 * 2 + 2));
+    assert_eq!(stats.scan_size.unwrap() * stats.num_samples, dat.len());
+    assert_eq!(stats.num_warnings, record.config.warnings.len());
+    assert!(stats.cfg_time.is_some());
+    assert!(stats.dat_time.is_some());
+}
+
+#[test]
+fn it_reports_statistics_for_combined_ascii_files() {
+    let dir = Path::new(SAMPLE_COMTRADE_DIR);
+    let cff = fs::read(dir.join("sample_2013_ascii.cff")).expect("unable to read sample file");
+
+    let (record, stats) = ComtradeParser::from_cff_bytes(&cff)
+        .parse_with_stats()
+        .expect("unable to parse COMTRADE files");
+
+    assert_eq!(stats.scan_size, None);
+    assert_eq!(stats.num_samples, record.data.timestamps.len());
+    assert!(stats.cfg_bytes > 0);
+    assert!(stats.dat_bytes > 0);
+    assert!(stats.cfg_bytes + stats.dat_bytes < cff.len());
+}
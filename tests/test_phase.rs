@@ -0,0 +1,87 @@
+use comtrade::{ComtradeParser, ComtradeParserBuilder, ComtradeWriter, Phase};
+
+// Channels with phases in the R/S/T convention.
+const CFG: &str = "\
+PHASE STATION,DEVICE 1,1999
+4,3A,1D
+1,IR,R,Line1,A,1.0,0.0,0,-32768,32767,1,1,S
+2,IS,S,Line1,A,1.0,0.0,0,-32768,32767,1,1,S
+3,IT,T,Line1,A,1.0,0.0,0,-32768,32767,1,1,S
+1,TRIP,,Line1,0
+50
+1
+1000,1
+01/02/2020,00:00:00.000000
+01/02/2020,00:00:00.000000
+ASCII
+1
+";
+
+const DAT: &str = "1,0,1,2,3,0\n";
+
+#[test]
+fn it_recognises_phase_conventions() {
+    let cases = [
+        ("A", Phase::A),
+        ("r", Phase::A),
+        ("L1", Phase::A),
+        ("b", Phase::B),
+        ("S", Phase::B),
+        ("l2", Phase::B),
+        ("C", Phase::C),
+        ("T", Phase::C),
+        ("L3", Phase::C),
+        ("n", Phase::N),
+        ("Pos", Phase::Positive),
+        ("+", Phase::Positive),
+        ("NEG", Phase::Negative),
+        ("-", Phase::Negative),
+        ("Zero", Phase::Zero),
+        ("0", Phase::Zero),
+        ("", Phase::Other("".to_string())),
+        ("AB", Phase::Other("AB".to_string())),
+    ];
+
+    for (value, expected) in cases {
+        assert_eq!(Phase::from(value), expected, "phase {:?}", value);
+    }
+    assert_eq!(Phase::Positive.as_str(), "Pos");
+    assert!(Phase::C.is_line_phase());
+    assert!(!Phase::N.is_line_phase());
+}
+
+#[test]
+fn it_parses_channel_phases() {
+    let record = ComtradeParserBuilder::new()
+        .cfg_file(CFG.as_bytes())
+        .dat_file(DAT.as_bytes())
+        .build()
+        .parse()
+        .expect("unable to parse COMTRADE files");
+
+    let phases: Vec<Phase> = record
+        .config
+        .analog_channels
+        .iter()
+        .map(|c| c.phase.clone())
+        .collect();
+    assert_eq!(phases, vec![Phase::A, Phase::B, Phase::C]);
+    assert_eq!(record.config.status_channels[0].phase, Phase::default());
+    assert_eq!(record.config.analog_channels[1].phase_raw, "S");
+}
+
+#[test]
+fn it_writes_phases_back_as_they_were() {
+    let mut record = ComtradeParser::from_bytes(CFG.as_bytes(), DAT.as_bytes())
+        .parse()
+        .expect("unable to parse COMTRADE files");
+    let cfg = ComtradeWriter::new(&record).cfg();
+    assert!(cfg.contains("\n1,IR,R,Line1,"), "{}", cfg);
+    assert!(cfg.contains("\n1,TRIP,,Line1,"), "{}", cfg);
+
+    // A phase changed since it was parsed is written with its standard label.
+    record.config.analog_channels[0].phase = Phase::N;
+    let cfg = ComtradeWriter::new(&record).cfg();
+    assert!(cfg.contains("\n1,IR,N,Line1,"), "{}", cfg);
+    assert!(cfg.contains("\n2,IS,S,Line1,"), "{}", cfg);
+}
@@ -0,0 +1,208 @@
+use std::f64::consts::{PI, SQRT_2};
+
+use float_cmp::approx_eq;
+
+use comtrade::analysis::{
+    flicker_severity, rms_half_cycle, voltage_events, voltage_unbalance, VoltageEventConfig,
+    VoltageEventKind,
+};
+
+const SAMPLES_PER_CYCLE: usize = 20;
+const NOMINAL: f64 = 100.0;
+
+/// Values of a 50 Hz voltage channel with RMS `NOMINAL` times `pu(sample)` at each
+/// sample, lagging phase A by `shift`.
+fn voltage(shift: f64, n_samples: usize, pu: impl Fn(usize) -> f64) -> Vec<f64> {
+    (0..n_samples)
+        .map(|k| {
+            let theta = 2.0 * PI * k as f64 / SAMPLES_PER_CYCLE as f64 - shift;
+            NOMINAL * SQRT_2 * pu(k) * theta.sin()
+        })
+        .collect()
+}
+
+fn config() -> VoltageEventConfig {
+    VoltageEventConfig::new(NOMINAL, SAMPLES_PER_CYCLE as f64, 50.0)
+}
+
+#[test]
+fn it_calculates_rms_over_cycle_every_half_cycle() {
+    let mut channel = voltage(0.0, 70, |k| if k < 40 { 1.0 } else { 0.5 });
+    channel[5] = f64::NAN;
+    let rms = rms_half_cycle(&channel, SAMPLES_PER_CYCLE as f64);
+
+    // Windows start every 10 samples, for as long as there's a full cycle.
+    assert_eq!(rms.len(), 6);
+    assert!(approx_eq!(f64, rms[1], 100.0, epsilon = 1e-9));
+    assert!(approx_eq!(
+        f64,
+        rms[3],
+        100.0 * (1.25f64 / 2.0).sqrt(),
+        epsilon = 1e-9
+    ));
+    assert!(approx_eq!(f64, rms[4], 50.0, epsilon = 1e-9));
+
+    // The missing value is skipped rather than counted as 0.
+    assert!(rms[0] > 95.0);
+
+    assert!(rms_half_cycle(&channel, 1.0).is_empty());
+}
+
+#[test]
+fn it_finds_dips_swells_and_interruptions() {
+    // Phase A dips to half between cycles 5 and 10, all phases swell by 20% between
+    // cycles 12 and 15 and are interrupted between cycles 22 and 25.
+    let pu = |phase_a: bool| {
+        move |k: usize| match k {
+            100..=199 if phase_a => 0.5,
+            240..=299 => 1.2,
+            440..=499 => 0.0,
+            _ => 1.0,
+        }
+    };
+    let channels = [
+        voltage(0.0, 600, pu(true)),
+        voltage(2.0 * PI / 3.0, 600, pu(false)),
+        voltage(-2.0 * PI / 3.0, 600, pu(false)),
+    ];
+    let events = voltage_events(&[&channels[0], &channels[1], &channels[2]], &config());
+
+    let kinds: Vec<VoltageEventKind> = events.iter().map(|e| e.kind).collect();
+    assert_eq!(
+        kinds,
+        vec![
+            VoltageEventKind::Dip,
+            VoltageEventKind::Swell,
+            VoltageEventKind::Dip,
+            VoltageEventKind::Interruption,
+        ]
+    );
+
+    let dip = &events[0];
+    assert_eq!(dip.start_index, 9);
+    assert!(approx_eq!(f64, dip.start_time, 0.11, epsilon = 1e-9));
+    assert!(approx_eq!(f64, dip.duration, 0.11, epsilon = 1e-9));
+    assert!(approx_eq!(f64, dip.magnitude, 50.0, epsilon = 1e-9));
+    assert!(approx_eq!(f64, dip.magnitude_pu, 0.5, epsilon = 1e-9));
+    assert_eq!(dip.worst_channel, 0);
+    assert!(dip.complete);
+
+    let swell = &events[1];
+    assert_eq!(swell.start_index, 23);
+    assert!(approx_eq!(f64, swell.duration, 0.07, epsilon = 1e-9));
+    assert!(approx_eq!(f64, swell.magnitude_pu, 1.2, epsilon = 1e-9));
+
+    // The interruption is also part of a longer dip.
+    assert_eq!(events[2].start_index, 43);
+    assert!(approx_eq!(f64, events[2].duration, 0.07, epsilon = 1e-9));
+    let interruption = &events[3];
+    assert_eq!(interruption.start_index, 44);
+    assert!(approx_eq!(f64, interruption.duration, 0.05, epsilon = 1e-9));
+    assert!(approx_eq!(f64, interruption.magnitude, 0.0, epsilon = 1e-9));
+}
+
+#[test]
+fn it_carries_events_across_missing_values() {
+    // Dips to half between cycles 5 and 10, with the whole of cycle 7 missing.
+    let mut channel = voltage(
+        0.0,
+        300,
+        |k| if (100..200).contains(&k) { 0.5 } else { 1.0 },
+    );
+    for value in &mut channel[140..160] {
+        *value = f64::NAN;
+    }
+    let events = voltage_events(&[&channel], &config());
+
+    assert_eq!(events.len(), 1);
+    assert_eq!(events[0].kind, VoltageEventKind::Dip);
+    assert_eq!(events[0].start_index, 9);
+    assert!(approx_eq!(f64, events[0].duration, 0.11, epsilon = 1e-9));
+    assert!(events[0].complete);
+}
+
+#[test]
+fn it_marks_events_cut_off_by_record_as_incomplete() {
+    let channel = voltage(0.0, 200, |k| if k < 150 { 1.0 } else { 0.8 });
+    let events = voltage_events(&[&channel], &config());
+
+    assert_eq!(events.len(), 1);
+    assert_eq!(events[0].kind, VoltageEventKind::Dip);
+    assert!(!events[0].complete);
+    assert!(approx_eq!(f64, events[0].magnitude_pu, 0.8, epsilon = 1e-9));
+
+    // Small changes within the hysteresis don't start an event.
+    let channel = voltage(0.0, 200, |k| if k < 150 { 1.0 } else { 0.95 });
+    assert!(voltage_events(&[&channel], &config()).is_empty());
+}
+
+#[test]
+fn it_calculates_voltage_unbalance() {
+    let balanced = [
+        voltage(0.0, 100, |_| 1.0),
+        voltage(2.0 * PI / 3.0, 100, |_| 1.0),
+        voltage(-2.0 * PI / 3.0, 100, |_| 1.0),
+    ];
+    let unbalance = voltage_unbalance(
+        [&balanced[0], &balanced[1], &balanced[2]],
+        SAMPLES_PER_CYCLE as f64,
+    )
+    .expect("expected unbalance");
+    assert!(unbalance.negative < 1e-12);
+    assert!(unbalance.zero < 1e-12);
+
+    // With phase A 10% low, each sequence component other than the positive is a third
+    // of the difference.
+    let low_a = voltage(0.0, 100, |_| 0.9);
+    let unbalance = voltage_unbalance(
+        [&low_a, &balanced[1], &balanced[2]],
+        SAMPLES_PER_CYCLE as f64,
+    )
+    .expect("expected unbalance");
+    let positive = (0.9 + 2.0) / 3.0;
+    assert!(approx_eq!(
+        f64,
+        unbalance.negative,
+        0.1 / 3.0 / positive,
+        epsilon = 1e-9
+    ));
+    assert!(approx_eq!(
+        f64,
+        unbalance.zero,
+        0.1 / 3.0 / positive,
+        epsilon = 1e-9
+    ));
+
+    assert!(voltage_unbalance([&low_a, &balanced[1], &balanced[2]], 1.0).is_none());
+}
+
+#[test]
+fn it_estimates_flicker_severity_from_voltage_changes() {
+    let steady = voltage(0.0, 1000, |_| 1.0);
+    let pst = flicker_severity(&steady, NOMINAL, SAMPLES_PER_CYCLE as f64).expect("expected Pst");
+    assert!(approx_eq!(f64, pst, 0.0));
+
+    // A single 3% step down: 2.3 * 3^3.2 seconds of flicker impression in 10 minutes.
+    let step = voltage(0.0, 1000, |k| if k < 500 { 1.0 } else { 0.97 });
+    let pst = flicker_severity(&step, NOMINAL, SAMPLES_PER_CYCLE as f64).expect("expected Pst");
+    let expected = (2.3 * 3f64.powf(3.2) / 600.0).powf(1.0 / 3.2);
+    assert!(approx_eq!(f64, pst, expected, epsilon = 1e-6));
+
+    // Stepping down and back up again is twice as many changes.
+    let pulse = voltage(
+        0.0,
+        1000,
+        |k| {
+            if (300..600).contains(&k) {
+                0.97
+            } else {
+                1.0
+            }
+        },
+    );
+    let pst = flicker_severity(&pulse, NOMINAL, SAMPLES_PER_CYCLE as f64).expect("expected Pst");
+    let expected = (2.0 * 2.3 * 3f64.powf(3.2) / 600.0).powf(1.0 / 3.2);
+    assert!(approx_eq!(f64, pst, expected, epsilon = 1e-6));
+
+    assert!(flicker_severity(&steady, NOMINAL, 1.0).is_none());
+}
@@ -0,0 +1,116 @@
+#![cfg(feature = "protobuf")]
+
+use comtrade::protobuf::{
+    from_protobuf, to_protobuf, ProtoRecord, ProtoRecordError, ProtoScan, PROTO_SCHEMA,
+};
+use comtrade::{ComtradeParserBuilder, Scan};
+
+const CFG_1999: &str = "\
+PROTO STATION,DEVICE 1,1999
+2,1A,1D
+1,IA,A,Line1,A,0.5,0.0,0,-32768,32767,1,1,S
+1,TRIP,,Line1,0
+50
+1
+1000,4
+01/02/2020,00:00:00.000000
+01/02/2020,00:00:00.001000
+ASCII
+1
+";
+
+const DAT_1999: &str = "\
+1,0,10,0
+2,1000,,0
+3,2000,30,1
+4,3000,40,1
+";
+
+#[test]
+fn it_round_trips_records_through_protobuf() {
+    let mut record = ComtradeParserBuilder::new()
+        .cfg_file(CFG_1999.as_bytes())
+        .dat_file(DAT_1999.as_bytes())
+        .build()
+        .parse()
+        .expect("unable to parse COMTRADE files");
+    record.config.header = "Fault on line 1".to_string();
+    record.config.analog_channels[0]
+        .extensions
+        .insert("INF/Filter_Order".to_string(), "2".to_string());
+
+    let message = ProtoRecord::from(&record);
+    assert_eq!(message.start_time, "2020-02-01T00:00:00");
+    assert_eq!(message.analog_channels[0].scaling_mode, "S");
+
+    let read = from_protobuf(&to_protobuf(&record)).expect("unable to read protobuf record");
+    assert_eq!(read.config.station_name, record.config.station_name);
+    assert_eq!(read.config.start_time, record.config.start_time);
+    assert_eq!(read.config.trigger_time, record.config.trigger_time);
+    assert_eq!(read.data.timestamps, record.data.timestamps);
+    assert_eq!(read.data.sample_numbers, record.data.sample_numbers);
+    assert_eq!(read.config.sampling_rates, record.config.sampling_rates);
+    assert_eq!(read.config.status_channels, record.config.status_channels);
+    assert_eq!(read.config.analog_channels[0].multiplier, 0.5);
+    assert_eq!(read.data.analog_data[0][0], 5.0);
+    assert!(read.data.analog_data[0][1].is_nan());
+    assert_eq!(read.config.header, record.config.header);
+    assert_eq!(
+        read.config.analog_channels[0].extensions,
+        record.config.analog_channels[0].extensions
+    );
+}
+
+#[test]
+fn it_returns_error_for_invalid_protobuf_records() {
+    assert!(matches!(
+        from_protobuf(&[0xff, 0xff]),
+        Err(ProtoRecordError::Decode(_))
+    ));
+
+    let record = ComtradeParserBuilder::new()
+        .cfg_file(CFG_1999.as_bytes())
+        .dat_file(DAT_1999.as_bytes())
+        .build()
+        .parse()
+        .expect("unable to parse COMTRADE files");
+    let mut message = ProtoRecord::from(&record);
+    message.analog_channels[0].values.pop();
+
+    assert!(matches!(
+        comtrade::Comtrade::try_from(message),
+        Err(ProtoRecordError::Invalid(_))
+    ));
+}
+
+#[test]
+fn it_converts_scans() {
+    let scan = Scan {
+        sample_number: 3,
+        timestamp: 2_000_000,
+        raw_timestamp: Some(2000),
+        analog_values: vec![15.0, f64::NAN],
+        status_values: vec![1],
+    };
+
+    let message = ProtoScan::from(&scan);
+    let read = Scan::from(message);
+    assert_eq!(read.sample_number, 3);
+    assert_eq!(read.raw_timestamp, Some(2000));
+    assert_eq!(read.analog_values[0], 15.0);
+    assert!(read.analog_values[1].is_nan());
+    assert_eq!(read.status_values, vec![1]);
+}
+
+#[test]
+fn it_has_a_schema_for_every_message() {
+    for message in [
+        "Record",
+        "SamplingRate",
+        "AnalogChannel",
+        "StatusChannel",
+        "Scan",
+    ] {
+        assert!(PROTO_SCHEMA.contains(&format!("message {} {{", message)));
+    }
+}
@@ -0,0 +1,145 @@
+use std::fs;
+use std::path::Path;
+
+use comtrade::{Comtrade, ComtradeParserBuilder, Event, PushParser, Scan};
+
+mod common;
+
+use common::SAMPLE_COMTRADE_DIR;
+
+fn feed_in_chunks(contents: &[u8], chunk_size: usize) -> Vec<Event> {
+    let mut parser = PushParser::new();
+    let mut events = vec![];
+    for chunk in contents.chunks(chunk_size) {
+        events.extend(parser.feed(chunk).expect("unable to parse chunk"));
+    }
+    events.extend(parser.finish().expect("unable to finish parsing"));
+    events
+}
+
+fn split_events(events: Vec<Event>) -> (Vec<Comtrade>, Vec<Scan>) {
+    let mut configs = vec![];
+    let mut scans = vec![];
+    for event in events {
+        match event {
+            Event::Config(record) => configs.push(*record),
+            Event::Scan(scan) => scans.push(scan),
+            _ => {}
+        }
+    }
+    (configs, scans)
+}
+
+fn assert_scans_match_record(scans: &[Scan], record: &Comtrade) {
+    assert_eq!(scans.len(), record.data.sample_numbers.len());
+    for (i, scan) in scans.iter().enumerate() {
+        assert_eq!(scan.sample_number, record.data.sample_numbers[i]);
+        assert_eq!(scan.timestamp, record.data.timestamps[i]);
+        for (j, data) in record.data.analog_data.iter().enumerate() {
+            assert_eq!(scan.analog_values[j], data[i]);
+        }
+        for (j, data) in record.data.status_data.iter().enumerate() {
+            assert_eq!(scan.status_values[j], data[i]);
+        }
+    }
+}
+
+#[test]
+fn it_produces_same_data_as_batch_parser_for_ascii_cff() {
+    let contents = fs::read(Path::new(SAMPLE_COMTRADE_DIR).join("sample_2013_ascii.cff"))
+        .expect("unable to find sample cff file");
+
+    let record = ComtradeParserBuilder::new()
+        .cff_file(contents.as_slice())
+        .build()
+        .parse()
+        .expect("unable to parse COMTRADE files");
+
+    for chunk_size in [1, 7, 64, contents.len()] {
+        let (configs, scans) = split_events(feed_in_chunks(&contents, chunk_size));
+
+        assert_eq!(configs.len(), 1);
+        assert_eq!(configs[0].config.station_name, record.config.station_name);
+        assert_eq!(
+            configs[0].config.sampling_rates,
+            record.config.sampling_rates
+        );
+        assert_eq!(
+            configs[0].config.analog_channels.len(),
+            record.config.analog_channels.len()
+        );
+        assert!(configs[0].data.sample_numbers.is_empty());
+        assert!(configs[0].data.analog_data.iter().all(Vec::is_empty));
+
+        assert_scans_match_record(&scans, &record);
+    }
+}
+
+#[test]
+fn it_decodes_binary_data_section_in_cff() {
+    let dir = Path::new(SAMPLE_COMTRADE_DIR);
+    let cfg = fs::read(dir.join("sample_2013_bin.cfg")).expect("unable to find sample cfg file");
+    let dat = fs::read(dir.join("sample_2013_bin.dat")).expect("unable to find sample dat file");
+
+    let record = ComtradeParserBuilder::new()
+        .cfg_file(cfg.as_slice())
+        .dat_file(dat.as_slice())
+        .build()
+        .parse()
+        .expect("unable to parse COMTRADE files");
+
+    let mut contents = b"--- file type: CFG ---\n".to_vec();
+    contents.extend_from_slice(&cfg);
+    contents
+        .extend_from_slice(format!("\n--- file type: DAT BINARY: {} ---\n", dat.len()).as_bytes());
+    contents.extend_from_slice(&dat);
+    contents.extend_from_slice(b"\n--- file type: HDR ---\nSome header text\n");
+
+    let events = feed_in_chunks(&contents, 13);
+    assert_eq!(
+        events.last(),
+        Some(&Event::Header("Some header text".to_string()))
+    );
+
+    let (configs, scans) = split_events(events);
+    assert_eq!(configs.len(), 1);
+    assert_scans_match_record(&scans, &record);
+}
+
+#[test]
+fn it_errors_on_truncated_binary_data_section() {
+    let mut parser = PushParser::new();
+    let dir = Path::new(SAMPLE_COMTRADE_DIR);
+    let cfg = fs::read(dir.join("sample_2013_bin.cfg")).expect("unable to find sample cfg file");
+    let dat = fs::read(dir.join("sample_2013_bin.dat")).expect("unable to find sample dat file");
+
+    let mut contents = b"--- file type: CFG ---\n".to_vec();
+    contents.extend_from_slice(&cfg);
+    contents
+        .extend_from_slice(format!("\n--- file type: DAT BINARY: {} ---\n", dat.len()).as_bytes());
+    contents.extend_from_slice(&dat[..dat.len() / 2]);
+
+    parser.feed(&contents).expect("unable to parse chunk");
+    assert!(parser.finish().is_err());
+}
+
+#[test]
+fn it_emits_trailing_content_after_data_section() {
+    let mut contents = fs::read(Path::new(SAMPLE_COMTRADE_DIR).join("sample_2013_ascii.cff"))
+        .expect("unable to find sample cff file");
+    contents.extend_from_slice(b"\nEXPORTED BY VENDOR TOOL v2\nchecksum=1234\n");
+
+    let events = feed_in_chunks(&contents, 16);
+    assert_eq!(
+        events.last(),
+        Some(&Event::Trailing(
+            "EXPORTED BY VENDOR TOOL v2\nchecksum=1234".to_string()
+        ))
+    );
+
+    let (_, scans) = split_events(events);
+    assert_eq!(scans.len(), 40);
+
+    let mut parser = PushParser::new().strict(true);
+    assert!(parser.feed(&contents).is_err());
+}
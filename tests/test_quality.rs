@@ -0,0 +1,81 @@
+use comtrade::{Comtrade, ComtradeParserBuilder, ParseResult, QualityMapping};
+
+// Current from a merging unit, with its validity flags in the first two status channels
+// and a breaker contact in the third.
+const CFG_2013: &str = "\
+MU STATION,DEVICE 1,2013
+4,1A,3D
+1,IA,A,Line1,A,1.0,0.0,0,-32768,32767,1,1,S
+1,IA_VALIDITY_0,,Line1,0
+2,IA_VALIDITY_1,,Line1,0
+3,TRIP,,Line1,0
+50
+1
+4000,4
+01/02/2020,00:00:00.000000
+01/02/2020,00:00:00.001000
+ASCII
+1
+0,0
+F,0
+";
+
+const DAT_2013: &str = "\
+1,0,10,0,0,0
+2,250,20,1,0,0
+3,500,30,0,1,1
+4,750,40,1,1,1
+";
+
+fn parse(mapping: QualityMapping) -> ParseResult<Comtrade> {
+    ComtradeParserBuilder::new()
+        .cfg_file(CFG_2013.as_bytes())
+        .dat_file(DAT_2013.as_bytes())
+        .quality_mapping(mapping)
+        .build()
+        .parse()
+}
+
+#[test]
+fn it_decodes_quality_flags_from_status_channels() {
+    let record = parse(QualityMapping::new(0, vec![0, 1])).expect("unable to parse");
+
+    assert_eq!(record.data.analog_quality, vec![vec![0, 1, 2, 3]]);
+    assert_eq!(record.quality(0, 2), Some(2));
+    assert_eq!(record.quality(0, 4), None);
+    assert_eq!(record.quality(1, 0), None);
+}
+
+#[test]
+fn it_has_no_quality_flags_without_a_mapping() {
+    let record = ComtradeParserBuilder::new()
+        .cfg_file(CFG_2013.as_bytes())
+        .dat_file(DAT_2013.as_bytes())
+        .build()
+        .parse()
+        .expect("unable to parse");
+
+    assert!(record.data.analog_quality.is_empty());
+    assert_eq!(record.quality(0, 0), None);
+}
+
+#[test]
+fn it_returns_error_for_mappings_not_in_the_record() {
+    assert!(parse(QualityMapping::new(1, vec![0])).is_err());
+    assert!(parse(QualityMapping::new(0, vec![3])).is_err());
+}
+
+#[test]
+fn it_keeps_quality_flags_with_their_samples_when_sliced() {
+    let mut record = ComtradeParserBuilder::new()
+        .cfg_file(CFG_2013.as_bytes())
+        .dat_file(DAT_2013.as_bytes())
+        .build()
+        .parse()
+        .expect("unable to parse");
+    assert!(record.apply_quality_mapping(&QualityMapping::new(0, vec![0, 1])));
+
+    let sliced = record.slice().with_samples(1..3).to_comtrade();
+
+    assert_eq!(sliced.data.analog_quality, vec![vec![1, 2]]);
+}
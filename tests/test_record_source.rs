@@ -0,0 +1,95 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::future::Future;
+use std::path::Path;
+use std::pin::pin;
+use std::task::{Context, Poll, Waker};
+
+use comtrade::source::{RecordFiles, RecordSource};
+use comtrade::Comtrade;
+
+mod common;
+
+use common::SAMPLE_COMTRADE_DIR;
+
+struct InMemory(BTreeMap<String, RecordFiles>);
+
+impl RecordSource for InMemory {
+    type Error = String;
+
+    fn list(&self) -> impl Future<Output = Result<Vec<String>, String>> + Send {
+        let names = self.0.keys().cloned().collect();
+        async move { Ok(names) }
+    }
+
+    fn fetch(&self, name: &str) -> impl Future<Output = Result<RecordFiles, String>> + Send {
+        let files = self
+            .0
+            .get(name)
+            .cloned()
+            .ok_or(format!("no record {}", name));
+        async move { files }
+    }
+}
+
+// The in-memory source never has to wait, so its futures are ready straight away.
+fn block_on<F: Future>(future: F) -> F::Output {
+    let mut future = pin!(future);
+    match future
+        .as_mut()
+        .poll(&mut Context::from_waker(Waker::noop()))
+    {
+        Poll::Ready(output) => output,
+        Poll::Pending => panic!("in-memory future wasn't ready"),
+    }
+}
+
+fn read(file_name: &str) -> Vec<u8> {
+    fs::read(Path::new(SAMPLE_COMTRADE_DIR).join(file_name)).expect("unable to read sample file")
+}
+
+#[test]
+fn it_parses_fetched_records() {
+    let mut records = BTreeMap::new();
+    records.insert(
+        "real_1999_bin".to_string(),
+        RecordFiles::Separate {
+            cfg: read("real_1999_bin.cfg"),
+            dat: read("real_1999_bin.dat"),
+            hdr: Some(read("real_1999_bin.hdr")),
+            inf: None,
+        },
+    );
+    records.insert(
+        "sample_2013_ascii".to_string(),
+        RecordFiles::Combined(read("sample_2013_ascii.cff")),
+    );
+    let source = InMemory(records);
+
+    let names = block_on(source.list()).expect("unable to list records");
+    assert_eq!(names, vec!["real_1999_bin", "sample_2013_ascii"]);
+
+    let separate = block_on(source.fetch("real_1999_bin"))
+        .expect("unable to fetch record")
+        .parse()
+        .expect("unable to parse record");
+    let expected = Comtrade::try_from(
+        Path::new(SAMPLE_COMTRADE_DIR)
+            .join("real_1999_bin.cfg")
+            .as_path(),
+    )
+    .expect("unable to parse record from disk");
+    assert_eq!(separate, expected);
+
+    let combined = block_on(source.fetch("sample_2013_ascii"))
+        .expect("unable to fetch record")
+        .parse()
+        .expect("unable to parse record");
+    assert_eq!(combined.config.station_name, "SMARTSTATION");
+    assert_eq!(combined.data.sample_numbers.len(), 40);
+
+    assert_eq!(
+        block_on(source.fetch("missing")),
+        Err("no record missing".to_string())
+    );
+}
@@ -0,0 +1,74 @@
+use std::fs::File;
+use std::io::BufReader;
+use std::path::Path;
+
+use chrono::Duration;
+
+use comtrade::report::{summary as summary_text, RecordSummary};
+use comtrade::{Comtrade, ComtradeParserBuilder};
+
+mod common;
+
+use common::SAMPLE_COMTRADE_DIR;
+
+fn sample_record() -> Comtrade {
+    let dir = Path::new(SAMPLE_COMTRADE_DIR);
+    let cfg_file = BufReader::new(
+        File::open(dir.join("sample_2013_ascii.cfg")).expect("unable to find sample cfg file"),
+    );
+    let dat_file = BufReader::new(
+        File::open(dir.join("sample_2013_ascii.dat")).expect("unable to find sample dat file"),
+    );
+
+    ComtradeParserBuilder::new()
+        .cfg_file(cfg_file)
+        .dat_file(dat_file)
+        .build()
+        .parse()
+        .expect("unable to parse COMTRADE files")
+}
+
+#[test]
+fn it_summarises_sample_record() {
+    let record = sample_record();
+    let summary = RecordSummary::new(&record);
+
+    assert_eq!(summary.station_name, "SMARTSTATION");
+    assert_eq!(summary.recording_device_id, "IED123");
+
+    let max_current_names: Vec<&str> = summary
+        .max_currents
+        .iter()
+        .map(|c| c.channel_name.as_str())
+        .collect();
+    assert_eq!(max_current_names, vec!["IA", "IB", "IC", "3I0"]);
+    assert_eq!(summary.max_currents[0].units, "A");
+    assert_eq!(summary.max_currents[0].peak, 30.92156982421875);
+
+    let status_changes: Vec<(&str, usize, u8)> = summary
+        .status_changes
+        .iter()
+        .map(|c| (c.channel_name.as_str(), c.sample_index, c.new_value))
+        .collect();
+    assert_eq!(
+        status_changes,
+        vec![("51N", 10, 1), ("51A", 13, 1), ("51B", 13, 1)]
+    );
+
+    let text = summary_text(&record);
+    assert!(text.contains("Station:         SMARTSTATION"));
+    assert!(text.contains("51N: 0 -> 1"));
+}
+
+#[test]
+fn it_detects_faulted_phases_from_rise_in_current_after_trigger() {
+    let mut record = sample_record();
+    record.config.trigger_time = record.config.start_time + Duration::microseconds(2500);
+
+    // Flat current in phase C so it doesn't look faulted.
+    record.data.analog_data[2] = vec![1.0; 40];
+
+    let summary = RecordSummary::new(&record);
+    assert!(summary.faulted_phases.contains(&"IA".to_string()));
+    assert!(!summary.faulted_phases.contains(&"IC".to_string()));
+}
@@ -0,0 +1,63 @@
+mod common;
+
+use chrono::{FixedOffset, NaiveDate, TimeZone};
+
+use comtrade::{Comtrade, ComtradeConfig, ComtradeData};
+
+use common::analog_channel;
+
+fn record(time_offset: Option<FixedOffset>) -> Comtrade {
+    let start_time = NaiveDate::from_ymd(2020, 1, 1).and_hms(12, 0, 0);
+    Comtrade {
+        config: ComtradeConfig {
+            start_time,
+            trigger_time: start_time,
+            analog_channels: vec![analog_channel(1, "A1", "V"), analog_channel(2, "A2", "V")],
+            time_offset,
+            ..Default::default()
+        },
+        data: ComtradeData {
+            sample_numbers: vec![1, 2, 3],
+            timestamps: vec![0, 1_000_000, 2_500_000],
+            analog_data: vec![vec![1.0, 2.0, 3.0], vec![-1.0, f64::NAN, -3.0]],
+            ..Default::default()
+        },
+    }
+}
+
+#[test]
+fn it_gives_rows_with_absolute_times_in_the_record_time_code() {
+    let offset = FixedOffset::west(5 * 3600);
+    let record = record(Some(offset));
+
+    let rows: Vec<_> = record.analog_rows().collect();
+    assert_eq!(record.analog_rows().len(), 3);
+    assert_eq!(rows.len(), 3);
+
+    assert_eq!(rows[0].0, offset.ymd(2020, 1, 1).and_hms(12, 0, 0));
+    assert_eq!(
+        rows[2].0,
+        offset.ymd(2020, 1, 1).and_hms_micro(12, 0, 0, 2500)
+    );
+    assert_eq!(
+        rows[2].0.naive_utc(),
+        NaiveDate::from_ymd(2020, 1, 1).and_hms_micro(17, 0, 0, 2500)
+    );
+
+    assert_eq!(rows[0].1, vec![1.0, -1.0]);
+    assert_eq!(rows[1].1[0], 2.0);
+    assert!(rows[1].1[1].is_nan());
+}
+
+#[test]
+fn it_gives_rows_in_utc_without_a_time_code() {
+    let record = record(None);
+
+    let (time, values) = record.analog_rows().nth(1).expect("row should exist");
+    assert_eq!(time.offset().local_minus_utc(), 0);
+    assert_eq!(
+        time.naive_utc(),
+        NaiveDate::from_ymd(2020, 1, 1).and_hms_milli(12, 0, 0, 1)
+    );
+    assert_eq!(values[0], 2.0);
+}
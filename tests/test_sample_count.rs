@@ -0,0 +1,101 @@
+use std::fs;
+use std::path::Path;
+
+use comtrade::{Comtrade, ComtradeParserBuilder, ParseResult, ParseStats, SampleCountPolicy};
+
+mod common;
+
+use common::SAMPLE_COMTRADE_DIR;
+
+// Sample number, timestamp, 4 analog values and one word of status channels.
+const SCAN_SIZE: usize = 4 + 4 + 4 * 2 + 2;
+
+fn sample_files() -> (Vec<u8>, Vec<u8>) {
+    let dir = Path::new(SAMPLE_COMTRADE_DIR);
+    let cfg = fs::read(dir.join("sample_2013_bin.cfg")).expect("unable to read sample file");
+    let dat = fs::read(dir.join("sample_2013_bin.dat")).expect("unable to read sample file");
+    (cfg, dat)
+}
+
+fn parse(dat: &[u8], policy: SampleCountPolicy) -> ParseResult<(Comtrade, ParseStats)> {
+    let (cfg, _) = sample_files();
+    ComtradeParserBuilder::new()
+        .cfg_file(cfg.as_slice())
+        .dat_file(dat)
+        .sample_count_policy(policy)
+        .build()
+        .parse_with_stats()
+}
+
+/// Sample data with a copy of the last sample added on the end, numbered as the next one.
+fn dat_with_extra_sample() -> Vec<u8> {
+    let (_, mut dat) = sample_files();
+    let mut extra = dat[dat.len() - SCAN_SIZE..].to_vec();
+    extra[0..4].copy_from_slice(&6u32.to_le_bytes());
+    dat.extend_from_slice(&extra);
+    dat
+}
+
+#[test]
+fn it_truncates_extra_samples_by_default() {
+    let dat = dat_with_extra_sample();
+
+    let (record, stats) = parse(&dat, SampleCountPolicy::default()).expect("unable to parse");
+
+    assert_eq!(record.config.total_num_samples, 5);
+    assert_eq!(record.data.sample_numbers, vec![1, 2, 3, 4, 5]);
+    assert_eq!(stats.num_samples, 5);
+    assert_eq!(stats.num_samples_found, 6);
+    assert!(record
+        .config
+        .warnings
+        .iter()
+        .any(|w| w.to_string().contains("found 6 samples")));
+}
+
+#[test]
+fn it_extends_the_record_to_all_samples_found() {
+    let dat = dat_with_extra_sample();
+
+    let (record, stats) = parse(&dat, SampleCountPolicy::Extend).expect("unable to parse");
+
+    assert_eq!(record.config.total_num_samples, 6);
+    assert_eq!(
+        record
+            .config
+            .sampling_rates
+            .last()
+            .unwrap()
+            .end_sample_number,
+        6
+    );
+    assert_eq!(record.data.sample_numbers, vec![1, 2, 3, 4, 5, 6]);
+    assert_eq!(record.data.analog_data[0].len(), 6);
+    assert_eq!(stats.num_samples_found, 6);
+}
+
+#[test]
+fn it_extends_the_record_to_fewer_samples_found() {
+    let (_, mut dat) = sample_files();
+    dat.truncate(dat.len() - SCAN_SIZE);
+
+    let (record, _) = parse(&dat, SampleCountPolicy::Extend).expect("unable to parse");
+
+    assert_eq!(record.config.total_num_samples, 4);
+    assert_eq!(record.data.sample_numbers, vec![1, 2, 3, 4]);
+}
+
+#[test]
+fn it_returns_error_for_mismatched_sample_counts() {
+    let (_, mut short_dat) = sample_files();
+    short_dat.truncate(short_dat.len() - SCAN_SIZE);
+
+    let error = parse(&dat_with_extra_sample(), SampleCountPolicy::Error).unwrap_err();
+    assert!(error.to_string().contains("found 6 samples"));
+
+    let error = parse(&short_dat, SampleCountPolicy::Error).unwrap_err();
+    assert!(error.to_string().contains("found 4 samples"));
+
+    let error = parse(&short_dat, SampleCountPolicy::Truncate).unwrap_err();
+    assert!(error.to_string().contains("found 4 samples"));
+}
@@ -0,0 +1,96 @@
+use comtrade::{
+    Comtrade, ComtradeParserBuilder, IrregularSample, IrregularSampleKind, ParseResult,
+    SampleOrderPolicy,
+};
+
+const CFG_1999: &str = "\
+GLITCHY STATION,DEVICE 1,1999
+2,1A,1D
+1,IA,A,Line1,A,1.0,0.0,0,-32768,32767,1,1,S
+1,TRIP,,Line1,0
+50
+1
+1000,4
+01/02/2020,00:00:00.000000
+01/02/2020,00:00:00.001000
+ASCII
+1
+";
+
+// Sample 3 arrives before sample 2, which is then repeated.
+const DAT_1999_OUT_OF_ORDER: &str = "\
+1,0,10,0
+3,2000,30,1
+2,1000,20,0
+2,1000,25,1
+";
+
+fn parse(dat: &str, policy: SampleOrderPolicy) -> ParseResult<Comtrade> {
+    ComtradeParserBuilder::new()
+        .cfg_file(CFG_1999.as_bytes())
+        .dat_file(dat.as_bytes())
+        .sample_order_policy(policy)
+        .build()
+        .parse()
+}
+
+fn expected_irregular_samples() -> Vec<IrregularSample> {
+    vec![
+        IrregularSample {
+            index: 2,
+            sample_number: 2,
+            kind: IrregularSampleKind::SampleNumberOutOfOrder,
+        },
+        IrregularSample {
+            index: 2,
+            sample_number: 2,
+            kind: IrregularSampleKind::TimestampBackwards,
+        },
+        IrregularSample {
+            index: 3,
+            sample_number: 2,
+            kind: IrregularSampleKind::DuplicateSampleNumber,
+        },
+        IrregularSample {
+            index: 3,
+            sample_number: 2,
+            kind: IrregularSampleKind::DuplicateTimestamp,
+        },
+    ]
+}
+
+#[test]
+fn it_keeps_out_of_order_samples_by_default() {
+    let record = parse(DAT_1999_OUT_OF_ORDER, SampleOrderPolicy::default())
+        .expect("unable to parse COMTRADE files");
+
+    assert_eq!(record.data.sample_numbers, vec![1, 3, 2, 2]);
+    assert_eq!(record.data.analog_data[0], vec![10.0, 30.0, 20.0, 25.0]);
+    assert_eq!(record.data.irregular_samples, expected_irregular_samples());
+    assert_eq!(record.config.warnings.len(), 1);
+}
+
+#[test]
+fn it_reorders_out_of_order_samples() {
+    let record = parse(DAT_1999_OUT_OF_ORDER, SampleOrderPolicy::Reorder)
+        .expect("unable to parse COMTRADE files");
+
+    assert_eq!(record.data.sample_numbers, vec![1, 2, 2, 3]);
+    assert_eq!(
+        record.data.timestamps,
+        vec![0, 1_000_000, 1_000_000, 2_000_000]
+    );
+    assert_eq!(record.data.analog_data[0], vec![10.0, 20.0, 25.0, 30.0]);
+    assert_eq!(record.data.status_data[0], vec![0, 0, 1, 1]);
+    assert_eq!(record.data.irregular_samples, expected_irregular_samples());
+}
+
+#[test]
+fn it_rejects_out_of_order_samples_with_error_policy() {
+    assert!(parse(DAT_1999_OUT_OF_ORDER, SampleOrderPolicy::Error).is_err());
+
+    let record = parse("1,0,10,0\n2,1000,20,0\n", SampleOrderPolicy::Error)
+        .expect("unable to parse COMTRADE files");
+    assert!(record.data.irregular_samples.is_empty());
+    assert!(record.config.warnings.is_empty());
+}
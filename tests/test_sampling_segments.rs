@@ -0,0 +1,103 @@
+use comtrade::{Comtrade, ComtradeParserBuilder, SamplingSegment};
+
+// Record sampled at 1kHz for 3 samples then 500Hz for 2.
+const CFG_1999: &str = "\
+RATE CHANGE STATION,DEVICE 1,1999
+2,1A,1D
+1,IA,A,Line1,A,1.0,0.0,0,-32768,32767,1,1,S
+1,TRIP,,Line1,0
+50
+2
+1000,3
+500,5
+01/02/2020,00:00:00.000000
+01/02/2020,00:00:00.001000
+ASCII
+1
+";
+
+const DAT_1999: &str = "\
+1,0,10,0
+2,1000,20,0
+3,2000,30,1
+4,4000,40,1
+5,6000,50,1
+";
+
+fn parse() -> Comtrade {
+    ComtradeParserBuilder::new()
+        .cfg_file(CFG_1999.as_bytes())
+        .dat_file(DAT_1999.as_bytes())
+        .build()
+        .parse()
+        .expect("unable to parse COMTRADE files")
+}
+
+#[test]
+fn it_splits_samples_into_sampling_segments() {
+    let record = parse();
+
+    assert_eq!(
+        record.sampling_segments(),
+        vec![
+            SamplingSegment {
+                rate_index: 0,
+                rate_hz: 1000.0,
+                samples: 0..3,
+            },
+            SamplingSegment {
+                rate_index: 1,
+                rate_hz: 500.0,
+                samples: 3..5,
+            },
+        ]
+    );
+    assert_eq!(record.segment_boundaries(), vec![3]);
+}
+
+#[test]
+fn it_has_no_sampling_segments_without_sampling_rates() {
+    let mut record = parse();
+    record.config.sampling_rates.clear();
+
+    assert!(record.sampling_segments().is_empty());
+    assert!(record.segment_boundaries().is_empty());
+}
+
+#[test]
+fn it_infers_sampling_rates_from_timestamps() {
+    let rates = parse().infer_sampling_rates();
+
+    assert_eq!(rates.len(), 2);
+    assert_eq!(rates[0].declared_hz, 1000.0);
+    assert_eq!(rates[0].samples, 0..3);
+    assert!((rates[0].inferred_hz.unwrap() - 1000.0).abs() < 1e-6);
+    assert!((rates[1].inferred_hz.unwrap() - 500.0).abs() < 1e-6);
+    assert!(!rates.iter().any(|rate| rate.is_discrepancy(0.01)));
+}
+
+#[test]
+fn it_warns_when_declared_sampling_rate_is_wrong() {
+    // The samples are really 2ms apart, but the `.cfg` file says 1kHz.
+    let dat = "1,0,10,0\n2,2001,20,0\n3,3999,30,1\n4,6000,40,1\n5,8000,50,1\n";
+    let cfg = CFG_1999.replace("2\n1000,3\n500,5\n", "1\n1000,5\n");
+    let parse = |infer: bool| {
+        ComtradeParserBuilder::new()
+            .cfg_file(cfg.as_bytes())
+            .dat_file(dat.as_bytes())
+            .infer_sampling_rates(infer)
+            .build()
+            .parse()
+            .expect("unable to parse COMTRADE files")
+    };
+
+    let record = parse(false);
+    assert!(record.config.warnings.is_empty());
+    let rates = record.infer_sampling_rates();
+    assert!((rates[0].inferred_hz.unwrap() - 500.0).abs() < 0.1);
+    assert!(rates[0].is_discrepancy(0.01));
+
+    let record = parse(true);
+    assert_eq!(record.config.warnings.len(), 1);
+    assert!(record.config.warnings[0].message().contains("1000 Hz"));
+}
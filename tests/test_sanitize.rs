@@ -0,0 +1,106 @@
+use std::fs::File;
+use std::io::BufReader;
+use std::path::Path;
+
+use comtrade::{Comtrade, ComtradeParserBuilder, SanitizeAction, SanitizeOptions};
+
+mod common;
+
+use common::SAMPLE_COMTRADE_DIR;
+
+fn sample_record() -> Comtrade {
+    let dir = Path::new(SAMPLE_COMTRADE_DIR);
+    let cfg_file = BufReader::new(
+        File::open(dir.join("sample_2013_ascii.cfg")).expect("unable to find sample cfg file"),
+    );
+    let dat_file = BufReader::new(
+        File::open(dir.join("sample_2013_ascii.dat")).expect("unable to find sample dat file"),
+    );
+
+    ComtradeParserBuilder::new()
+        .cfg_file(cfg_file)
+        .dat_file(dat_file)
+        .hdr_file("Fault on line to Somewhere".as_bytes())
+        .inf_file("[Public Record_Information]\nSource=Somewhere Substation\n".as_bytes())
+        .keep_source_files(true)
+        .build()
+        .parse()
+        .expect("unable to parse COMTRADE files")
+}
+
+#[test]
+fn it_clears_identifying_information_by_default() {
+    let original = sample_record();
+    assert_eq!(original.config.header, "Fault on line to Somewhere");
+
+    let mut record = original.clone();
+    record.config.extra_cfg_lines = vec!["SOMEWHERE RELAY,SERIAL 1234".to_string()];
+    record.sanitize(SanitizeOptions::default());
+
+    assert_eq!(record.config.station_name, "");
+    assert_eq!(record.config.recording_device_id, "");
+    assert_eq!(record.config.header, "");
+    assert!(record
+        .config
+        .analog_channels
+        .iter()
+        .all(|c| c.circuit_component_being_monitored.is_empty()));
+    assert!(record
+        .config
+        .status_channels
+        .iter()
+        .all(|c| c.circuit_component_being_monitored.is_empty()));
+
+    assert!(record.config.extra_cfg_lines.is_empty());
+    assert_eq!(
+        record
+            .config
+            .extensions
+            .get("Public Record_Information/Source"),
+        Some(&String::new())
+    );
+    assert!(original.data.source_files.is_some());
+    assert!(record.data.source_files.is_none());
+
+    assert_eq!(record.data.analog_data, original.data.analog_data);
+    assert_eq!(record.data.status_data, original.data.status_data);
+    for (sanitized, original) in record
+        .config
+        .analog_channels
+        .iter()
+        .zip(&original.config.analog_channels)
+    {
+        assert_eq!(sanitized.name, original.name);
+    }
+}
+
+#[test]
+fn it_hashes_identifying_information_consistently() {
+    let options = SanitizeOptions {
+        station_name: SanitizeAction::Hash,
+        recording_device_id: SanitizeAction::Keep,
+        salt: "secret".to_string(),
+        ..SanitizeOptions::default()
+    };
+
+    let original = sample_record();
+    let mut first = original.clone();
+    first.sanitize(options.clone());
+    let mut second = original.clone();
+    second.sanitize(options.clone());
+
+    assert_ne!(first.config.station_name, original.config.station_name);
+    assert_eq!(first.config.station_name.len(), 16);
+    assert_eq!(first.config.station_name, second.config.station_name);
+    assert_eq!(
+        first.config.recording_device_id,
+        original.config.recording_device_id
+    );
+
+    let mut other_salt = original.clone();
+    other_salt.sanitize(SanitizeOptions {
+        salt: "other".to_string(),
+        ..options
+    });
+    assert_ne!(other_salt.config.station_name, first.config.station_name);
+}
@@ -0,0 +1,61 @@
+use std::fs;
+use std::path::Path;
+
+use comtrade::{ComtradeParser, ParserScratch};
+
+mod common;
+
+use common::{assert_comtrades_eq, SAMPLE_COMTRADE_DIR};
+
+#[test]
+fn it_parses_records_the_same_with_a_reused_scratch() {
+    let dir = Path::new(SAMPLE_COMTRADE_DIR);
+    let files: Vec<(Vec<u8>, Vec<u8>)> = [
+        "sample_2013_bin",
+        "sample_2013_ascii",
+        "sample_1999_bin",
+        "real_1999_bin",
+        "sample_2013_ascii",
+    ]
+    .iter()
+    .map(|name| {
+        (
+            fs::read(dir.join(format!("{}.cfg", name))).expect("unable to read sample file"),
+            fs::read(dir.join(format!("{}.dat", name))).expect("unable to read sample file"),
+        )
+    })
+    .collect();
+
+    let mut scratch = ParserScratch::new();
+    for (cfg, dat) in files.iter() {
+        let expected = ComtradeParser::from_bytes(cfg, dat)
+            .parse()
+            .expect("unable to parse COMTRADE files");
+        let record = ComtradeParser::from_bytes(cfg, dat)
+            .parse_with_scratch(&mut scratch)
+            .expect("unable to parse COMTRADE files");
+
+        assert_comtrades_eq(&expected, &record);
+        scratch.recycle(record);
+    }
+}
+
+#[test]
+fn it_keeps_the_scratch_usable_after_a_failed_parse() {
+    let dir = Path::new(SAMPLE_COMTRADE_DIR);
+    let cfg = fs::read(dir.join("sample_2013_bin.cfg")).expect("unable to read sample file");
+    let dat = fs::read(dir.join("sample_2013_bin.dat")).expect("unable to read sample file");
+
+    let mut scratch = ParserScratch::new();
+    assert!(ComtradeParser::from_bytes(&cfg[..cfg.len() / 2], &dat)
+        .parse_with_scratch(&mut scratch)
+        .is_err());
+
+    let record = ComtradeParser::from_bytes(&cfg, &dat)
+        .parse_with_scratch(&mut scratch)
+        .expect("unable to parse COMTRADE files");
+    let expected = ComtradeParser::from_bytes(&cfg, &dat)
+        .parse()
+        .expect("unable to parse COMTRADE files");
+    assert_comtrades_eq(&expected, &record);
+}
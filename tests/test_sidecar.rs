@@ -0,0 +1,77 @@
+#![cfg(feature = "sidecar")]
+
+mod common;
+
+use std::fs;
+use std::path::Path;
+
+use comtrade::sidecar::{decompress, read_sidecar};
+use comtrade::{Comtrade, ComtradeWriter, DataFormat};
+
+use common::{assert_comtrades_eq, SAMPLE_COMTRADE_DIR};
+
+fn read_sample(name: &str) -> (Vec<u8>, Vec<u8>, Comtrade) {
+    let dir = Path::new(SAMPLE_COMTRADE_DIR);
+    let cfg_path = dir.join(format!("{}.cfg", name));
+    let dat_path = dir.join(format!("{}.dat", name));
+    let record =
+        Comtrade::from_cfg_dat_paths(&cfg_path, &dat_path).expect("unable to parse sample");
+    (
+        fs::read(cfg_path).unwrap(),
+        fs::read(dat_path).unwrap(),
+        record,
+    )
+}
+
+#[test]
+fn it_compresses_binary_dat_files_losslessly() {
+    for name in ["real_1999_bin", "sample_2013_bin"] {
+        let (cfg, dat, record) = read_sample(name);
+        let sidecar = ComtradeWriter::new(&record).sidecar();
+
+        let (format, decompressed) = decompress(&sidecar).expect("unable to decompress sidecar");
+        assert_eq!(format, record.config.data_format);
+        assert_eq!(decompressed, dat, "{} .dat file changed", name);
+
+        let read = read_sidecar(&cfg, &sidecar).expect("unable to read sidecar");
+        assert_comtrades_eq(&read, &record);
+    }
+
+    let (_, dat, record) = read_sample("real_1999_bin");
+    let sidecar = ComtradeWriter::new(&record).sidecar();
+    assert!(
+        sidecar.len() * 2 < dat.len(),
+        "sidecar is {} bytes for a {} byte .dat file",
+        sidecar.len(),
+        dat.len()
+    );
+}
+
+#[test]
+fn it_stores_ascii_records_as_binary32() {
+    let (cfg, _, record) = read_sample("sample_2013_ascii");
+    let sidecar = ComtradeWriter::new(&record).sidecar();
+
+    let (format, _) = decompress(&sidecar).unwrap();
+    assert_eq!(format, DataFormat::Binary32);
+
+    let read = read_sidecar(&cfg, &sidecar).expect("unable to read sidecar");
+    assert_eq!(read.config.data_format, DataFormat::Ascii);
+    assert_eq!(read.data.timestamps, record.data.timestamps);
+    assert_eq!(read.config.analog_channels, record.config.analog_channels);
+    assert_eq!(read.config.status_channels, record.config.status_channels);
+}
+
+#[test]
+fn it_returns_error_for_invalid_sidecars() {
+    let (cfg, _, record) = read_sample("sample_2013_bin");
+    let sidecar = ComtradeWriter::new(&record).sidecar();
+
+    assert!(decompress(b"not a sidecar").is_err());
+    assert!(decompress(&sidecar[..sidecar.len() - 4]).is_err());
+
+    // A sidecar for a record with different channels.
+    let (_, _, other) = read_sample("sample_2013_ascii");
+    let other_sidecar = ComtradeWriter::new(&other).sidecar();
+    assert!(read_sidecar(&cfg, &other_sidecar).is_err());
+}
@@ -0,0 +1,122 @@
+use comtrade::{Comtrade, ComtradeParserBuilder};
+
+const CFG_1999: &str = "\
+SLICED STATION,DEVICE 1,1999
+4,2A,2D
+1,IA,A,Line1,A,1.0,0.0,0,-32768,32767,1,1,S
+2,IB,B,Line1,A,1.0,0.0,0,-32768,32767,1,1,S
+1,TRIP,,Line1,0
+2,CLOSE,,Line1,0
+50
+1
+1000,5
+01/02/2020,00:00:00.000000
+01/02/2020,00:00:00.001000
+ASCII
+1
+";
+
+const DAT_1999: &str = "\
+1,0,10,-10,0,1
+2,1000,20,-20,0,1
+3,2000,30,-30,1,0
+4,3000,40,-40,1,0
+5,4000,50,-50,1,0
+";
+
+fn parse() -> Comtrade {
+    ComtradeParserBuilder::new()
+        .cfg_file(CFG_1999.as_bytes())
+        .dat_file(DAT_1999.as_bytes())
+        .build()
+        .parse()
+        .expect("unable to parse COMTRADE files")
+}
+
+#[test]
+fn it_views_time_range_and_channel_subset() {
+    let record = parse();
+    let slice = record
+        .slice()
+        .with_time_range(1_000_000, 3_500_000)
+        .with_analog_channels(&[1])
+        .with_status_channels(&[0]);
+
+    assert_eq!(slice.sample_range(), 1..4);
+    assert_eq!(slice.sample_numbers(), &[2, 3, 4]);
+    assert_eq!(slice.timestamps(), &[1_000_000, 2_000_000, 3_000_000]);
+
+    let analog: Vec<_> = slice.analog_channels().collect();
+    assert_eq!(analog.len(), 1);
+    assert_eq!(analog[0].channel.name, "IB");
+    assert_eq!(analog[0].data, &[-20.0, -30.0, -40.0]);
+
+    let status: Vec<_> = slice.status_channels().collect();
+    assert_eq!(status.len(), 1);
+    assert_eq!(status[0].channel.name, "TRIP");
+    assert_eq!(status[0].data, &[0, 1, 1]);
+
+    // Samples are relative to the existing view.
+    let narrower = slice.clone().with_samples(1..10);
+    assert_eq!(narrower.sample_numbers(), &[3, 4]);
+    assert!(slice
+        .with_time_range(1_000_000_000, 2_000_000_000)
+        .is_empty());
+}
+
+#[test]
+fn it_copies_slice_out_into_record() {
+    let record = parse();
+    let sliced = record
+        .slice()
+        .with_samples(3..5)
+        .with_analog_channels(&[1])
+        .to_comtrade();
+
+    assert_eq!(sliced.data.sample_numbers, vec![4, 5]);
+    assert_eq!(sliced.config.total_num_samples, 2);
+    assert_eq!(sliced.config.num_analog_channels, 1);
+    assert_eq!(sliced.config.num_status_channels, 2);
+    assert_eq!(sliced.config.num_total_channels, 3);
+    assert_eq!(sliced.config.analog_channels[0].index, 1);
+    assert_eq!(sliced.config.analog_channels[0].name, "IB");
+    assert_eq!(sliced.data.analog_data[0], vec![-40.0, -50.0]);
+    assert_eq!(sliced.data.status_data[1], vec![0, 0]);
+
+    assert_eq!(record.slice().to_comtrade(), record);
+}
+
+#[test]
+fn it_iterates_over_whole_cycles() {
+    // Two samples per cycle, so the fifth sample is half a cycle left over.
+    let cfg = CFG_1999.replace("\n50\n", "\n500\n");
+    let record = ComtradeParserBuilder::new()
+        .cfg_file(cfg.as_bytes())
+        .dat_file(DAT_1999.as_bytes())
+        .build()
+        .parse()
+        .expect("unable to parse COMTRADE files");
+
+    let cycles: Vec<_> = record.cycles().collect();
+    assert_eq!(cycles.len(), 2);
+    assert_eq!(cycles[0].sample_range(), 0..2);
+    assert_eq!(cycles[1].sample_range(), 2..4);
+    let analog: Vec<_> = cycles[1].analog_channels().collect();
+    assert_eq!(analog[0].data, &[30.0, 40.0]);
+
+    // Cycles of a narrower view are timed from its first sample and keep its channels.
+    let cycles: Vec<_> = record
+        .slice()
+        .with_samples(1..5)
+        .with_analog_channels(&[1])
+        .cycles()
+        .collect();
+    assert_eq!(cycles.len(), 2);
+    assert_eq!(cycles[0].sample_range(), 1..3);
+    let analog: Vec<_> = cycles[0].analog_channels().collect();
+    assert_eq!(analog.len(), 1);
+    assert_eq!(analog[0].data, &[-20.0, -30.0]);
+
+    // Not even one whole cycle at 50 Hz.
+    assert_eq!(parse().cycles().count(), 0);
+}
@@ -0,0 +1,70 @@
+use comtrade::{ComtradeParserBuilder, StatusBits};
+
+const CFG_1999: &str = "\
+TRIP STATION,DEVICE 1,1999
+3,1A,2D
+1,VA,A,Line1,V,1.0,0.0,0,-32768,32767,1,1,S
+1,TRIP A,,Line1,0
+2,TRIP B,,Line1,0
+50
+1
+1000,4
+01/02/2020,00:00:00.000000
+01/02/2020,00:00:00.001000
+ASCII
+1
+";
+
+const DAT_1999: &str = "\
+1,0,10,0,0
+2,1000,20,1,0
+3,2000,30,1,1
+4,3000,40,0,1
+";
+
+#[test]
+fn it_packs_status_channels_into_bits() {
+    let record = ComtradeParserBuilder::new()
+        .cfg_file(CFG_1999.as_bytes())
+        .dat_file(DAT_1999.as_bytes())
+        .build()
+        .parse()
+        .expect("unable to parse COMTRADE files");
+
+    let trip_a = record.status_bits(0).unwrap();
+    let trip_b = record.status_bits(1).unwrap();
+
+    assert_eq!(trip_a.len(), 4);
+    assert_eq!(trip_a.to_values(), record.data.status_data[0]);
+    assert_eq!(trip_a.get(1), Some(true));
+    assert_eq!(trip_a.get(4), None);
+    assert_eq!((&trip_a & &trip_b).to_values(), vec![0, 0, 1, 0]);
+    assert_eq!((&trip_a | &trip_b).to_values(), vec![0, 1, 1, 1]);
+    assert_eq!((&trip_a ^ &trip_b).to_values(), vec![0, 1, 0, 1]);
+    assert_eq!((!&trip_a).to_values(), vec![1, 0, 0, 1]);
+}
+
+#[test]
+fn it_combines_bits_across_words() {
+    let values: Vec<u8> = (0..200).map(|i| (i % 3 == 0) as u8).collect();
+    let others: Vec<u8> = (0..150).map(|i| (i % 2 == 0) as u8).collect();
+    let bits = StatusBits::from_values(&values);
+    let other_bits = StatusBits::from_values(&others);
+
+    assert_eq!(bits.count_ones(), 67);
+    assert_eq!(bits.count_zeros(), 133);
+    assert_eq!(bits.ones().take(3).collect::<Vec<_>>(), vec![0, 3, 6]);
+    assert_eq!(bits.ones().last(), Some(198));
+
+    // Combined with a shorter channel, the result is as long as the shorter one.
+    let both = &bits & &other_bits;
+    assert_eq!(both.len(), 150);
+    assert_eq!(
+        both.ones().collect::<Vec<_>>(),
+        (0..150).filter(|i| i % 6 == 0).collect::<Vec<_>>()
+    );
+
+    let inverted = !&bits;
+    assert_eq!(inverted.len(), 200);
+    assert_eq!(inverted.count_ones(), 133);
+}
@@ -0,0 +1,61 @@
+use comtrade::{Comtrade, ComtradeParserBuilder, StatusGroup, StatusTransition};
+
+// Tap position given as a 3-bit number over status channels 2 to 4, least
+// significant bit first, with an unrelated trip signal in channel 1.
+const CFG_1999: &str = "\
+TAP CHANGER STATION,DEVICE 1,1999
+5,1A,4D
+1,VA,A,Line1,V,1.0,0.0,0,-32768,32767,1,1,S
+1,TRIP,,Line1,0
+2,TAP BIT 0,,Line1,0
+3,TAP BIT 1,,Line1,0
+4,TAP BIT 2,,Line1,0
+50
+1
+1000,4
+01/02/2020,00:00:00.000000
+01/02/2020,00:00:00.001000
+ASCII
+1
+";
+
+const DAT_1999: &str = "\
+1,0,10,0,1,0,1
+2,1000,20,1,1,0,1
+3,2000,30,1,0,1,1
+4,3000,40,0,0,1,1
+";
+
+fn parse() -> Comtrade {
+    ComtradeParserBuilder::new()
+        .cfg_file(CFG_1999.as_bytes())
+        .dat_file(DAT_1999.as_bytes())
+        .build()
+        .parse()
+        .expect("unable to parse COMTRADE files")
+}
+
+#[test]
+fn it_decodes_multi_bit_status_group() {
+    let record = parse();
+    let tap_position = StatusGroup::new("TAP POSITION", vec![1, 2, 3]);
+
+    assert_eq!(tap_position.decode(&record), Some(vec![5, 5, 6, 6]));
+    assert_eq!(
+        tap_position.transitions(&record),
+        Some(vec![StatusTransition {
+            index: 2,
+            from: 5,
+            to: 6,
+        }])
+    );
+}
+
+#[test]
+fn it_fails_to_decode_status_group_with_missing_channel() {
+    let record = parse();
+    let group = StatusGroup::new("MISSING", vec![3, 4]);
+
+    assert_eq!(group.decode(&record), None);
+    assert_eq!(group.transitions(&record), None);
+}
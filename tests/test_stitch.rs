@@ -0,0 +1,184 @@
+mod common;
+
+use chrono::{Duration, FixedOffset, NaiveDate, NaiveDateTime};
+
+use comtrade::{
+    stitch, AnalogChannel, Comtrade, ComtradeConfig, ComtradeData, SamplingRate, StitchError,
+};
+
+use common::{analog_channel, status_channel};
+
+const SAMPLE_RATE: f64 = 1000.0;
+
+fn first_start() -> NaiveDateTime {
+    NaiveDate::from_ymd(2021, 6, 3).and_hms(8, 15, 0)
+}
+
+/// Record of `n_samples` at 1 kHz starting `start_ms` after the first record, with the
+/// analog values counting up from `first_value`.
+fn record(start_ms: i64, n_samples: usize, first_value: f64) -> Comtrade {
+    let start_time = first_start() + Duration::milliseconds(start_ms);
+    Comtrade {
+        config: ComtradeConfig {
+            station_name: "SUBSTATION".to_string(),
+            recording_device_id: "RELAY1".to_string(),
+            analog_channels: vec![AnalogChannel {
+                min_value: first_value,
+                max_value: first_value + n_samples as f64,
+                ..analog_channel(1, "IA", "A")
+            }],
+            status_channels: vec![status_channel(1, "TRIP")],
+            num_total_channels: 2,
+            num_analog_channels: 1,
+            num_status_channels: 1,
+            line_frequency: 50.0,
+            sampling_rates: vec![SamplingRate {
+                rate_hz: SAMPLE_RATE,
+                end_sample_number: n_samples as u64,
+            }],
+            total_num_samples: n_samples as u64,
+            start_time,
+            trigger_time: start_time + Duration::milliseconds(2),
+            ..Default::default()
+        },
+        data: ComtradeData {
+            sample_numbers: (1..=n_samples as u64).collect(),
+            timestamps: (0..n_samples as i64).map(|i| i * 1_000_000).collect(),
+            raw_timestamps: (0..n_samples as u32).map(|i| Some(i * 1000)).collect(),
+            analog_data: vec![(0..n_samples).map(|i| first_value + i as f64).collect()],
+            status_data: vec![vec![1; n_samples]],
+            ..Default::default()
+        },
+    }
+}
+
+#[test]
+fn it_stitches_back_to_back_records() {
+    let records = [record(0, 5, 0.0), record(5, 3, 5.0), record(8, 4, 8.0)];
+    let stitched = stitch(&records).expect("records should stitch");
+
+    assert_eq!(stitched.data.sample_numbers, (1..=12).collect::<Vec<u64>>());
+    assert_eq!(stitched.config.total_num_samples, 12);
+    assert_eq!(
+        stitched.data.timestamps,
+        (0..12).map(|i| i * 1_000_000).collect::<Vec<i64>>()
+    );
+    assert_eq!(
+        stitched.data.analog_data[0],
+        (0..12).map(|i| i as f64).collect::<Vec<f64>>()
+    );
+    assert_eq!(stitched.config.analog_channels[0].max_value, 12.0);
+    assert_eq!(stitched.data.status_data[0].len(), 12);
+    assert_eq!(
+        stitched.config.sampling_rates,
+        vec![SamplingRate {
+            rate_hz: SAMPLE_RATE,
+            end_sample_number: 12
+        }]
+    );
+    assert_eq!(stitched.config.start_time, records[0].config.start_time);
+    assert_eq!(stitched.config.trigger_time, records[0].config.trigger_time);
+    assert!(stitched.data.raw_timestamps.is_empty());
+
+    // A single record comes back as it was, less its raw timestamps.
+    let single = stitch(&records[..1]).expect("record should stitch");
+    assert_eq!(single.data.timestamps, records[0].data.timestamps);
+}
+
+#[test]
+fn it_keeps_quality_flags_only_if_every_record_has_them() {
+    let mut records = [record(0, 2, 0.0), record(2, 3, 2.0), record(5, 1, 5.0)];
+    records[0].data.analog_quality = vec![vec![0, 1]];
+    records[1].data.analog_quality = vec![vec![2, 3, 0]];
+
+    let stitched = stitch(&records[..2]).expect("records should stitch");
+    assert_eq!(stitched.data.analog_quality, vec![vec![0, 1, 2, 3, 0]]);
+    assert_eq!(stitched.quality(0, 3), Some(3));
+
+    let stitched = stitch(&records).expect("records should stitch");
+    assert_eq!(stitched.quality(0, 0), None);
+}
+
+#[test]
+fn it_joins_different_sampling_rates_into_one_table() {
+    let mut second = record(5, 3, 5.0);
+    second.config.sampling_rates = vec![
+        SamplingRate {
+            rate_hz: SAMPLE_RATE,
+            end_sample_number: 1,
+        },
+        SamplingRate {
+            rate_hz: 500.0,
+            end_sample_number: 3,
+        },
+    ];
+    second.data.timestamps = vec![0, 2_000_000, 4_000_000];
+    let stitched = stitch(&[record(0, 5, 0.0), second]).expect("records should stitch");
+
+    assert_eq!(
+        stitched.config.sampling_rates,
+        vec![
+            SamplingRate {
+                rate_hz: SAMPLE_RATE,
+                end_sample_number: 6
+            },
+            SamplingRate {
+                rate_hz: 500.0,
+                end_sample_number: 8
+            }
+        ]
+    );
+    assert_eq!(
+        stitched.data.timestamps[5..],
+        [5_000_000, 7_000_000, 9_000_000]
+    );
+}
+
+#[test]
+fn it_uses_utc_offsets_to_check_records_follow_on() {
+    // Second record is in a time zone an hour ahead, so its local start time is an hour
+    // later.
+    let mut first = record(0, 5, 0.0);
+    first.config.time_offset = Some(FixedOffset::east(0));
+    let mut second = record(5 + 3_600_000, 3, 5.0);
+    second.config.time_offset = Some(FixedOffset::east(3600));
+
+    let stitched = stitch(&[first, second]).expect("records should stitch");
+    assert_eq!(stitched.data.timestamps[5], 5_000_000);
+}
+
+#[test]
+fn it_refuses_records_that_dont_follow_on() {
+    assert_eq!(stitch(&[]), Err(StitchError::NoRecords));
+
+    // 2 ms gap where there should be 1 ms.
+    assert_eq!(
+        stitch(&[record(0, 5, 0.0), record(6, 3, 6.0)]),
+        Err(StitchError::NotContiguous {
+            index: 1,
+            gap: 2_000_000
+        })
+    );
+
+    // Overlapping records.
+    assert_eq!(
+        stitch(&[record(0, 5, 0.0), record(3, 3, 3.0)]),
+        Err(StitchError::NotContiguous {
+            index: 1,
+            gap: -1_000_000
+        })
+    );
+
+    let mut other_device = record(5, 3, 5.0);
+    other_device.config.recording_device_id = "RELAY2".to_string();
+    assert_eq!(
+        stitch(&[record(0, 5, 0.0), other_device]),
+        Err(StitchError::DifferentDevice { index: 1 })
+    );
+
+    let mut other_channels = record(5, 3, 5.0);
+    other_channels.config.analog_channels[0].units = "kA".to_string();
+    let error = stitch(&[record(0, 5, 0.0), other_channels]).unwrap_err();
+    assert_eq!(error, StitchError::DifferentChannels { index: 1 });
+    assert_eq!(error.to_string(), "record 1 has different channels");
+}
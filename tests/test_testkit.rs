@@ -0,0 +1,78 @@
+#![cfg(feature = "testkit")]
+
+use comtrade::testkit::TestRecord;
+use comtrade::{ComtradeParser, DataFormat, FormatRevision};
+
+#[test]
+fn it_parses_test_records_for_every_revision_and_format() {
+    for test_record in TestRecord::all() {
+        let test_record = TestRecord {
+            num_analog_channels: 3,
+            num_status_channels: 20,
+            num_samples: 50,
+            ..test_record
+        };
+        let record = test_record
+            .files()
+            .parser()
+            .parse()
+            .unwrap_or_else(|e| panic!("unable to parse {:?}: {}", test_record, e));
+
+        assert_eq!(record.config.revision, test_record.revision);
+        assert_eq!(record.config.data_format, test_record.data_format);
+        assert!(
+            record.config.warnings.is_empty(),
+            "{:?}",
+            record.config.warnings
+        );
+        assert_eq!(record.config.analog_channels.len(), 3);
+        assert_eq!(record.config.status_channels.len(), 20);
+        assert_eq!(record.config.analog_channels[2].name, "A3");
+
+        for sample in 0..50 {
+            assert_eq!(
+                record.data.timestamps[sample as usize],
+                test_record.timestamp(sample)
+            );
+            for (channel, data) in record.data.analog_data.iter().enumerate() {
+                assert_eq!(
+                    data[sample as usize],
+                    test_record.analog_value(channel as u32, sample)
+                );
+            }
+            for (channel, data) in record.data.status_data.iter().enumerate() {
+                assert_eq!(
+                    data[sample as usize],
+                    test_record.status_value(channel as u32, sample)
+                );
+            }
+        }
+    }
+}
+
+#[test]
+fn it_generates_combined_files() {
+    let test_record = TestRecord::new(FormatRevision::Revision2013, DataFormat::Ascii);
+    let files = test_record.files();
+    let from_cff = ComtradeParser::from_cff_bytes(&files.cff())
+        .parse()
+        .expect("unable to parse .cff file");
+    let from_cfg = files.parser().parse().expect("unable to parse .cfg file");
+
+    assert_eq!(
+        from_cff.config.analog_channels,
+        from_cfg.config.analog_channels
+    );
+    assert_eq!(
+        from_cff.config.status_channels,
+        from_cfg.config.status_channels
+    );
+    assert_eq!(from_cff.data.timestamps, from_cfg.data.timestamps);
+
+    let binary = TestRecord::new(FormatRevision::Revision2013, DataFormat::Float32).files();
+    let cff = String::from_utf8_lossy(&binary.cff()).into_owned();
+    assert!(cff.contains(&format!(
+        "--- file type: DAT FLOAT32: {} ---\r\n",
+        binary.dat.len()
+    )));
+}
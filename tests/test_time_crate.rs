@@ -0,0 +1,48 @@
+#![cfg(feature = "time")]
+
+use chrono::{FixedOffset, NaiveDate};
+use comtrade::{Comtrade, ComtradeConfig, ComtradeData};
+use time::macros::datetime;
+
+fn record(time_offset: Option<FixedOffset>) -> Comtrade {
+    let start_time = NaiveDate::from_ymd(2020, 7, 1).and_hms_micro(12, 0, 0, 250);
+    Comtrade {
+        config: ComtradeConfig {
+            start_time,
+            trigger_time: start_time + chrono::Duration::milliseconds(100),
+            time_offset,
+            ..Default::default()
+        },
+        data: ComtradeData {
+            timestamps: vec![0, 1_000_000],
+            ..Default::default()
+        },
+    }
+}
+
+#[test]
+fn it_gives_times_in_time_code() {
+    let record = record(Some(FixedOffset::west(5 * 3600 + 1800)));
+
+    assert_eq!(
+        record.start_offset_date_time(),
+        Some(datetime!(2020-07-01 12:00:00.000250 -5:30))
+    );
+    assert_eq!(
+        record.trigger_offset_date_time(),
+        Some(datetime!(2020-07-01 12:00:00.100250 -5:30))
+    );
+    assert_eq!(
+        record.absolute_offset_date_time(1),
+        Some(datetime!(2020-07-01 12:00:00.001250 -5:30))
+    );
+    assert_eq!(record.absolute_offset_date_time(2), None);
+}
+
+#[test]
+fn it_gives_times_in_utc_without_time_code() {
+    assert_eq!(
+        record(None).start_offset_date_time(),
+        Some(datetime!(2020-07-01 12:00:00.000250 UTC))
+    );
+}
@@ -0,0 +1,50 @@
+#![cfg(feature = "tz")]
+
+use chrono::{FixedOffset, NaiveDate};
+use chrono_tz::Tz;
+use comtrade::{Comtrade, ComtradeConfig};
+
+fn record(month: u32, time_offset: Option<i32>, local_offset: Option<i32>) -> Comtrade {
+    let start_time = NaiveDate::from_ymd(2020, month, 1).and_hms(12, 0, 0);
+    Comtrade {
+        config: ComtradeConfig {
+            start_time,
+            trigger_time: start_time + chrono::Duration::milliseconds(100),
+            time_offset: time_offset.map(FixedOffset::east),
+            local_offset: local_offset.map(FixedOffset::east),
+            ..Default::default()
+        },
+        data: Default::default(),
+    }
+}
+
+#[test]
+fn it_matches_time_zones_to_local_offset_with_daylight_saving() {
+    let summer = record(7, Some(3600), Some(3600));
+    let winter = record(1, Some(3600), Some(3600));
+
+    assert!(summer.local_time_zones().contains(&Tz::Europe__London));
+    assert!(!winter.local_time_zones().contains(&Tz::Europe__London));
+    assert!(winter.local_time_zones().contains(&Tz::Europe__Paris));
+}
+
+#[test]
+fn it_has_no_time_zones_without_local_offset() {
+    assert!(record(7, Some(0), None).local_time_zones().is_empty());
+}
+
+#[test]
+fn it_renders_start_and_trigger_times_in_time_zone() {
+    // Times are recorded in UTC, the recorder is in New York.
+    let record = record(7, Some(0), Some(-4 * 3600));
+
+    assert_eq!(
+        record.start_time_in(Tz::America__New_York).to_string(),
+        "2020-07-01 08:00:00 EDT"
+    );
+    assert_eq!(
+        record.trigger_time_in(Tz::America__New_York).to_string(),
+        "2020-07-01 08:00:00.100 EDT"
+    );
+    assert!(record.local_time_zones().contains(&Tz::America__New_York));
+}
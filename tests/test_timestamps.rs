@@ -0,0 +1,105 @@
+use comtrade::{Comtrade, ComtradeParserBuilder, Timestamp};
+
+const CFG_1999_TEMPLATE: &str = "\
+TIMING STATION,DEVICE 1,1999
+1,1A,0D
+1,IA,A,Line1,A,1.0,0.0,0,-32768,32767,1,1,S
+50
+{rates}
+01/02/2020,00:00:00.000000
+01/02/2020,00:00:00.001000
+ASCII
+{timemult}
+";
+
+fn parse(rates: &str, timemult: &str, dat: &str) -> Comtrade {
+    let cfg = CFG_1999_TEMPLATE
+        .replace("{rates}", rates)
+        .replace("{timemult}", timemult);
+
+    ComtradeParserBuilder::new()
+        .cfg_file(cfg.as_bytes())
+        .dat_file(dat.as_bytes())
+        .build()
+        .parse()
+        .expect("unable to parse COMTRADE files")
+}
+
+#[test]
+fn it_keeps_missing_raw_timestamps() {
+    let record = parse("1\n1000,3", "1", "1,0,10\n2,,20\n3,2000,30\n");
+
+    assert_eq!(record.data.raw_timestamps, vec![Some(0), None, Some(2000)]);
+    assert_eq!(record.data.timestamps, vec![0, 1_000_000, 2_000_000]);
+    assert_eq!(
+        record.timestamp(1),
+        Some(Timestamp {
+            raw: None,
+            nanoseconds: 1_000_000,
+        })
+    );
+    assert!(record.timestamp(1).unwrap().is_missing());
+    assert!(!record.timestamp(2).unwrap().is_missing());
+    assert_eq!(record.timestamp(3), None);
+}
+
+#[test]
+fn it_computes_critical_timestamps_from_raw_values() {
+    // No sampling rates, so the timestamps in the data file are used, in microseconds
+    // scaled up by the time multiplication factor.
+    let record = parse("0\n0,3", "2", "1,0,10\n2,500,20\n3,1200,30\n");
+
+    let timestamps: Vec<Timestamp> = record.iter_timestamps().collect();
+    assert_eq!(
+        timestamps,
+        vec![
+            Timestamp {
+                raw: Some(0),
+                nanoseconds: 0,
+            },
+            Timestamp {
+                raw: Some(500),
+                nanoseconds: 1_000_000,
+            },
+            Timestamp {
+                raw: Some(1200),
+                nanoseconds: 2_400_000,
+            },
+        ]
+    );
+    assert_eq!(timestamps[2].seconds(), 0.0024);
+}
+
+#[test]
+fn it_counts_on_past_timestamp_rollover() {
+    let record = parse(
+        "0\n0,4",
+        "1",
+        "1,4294967000,10\n2,4294967295,20\n3,200,30\n4,700,40\n",
+    );
+
+    assert!(record.timestamp_overflow_detected());
+    assert_eq!(
+        record.data.timestamps,
+        vec![
+            4_294_967_000_000,
+            4_294_967_295_000,
+            4_294_967_496_000,
+            4_294_967_996_000,
+        ]
+    );
+    assert!(record.data.irregular_samples.is_empty());
+    assert!(record
+        .config
+        .warnings
+        .iter()
+        .any(|w| w.to_string().contains("roll over")));
+}
+
+#[test]
+fn it_does_not_flag_small_backwards_steps_as_rollover() {
+    let record = parse("0\n0,3", "1", "1,0,10\n2,500,20\n3,400,30\n");
+
+    assert!(!record.timestamp_overflow_detected());
+    assert_eq!(record.data.timestamps, vec![0, 500_000, 400_000]);
+}
@@ -0,0 +1,72 @@
+use std::path::Path;
+
+use comtrade::{Comtrade, UnitFix};
+
+mod common;
+
+use common::SAMPLE_COMTRADE_DIR;
+
+fn sample_record() -> Comtrade {
+    Comtrade::try_from(
+        Path::new(SAMPLE_COMTRADE_DIR)
+            .join("sample_2013_ascii.cff")
+            .as_path(),
+    )
+    .expect("unable to parse sample record")
+}
+
+#[test]
+fn it_leaves_plausible_units_alone() {
+    let mut record = sample_record();
+    assert!(record.check_units().is_empty());
+
+    // Small values could just be a quiet channel.
+    record.config.analog_channels[1].units = "kA".to_string();
+    assert!(record.check_units().is_empty());
+}
+
+#[test]
+fn it_suggests_units_matching_values() {
+    let mut record = sample_record();
+    record.config.analog_channels[0].units = "KV".to_string();
+    record.data.analog_data[0] = vec![-325_269.1, 12.0, 325_269.1];
+    record.config.analog_channels[1].units = "kA".to_string();
+    record.data.analog_data[1] = vec![40_000.0, -52_000.0];
+
+    let report = record.check_units();
+    assert_eq!(
+        report.fixes,
+        vec![
+            UnitFix {
+                channel_index: 1,
+                channel_name: "IA".to_string(),
+                units: "KV".to_string(),
+                suggested_units: "V".to_string(),
+                peak: 325_269.1,
+            },
+            UnitFix {
+                channel_index: 2,
+                channel_name: "IB".to_string(),
+                units: "kA".to_string(),
+                suggested_units: "A".to_string(),
+                peak: 52_000.0,
+            },
+        ]
+    );
+
+    // Checking doesn't change anything.
+    assert_eq!(record.config.analog_channels[0].units, "KV");
+}
+
+#[test]
+fn it_fixes_units_without_changing_values() {
+    let mut record = sample_record();
+    record.config.analog_channels[0].units = "MV".to_string();
+    record.data.analog_data[0] = vec![230.0, -230.0];
+
+    let report = record.fix_units();
+    assert_eq!(report.fixes.len(), 1);
+    assert_eq!(record.config.analog_channels[0].units, "kV");
+    assert_eq!(record.data.analog_data[0], vec![230.0, -230.0]);
+    assert!(record.check_units().is_empty());
+}
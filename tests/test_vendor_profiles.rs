@@ -0,0 +1,77 @@
+use std::io::Cursor;
+
+use chrono::NaiveDate;
+
+use comtrade::{
+    AnalogScalingMode, Comtrade, ComtradeParserBuilder, DataFormat, ParseResult, Phase, Quirks,
+    VendorProfile,
+};
+
+const CFG_1999_QUIRKY: &str = "\
+STATION,DEVICE,1999
+2,1A,1D
+1,  IA  , A ,Line1, kA ,0.5,0.0,0,-32768,32767,1,1,Primary
+1, TRIP ,,Line1,0
+50
+1
+1000,2
+02/13/2020,00:00:00.000000
+02/13/2020,00:00:00.001000
+Ascii 
+1
+";
+
+const DAT_1999: &str = "\
+1,0,10,0
+2,1000,20,1
+";
+
+fn parse(profile: VendorProfile) -> ParseResult<Comtrade> {
+    ComtradeParserBuilder::new()
+        .cfg_file(Cursor::new(CFG_1999_QUIRKY.as_bytes()))
+        .dat_file(Cursor::new(DAT_1999.as_bytes()))
+        .vendor_profile(profile)
+        .build()
+        .parse()
+}
+
+#[test]
+fn it_fails_on_quirky_files_without_vendor_profile() {
+    assert!(parse(VendorProfile::Standard).is_err());
+}
+
+#[test]
+fn it_handles_quirks_enabled_by_custom_profile() {
+    let profile = VendorProfile::Custom(
+        Quirks::new()
+            .trim_channel_fields(true)
+            .month_first_dates(true)
+            .nonstandard_tokens(true),
+    );
+    let record = parse(profile).expect("unable to parse COMTRADE files");
+
+    let analog = &record.config.analog_channels[0];
+    assert_eq!(analog.name, "IA");
+    assert_eq!(analog.phase, Phase::A);
+    assert_eq!(analog.units, "kA");
+    assert_eq!(analog.scaling_mode, AnalogScalingMode::Primary);
+    assert_eq!(record.config.status_channels[0].name, "TRIP");
+
+    assert_eq!(record.config.data_format, DataFormat::Ascii);
+    assert_eq!(
+        record.config.start_time,
+        NaiveDate::from_ymd(2020, 2, 13).and_hms(0, 0, 0)
+    );
+}
+
+#[test]
+fn it_maps_vendor_profiles_to_quirks() {
+    assert_eq!(VendorProfile::Standard.quirks(), Quirks::default());
+    assert!(VendorProfile::Sel.quirks().trim_channel_fields);
+    assert!(VendorProfile::Ge.quirks().month_first_dates);
+    assert!(VendorProfile::Omicron.quirks().nonstandard_tokens);
+    assert_eq!(
+        VendorProfile::Siemens.quirks(),
+        Quirks::new().trim_channel_fields(true)
+    );
+}
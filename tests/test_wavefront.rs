@@ -0,0 +1,68 @@
+use comtrade::analysis::{wavefront_arrival, WavefrontConfig, WavefrontDetector};
+
+const SAMPLE_RATE: f64 = 1e6;
+
+/// Values of a current channel sampled at 1 MHz with a travelling wave front of height
+/// `step` arriving `arrival` seconds after the first sample, rising over a microsecond or
+/// so, plus some noise.
+fn channel(arrival: f64, step: f64, noise: f64) -> Vec<f64> {
+    // Simple linear congruential generator so the noise is the same every run.
+    let mut state: u64 = 12345;
+    (0..500)
+        .map(|k| {
+            state = state
+                .wrapping_mul(6364136223846793005)
+                .wrapping_add(1442695040888963407);
+            let uniform = (state >> 11) as f64 / (1u64 << 53) as f64 - 0.5;
+            let t = k as f64 / SAMPLE_RATE;
+            step * (1.0 + ((t - arrival) / 0.5e-6).tanh()) / 2.0 + noise * uniform
+        })
+        .collect()
+}
+
+#[test]
+fn it_times_wavefront_arrival_to_a_fraction_of_a_sample() {
+    let arrival = 123.4e-6;
+    let config = WavefrontConfig::new(SAMPLE_RATE);
+    let front = wavefront_arrival(&channel(arrival, 100.0, 1.0), &config)
+        .expect("expected wavefront arrival");
+
+    assert!((front.time - arrival).abs() < 0.2e-6, "{:?}", front);
+    assert!(front.sample_index <= 124);
+    assert!(front.sample_index >= 120);
+    assert!(front.peak > 0.0);
+    assert!(front.signal_to_noise > config.threshold);
+
+    // Falling fronts are found just the same.
+    let front = wavefront_arrival(&channel(arrival, -100.0, 1.0), &config)
+        .expect("expected wavefront arrival");
+    assert!((front.time - arrival).abs() < 0.2e-6);
+    assert!(front.peak < 0.0);
+}
+
+#[test]
+fn it_finds_wavefront_in_noise_with_wavelet_detector() {
+    let arrival = 301.7e-6;
+    let data = channel(arrival, 20.0, 5.0);
+    let config = WavefrontConfig {
+        detector: WavefrontDetector::Wavelet { scale: 4 },
+        ..WavefrontConfig::new(SAMPLE_RATE)
+    };
+    let front = wavefront_arrival(&data, &config).expect("expected wavefront arrival");
+    assert!((front.time - arrival).abs() < 1e-6, "{:?}", front);
+
+    // Averaging over more samples stands out further from the noise.
+    let derivative = wavefront_arrival(&data, &WavefrontConfig::new(SAMPLE_RATE))
+        .map_or(0.0, |f| f.signal_to_noise);
+    assert!(front.signal_to_noise > derivative);
+}
+
+#[test]
+fn it_finds_no_wavefront_in_noise_alone() {
+    let config = WavefrontConfig::new(SAMPLE_RATE);
+    assert!(wavefront_arrival(&channel(0.0, 0.0, 1.0), &config).is_none());
+
+    let mut flat = channel(0.0, 0.0, 0.0);
+    flat[10] = f64::NAN;
+    assert!(wavefront_arrival(&flat, &config).is_none());
+}
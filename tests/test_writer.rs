@@ -0,0 +1,267 @@
+mod common;
+
+use std::path::Path;
+
+use comtrade::{
+    AnalogChannel, AnalogScalingMode, AppendChannelError, Comtrade, ComtradeParser, ComtradeWriter,
+    Phase, StatusChannel,
+};
+
+use common::{assert_comtrades_eq, status_channel, SAMPLE_COMTRADE_DIR};
+
+fn parse_sample(name: &str) -> Comtrade {
+    let dir = Path::new(SAMPLE_COMTRADE_DIR);
+    Comtrade::from_cfg_dat_paths(
+        dir.join(format!("{}.cfg", name)),
+        dir.join(format!("{}.dat", name)),
+    )
+    .expect("unable to parse sample files")
+}
+
+fn write_and_parse(record: &Comtrade) -> Comtrade {
+    let writer = ComtradeWriter::new(record);
+    let (cfg, dat) = (writer.cfg(), writer.dat());
+    ComtradeParser::from_bytes(cfg.as_bytes(), &dat)
+        .parse()
+        .expect("unable to parse written files")
+}
+
+#[test]
+fn it_writes_records_that_parse_the_same() {
+    for name in ["sample_1999_bin", "sample_2013_ascii", "sample_2013_bin"] {
+        let record = parse_sample(name);
+        let written = write_and_parse(&record);
+        assert_comtrades_eq(&written, &record);
+    }
+}
+
+#[test]
+fn it_appends_computed_channels() {
+    let mut record = parse_sample("sample_1999_bin");
+    let residual: Vec<f64> = (0..record.data.timestamps.len())
+        .map(|i| (0..3).map(|c| record.data.analog_data[c][i]).sum())
+        .collect();
+    let template = record.config.analog_channels[0].clone();
+
+    let index = record
+        .append_analog_channel(
+            AnalogChannel {
+                name: "3V0".to_string(),
+                name_raw: String::new(),
+                phase: Phase::Zero,
+                ..template.clone()
+            },
+            residual.clone(),
+        )
+        .expect("unable to append analog channel");
+    assert_eq!(index, 5);
+
+    let tripped: Vec<u8> = residual.iter().map(|v| (v.abs() > 10.0) as u8).collect();
+    let index = record
+        .append_status_channel(
+            StatusChannel {
+                name_raw: String::new(),
+                phase: Phase::Zero,
+                ..status_channel(0, "3V0 HIGH")
+            },
+            tripped.clone(),
+        )
+        .expect("unable to append status channel");
+    assert_eq!(index, 17);
+    assert_eq!(record.config.num_analog_channels, 5);
+    assert_eq!(record.config.num_status_channels, 17);
+    assert_eq!(record.config.num_total_channels, 22);
+
+    let written = write_and_parse(&record);
+    let channel = written
+        .analog_channel("3V0")
+        .expect("missing appended analog channel");
+    assert_eq!(channel.index, 5);
+    assert_eq!(channel.phase, Phase::Zero);
+    let largest = residual.iter().fold(0.0f64, |m, v| m.max(v.abs()));
+    let values = written.analog_data("3V0").unwrap();
+    for (value, expected) in values.iter().zip(residual.iter()) {
+        // Rounded to one step of the 16-bit range.
+        assert!((value - expected).abs() <= largest / 32767.0);
+    }
+    assert_eq!(
+        written
+            .status_data("3V0 HIGH")
+            .expect("missing appended status channel"),
+        tripped
+    );
+
+    let error = record
+        .append_analog_channel(template, vec![0.0; 3])
+        .unwrap_err();
+    assert_eq!(
+        error,
+        AppendChannelError::WrongLength {
+            expected: residual.len(),
+            found: 3
+        }
+    );
+}
+
+#[test]
+fn it_keeps_channel_name_padding_unless_renamed() {
+    let mut record = parse_sample("sample_1999_bin");
+    let name = record.config.analog_channels[0].name.clone();
+    record.config.analog_channels[0].name_raw = format!("{}   ", name);
+    let padded = format!(",{}   ,", name);
+
+    assert!(ComtradeWriter::new(&record).cfg().contains(&padded));
+    let cfg = ComtradeWriter::new(&record).name_padding(false).cfg();
+    assert!(!cfg.contains(&padded));
+    assert!(cfg.contains(&format!(",{},", name)));
+
+    record.config.analog_channels[0].name = "RENAMED".to_string();
+    let cfg = ComtradeWriter::new(&record).cfg();
+    assert!(!cfg.contains(&padded));
+    assert!(cfg.contains(",RENAMED,"));
+}
+
+#[test]
+fn it_writes_secondary_channels_in_primary_values() {
+    let record = parse_sample("sample_2013_ascii");
+    assert_eq!(
+        record.config.analog_channels[0].scaling_mode,
+        AnalogScalingMode::Secondary
+    );
+
+    let writer = ComtradeWriter::new(&record).primary_values(true);
+    let (cfg, dat) = (writer.cfg(), writer.dat());
+    assert_eq!(dat, ComtradeWriter::new(&record).dat());
+    let written = ComtradeParser::from_bytes(cfg.as_bytes(), &dat)
+        .parse()
+        .expect("unable to parse written files");
+
+    for ((channel, data), (original, original_data)) in written
+        .analog_channels_with_data()
+        .zip(record.analog_channels_with_data())
+    {
+        assert_eq!(channel.scaling_mode, AnalogScalingMode::Primary);
+        assert_eq!(channel.primary_factor, original.primary_factor);
+        assert_eq!(channel.min_value, original.min_value);
+        let ratio = original.primary_factor / original.secondary_factor;
+        for (value, secondary) in data.iter().zip(original_data.iter()) {
+            assert!((value - secondary * ratio).abs() < 1e-9 * value.abs().max(1.0));
+        }
+    }
+}
+
+#[test]
+fn it_passes_unmodified_records_through_byte_for_byte() {
+    let dir = Path::new(SAMPLE_COMTRADE_DIR);
+    for name in ["real_1999_bin", "sample_2013_ascii"] {
+        let cfg = std::fs::read(dir.join(format!("{}.cfg", name))).unwrap();
+        let dat = std::fs::read(dir.join(format!("{}.dat", name))).unwrap();
+        let mut record = comtrade::ComtradeParserBuilder::new()
+            .cfg_file(&cfg[..])
+            .dat_file(&dat[..])
+            .keep_source_files(true)
+            .build()
+            .parse()
+            .expect("unable to parse sample files");
+
+        assert!(record.is_unmodified());
+        let writer = ComtradeWriter::new(&record).passthrough(true);
+        assert_eq!(writer.cfg().as_bytes(), &cfg[..]);
+        assert_eq!(writer.dat(), dat);
+
+        record.data.analog_data[0][0] += 1.0;
+        assert!(!record.is_unmodified());
+        let writer = ComtradeWriter::new(&record).passthrough(true);
+        assert_ne!(writer.dat(), dat);
+    }
+}
+
+#[test]
+fn it_does_not_keep_source_files_by_default() {
+    let record = parse_sample("sample_2013_ascii");
+
+    assert_eq!(record.data.source_files, None);
+    assert!(!record.is_unmodified());
+}
+
+const ASCII_CFG: &str = "\
+STATION,DEVICE,2013
+1,1A,0D
+1,VA,A,,V,1.0,0.0,0,-99999,99999,1,1,P
+50
+1
+1000,3
+01/01/2020,00:00:00.000000
+01/01/2020,00:00:00.000000
+ASCII
+1
+0,0
+0,0
+";
+
+const ASCII_DAT: &str = "\
+1,0,0.1
+2,1000,-123.456789
+3,2000,0.000025
+";
+
+#[test]
+fn it_writes_shortest_round_trip_reals_in_ascii() {
+    let record = ComtradeParser::from_bytes(ASCII_CFG.as_bytes(), ASCII_DAT.as_bytes())
+        .parse()
+        .expect("unable to parse files");
+    let dat = ComtradeWriter::new(&record).dat();
+
+    assert_eq!(
+        String::from_utf8(dat.clone()).unwrap(),
+        "1,0,0.1\r\n2,1000,-123.456789\r\n3,2000,0.000025\r\n"
+    );
+    let written = ComtradeParser::from_bytes(ASCII_CFG.as_bytes(), &dat)
+        .parse()
+        .expect("unable to parse written files");
+    assert_eq!(
+        written.config.analog_channels,
+        record.config.analog_channels
+    );
+}
+
+#[test]
+fn it_rounds_ascii_reals_to_a_precision() {
+    let record = ComtradeParser::from_bytes(ASCII_CFG.as_bytes(), ASCII_DAT.as_bytes())
+        .parse()
+        .expect("unable to parse files");
+    let dat = ComtradeWriter::new(&record).precision(Some(3)).dat();
+
+    assert_eq!(
+        String::from_utf8(dat).unwrap(),
+        "1,0,0.1\r\n2,1000,-123.457\r\n3,2000,0\r\n"
+    );
+}
+
+#[test]
+fn it_passes_sections_of_cff_files_through_byte_for_byte() {
+    let dir = Path::new(SAMPLE_COMTRADE_DIR);
+    let cfg = std::fs::read_to_string(dir.join("sample_2013_ascii.cfg")).unwrap();
+    let dat = std::fs::read_to_string(dir.join("sample_2013_ascii.dat")).unwrap();
+    // Line endings in the sections are kept as they are, even when they're mixed.
+    let cfg = format!("{}\n", cfg.trim_end()).replace('\n', "\r\n");
+    let dat = format!("{}\n", dat.trim_end()).replace("\r\n", "\n");
+    let cff = format!(
+        "--- file type: CFG ---\r\n{}--- file type: HDR ---\nSome header text\n\
+         --- file type: DAT ASCII ---\n{}",
+        cfg, dat
+    );
+
+    let record = comtrade::ComtradeParserBuilder::new()
+        .cff_file(cff.as_bytes())
+        .keep_source_files(true)
+        .build()
+        .parse()
+        .expect("unable to parse cff file");
+
+    assert_eq!(record.config.header, "Some header text");
+    assert!(record.is_unmodified());
+    let writer = ComtradeWriter::new(&record).passthrough(true);
+    assert_eq!(writer.cfg(), cfg);
+    assert_eq!(writer.dat(), dat.into_bytes());
+}
@@ -0,0 +1,94 @@
+#![cfg(feature = "xml")]
+
+use comtrade::xml::{config_from_xml, config_to_xml, XmlConfigError};
+use comtrade::{ComtradeParser, ComtradeParserBuilder};
+
+const CFG_2013: &str = "\
+XML <STATION> & CO,DEVICE 1,2013
+2,1A,1D
+1,IA,A,Line1,A,0.5,0.0,0,-32768,32767,1,1,S
+1,TRIP,,Line1,0
+50
+1
+1000,4
+01/02/2020,00:00:00.000000
+01/02/2020,00:00:00.001000
+ASCII
+1
++1h30,-5
+B,1
+VENDOR,\"quoted\"
+";
+
+const DAT: &str = "\
+1,0,10,0
+2,1000,20,0
+3,2000,30,1
+4,3000,40,1
+";
+
+#[test]
+fn it_round_trips_config_through_xml() {
+    let config = ComtradeParserBuilder::new()
+        .cfg_file(CFG_2013.as_bytes())
+        .build()
+        .parse_comtrade_config()
+        .expect("unable to parse .cfg file");
+
+    let xml = config_to_xml(&config);
+    assert!(xml.contains("stationName=\"XML &lt;STATION&gt; &amp; CO\""));
+    assert!(xml.contains("timeOffsetMinutes=\"90\""));
+
+    let read = config_from_xml(&xml).expect("unable to read XML configuration");
+    assert_eq!(
+        read,
+        comtrade::ComtradeConfig {
+            warnings: vec![],
+            ..config.clone()
+        }
+    );
+
+    let from_xml = ComtradeParser::from_config(&read, DAT.as_bytes())
+        .parse()
+        .expect("unable to parse .dat file against XML configuration");
+    let from_cfg = ComtradeParser::from_config(&config, DAT.as_bytes())
+        .parse()
+        .expect("unable to parse .dat file");
+    assert_eq!(
+        from_xml.config.analog_channels,
+        from_cfg.config.analog_channels
+    );
+    assert_eq!(from_xml.data.timestamps, from_cfg.data.timestamps);
+}
+
+#[test]
+fn it_keeps_decimal_commas_in_xml_config() {
+    let cfg = CFG_2013.replace(',', ";").replace("0.5", "0,5");
+    let dat = DAT.replace(',', ";");
+    let config = ComtradeParserBuilder::new()
+        .cfg_file(cfg.as_bytes())
+        .build()
+        .parse_comtrade_config()
+        .expect("unable to parse .cfg file");
+    assert!(config.decimal_comma);
+
+    let read = config_from_xml(&config_to_xml(&config)).expect("unable to read XML configuration");
+    assert!(read.decimal_comma);
+    let record = ComtradeParser::from_config(&read, dat.as_bytes())
+        .parse()
+        .expect("unable to parse .dat file against XML configuration");
+    assert_eq!(record.data.analog_data[0], vec![5.0, 10.0, 15.0, 20.0]);
+}
+
+#[test]
+fn it_rejects_invalid_xml_config() {
+    assert!(matches!(
+        config_from_xml("<ComtradeConfig revision=\"1999\"/>"),
+        Err(XmlConfigError::Invalid(_))
+    ));
+    assert!(matches!(
+        config_from_xml("<AnalogChannel index=\"1\"/>"),
+        Err(XmlConfigError::Invalid(_))
+    ));
+    assert!(config_from_xml("<ComtradeConfig").is_err());
+}
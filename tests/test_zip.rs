@@ -0,0 +1,60 @@
+#![cfg(feature = "zip")]
+
+use std::io::{Cursor, Read};
+
+use comtrade::{ComName, ComtradeParserBuilder, ComtradeWriter};
+
+const CFG_1999: &str = "\
+ZIP STATION,DEVICE 1,1999
+1,1A,0D
+1,IA,A,Line1,A,1.0,0.0,0,-32768,32767,1,1,S
+50
+1
+1000,2
+01/02/2020,10:20:30.000000
+01/02/2020,10:20:30.001000
+ASCII
+1
+";
+
+const DAT_1999: &str = "\
+1,0,10
+2,1000,20
+";
+
+#[test]
+fn it_writes_records_into_zip_archives() {
+    let mut record = ComtradeParserBuilder::new()
+        .cfg_file(CFG_1999.as_bytes())
+        .dat_file(DAT_1999.as_bytes())
+        .build()
+        .parse()
+        .expect("unable to parse COMTRADE files");
+    record.config.header = "Fault on line 1".to_string();
+
+    let name = ComName::from_record(&record);
+    let zip = ComtradeWriter::new(&record)
+        .write_zip(Cursor::new(vec![]), &name)
+        .expect("unable to write zip archive")
+        .into_inner();
+
+    let mut archive = zip::ZipArchive::new(Cursor::new(zip)).expect("invalid zip archive");
+    let mut names: Vec<&str> = archive.file_names().collect();
+    names.sort();
+    assert_eq!(
+        names,
+        vec![
+            "200201,102030000,0,ZIP STATION,DEVICE 1,,0.001s.cfg",
+            "200201,102030000,0,ZIP STATION,DEVICE 1,,0.001s.dat",
+            "200201,102030000,0,ZIP STATION,DEVICE 1,,0.001s.hdr",
+        ]
+    );
+
+    let mut header = String::new();
+    archive
+        .by_name(&name.file_name("hdr"))
+        .unwrap()
+        .read_to_string(&mut header)
+        .unwrap();
+    assert_eq!(header, "Fault on line 1");
+}